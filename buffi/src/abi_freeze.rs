@@ -0,0 +1,232 @@
+//! Fail the generation if the wire format changes without an explicit snapshot bump
+//!
+//! With [`Config::abi_snapshot`](crate::Config::abi_snapshot) set, the registry of all types is
+//! compared with the snapshot stored at that path (as JSON) before any type is written. Each
+//! type of the snapshot needs to exist with exactly the same format: the same fields in the same
+//! order, the same variants at the same indices and the same field types. New types can be
+//! added freely, as they do not change how existing types are serialized.
+//!
+//! If the snapshot does not exist yet, it is created. To accept a change of the wire format,
+//! run the generation with the `BUFFI_BUMP_ABI_SNAPSHOT` environment variable set, which
+//! overwrites the snapshot with the current registry. Commit the snapshot, so that changes of
+//! it show up in reviews.
+
+use crate::Error;
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+use std::path::Path;
+
+/// Set this environment variable to overwrite the snapshot with the current registry
+const BUMP_VARIABLE: &str = "BUFFI_BUMP_ABI_SNAPSHOT";
+
+/// Compares `registry` with the snapshot at `path`, fails with a list of all wire format
+/// changes
+pub(crate) fn check(registry: &Registry, path: &Path) -> Result<(), Error> {
+    let io_error = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    if !path.exists() || std::env::var_os(BUMP_VARIABLE).is_some() {
+        let snapshot = serde_json::to_string_pretty(registry)
+            .map_err(|e| Error::Generation(format!("Failed to serialize the registry: {e}")))?;
+        std::fs::write(path, snapshot + "\n").map_err(io_error)?;
+        println!("Wrote the ABI snapshot `{}`", path.display());
+        return Ok(());
+    }
+    let snapshot = std::fs::read_to_string(path).map_err(io_error)?;
+    let snapshot: Registry = serde_json::from_str(&snapshot).map_err(|e| {
+        Error::Generation(format!("Invalid ABI snapshot `{}`: {e}", path.display()))
+    })?;
+    let changes = changes(&snapshot, registry);
+    if !changes.is_empty() {
+        return Err(Error::Generation(format!(
+            "The wire format differs from the ABI snapshot `{}`:\n  {}\n\
+             Set `{BUMP_VARIABLE}` to update the snapshot if this change is intended",
+            path.display(),
+            changes.join("\n  ")
+        )));
+    }
+    Ok(())
+}
+
+/// Describes each difference of the types of `snapshot` in `registry`
+fn changes(snapshot: &Registry, registry: &Registry) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (name, frozen) in snapshot {
+        let Some(current) = registry.get(name) else {
+            changes.push(format!("`{name}` was removed"));
+            continue;
+        };
+        if frozen == current {
+            continue;
+        }
+        match (frozen, current) {
+            (ContainerFormat::Struct(frozen), ContainerFormat::Struct(current)) => {
+                field_changes(name, "field", frozen, current, &mut changes);
+            }
+            (ContainerFormat::Enum(frozen), ContainerFormat::Enum(current)) => {
+                for (idx, variant) in frozen {
+                    let Some(other) = current.get(idx) else {
+                        changes.push(format!(
+                            "`{name}`: the variant `{}` (index {idx}) was removed",
+                            variant.name
+                        ));
+                        continue;
+                    };
+                    if other.name != variant.name {
+                        changes.push(format!(
+                            "`{name}`: the variant at index {idx} changed from `{}` to `{}`",
+                            variant.name, other.name
+                        ));
+                    } else if other.value != variant.value {
+                        variant_changes(
+                            &format!("{name}::{}", variant.name),
+                            &variant.value,
+                            &other.value,
+                            &mut changes,
+                        );
+                    }
+                }
+                for (idx, variant) in current.range(frozen.len() as u32..) {
+                    changes.push(format!(
+                        "`{name}`: the variant `{}` was added at index {idx}",
+                        variant.name
+                    ));
+                }
+            }
+            (frozen, current) => {
+                changes.push(format!("`{name}` changed from {frozen:?} to {current:?}"))
+            }
+        }
+    }
+    changes
+}
+
+fn variant_changes(
+    name: &str,
+    frozen: &VariantFormat,
+    current: &VariantFormat,
+    changes: &mut Vec<String>,
+) {
+    match (frozen, current) {
+        (VariantFormat::Struct(frozen), VariantFormat::Struct(current)) => {
+            field_changes(name, "field", frozen, current, changes);
+        }
+        (VariantFormat::Tuple(frozen), VariantFormat::Tuple(current)) => {
+            let named = |formats: &[Format]| {
+                formats
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value)| Named {
+                        name: idx.to_string(),
+                        value: value.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            field_changes(name, "element", &named(frozen), &named(current), changes);
+        }
+        (frozen, current) => {
+            changes.push(format!("`{name}` changed from {frozen:?} to {current:?}"))
+        }
+    }
+}
+
+/// Compares the fields (or elements) of `name` position by position
+fn field_changes(
+    name: &str,
+    kind: &str,
+    frozen: &[Named<Format>],
+    current: &[Named<Format>],
+    changes: &mut Vec<String>,
+) {
+    for idx in 0..frozen.len().max(current.len()) {
+        match (frozen.get(idx), current.get(idx)) {
+            (Some(f), Some(c)) if f.name != c.name => changes.push(format!(
+                "`{name}`: the {kind} at position {idx} changed from `{}` to `{}`",
+                f.name, c.name
+            )),
+            (Some(f), Some(c)) if f.value != c.value => changes.push(format!(
+                "`{name}`: the {kind} `{}` changed from {:?} to {:?}",
+                f.name, f.value, c.value
+            )),
+            (Some(f), None) => {
+                changes.push(format!("`{name}`: the {kind} `{}` was removed", f.name))
+            }
+            (None, Some(c)) => changes.push(format!("`{name}`: the {kind} `{}` was added", c.name)),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn field(name: &str, value: Format) -> Named<Format> {
+        Named {
+            name: name.to_owned(),
+            value,
+        }
+    }
+
+    fn variant(name: &str, value: VariantFormat) -> Named<VariantFormat> {
+        Named {
+            name: name.to_owned(),
+            value,
+        }
+    }
+
+    #[test]
+    fn wire_format_changes_are_reported() {
+        let mut snapshot = Registry::new();
+        snapshot.insert(
+            String::from("Point"),
+            ContainerFormat::Struct(vec![field("x", Format::F64), field("y", Format::F64)]),
+        );
+        snapshot.insert(
+            String::from("Shape"),
+            ContainerFormat::Enum(BTreeMap::from([
+                (0, variant("Point", VariantFormat::Unit)),
+                (
+                    1,
+                    variant("Circle", VariantFormat::Tuple(vec![Format::F64])),
+                ),
+            ])),
+        );
+        snapshot.insert(String::from("Removed"), ContainerFormat::UnitStruct);
+
+        let mut registry = snapshot.clone();
+        registry.remove("Removed");
+        registry.insert(String::from("Added"), ContainerFormat::UnitStruct);
+        assert_eq!(changes(&snapshot, &registry), ["`Removed` was removed"]);
+
+        registry.insert(
+            String::from("Point"),
+            ContainerFormat::Struct(vec![field("y", Format::F64), field("x", Format::F32)]),
+        );
+        registry.insert(
+            String::from("Shape"),
+            ContainerFormat::Enum(BTreeMap::from([
+                (0, variant("Point", VariantFormat::Unit)),
+                (
+                    1,
+                    variant("Circle", VariantFormat::Tuple(vec![Format::F32])),
+                ),
+                (
+                    2,
+                    variant("Square", VariantFormat::Tuple(vec![Format::F64])),
+                ),
+            ])),
+        );
+        assert_eq!(
+            changes(&snapshot, &registry),
+            [
+                "`Point`: the field at position 0 changed from `x` to `y`",
+                "`Point`: the field at position 1 changed from `y` to `x`",
+                "`Removed` was removed",
+                "`Shape::Circle`: the element `0` changed from F64 to F32",
+                "`Shape`: the variant `Square` was added at index 2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Wrappers without standard library types in their signatures
+//!
+//! If [`Config::stl_free_adapters`](crate::Config::stl_free_adapters) is set, an additional
+//! `adapters.hpp` file contains a `*Adapter` class template for each `*Holder` class and
+//! templates for the free-standing functions. Their signatures use the string and vector types
+//! of a traits type instead of `std::string` and `std::vector`, so engines that prohibit the
+//! standard library in their module interfaces (like Unreal Engine with `FString` and `TArray`)
+//! can plug in their own types. `adapter_traits.hpp` contains the conversions and `StdTypes`,
+//! which documents the required members of a traits type.
+//!
+//! Other types (generated structs, `std::optional`, ...) are passed unchanged.
+
+use crate::embind::{FunctionSignature, WrapperFunctions};
+use crate::{create_file, finish_file, to_cpp_type_name, Config, Error};
+use crate::{write_function_footer, write_function_header};
+use serde_reflection::Format;
+use std::fs::File;
+use std::io::{BufWriter, Write as _};
+use std::path::PathBuf;
+
+/// The traits type using the standard library and the conversions between the types of a
+/// traits type and the standard library types
+const ADAPTER_TRAITS: &str = r#"// The types used by the standard library, each traits type needs the same members.
+// For Unreal Engine this could look like:
+//
+// struct UnrealTypes {
+//     using String = FString;
+//     template <typename T>
+//     using Vector = TArray<T>;
+//
+//     static std::string to_std(const FString& value) { return TCHAR_TO_UTF8(*value); }
+//     static FString from_std(const std::string& value) { return UTF8_TO_TCHAR(value.c_str()); }
+//     template <typename T>
+//     static size_t size(const TArray<T>& value) { return value.Num(); }
+//     template <typename T>
+//     static const T& at(const TArray<T>& value, size_t idx) { return value[idx]; }
+//     template <typename T>
+//     static void push_back(TArray<T>& value, T&& element) { value.Add(std::move(element)); }
+// };
+struct StdTypes {
+    using String = std::string;
+    template <typename T>
+    using Vector = std::vector<T>;
+
+    static std::string to_std(const String& value) { return value; }
+    static String from_std(const std::string& value) { return value; }
+    template <typename T>
+    static size_t size(const Vector<T>& value) { return value.size(); }
+    // `decltype(auto)`, as `std::vector<bool>` returns its elements by value
+    template <typename T>
+    static decltype(auto) at(const Vector<T>& value, size_t idx) { return value[idx]; }
+    template <typename T>
+    static void push_back(Vector<T>& value, T&& element) { value.push_back(std::move(element)); }
+};
+
+namespace adapter {
+
+// Converts `T` into the type used by `Traits` and back, all types except
+// strings and vectors are passed unchanged
+template <typename Traits, typename T>
+struct Convert {
+    using Type = T;
+
+    static const T& to_std(const T& value) { return value; }
+    static T from_std(T value) { return value; }
+};
+
+template <typename Traits>
+struct Convert<Traits, std::string> {
+    using Type = typename Traits::String;
+
+    static std::string to_std(const Type& value) { return Traits::to_std(value); }
+    static Type from_std(const std::string& value) { return Traits::from_std(value); }
+};
+
+template <typename Traits, typename T>
+struct Convert<Traits, std::vector<T>> {
+    using Type = typename Traits::template Vector<typename Convert<Traits, T>::Type>;
+
+    static std::vector<T> to_std(const Type& value) {
+        std::vector<T> out;
+        const size_t size = Traits::size(value);
+        out.reserve(size);
+        for (size_t idx = 0; idx < size; ++idx) {
+            out.push_back(Convert<Traits, T>::to_std(Traits::at(value, idx)));
+        }
+        return out;
+    }
+
+    static Type from_std(std::vector<T> value) {
+        Type out;
+        for (auto&& element : value) {
+            Traits::push_back(out, Convert<Traits, T>::from_std(std::move(element)));
+        }
+        return out;
+    }
+};
+
+// The type used by `Traits` instead of `T`
+template <typename Traits, typename T>
+using Adapted = typename Convert<Traits, T>::Type;
+
+}  // end of namespace adapter
+"#;
+
+fn is_void(format: &Format) -> bool {
+    matches!(format, Format::Tuple(t) if t.is_empty())
+}
+
+/// Writes a function forwarding to `target` with converted arguments and result
+fn write_adapted_function(
+    out: &mut BufWriter<File>,
+    signature: &FunctionSignature,
+    target: &str,
+    indent: &str,
+) -> Result<(), Error> {
+    let output = to_cpp_type_name(&signature.output)?;
+    let return_type = if is_void(&signature.output) {
+        output.clone()
+    } else {
+        format!("adapter::Adapted<Traits, {output}>")
+    };
+    let inputs = signature
+        .inputs
+        .iter()
+        .map(|(name, format)| {
+            Ok(format!(
+                "const adapter::Adapted<Traits, {}>& {name}",
+                to_cpp_type_name(format)?
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?
+        .join(", ");
+    let arguments = signature
+        .inputs
+        .iter()
+        .map(|(name, format)| {
+            Ok(format!(
+                "adapter::Convert<Traits, {}>::to_std({name})",
+                to_cpp_type_name(format)?
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?
+        .join(", ");
+    let name = &signature.cpp_name;
+    writeln!(out, "{indent}{return_type} {name}({inputs}) {{").unwrap();
+    if is_void(&signature.output) {
+        writeln!(out, "{indent}    {target}{name}({arguments});").unwrap();
+    } else {
+        writeln!(
+            out,
+            "{indent}    return adapter::Convert<Traits, {output}>::from_std({target}{name}({arguments}));"
+        )
+        .unwrap();
+    }
+    writeln!(out, "{indent}}}").unwrap();
+    Ok(())
+}
+
+/// Generates `adapter_traits.hpp` and `adapters.hpp` for the given wrapper functions
+pub(crate) fn generate_adapters(
+    functions: &WrapperFunctions,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let out_dir = PathBuf::from(out_dir);
+
+    let path = out_dir.join(format!("{file_prefix}_adapter_traits.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include <string>").unwrap();
+    writeln!(writer, "#include <utility>").unwrap();
+    writeln!(writer, "#include <vector>\n").unwrap();
+    writeln!(writer, "namespace {namespace} {{\n").unwrap();
+    write!(writer, "{ADAPTER_TRAITS}").unwrap();
+    writeln!(writer, "\n}}  // end of namespace {namespace}").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+
+    let path = out_dir.join(format!("{file_prefix}_adapters.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(
+        writer,
+        "#include \"{file_prefix}_adapter_traits.hpp\"  // IWYU pragma: export"
+    )
+    .unwrap();
+    for class in &functions.classes {
+        writeln!(
+            writer,
+            "#include \"{file_prefix}_{}.hpp\"",
+            class.name.to_ascii_lowercase()
+        )
+        .unwrap();
+    }
+    writeln!(
+        writer,
+        "#include \"{file_prefix}_free_standing_functions.hpp\"\n"
+    )
+    .unwrap();
+    writeln!(writer, "namespace {namespace} {{\n").unwrap();
+    for class in &functions.classes {
+        let name = &class.name;
+        writeln!(writer, "template <typename Traits = StdTypes>").unwrap();
+        writeln!(writer, "class {name}Adapter {{").unwrap();
+        writeln!(writer, "    {name}Holder inner;").unwrap();
+        writeln!(writer, "public:").unwrap();
+        writeln!(
+            writer,
+            "    explicit {name}Adapter({name}* ptr) : inner(ptr) {{}}\n"
+        )
+        .unwrap();
+        writeln!(writer, "    bool is_valid() const {{").unwrap();
+        writeln!(writer, "        return this->inner.is_valid();").unwrap();
+        writeln!(writer, "    }}\n").unwrap();
+        writeln!(writer, "    {name}Holder& holder() {{").unwrap();
+        writeln!(writer, "        return this->inner;").unwrap();
+        writeln!(writer, "    }}").unwrap();
+        for function in &class.functions {
+            writeln!(writer).unwrap();
+            write_adapted_function(&mut writer, function, "this->inner.", "    ")?;
+        }
+        writeln!(writer, "}};\n").unwrap();
+    }
+    writeln!(writer, "namespace adapter {{").unwrap();
+    for function in &functions.free_standing {
+        writeln!(writer).unwrap();
+        writeln!(writer, "template <typename Traits = StdTypes>").unwrap();
+        write_adapted_function(&mut writer, function, &format!("{namespace}::"), "")?;
+    }
+    writeln!(writer, "\n}}  // end of namespace adapter\n").unwrap();
+    writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Check at runtime that the loaded library matches the generated bindings
+//!
+//! If [`Config::api_version`](crate::Config::api_version) is set, the generated
+//! `api_functions.hpp` contains the version as `constexpr` constants. The extern function
+//! generated via [`export_api_version!`](crate::export_api_version) returns the version of
+//! the library, so C++ code can compare both before calling any other function.
+
+/// Encodes a version as a single number
+///
+/// The major version is stored in the upper 32 bits, the minor version in the next 16 bits
+/// and the patch version in the lower 16 bits, so versions compare like the encoded numbers.
+pub const fn encode(major: u32, minor: u16, patch: u16) -> u64 {
+    ((major as u64) << 32) | ((minor as u64) << 16) | patch as u64
+}
+
+/// Parses a semantic version like `1.2.3`
+///
+/// Pre-release and build metadata (`1.2.3-beta+build`) are ignored.
+pub(crate) fn parse(version: &str) -> Option<(u32, u16, u16)> {
+    let version = version
+        .split(['-', '+'])
+        .next()
+        .expect("Split returns at least one element");
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((major, minor, patch))
+}
+
+/// Generates the `buffi_api_version` extern function
+///
+/// Without arguments the version of the crate calling this macro is used, otherwise
+/// pass the major, minor and patch version (`export_api_version!(1, 2, 0)`). This needs to
+/// match `Config::api_version`. For a custom function prefix pass it first
+/// (`export_api_version!(prefix = "myapi")` or `export_api_version!(prefix = "myapi", 1, 2, 0)`).
+///
+/// This macro needs to be called in your API crate, so that the function is picked up
+/// by the code generation.
+#[macro_export]
+macro_rules! export_api_version {
+    () => {
+        $crate::export_api_version!(prefix = "buffi");
+    };
+    (prefix = $prefix:literal) => {
+        $crate::export_api_version!(
+            prefix = $prefix,
+            env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap(),
+            env!("CARGO_PKG_VERSION_MINOR").parse().unwrap(),
+            env!("CARGO_PKG_VERSION_PATCH").parse().unwrap()
+        );
+    };
+    (prefix = $prefix:literal, $major: expr, $minor: expr, $patch: expr) => {
+        $crate::__prefixed! {
+            prefix = $prefix;
+
+            /// The version of this library
+            ///
+            /// The major version is stored in the upper 32 bits, the minor version in the next
+            /// 16 bits and the patch version in the lower 16 bits. Compare it with the
+            /// `API_VERSION` constant of the generated bindings
+            #[no_mangle]
+            pub extern "C" fn api_version() -> u64 {
+                $crate::api_version::encode($major, $minor, $patch)
+            }
+        }
+    };
+    ($major: expr, $minor: expr, $patch: expr) => {
+        $crate::export_api_version!(prefix = "buffi", $major, $minor, $patch);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(parse("1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse("0.10.0-beta.1+build"), Some((0, 10, 0)));
+        assert_eq!(parse("1.2"), None);
+        assert_eq!(parse("1.2.3.4"), None);
+        assert_eq!(parse("1.x.3"), None);
+    }
+
+    #[test]
+    fn encoded_versions_are_ordered() {
+        assert_eq!(encode(1, 2, 3), 0x0000_0001_0002_0003);
+        assert!(encode(1, 0, 0) > encode(0, 65535, 65535));
+        assert!(encode(1, 2, 0) > encode(1, 1, 9));
+    }
+}
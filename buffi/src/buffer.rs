@@ -0,0 +1,58 @@
+//! Byte buffers allocated for the caller
+//!
+//! Usually the C++ side passes pointers to its own memory to the exported functions. Hosts that
+//! can not do that, like JavaScript calling into a `wasm32-unknown-unknown` module, need to
+//! allocate the argument buffers in the linear memory of the module instead. The function
+//! generated via [`export_alloc_byte_buffer!`](crate::export_alloc_byte_buffer) does that, the
+//! buffers are freed via `{prefix}_free_byte_buffer` like the result buffers.
+
+/// Allocates a zeroed buffer of `size` bytes
+///
+/// The buffer can be freed like a result buffer, i.e. via `Vec::from_raw_parts(ptr, size, size)`.
+pub fn alloc_byte_buffer(size: usize) -> *mut u8 {
+    crate::stats::record_allocation(size);
+    let buffer = vec![0_u8; size].into_boxed_slice();
+    Box::into_raw(buffer).cast()
+}
+
+/// Generates the `buffi_alloc_byte_buffer` extern function
+///
+/// This macro needs to be called in your API crate, so that the function is picked up
+/// by the code generation. For a custom function prefix (see `Config::function_prefix`) call it
+/// as `buffi::export_alloc_byte_buffer!(prefix = "myapi")` to generate `myapi_alloc_byte_buffer`.
+#[macro_export]
+macro_rules! export_alloc_byte_buffer {
+    () => {
+        $crate::export_alloc_byte_buffer!(prefix = "buffi");
+    };
+    (prefix = $prefix:literal) => {
+        $crate::__prefixed! {
+            prefix = $prefix;
+
+            /// Allocate a buffer of `size` bytes in the memory of this library, for example to
+            /// pass arguments from a WASM host
+            ///
+            /// The buffer needs to be freed via `{prefix}_free_byte_buffer`
+            #[no_mangle]
+            pub extern "C" fn alloc_byte_buffer(size: usize) -> *mut u8 {
+                $crate::buffer::alloc_byte_buffer(size)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::alloc_byte_buffer;
+
+    #[test]
+    fn buffers_can_be_freed_as_vec() {
+        for size in [0, 1, 1024] {
+            let ptr = alloc_byte_buffer(size);
+            assert!(!ptr.is_null());
+            // SAFETY: the buffer was allocated as boxed slice of `size` bytes
+            let buffer = unsafe { Vec::from_raw_parts(ptr, size, size) };
+            assert!(buffer.iter().all(|b| *b == 0));
+        }
+    }
+}
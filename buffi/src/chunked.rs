@@ -0,0 +1,212 @@
+//! Transfer large `Vec` results in several chunks
+//!
+//! Functions marked with `#[buffi(chunked)]` do not serialize their whole result into a single
+//! buffer. Instead the extern function returns a [`ChunkedResult`] and the C++ side requests one
+//! chunk after another via `buffi_next_chunk`. All chunks are serialized into the same buffer,
+//! so the serialized form of only a single chunk is kept in memory at a time.
+//!
+//! The extern functions are generated via [`export_chunked_results!`](crate::export_chunked_results).
+
+/// The number of elements per chunk if `#[buffi(chunked)]` is used without a length
+pub const DEFAULT_CHUNK_LEN: usize = 65536;
+
+type SerializeChunk = dyn FnMut(&mut Vec<u8>) -> bool + Send;
+
+/// A result that is transferred in several chunks
+pub struct ChunkedResult {
+    len: usize,
+    buffer: Vec<u8>,
+    next: Box<SerializeChunk>,
+}
+
+impl ChunkedResult {
+    /// Split `items` into chunks of `chunk_len` elements
+    ///
+    /// `serialize` is called for each chunk once it is requested and appends the
+    /// serialized chunk to the given buffer.
+    pub fn new<T, E>(
+        items: Vec<T>,
+        chunk_len: usize,
+        mut serialize: impl FnMut(&[T], &mut Vec<u8>) -> Result<(), E> + Send + 'static,
+    ) -> Self
+    where
+        T: Send + 'static,
+    {
+        let len = items.len();
+        let chunk_len = chunk_len.max(1);
+        let mut offset = 0;
+        let next = move |buffer: &mut Vec<u8>| {
+            if offset >= items.len() {
+                return false;
+            }
+            let end = usize::min(offset.saturating_add(chunk_len), items.len());
+            let chunk = &items[offset..end];
+            // a failed chunk ends the transfer instead of skipping the chunk
+            offset = if serialize(chunk, buffer).is_ok() {
+                end
+            } else {
+                items.len()
+            };
+            offset == end
+        };
+        Self {
+            len,
+            buffer: Vec::new(),
+            next: Box::new(next),
+        }
+    }
+
+    /// The number of elements in all chunks
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the result does not contain any elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Serializes the next chunk, returns `None` after the last chunk
+    ///
+    /// If a chunk cannot be serialized, the transfer ends early. The C++ side detects
+    /// this by comparing the number of received elements with [`ChunkedResult::len`].
+    pub fn next_chunk(&mut self) -> Option<&[u8]> {
+        self.buffer.clear();
+        if (self.next)(&mut self.buffer) {
+            Some(&self.buffer)
+        } else {
+            None
+        }
+    }
+}
+
+/// Generates the `buffi_next_chunk`, `buffi_chunked_result_len` and `buffi_free_chunked_result`
+/// extern functions
+///
+/// These functions are required by the C++ wrappers of functions marked with `#[buffi(chunked)]`.
+/// This macro needs to be called in your API crate, so that the functions are picked up
+/// by the code generation. For a custom function prefix (see `Config::function_prefix`) call it
+/// as `buffi::export_chunked_results!(prefix = "myapi")` to generate `myapi_next_chunk` and so on.
+#[macro_export]
+macro_rules! export_chunked_results {
+    () => {
+        $crate::export_chunked_results!(prefix = "buffi");
+    };
+    (prefix = $prefix:literal) => {
+        $crate::__prefixed! {
+            prefix = $prefix;
+
+            /// Serializes the next chunk of `chunks` and points `out_ptr` to it
+            ///
+            /// The buffer is owned by `chunks` and overwritten by the next call, so it must
+            /// not be freed. Returns 0 after the last chunk.
+            ///
+            /// # Safety
+            /// `chunks` needs to be returned by a chunked function and must not be freed yet
+            #[no_mangle]
+            pub unsafe extern "C" fn next_chunk(
+                chunks: *mut $crate::chunked::ChunkedResult,
+                out_ptr: *mut *const u8,
+            ) -> usize {
+                if chunks.is_null() || out_ptr.is_null() {
+                    return 0;
+                }
+                let chunks = unsafe { &mut *chunks };
+                match chunks.next_chunk() {
+                    Some(chunk) => {
+                        unsafe { *out_ptr = chunk.as_ptr() };
+                        chunk.len()
+                    }
+                    None => {
+                        unsafe { *out_ptr = std::ptr::null() };
+                        0
+                    }
+                }
+            }
+
+            /// The number of elements in all chunks of `chunks`
+            ///
+            /// # Safety
+            /// `chunks` needs to be returned by a chunked function and must not be freed yet
+            #[no_mangle]
+            pub unsafe extern "C" fn chunked_result_len(
+                chunks: *const $crate::chunked::ChunkedResult,
+            ) -> usize {
+                if chunks.is_null() {
+                    return 0;
+                }
+                unsafe { &*chunks }.len()
+            }
+
+            /// Free the chunks returned by a chunked function
+            ///
+            /// # Safety
+            /// `chunks` needs to be returned by a chunked function and must not be freed yet
+            #[no_mangle]
+            pub unsafe extern "C" fn free_chunked_result(
+                chunks: *mut $crate::chunked::ChunkedResult,
+            ) {
+                if !chunks.is_null() {
+                    drop(unsafe { Box::from_raw(chunks) });
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize(chunk: &[i64], buffer: &mut Vec<u8>) -> Result<(), ()> {
+        buffer.extend(chunk.iter().map(|i| *i as u8));
+        Ok(())
+    }
+
+    #[test]
+    fn items_are_split_into_chunks() {
+        let mut chunks = ChunkedResult::new(vec![1, 2, 3, 4, 5], 2, serialize);
+        assert_eq!(chunks.len(), 5);
+        assert_eq!(chunks.next_chunk(), Some(&[1, 2][..]));
+        assert_eq!(chunks.next_chunk(), Some(&[3, 4][..]));
+        assert_eq!(chunks.next_chunk(), Some(&[5][..]));
+        assert_eq!(chunks.next_chunk(), None);
+        assert_eq!(chunks.next_chunk(), None);
+    }
+
+    #[test]
+    fn empty_results_have_no_chunks() {
+        let mut chunks = ChunkedResult::new(Vec::new(), 2, serialize);
+        assert!(chunks.is_empty());
+        assert_eq!(chunks.next_chunk(), None);
+    }
+
+    #[test]
+    fn serialization_errors_end_the_transfer() {
+        let mut chunks = ChunkedResult::new(vec![1, 2, 3], 1, |chunk: &[i64], buffer| {
+            if chunk[0] == 2 {
+                Err(())
+            } else {
+                serialize(chunk, buffer)
+            }
+        });
+        assert_eq!(chunks.next_chunk(), Some(&[1][..]));
+        assert_eq!(chunks.next_chunk(), None);
+        assert_eq!(chunks.next_chunk(), None);
+    }
+
+    crate::export_chunked_results!(prefix = "chunked_test");
+
+    #[test]
+    fn extern_functions_use_the_prefix() {
+        let chunks = Box::into_raw(Box::new(ChunkedResult::new(vec![1, 2, 3], 2, serialize)));
+        let mut chunk_ptr = std::ptr::null();
+        // SAFETY: `chunks` is a valid chunked result that is freed at the end
+        unsafe {
+            assert_eq!(chunked_test_chunked_result_len(chunks), 3);
+            assert_eq!(chunked_test_next_chunk(chunks, &mut chunk_ptr), 2);
+            assert_eq!(std::slice::from_raw_parts(chunk_ptr, 2), &[1, 2]);
+            chunked_test_free_chunked_result(chunks);
+        }
+    }
+}
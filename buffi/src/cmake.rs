@@ -0,0 +1,250 @@
+//! Consume the generated headers from CMake
+//!
+//! If [`Config::cmake`](crate::Config::cmake) is set, a `{file_prefix}-config.cmake` is written
+//! to the output directory. It defines an `INTERFACE` library that adds the include directories
+//! of the generated headers, requires the C++ standard they need and links the API library
+//! (including the system libraries a static Rust library depends on). It can be found via
+//! `find_package`, or pulled in via `add_subdirectory` by the `CMakeLists.txt` next to it.
+
+use crate::{create_file, finish_file, write_foreign_file_comment, Config, Error};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// The oldest standard the generated headers compile with
+const MIN_CXX_STANDARD: u32 = 17;
+
+/// How the API library is built, see [`CMakeIntegration::library_kind`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryKind {
+    /// A `staticlib`, linked together with the system libraries the Rust standard library needs
+    #[default]
+    Static,
+    /// A `cdylib`, linked via its import library on Windows
+    Dynamic,
+}
+
+/// The CMake files generated next to the headers, see [`Config::cmake`](crate::Config::cmake)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CMakeIntegration {
+    /// The name of the `INTERFACE` library, the file prefix by default
+    pub target_name: Option<String>,
+    /// The C++ standard required from targets linking the library (at least and by default 17)
+    pub cxx_standard: Option<u32>,
+    /// Whether the API library is linked statically (the default) or dynamically
+    pub library_kind: Option<LibraryKind>,
+    /// The directory containing the built API library (e.g. `target/release`), relative paths
+    /// are relative to the output directory, which is also the default
+    pub library_dir: Option<PathBuf>,
+    /// The libraries linked in addition to a static API library, a default for the current
+    /// platform is used if unset. `cargo rustc -- --print native-static-libs` prints the exact
+    /// list
+    pub native_libraries: Option<Vec<String>>,
+}
+
+/// Converts `path` to a CMake path, relative paths are resolved against the generated file
+fn cmake_path(path: &Path) -> String {
+    let path = path.display().to_string().replace('\\', "/");
+    if Path::new(&path).is_absolute() || path.starts_with('/') {
+        path
+    } else if path.is_empty() || path == "." {
+        String::from("${CMAKE_CURRENT_LIST_DIR}")
+    } else {
+        format!("${{CMAKE_CURRENT_LIST_DIR}}/{path}")
+    }
+}
+
+/// The content of `{file_prefix}-config.cmake`, without the file comment
+fn config_file(cmake: &CMakeIntegration, config: &Config, target: &str) -> Result<String, Error> {
+    let cxx_standard = cmake.cxx_standard.unwrap_or(MIN_CXX_STANDARD);
+    if cxx_standard < MIN_CXX_STANDARD {
+        return Err(Error::Generation(format!(
+            "The generated headers require at least C++{MIN_CXX_STANDARD}, got C++{cxx_standard}"
+        )));
+    }
+    // cargo replaces `-` in the name of the library
+    let library_name = config.api_lib_name.replace('-', "_");
+    let library_dir = cmake_path(cmake.library_dir.as_deref().unwrap_or(Path::new("")));
+
+    let mut include_dirs = vec![cmake_path(Path::new(""))];
+    if let Some(ref layout) = config.layout {
+        for directory in [&layout.headers, &layout.extern_declarations]
+            .into_iter()
+            .flatten()
+        {
+            let directory = cmake_path(directory);
+            if !include_dirs.contains(&directory) {
+                include_dirs.push(directory);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "if(NOT TARGET {target})").unwrap();
+    writeln!(out, "  add_library({target} INTERFACE)").unwrap();
+    for directory in include_dirs {
+        writeln!(
+            out,
+            "  target_include_directories({target} INTERFACE \"{directory}\")"
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "  target_compile_features({target} INTERFACE cxx_std_{cxx_standard})"
+    )
+    .unwrap();
+    match cmake.library_kind.unwrap_or_default() {
+        LibraryKind::Static => {
+            writeln!(
+                out,
+                "  target_link_libraries({target} INTERFACE \"{library_dir}/${{CMAKE_STATIC_LIBRARY_PREFIX}}{library_name}${{CMAKE_STATIC_LIBRARY_SUFFIX}}\")"
+            )
+            .unwrap();
+            match cmake.native_libraries {
+                Some(ref libraries) if libraries.is_empty() => {}
+                Some(ref libraries) => {
+                    writeln!(
+                        out,
+                        "  target_link_libraries({target} INTERFACE {})",
+                        libraries.join(" ")
+                    )
+                    .unwrap();
+                }
+                None => {
+                    writeln!(out, "  if(WIN32)").unwrap();
+                    writeln!(
+                        out,
+                        "    target_link_libraries({target} INTERFACE ws2_32 userenv bcrypt ntdll)"
+                    )
+                    .unwrap();
+                    writeln!(out, "  else()").unwrap();
+                    writeln!(out, "    find_package(Threads REQUIRED)").unwrap();
+                    writeln!(
+                        out,
+                        "    target_link_libraries({target} INTERFACE Threads::Threads ${{CMAKE_DL_LIBS}} m)"
+                    )
+                    .unwrap();
+                    writeln!(out, "  endif()").unwrap();
+                }
+            }
+        }
+        LibraryKind::Dynamic => {
+            writeln!(out, "  if(WIN32)").unwrap();
+            writeln!(
+                out,
+                "    target_link_libraries({target} INTERFACE \"{library_dir}/{library_name}.dll.lib\")"
+            )
+            .unwrap();
+            writeln!(out, "  else()").unwrap();
+            writeln!(
+                out,
+                "    target_link_libraries({target} INTERFACE \"{library_dir}/${{CMAKE_SHARED_LIBRARY_PREFIX}}{library_name}${{CMAKE_SHARED_LIBRARY_SUFFIX}}\")"
+            )
+            .unwrap();
+            writeln!(out, "  endif()").unwrap();
+        }
+    }
+    writeln!(out, "endif()").unwrap();
+    Ok(out)
+}
+
+/// Generates `{file_prefix}-config.cmake` and a `CMakeLists.txt` including it
+pub(crate) fn generate_cmake_files(
+    cmake: &CMakeIntegration,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let target = cmake.target_name.as_ref().unwrap_or(file_prefix);
+    let out_dir = PathBuf::from(out_dir);
+
+    let config_name = format!("{file_prefix}-config.cmake");
+    let path = out_dir.join(&config_name);
+    let mut writer = create_file(&path)?;
+    write_foreign_file_comment(&mut writer, config, &path, provenance, |text| {
+        format!("#{text}")
+    })?;
+    write!(writer, "{}", config_file(cmake, config, target)?).unwrap();
+    finish_file(writer, &path)?;
+
+    let path = out_dir.join("CMakeLists.txt");
+    let mut writer = create_file(&path)?;
+    write_foreign_file_comment(&mut writer, config, &path, provenance, |text| {
+        format!("#{text}")
+    })?;
+    writeln!(
+        writer,
+        "include(\"${{CMAKE_CURRENT_LIST_DIR}}/{config_name}\")"
+    )
+    .unwrap();
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_the_static_library_by_default() {
+        let config = Config::new(
+            String::from("api"),
+            String::from("api-lib"),
+            String::from("api-lib"),
+            Vec::new(),
+        );
+        let cmake = CMakeIntegration {
+            library_dir: Some(PathBuf::from("../target/release")),
+            ..Default::default()
+        };
+        let content = config_file(&cmake, &config, "api").unwrap();
+        assert!(content.contains("add_library(api INTERFACE)"));
+        assert!(content
+            .contains("target_include_directories(api INTERFACE \"${CMAKE_CURRENT_LIST_DIR}\")"));
+        assert!(content.contains("target_compile_features(api INTERFACE cxx_std_17)"));
+        assert!(content.contains("\"${CMAKE_CURRENT_LIST_DIR}/../target/release/${CMAKE_STATIC_LIBRARY_PREFIX}api_lib${CMAKE_STATIC_LIBRARY_SUFFIX}\""));
+        assert!(content.contains("Threads::Threads"));
+    }
+
+    #[test]
+    fn links_the_import_library_of_a_dynamic_library_on_windows() {
+        let config = Config::new(
+            String::from("api"),
+            String::from("api_lib"),
+            String::from("api_lib"),
+            Vec::new(),
+        );
+        let cmake = CMakeIntegration {
+            cxx_standard: Some(20),
+            library_kind: Some(LibraryKind::Dynamic),
+            library_dir: Some(PathBuf::from("/opt/api/lib")),
+            ..Default::default()
+        };
+        let content = config_file(&cmake, &config, "api").unwrap();
+        assert!(content.contains("target_compile_features(api INTERFACE cxx_std_20)"));
+        assert!(content.contains("\"/opt/api/lib/api_lib.dll.lib\""));
+        assert!(content.contains(
+            "\"/opt/api/lib/${CMAKE_SHARED_LIBRARY_PREFIX}api_lib${CMAKE_SHARED_LIBRARY_SUFFIX}\""
+        ));
+        assert!(!content.contains("Threads::Threads"));
+    }
+    #[test]
+    fn rejects_standards_before_cpp17() {
+        let config = Config::new(
+            String::from("api"),
+            String::from("api_lib"),
+            String::from("api_lib"),
+            Vec::new(),
+        );
+        let cmake = CMakeIntegration {
+            cxx_standard: Some(14),
+            ..Default::default()
+        };
+        let error = config_file(&cmake, &config, "api").unwrap_err();
+        assert!(error.to_string().contains("at least C++17"));
+    }
+}
@@ -0,0 +1,20 @@
+//! The JSON encoding of the debug functions
+//!
+//! With the `debug_json` feature of `buffi_macro`, a `buffi_json_{function_name}` function is
+//! exported next to each extern function that returns its result via `out_ptr`. It takes and
+//! returns the same buffers, but the arguments and the result are encoded as JSON instead of
+//! bincode, so that they can be read in a debugger or written by scripting languages. The
+//! generated code calls [`serialize`] and [`deserialize`] in place of bincode.
+//!
+//! Errors are returned as strings, which the `SerializableError` of the API crate can be
+//! created from. Use the bincode functions in production, JSON is larger and slower to decode.
+
+/// Encodes `value` as JSON
+pub fn serialize<T: serde::Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(value).map_err(|e| e.to_string())
+}
+
+/// Decodes a value from the JSON in `bytes`
+pub fn deserialize<'a, T: serde::Deserialize<'a>>(bytes: &'a [u8]) -> Result<T, String> {
+    serde_json::from_slice(bytes).map_err(|e| e.to_string())
+}
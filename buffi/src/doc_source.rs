@@ -0,0 +1,100 @@
+//! Where the rustdoc JSON output of the API crates comes from
+//!
+//! [`generate_bindings`](crate::generate_bindings) runs rustdoc via cargo ([`CargoRustdoc`]).
+//! Other sources, like pre-built JSON artifacts ([`JsonDirectory`]) or fixtures in tests, can be
+//! used via [`generate_bindings_with_source`](crate::generate_bindings_with_source).
+
+use crate::{generate_docs, Config};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Provides the rustdoc JSON output of the crates listed in a [`Config`]
+pub trait DocSource {
+    /// Called once before any documentation is loaded, e.g. to run rustdoc
+    fn prepare(&mut self, config: &Config) -> Result<(), Box<dyn Error>>;
+
+    /// Loads the documentation of `crate_name`
+    ///
+    /// This is the name of the API library or a crate name as used in paths (so with `_`
+    /// instead of `-`).
+    fn load(&self, crate_name: &str) -> Result<rustdoc_types::Crate, Box<dyn Error>>;
+}
+
+/// Runs `cargo rustdoc` for all crates of the config and reads the JSON files it writes
+#[derive(Debug, Default)]
+pub struct CargoRustdoc {
+    json_directory: Option<PathBuf>,
+}
+
+impl DocSource for CargoRustdoc {
+    fn prepare(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        let mut rustdoc_flags = config.rustdoc_flags.clone().unwrap_or_default();
+        if let Some(target) = &config.target {
+            rustdoc_flags.extend([String::from("--target"), target.clone()]);
+        }
+        let target_directory = generate_docs(
+            &config.api_lib_name,
+            &config.rustdoc_crates,
+            config.crate_feature_flags.as_ref().unwrap_or(&Vec::new()),
+            &rustdoc_flags,
+            config.include_hidden_items == Some(true),
+            config.force_regenerate == Some(true),
+        )?;
+        let mut json_directory = PathBuf::from(target_directory);
+        if let Some(target) = &config.target {
+            json_directory.push(target);
+        }
+        json_directory.push("doc");
+        self.json_directory = Some(json_directory);
+        Ok(())
+    }
+
+    fn load(&self, crate_name: &str) -> Result<rustdoc_types::Crate, Box<dyn Error>> {
+        let json_directory = self
+            .json_directory
+            .as_ref()
+            .ok_or("`CargoRustdoc::prepare` needs to be called first")?;
+        JsonDirectory::new(json_directory).load(crate_name)
+    }
+}
+
+/// Reads pre-built rustdoc JSON files (`{crate_name}.json`) from a directory
+#[derive(Debug)]
+pub struct JsonDirectory {
+    path: PathBuf,
+}
+
+impl JsonDirectory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl DocSource for JsonDirectory {
+    fn prepare(&mut self, _config: &Config) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn load(&self, crate_name: &str) -> Result<rustdoc_types::Crate, Box<dyn Error>> {
+        let content = std::fs::read_to_string(self.path.join(format!("{crate_name}.json")))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_json_files_are_reported() {
+        let source = JsonDirectory::new(std::env::temp_dir().join("buffi_missing_docs"));
+        assert!(source.load("missing_crate").is_err());
+    }
+
+    #[test]
+    fn cargo_rustdoc_needs_to_be_prepared() {
+        let source = CargoRustdoc::default();
+        let error = source.load("buffi").unwrap_err();
+        assert!(error.to_string().contains("prepare"));
+    }
+}
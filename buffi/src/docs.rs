@@ -0,0 +1,464 @@
+//! Conversion of Rust doc comments into plain C++ comments
+//!
+//! Rust doc comments are written in Markdown and often contain intra-doc links,
+//! code fences and headings. These read poorly as raw `//` comments, so they are
+//! rendered into plain text before they are written to the generated headers.
+//!
+//! With [`DocStyle::Doxygen`] the docs of the generated functions are written as
+//! `/** ... */` blocks instead. The `# Arguments`, `# Returns` and `# Errors` sections
+//! become the descriptions of the `@param`, `@return` and `@throws` tags.
+
+use serde::{Deserialize, Serialize};
+
+/// How the docs of the generated functions are written, see
+/// [`Config::doc_style`](crate::Config::doc_style)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DocStyle {
+    /// `// line` comments
+    #[default]
+    Plain,
+    /// `/** ... */` blocks with `@param`, `@return` and `@throws` tags
+    Doxygen,
+}
+
+/// The signature of a generated C++ function, as far as it is documented by Doxygen tags
+pub(crate) struct FunctionDocs<'a> {
+    /// The names of the parameters, with a description for those that are not arguments of
+    /// the Rust function
+    pub(crate) params: Vec<(&'a str, Option<&'a str>)>,
+    /// The returned C++ type, `None` for `void`
+    pub(crate) returns: Option<&'a str>,
+    /// The C++ type of the thrown error, if the Rust function returns a `Result`
+    pub(crate) throws: Option<&'a str>,
+}
+
+/// Writes the comment of a generated function, `notes` describe the generated function and
+/// follow the docs of the Rust function
+pub(crate) fn write_function_docs(
+    out: &mut impl std::io::Write,
+    style: DocStyle,
+    indent: &str,
+    docs: Option<&str>,
+    notes: &[&str],
+    function: &FunctionDocs,
+) {
+    if style == DocStyle::Plain {
+        let docs = docs.map(to_cpp_doc).unwrap_or_default();
+        for line in docs.lines() {
+            writeln!(out, "{}", format!("{indent}// {line}").trim_end()).unwrap();
+        }
+        if !docs.is_empty() && !notes.is_empty() {
+            writeln!(out, "{indent}//").unwrap();
+        }
+        for note in notes {
+            writeln!(out, "{indent}// {note}").unwrap();
+        }
+        return;
+    }
+    let sections = DocSections::parse(docs.unwrap_or_default());
+    let mut lines = to_cpp_doc(&sections.description)
+        .lines()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if !lines.is_empty() && !notes.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(notes.iter().map(|note| note.to_string()));
+    let mut tags = Vec::new();
+    for (name, description) in &function.params {
+        let description = sections
+            .arguments
+            .iter()
+            .find(|(argument, _)| argument == name)
+            .map(|(_, description)| description.as_str())
+            .or(*description);
+        tags.push(match description {
+            Some(description) => format!("@param {name} {description}"),
+            None => format!("@param {name}"),
+        });
+    }
+    if let Some(returns) = function.returns {
+        let description = sections
+            .returns
+            .unwrap_or_else(|| format!("The result as `{returns}`"));
+        tags.push(format!("@return {description}"));
+    }
+    if let Some(throws) = function.throws {
+        let description = sections
+            .errors
+            .unwrap_or_else(|| String::from("If the Rust function returns an error"));
+        tags.push(format!("@throws {throws} {description}"));
+    }
+    if !lines.is_empty() && !tags.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(tags);
+    if lines.is_empty() {
+        return;
+    }
+    writeln!(out, "{indent}/**").unwrap();
+    for line in lines {
+        writeln!(out, "{}", format!("{indent} * {line}").trim_end()).unwrap();
+    }
+    writeln!(out, "{indent} */").unwrap();
+}
+
+/// A doc comment split into the sections that become Doxygen tags and the remaining
+/// description
+#[derive(Debug, Default, PartialEq)]
+struct DocSections {
+    /// The Markdown without the sections below
+    description: String,
+    /// The items of the `# Arguments` section, like ``* `name` - description``
+    arguments: Vec<(String, String)>,
+    /// The `# Returns` section
+    returns: Option<String>,
+    /// The `# Errors` section
+    errors: Option<String>,
+}
+
+impl DocSections {
+    fn parse(docs: &str) -> Self {
+        #[derive(PartialEq)]
+        enum Section {
+            Description,
+            Arguments,
+            Returns,
+            Errors,
+        }
+
+        let mut sections = Self::default();
+        let mut description = Vec::new();
+        let mut returns = Vec::new();
+        let mut errors = Vec::new();
+        let mut section = Section::Description;
+        let mut code_block = false;
+        for line in docs.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                code_block = !code_block;
+            }
+            let heading = trimmed
+                .strip_prefix('#')
+                .filter(|heading| !code_block && heading.starts_with(['#', ' ']));
+            if let Some(heading) = heading {
+                section = match heading.trim_start_matches('#').trim() {
+                    "Arguments" | "Parameters" => Section::Arguments,
+                    "Returns" => Section::Returns,
+                    "Errors" => Section::Errors,
+                    _ => Section::Description,
+                };
+                if section != Section::Description {
+                    continue;
+                }
+            }
+            match section {
+                Section::Description => description.push(line),
+                Section::Arguments => match argument_item(line) {
+                    Some(argument) => sections.arguments.push(argument),
+                    None if !trimmed.is_empty() => {
+                        // continuation lines of the last argument
+                        if let Some((_, description)) = sections.arguments.last_mut() {
+                            description.push(' ');
+                            description.push_str(trimmed);
+                        }
+                    }
+                    None => {}
+                },
+                Section::Returns => returns.push(line),
+                Section::Errors => errors.push(line),
+            }
+        }
+        sections.description = description.join("\n");
+        for (_, description) in &mut sections.arguments {
+            *description = to_single_line(description);
+        }
+        sections.returns = Some(to_single_line(&returns.join("\n"))).filter(|r| !r.is_empty());
+        sections.errors = Some(to_single_line(&errors.join("\n"))).filter(|e| !e.is_empty());
+        sections
+    }
+}
+
+/// A list item like ``* `name` - description`` or `- name: description`
+fn argument_item(line: &str) -> Option<(String, String)> {
+    let item = line.trim_start().strip_prefix(['*', '-'])?.trim_start();
+    let (name, description) = match item.strip_prefix('`') {
+        Some(item) => item.split_once('`')?,
+        None => item.split_once(|c: char| c.is_whitespace() || c == ':')?,
+    };
+    let description = description
+        .trim_start()
+        .trim_start_matches(['-', ':'])
+        .trim_start();
+    Some((name.to_owned(), description.to_owned()))
+}
+
+/// Renders the Markdown of a section as a single line, as expected after a Doxygen tag
+fn to_single_line(docs: &str) -> String {
+    to_cpp_doc(docs)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Render the Markdown of a Rust doc comment as plain text
+///
+/// * Intra-doc links are replaced by their text, external links keep their target
+/// * Code fences are removed and the code is indented instead (hidden `# ` lines of
+///   Rust code blocks are dropped)
+/// * Headings end with a colon
+/// * `**strong**` emphasis is removed
+/// * Link reference definitions and consecutive empty lines are removed
+pub(crate) fn to_cpp_doc(docs: &str) -> String {
+    let mut out = Vec::new();
+    let mut code_block: Option<bool> = None;
+    for line in docs.lines() {
+        let trimmed = line.trim_start();
+        if let Some(lang) = trimmed
+            .strip_prefix("```")
+            .or_else(|| trimmed.strip_prefix("~~~"))
+        {
+            code_block = match code_block {
+                Some(_) => None,
+                None => Some(is_rust_code_block(lang)),
+            };
+            continue;
+        }
+        match code_block {
+            Some(is_rust) => {
+                if is_rust && (trimmed == "#" || trimmed.starts_with("# ")) {
+                    continue;
+                }
+                if line.is_empty() {
+                    out.push(String::new());
+                } else {
+                    out.push(format!("    {line}"));
+                }
+            }
+            None if is_link_reference_definition(trimmed) => {}
+            None => {
+                let line = strip_inline_markdown(line);
+                let line = match line.trim_start().strip_prefix('#') {
+                    Some(heading) if heading.starts_with(['#', ' ']) => {
+                        format!("{}:", heading.trim_start_matches('#').trim())
+                    }
+                    _ => line,
+                };
+                if line.trim().is_empty() && out.last().is_none_or(|l: &String| l.is_empty()) {
+                    continue;
+                }
+                out.push(line.trim_end().to_owned());
+            }
+        }
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+fn is_rust_code_block(lang: &str) -> bool {
+    let lang = lang.trim();
+    lang.is_empty()
+        || lang
+            .split(',')
+            .any(|l| matches!(l.trim(), "rust" | "ignore" | "no_run" | "should_panic"))
+}
+
+/// Lines like `[name]: crate::path`
+fn is_link_reference_definition(line: &str) -> bool {
+    line.strip_prefix('[')
+        .and_then(|l| l.split_once("]:"))
+        .is_some_and(|(name, _)| !name.is_empty() && !name.contains(']'))
+}
+
+/// Something like `Foo`, `crate::Foo::bar` or `bar()`
+fn is_path(s: &str) -> bool {
+    let s = s.strip_suffix("()").unwrap_or(s);
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+fn is_url(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://")
+}
+
+/// Removes links and strong emphasis, but keeps inline code as it is
+fn strip_inline_markdown(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '`' => {
+                // inline code is kept verbatim
+                let end = rest[1..].find('`').map_or(rest.len(), |e| e + 2);
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            '*' if rest.starts_with("**") => {
+                rest = &rest[2..];
+            }
+            '[' => {
+                let Some(close) = find_closing_bracket(rest) else {
+                    out.push(c);
+                    rest = &rest[1..];
+                    continue;
+                };
+                let text = &rest[1..close];
+                let after = &rest[close + 1..];
+                if let Some(target) = after.strip_prefix('(') {
+                    if let Some(end) = target.find(')') {
+                        out.push_str(&strip_inline_markdown(text));
+                        let (link, tail) = target.split_at(end);
+                        if is_url(link) {
+                            out.push_str(&format!(" ({link})"));
+                        }
+                        rest = &tail[1..];
+                        continue;
+                    }
+                } else if let Some(reference) = after.strip_prefix('[') {
+                    if let Some(end) = reference.find(']') {
+                        out.push_str(&strip_inline_markdown(text));
+                        rest = &reference[end + 1..];
+                        continue;
+                    }
+                } else if is_path(text.trim_matches('`')) {
+                    out.push_str(text);
+                    rest = after;
+                    continue;
+                }
+                out.push(c);
+                rest = &rest[1..];
+            }
+            c => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+fn find_closing_bracket(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_code = false;
+    for (idx, c) in s.char_indices() {
+        match c {
+            '`' => in_code = !in_code,
+            '[' if !in_code => depth += 1,
+            ']' if !in_code => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_cpp_doc, write_function_docs, DocSections, DocStyle, FunctionDocs};
+
+    #[test]
+    fn plain_text_is_unchanged() {
+        let docs = "Some content\n\n* `ptr`: The ptr to the buffer\n* `size`: The size";
+        assert_eq!(to_cpp_doc(docs), docs);
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            to_cpp_doc("Uses [`Foo`] and [`bar`](crate::bar) via [Baz]"),
+            "Uses `Foo` and `bar` via Baz"
+        );
+        assert_eq!(
+            to_cpp_doc("See [the docs](https://docs.rs) or [the type][ty]\n\n[ty]: crate::Type"),
+            "See the docs (https://docs.rs) or the type"
+        );
+        // not a link
+        assert_eq!(
+            to_cpp_doc("An array like [f64; 3]"),
+            "An array like [f64; 3]"
+        );
+    }
+
+    #[test]
+    fn code_fences_and_headings() {
+        let docs = "# Example\n\n```rust\n# use foo::Bar;\nlet b = Bar::new();\n```\n\n## Safety\nBe **careful**";
+        assert_eq!(
+            to_cpp_doc(docs),
+            "Example:\n\n    let b = Bar::new();\n\nSafety:\nBe careful"
+        );
+    }
+
+    #[test]
+    fn empty_lines_are_collapsed() {
+        assert_eq!(to_cpp_doc("First\n\n\nSecond\n\n"), "First\n\nSecond");
+    }
+
+    #[test]
+    fn sections_become_doxygen_tags() {
+        let docs =
+            "Scales the point\n\n# Arguments\n\n* `point` - The point to\n  scale\n* `factor`: \
+                    The [factor](crate::Factor)\n\n# Errors\n\nIf `factor` is negative";
+        let sections = DocSections::parse(docs);
+        assert_eq!(
+            sections.arguments,
+            [
+                (String::from("point"), String::from("The point to scale")),
+                (String::from("factor"), String::from("The factor")),
+            ]
+        );
+        assert_eq!(sections.returns, None);
+
+        let function = FunctionDocs {
+            params: vec![("point", None), ("factor", None), ("scale", None)],
+            returns: Some("Point"),
+            throws: Some("SerializableError"),
+        };
+        let mut out = Vec::new();
+        write_function_docs(
+            &mut out,
+            DocStyle::Doxygen,
+            "    ",
+            Some(docs),
+            &[],
+            &function,
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "    /**\n     * Scales the point\n     *\n     * @param point The point to scale\n     \
+             * @param factor The factor\n     * @param scale\n     * @return The result as `Point`\n     \
+             * @throws SerializableError If `factor` is negative\n     */\n"
+        );
+    }
+
+    #[test]
+    fn plain_docs_are_followed_by_the_notes() {
+        let function = FunctionDocs {
+            params: Vec::new(),
+            returns: None,
+            throws: None,
+        };
+        let mut out = Vec::new();
+        write_function_docs(
+            &mut out,
+            DocStyle::Plain,
+            "    ",
+            Some("# Arguments\n* `a` - A"),
+            &["Does not block"],
+            &function,
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "    // Arguments:\n    // * `a` - A\n    //\n    // Does not block\n"
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! Emscripten bindings for the generated C++ API
+//!
+//! If [`Config::embind`](crate::Config::embind) is set, an additional `embind.cpp` file registers
+//! the `*Holder` classes, the free-standing functions and all types they use with embind. Compile
+//! it together with your Rust library via Emscripten to get JavaScript classes for your API. The
+//! matching TypeScript declarations are written to `embind.d.ts`.
+//!
+//! Embind cannot represent every type of the C++ API: enums (`std::variant`), maps, tuples and
+//! recursive types are not supported, functions using such types are skipped. 64 bit integers
+//! are passed as `BigInt`, which requires linking with `-sWASM_BIGINT`.
+
+use crate::summary::SkippedItem;
+use crate::{create_file, finish_file, to_cpp_type_name, type_names::to_type_name, Config, Error};
+use serde_reflection::{ContainerFormat, Format, Registry};
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// The signature of a generated C++ wrapper function
+pub(crate) struct FunctionSignature {
+    /// The name of the Rust function
+    pub(crate) name: String,
+    /// The name of the C++ function, differs from `name` for `#[buffi(overload = "...")]`
+    pub(crate) cpp_name: String,
+    /// The arguments without `self`
+    pub(crate) inputs: Vec<(String, Format)>,
+    pub(crate) output: Format,
+}
+
+/// A generated `*Holder` class
+pub(crate) struct HolderClass {
+    /// The name of the wrapped Rust type
+    pub(crate) name: String,
+    /// Extern functions without arguments that return a pointer to the wrapped type
+    pub(crate) factories: Vec<String>,
+    /// Whether the holder has a static `create()` function (see `#[buffi(constructor)]`)
+    pub(crate) has_constructor: bool,
+    pub(crate) functions: Vec<FunctionSignature>,
+}
+
+/// All generated C++ wrapper functions
+#[derive(Default)]
+pub(crate) struct WrapperFunctions {
+    pub(crate) classes: Vec<HolderClass>,
+    pub(crate) free_standing: Vec<FunctionSignature>,
+}
+
+/// The types that need to be registered with embind, ordered by their JS name
+struct Registrations<'a> {
+    registry: &'a Registry,
+    namespace: &'a str,
+    /// The C++ registration code of each type
+    cpp: BTreeMap<String, String>,
+    /// The TypeScript declaration of each type
+    ts: BTreeMap<String, String>,
+    /// The registered vectors, which can be constructed from JS
+    vectors: BTreeMap<String, Format>,
+}
+
+impl<'a> Registrations<'a> {
+    fn new(registry: &'a Registry, namespace: &'a str) -> Self {
+        Self {
+            registry,
+            namespace,
+            cpp: BTreeMap::new(),
+            ts: BTreeMap::new(),
+            vectors: BTreeMap::new(),
+        }
+    }
+
+    /// Whether embind can represent the given type, `visiting` contains the types that
+    /// are currently checked to detect recursive types
+    fn is_supported(&self, format: &Format, visiting: &mut Vec<String>) -> bool {
+        match format {
+            Format::Bool
+            | Format::I8
+            | Format::I16
+            | Format::I32
+            | Format::I64
+            | Format::U8
+            | Format::U16
+            | Format::U32
+            | Format::U64
+            | Format::F32
+            | Format::F64
+            | Format::Str => true,
+            // embind represents an empty optional as `undefined`, which cannot be nested
+            Format::Option(t) => {
+                !matches!(**t, Format::Option(_)) && self.is_supported(t, visiting)
+            }
+            Format::Seq(t) | Format::TupleArray { content: t, .. } => {
+                self.is_supported(t, visiting)
+            }
+            Format::TypeName(name) => {
+                if visiting.contains(name) {
+                    return false;
+                }
+                visiting.push(name.clone());
+                let supported = match self.registry.get(name) {
+                    Some(ContainerFormat::UnitStruct) => true,
+                    Some(ContainerFormat::NewTypeStruct(t)) => self.is_supported(t, visiting),
+                    Some(ContainerFormat::Struct(fields)) => {
+                        fields.iter().all(|f| self.is_supported(&f.value, visiting))
+                    }
+                    _ => false,
+                };
+                visiting.pop();
+                supported
+            }
+            _ => false,
+        }
+    }
+
+    fn is_supported_signature(&self, signature: &FunctionSignature) -> bool {
+        signature
+            .inputs
+            .iter()
+            .all(|(_, f)| self.is_supported(f, &mut Vec::new()))
+            && (is_void(&signature.output) || self.is_supported(&signature.output, &mut Vec::new()))
+    }
+
+    /// A pointer to the wrapper of `signature` in `scope`
+    ///
+    /// Overloaded wrappers are selected by their signature and bound under the name of the Rust
+    /// function, as JavaScript has no overloads.
+    fn function_pointer(
+        &self,
+        signature: &FunctionSignature,
+        scope: &str,
+        member: bool,
+    ) -> Result<String, Error> {
+        let pointer = format!("&{scope}::{}", signature.cpp_name);
+        if signature.name == signature.cpp_name {
+            return Ok(pointer);
+        }
+        let output = if is_void(&signature.output) {
+            String::from("void")
+        } else {
+            self.cpp_type(&signature.output)?
+        };
+        let inputs = signature
+            .inputs
+            .iter()
+            .map(|(_, format)| Ok(format!("const {}&", self.cpp_type(format)?)))
+            .collect::<Result<Vec<_>, Error>>()?
+            .join(", ");
+        if member {
+            Ok(format!(
+                "emscripten::select_overload<{output}({inputs}), {scope}>({pointer})"
+            ))
+        } else {
+            Ok(format!(
+                "emscripten::select_overload<{output}({inputs})>({pointer})"
+            ))
+        }
+    }
+
+    /// The C++ type including the namespace for generated types
+    fn cpp_type(&self, format: &Format) -> Result<String, Error> {
+        Ok(match format {
+            Format::TypeName(n) => format!("{}::{n}", self.namespace),
+            Format::Option(t) => format!("std::optional<{}>", self.cpp_type(t)?),
+            Format::Seq(t) => format!("std::vector<{}>", self.cpp_type(t)?),
+            Format::Map { key, value } => {
+                format!(
+                    "std::map<{}, {}>",
+                    self.cpp_type(key)?,
+                    self.cpp_type(value)?
+                )
+            }
+            Format::Tuple(elements) if !elements.is_empty() => {
+                let elements = elements
+                    .iter()
+                    .map(|e| self.cpp_type(e))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                format!("std::tuple<{}>", elements.join(", "))
+            }
+            Format::TupleArray { content, size } => {
+                format!("std::array<{}, {size}>", self.cpp_type(content)?)
+            }
+            f => to_cpp_type_name(f)?,
+        })
+    }
+
+    /// Registers the given type and all types it contains
+    fn register(&mut self, format: &Format) -> Result<(), Error> {
+        let js_name = to_type_name(format)?.into_owned();
+        if self.cpp.contains_key(&js_name) {
+            return Ok(());
+        }
+        let cpp_type = self.cpp_type(format)?;
+        match format {
+            Format::Option(t) => {
+                self.cpp.insert(
+                    js_name,
+                    format!(
+                        "    emscripten::register_optional<{}>();",
+                        self.cpp_type(t)?
+                    ),
+                );
+                self.register(t)?;
+            }
+            Format::Seq(t) => {
+                self.vectors.insert(js_name.clone(), format.clone());
+                self.cpp.insert(
+                    js_name.clone(),
+                    format!(
+                        "    emscripten::register_vector<{}>(\"{js_name}\");",
+                        self.cpp_type(t)?
+                    ),
+                );
+                self.register(t)?;
+            }
+            Format::TupleArray { content, size } => {
+                let mut code = format!("    emscripten::value_array<{cpp_type}>(\"{js_name}\")");
+                for idx in 0..*size {
+                    code += &format!("\n        .element(emscripten::index<{idx}>())");
+                }
+                self.cpp.insert(js_name, code + ";");
+                self.register(content)?;
+            }
+            Format::TypeName(name) => {
+                // borrow the registry independently of `self`, which is borrowed mutably below
+                let registry = self.registry;
+                let fields = match &registry[name] {
+                    ContainerFormat::NewTypeStruct(t) => vec![(String::from("value"), &**t)],
+                    ContainerFormat::Struct(fields) => {
+                        fields.iter().map(|f| (f.name.clone(), &f.value)).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let mut code = format!("    emscripten::value_object<{cpp_type}>(\"{js_name}\")");
+                let mut ts = format!("export interface {js_name} {{\n");
+                for (field, _) in &fields {
+                    code += &format!("\n        .field(\"{field}\", &{cpp_type}::{field})");
+                }
+                for (field, format) in &fields {
+                    ts += &format!("    {field}: {};\n", ts_type(format));
+                }
+                self.cpp.insert(js_name.clone(), code + ";");
+                self.ts.insert(js_name, ts + "}\n");
+                for (_, format) in fields {
+                    self.register(format)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn is_void(format: &Format) -> bool {
+    matches!(format, Format::Unit) || matches!(format, Format::Tuple(t) if t.is_empty())
+}
+
+/// The TypeScript type embind uses for the given type
+fn ts_type(format: &Format) -> String {
+    match format {
+        Format::Bool => String::from("boolean"),
+        Format::I64 | Format::U64 => String::from("bigint"),
+        Format::Str => String::from("string"),
+        Format::Option(t) => format!("{} | undefined", ts_type(t)),
+        Format::Seq(t) => format!("EmbindVector<{}>", ts_type(t)),
+        Format::TupleArray { content, size } => {
+            format!("[{}]", vec![ts_type(content); *size].join(", "))
+        }
+        Format::TypeName(name) => name.clone(),
+        f if is_void(f) => String::from("void"),
+        _ => String::from("number"),
+    }
+}
+
+fn ts_signature(signature: &FunctionSignature) -> String {
+    let inputs = signature
+        .inputs
+        .iter()
+        .map(|(name, format)| format!("{name}: {}", ts_type(format)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{}({inputs}): {}",
+        signature.name,
+        ts_type(&signature.output)
+    )
+}
+
+/// Generates `embind.cpp` and `embind.d.ts` for the given wrapper functions
+pub(crate) fn generate_embind_bindings(
+    registry: &Registry,
+    functions: &WrapperFunctions,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<Vec<SkippedItem>, Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let mut registrations = Registrations::new(registry, namespace);
+
+    // the bindings of the classes and free-standing functions, registered types are added
+    // in front of them afterwards
+    let mut bindings = String::new();
+    let mut module = Vec::new();
+    let mut interfaces = Vec::new();
+    let mut skipped = Vec::new();
+    for class in &functions.classes {
+        let name = &class.name;
+        let holder = format!("{namespace}::{name}Holder");
+        bindings += &format!("    emscripten::class_<{holder}>(\"{name}\")\n");
+        bindings += &format!(
+            "        .class_function(\"fromPointer\", +[](std::uintptr_t ptr) {{\n            \
+             return {holder}(reinterpret_cast<{name}*>(ptr));\n        }})\n"
+        );
+        if class.has_constructor {
+            bindings += &format!("        .class_function(\"create\", &{holder}::create)\n");
+        }
+        bindings += &format!("        .function(\"is_valid\", &{holder}::is_valid)");
+        let mut ts = format!("export interface {name} {{\n    is_valid(): boolean;\n");
+        for function in &class.functions {
+            if registrations.is_supported_signature(function) {
+                bindings += &format!(
+                    "\n        .function(\"{}\", {})",
+                    function.name,
+                    registrations.function_pointer(function, &holder, true)?
+                );
+                ts += &format!("    {};\n", ts_signature(function));
+                register_signature(&mut registrations, function)?;
+            } else {
+                skipped.push(format!("{name}::{}", function.name));
+            }
+        }
+        bindings += ";\n";
+        interfaces.push(ts + "    delete(): void;\n}\n");
+        if class.has_constructor {
+            module.push(format!(
+                "    {name}: {{ fromPointer(ptr: number): {name}; create(): {name} }};"
+            ));
+        } else {
+            module.push(format!(
+                "    {name}: {{ fromPointer(ptr: number): {name} }};"
+            ));
+        }
+        for factory in &class.factories {
+            bindings += &format!(
+                "    emscripten::function(\"{factory}\", +[]() {{\n        \
+                 return {holder}({factory}());\n    }});\n"
+            );
+            module.push(format!("    {factory}(): {name};"));
+        }
+    }
+    for function in &functions.free_standing {
+        if registrations.is_supported_signature(function) {
+            bindings += &format!(
+                "    emscripten::function(\"{}\", {});\n",
+                function.name,
+                registrations.function_pointer(function, namespace, false)?
+            );
+            module.push(format!("    {};", ts_signature(function)));
+            register_signature(&mut registrations, function)?;
+        } else {
+            skipped.push(function.name.clone());
+        }
+    }
+
+    let out_dir = PathBuf::from(out_dir);
+    let path = out_dir.join(format!("{file_prefix}_embind.cpp"));
+    let mut writer = create_file(&path)?;
+    crate::write_file_comment(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include <cstdint>").unwrap();
+    writeln!(writer, "#include <emscripten/bind.h>\n").unwrap();
+    for class in &functions.classes {
+        writeln!(
+            writer,
+            "#include \"{file_prefix}_{}.hpp\"",
+            class.name.to_ascii_lowercase()
+        )
+        .unwrap();
+    }
+    writeln!(
+        writer,
+        "#include \"{file_prefix}_free_standing_functions.hpp\"\n"
+    )
+    .unwrap();
+    if !skipped.is_empty() {
+        writeln!(
+            writer,
+            "// The following functions are not available, as embind cannot represent their types:"
+        )
+        .unwrap();
+        for name in &skipped {
+            writeln!(writer, "// * `{name}`").unwrap();
+        }
+        writeln!(writer).unwrap();
+    }
+    writeln!(writer, "EMSCRIPTEN_BINDINGS({file_prefix}) {{").unwrap();
+    for code in registrations.cpp.values() {
+        writeln!(writer, "{code}").unwrap();
+    }
+    if !registrations.cpp.is_empty() {
+        writeln!(writer).unwrap();
+    }
+    write!(writer, "{bindings}").unwrap();
+    writeln!(writer, "}}").unwrap();
+    finish_file(writer, &path)?;
+
+    let path = out_dir.join(format!("{file_prefix}_embind.d.ts"));
+    let mut writer = create_file(&path)?;
+    crate::write_file_comment(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "export interface EmbindVector<T> {{").unwrap();
+    writeln!(writer, "    size(): number;").unwrap();
+    writeln!(writer, "    get(index: number): T | undefined;").unwrap();
+    writeln!(writer, "    set(index: number, value: T): boolean;").unwrap();
+    writeln!(writer, "    push_back(value: T): void;").unwrap();
+    writeln!(writer, "    resize(size: number, value: T): void;").unwrap();
+    writeln!(writer, "    delete(): void;").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    for ts in registrations.ts.values().chain(&interfaces) {
+        writeln!(writer, "{ts}").unwrap();
+    }
+    writeln!(writer, "export interface MainModule {{").unwrap();
+    for (js_name, format) in &registrations.vectors {
+        writeln!(writer, "    {js_name}: {{ new (): {} }};", ts_type(format)).unwrap();
+    }
+    for line in module {
+        writeln!(writer, "{line}").unwrap();
+    }
+    writeln!(writer, "}}").unwrap();
+    finish_file(writer, &path)?;
+
+    Ok(skipped
+        .into_iter()
+        .map(|name| SkippedItem {
+            name,
+            reason: String::from("embind cannot represent its types"),
+        })
+        .collect())
+}
+
+fn register_signature(
+    registrations: &mut Registrations,
+    signature: &FunctionSignature,
+) -> Result<(), Error> {
+    for (_, format) in &signature.inputs {
+        registrations.register(format)?;
+    }
+    if !is_void(&signature.output) {
+        registrations.register(&signature.output)?;
+    }
+    Ok(())
+}
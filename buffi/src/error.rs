@@ -0,0 +1,145 @@
+//! The error returned if the bindings cannot be generated
+//!
+//! Type resolution fails deep inside the recursion over the rustdoc output (e.g. for a type
+//! that cannot be expressed in C++). Each level of the recursion adds the type it reflects to
+//! the returned [`Error::Unsupported`], the function is added once the error reaches the
+//! function using the type.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Why the bindings could not be generated
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The given output directory does not exist
+    MissingOutputDirectory(PathBuf),
+    /// Running rustdoc or loading its JSON output failed
+    Rustdoc(String),
+    /// A function of the API uses something that cannot be expressed in the bindings
+    Unsupported {
+        /// The function, including the type of `self` (like `TestClient::get`)
+        function: String,
+        /// The types that were being reflected, starting with the outermost one
+        types: Vec<String>,
+        /// What is not supported
+        message: String,
+    },
+    /// Post-processing the generated files failed
+    Io {
+        /// The file or directory that could not be processed
+        path: PathBuf,
+        /// The underlying error
+        source: std::io::Error,
+    },
+    /// Any other failure while generating the bindings
+    Generation(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingOutputDirectory(path) => {
+                write!(
+                    f,
+                    "The output directory `{}` does not exist",
+                    path.display()
+                )
+            }
+            Error::Rustdoc(message) => write!(f, "Failed to run rustdoc: {message}"),
+            Error::Unsupported {
+                function,
+                types,
+                message,
+            } => {
+                write!(f, "Cannot generate bindings for `{function}`")?;
+                if !types.is_empty() {
+                    let types = types
+                        .iter()
+                        .map(|t| format!("`{t}`"))
+                        .collect::<Vec<_>>()
+                        .join(" > ");
+                    write!(f, " (while reflecting {types})")?;
+                }
+                write!(f, ": {message}")
+            }
+            Error::Io { path, source } => {
+                write!(f, "Failed to process `{}`: {source}", path.display())
+            }
+            Error::Generation(message) => write!(f, "Failed to generate bindings: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// An [`Error::Unsupported`] that is not yet attributed to a function or type
+    pub(crate) fn unsupported(message: impl Into<String>) -> Self {
+        Error::Unsupported {
+            function: String::new(),
+            types: Vec::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Adds `name` as the outermost type that was being reflected
+    pub(crate) fn reflecting(mut self, name: &str) -> Self {
+        if let Error::Unsupported { ref mut types, .. } = self {
+            types.insert(0, name.to_owned());
+        }
+        self
+    }
+
+    /// Attributes the error to the function `name`
+    pub(crate) fn in_function(mut self, name: &str) -> Self {
+        if let Error::Unsupported {
+            ref mut function, ..
+        } = self
+        {
+            name.clone_into(function);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_items_name_the_function_and_types() {
+        let error = Error::Unsupported {
+            function: String::from("TestClient::get"),
+            types: vec![String::from("Outer"), String::from("Inner")],
+            message: String::from("not implemented"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Cannot generate bindings for `TestClient::get` \
+             (while reflecting `Outer` > `Inner`): not implemented"
+        );
+    }
+
+    #[test]
+    fn errors_collect_the_types_and_the_function() {
+        let error = Error::unsupported("not implemented")
+            .reflecting("Inner")
+            .reflecting("Outer")
+            .in_function("TestClient::get");
+        assert_eq!(
+            error.to_string(),
+            "Cannot generate bindings for `TestClient::get` \
+             (while reflecting `Outer` > `Inner`): not implemented"
+        );
+        let error = Error::Rustdoc(String::from("no crates")).reflecting("Outer");
+        assert!(matches!(error, Error::Rustdoc(ref m) if m == "no crates"));
+    }
+}
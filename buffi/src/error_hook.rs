@@ -0,0 +1,76 @@
+//! Convert panics and internal failures into the error type of the API crate
+//!
+//! By default, the functions generated by `#[buffi_macro::exported]` convert panic payloads
+//! and their own failures (like null pointers or arguments that cannot be deserialized) into
+//! the `SerializableError` of the API crate via `From`, which only keeps the message. With
+//! `#[buffi_macro::exported(error_hook = path::to::function)]` they are passed to the given
+//! function instead, which can map each kind of failure to its own error code or severity:
+//!
+//! ```
+//! use buffi::error_hook::InternalFailure;
+//!
+//! pub struct SerializableError {
+//!     pub code: u32,
+//!     pub message: String,
+//! }
+//!
+//! pub fn internal_error(failure: InternalFailure) -> SerializableError {
+//!     let code = match failure {
+//!         InternalFailure::Panic(_) => 500,
+//!         InternalFailure::NullPointer(_) | InternalFailure::Serialization(_) => 400,
+//!         _ => 503,
+//!     };
+//!     SerializableError {
+//!         code,
+//!         message: failure.to_string(),
+//!     }
+//! }
+//! ```
+//!
+//! The `From` implementations are still required for errors returned by the wrapped functions.
+
+use std::any::Any;
+use std::fmt;
+
+/// A failure of a generated extern function that is not an error returned by the wrapped
+/// function
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InternalFailure {
+    /// The wrapped function panicked, with the panic payload (including the details recorded
+    /// by the panic hook if `panic_details` is set)
+    Panic(Box<dyn Any + Send>),
+    /// A pointer passed to the extern function is null
+    NullPointer(String),
+    /// An argument could not be deserialized or the result could not be serialized
+    Serialization(String),
+    /// The async runtime failed to execute the function or the function would block a thread
+    /// that must not block (see `detect_reentrancy`)
+    Runtime(String),
+}
+
+impl fmt::Display for InternalFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic(payload) => write!(f, "{}", crate::panic_hook::payload_message(&**payload)),
+            Self::NullPointer(message) | Self::Serialization(message) | Self::Runtime(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InternalFailure;
+
+    #[test]
+    fn panic_payloads_are_displayed_as_message() {
+        let payload = std::panic::catch_unwind(|| panic!("failed with {}", 42)).unwrap_err();
+        assert_eq!(
+            InternalFailure::Panic(payload).to_string(),
+            "failed with 42"
+        );
+        assert_eq!(InternalFailure::Panic(Box::new(1_u8)).to_string(), "");
+    }
+}
@@ -0,0 +1,145 @@
+//! Push events of the Rust side to listeners registered by the C++ side
+//!
+//! A type marked with `#[buffi_macro::event]` gets an [`EventSink`], which is returned by
+//! `Event::sink()`. The C++ side subscribes via the generated `buffi_subscribe_{event_name}`
+//! function (or the `{EventName}Subscription` class of `events.hpp`) and receives each
+//! emitted event serialized.
+
+use crate::imported::ImportedObject;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// The signature of the callback that receives serialized events
+///
+/// The buffer is only valid for the duration of the call.
+pub type EventCallback = extern "C" fn(context: *mut c_void, event: *const u8, event_size: usize);
+
+struct Listener {
+    id: u64,
+    callback: EventCallback,
+    context: ImportedObject,
+}
+
+/// The listeners of the events of type `T`
+pub struct EventSink<T> {
+    listeners: RwLock<Vec<Arc<Listener>>>,
+    next_id: AtomicU64,
+    serialize: fn(&T) -> Vec<u8>,
+}
+
+impl<T> EventSink<T> {
+    /// Creates a sink without listeners, `serialize` is only called for events with listeners
+    pub const fn new(serialize: fn(&T) -> Vec<u8>) -> Self {
+        Self {
+            listeners: RwLock::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            serialize,
+        }
+    }
+
+    /// Registers a listener and returns the id to unsubscribe it, which is never 0
+    ///
+    /// # Safety
+    ///
+    /// `context` needs to stay valid until `drop` is called with it, `callback` may be
+    /// called from any thread
+    pub unsafe fn subscribe(
+        &self,
+        callback: EventCallback,
+        context: *mut c_void,
+        drop: Option<extern "C" fn(context: *mut c_void)>,
+    ) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let listener = Listener {
+            id,
+            callback,
+            // SAFETY: guaranteed by the caller
+            context: unsafe { ImportedObject::new(context, drop) },
+        };
+        let mut listeners = self.listeners.write().unwrap_or_else(|e| e.into_inner());
+        listeners.push(Arc::new(listener));
+        id
+    }
+
+    /// Removes the listener with the given id, unknown ids are ignored
+    ///
+    /// The context of the listener is released once a currently running call returned.
+    pub fn unsubscribe(&self, id: u64) {
+        let mut listeners = self.listeners.write().unwrap_or_else(|e| e.into_inner());
+        let removed = listeners
+            .iter()
+            .position(|l| l.id == id)
+            .map(|idx| listeners.remove(idx));
+        // the context is released without holding the lock, so that its destructor
+        // can (un)subscribe as well
+        drop(listeners);
+        drop(removed);
+    }
+
+    /// Whether any listener is registered, e.g. to skip creating expensive events
+    pub fn has_listeners(&self) -> bool {
+        !self
+            .listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+
+    /// Passes `event` to all listeners on the current thread
+    pub fn emit(&self, event: &T) {
+        // listeners are called without holding the lock, so that they can (un)subscribe
+        let listeners = self
+            .listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if listeners.is_empty() {
+            return;
+        }
+        let event = (self.serialize)(event);
+        for listener in listeners {
+            (listener.callback)(listener.context.context(), event.as_ptr(), event.len());
+        }
+    }
+}
+
+/// A type marked with `#[buffi_macro::event]`
+pub trait Event: Sized + 'static {
+    /// The listeners of this event
+    fn sink() -> &'static EventSink<Self>;
+
+    /// Passes this event to all listeners, see [`EventSink::emit`]
+    fn emit(&self) {
+        Self::sink().emit(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static RECEIVED: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+    extern "C" fn record(_context: *mut c_void, event: *const u8, event_size: usize) {
+        // SAFETY: the sink passes a valid buffer of `event_size` bytes
+        let event = unsafe { std::slice::from_raw_parts(event, event_size) };
+        RECEIVED.lock().unwrap().push(event.to_vec());
+    }
+
+    #[test]
+    fn events_are_passed_to_subscribed_listeners() {
+        let sink = EventSink::<u8>::new(|event| vec![*event]);
+        sink.emit(&1);
+        // SAFETY: the context is never dereferenced
+        let id = unsafe { sink.subscribe(record, std::ptr::null_mut(), None) };
+        assert_ne!(id, 0);
+        assert!(sink.has_listeners());
+        sink.emit(&2);
+        sink.unsubscribe(id);
+        sink.emit(&3);
+        assert!(!sink.has_listeners());
+        assert_eq!(*RECEIVED.lock().unwrap(), [vec![2]]);
+    }
+}
@@ -0,0 +1,255 @@
+//! Skip rustdoc for crates whose sources did not change since the last run
+//!
+//! [`generate_docs`](crate::generate_docs) stores a fingerprint next to the JSON output of each
+//! documented crate (`{crate_name}.buffi-fingerprint`). It covers the toolchain, `Cargo.lock`,
+//! the `cargo rustdoc` command and the sources of the package and its path dependencies (the
+//! manifest, the build script and the directory of the library target). As long as it matches,
+//! the existing JSON output is reused. Setting
+//! [`Config::force_regenerate`](crate::Config::force_regenerate) (or removing the fingerprint
+//! files) forces all crates to be documented again, e.g. if documentation is included from
+//! other files.
+
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A package as reported by `cargo metadata`
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct Package {
+    name: String,
+    manifest_path: PathBuf,
+    dependencies: Vec<Dependency>,
+    targets: Vec<Target>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Target {
+    kind: Vec<String>,
+    src_path: PathBuf,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Dependency {
+    /// Only set for path dependencies
+    path: Option<PathBuf>,
+    /// `None` for normal dependencies
+    kind: Option<String>,
+}
+
+/// The fingerprints of the crates documented in one run
+pub(crate) struct Fingerprints<'a> {
+    json_directory: PathBuf,
+    /// The toolchain and `Cargo.lock`, shared by all crates
+    workspace: DefaultHasher,
+    packages: &'a [Package],
+}
+
+impl<'a> Fingerprints<'a> {
+    pub(crate) fn new(
+        target_directory: &str,
+        rustdoc_flags: &[String],
+        workspace_root: &Path,
+        packages: &'a [Package],
+    ) -> Self {
+        let mut json_directory = PathBuf::from(target_directory);
+        if let Some(target) = target(rustdoc_flags) {
+            json_directory.push(target);
+        }
+        json_directory.push("doc");
+
+        let mut workspace = DefaultHasher::new();
+        let toolchain = Command::new("rustc")
+            .arg("-vV")
+            .stderr(Stdio::inherit())
+            .output()
+            .map(|output| output.stdout)
+            .unwrap_or_default();
+        toolchain.hash(&mut workspace);
+        std::fs::read(workspace_root.join("Cargo.lock"))
+            .unwrap_or_default()
+            .hash(&mut workspace);
+        std::env::var_os("RUSTFLAGS").hash(&mut workspace);
+        std::env::var_os("RUSTDOCFLAGS").hash(&mut workspace);
+        Self {
+            json_directory,
+            workspace,
+            packages,
+        }
+    }
+
+    /// The fingerprint of documenting `crate_name` via `command`, `None` if the sources of the
+    /// crate are unknown
+    pub(crate) fn fingerprint(&self, crate_name: &str, command: &Command) -> Option<String> {
+        let mut hasher = self.workspace.clone();
+        command.get_args().for_each(|arg| arg.hash(&mut hasher));
+        for (key, value) in command.get_envs() {
+            (key, value).hash(&mut hasher);
+        }
+        for path in self.sources(crate_name)? {
+            if path.is_dir() {
+                hash_directory(&path, &path, &mut hasher).ok()?;
+            } else {
+                path.hash(&mut hasher);
+                std::fs::read(&path).ok()?.hash(&mut hasher);
+            }
+        }
+        Some(format!("{:016x}", hasher.finish()))
+    }
+
+    /// Whether the JSON output of `crate_name` exists and was generated with `fingerprint`
+    pub(crate) fn is_up_to_date(&self, crate_name: &str, fingerprint: &str) -> bool {
+        self.json_file(crate_name).exists()
+            && std::fs::read_to_string(self.fingerprint_file(crate_name))
+                .is_ok_and(|stored| stored == fingerprint)
+    }
+
+    /// Removes the JSON output of `crate_name` and its fingerprint
+    pub(crate) fn invalidate(&self, crate_name: &str) {
+        let _ = std::fs::remove_file(self.json_file(crate_name));
+        let _ = std::fs::remove_file(self.fingerprint_file(crate_name));
+    }
+
+    /// Stores the `fingerprint` of the JSON output of `crate_name` that was just generated
+    pub(crate) fn store(&self, crate_name: &str, fingerprint: &str) {
+        if let Err(e) = std::fs::write(self.fingerprint_file(crate_name), fingerprint) {
+            eprintln!("Failed to store the fingerprint of `{crate_name}`: {e}");
+        }
+    }
+
+    fn json_file(&self, crate_name: &str) -> PathBuf {
+        self.json_directory
+            .join(format!("{}.json", crate_name.replace('-', "_")))
+    }
+
+    fn fingerprint_file(&self, crate_name: &str) -> PathBuf {
+        self.json_directory.join(format!(
+            "{}.buffi-fingerprint",
+            crate_name.replace('-', "_")
+        ))
+    }
+
+    /// The source files and directories of the package `crate_name` and its path dependencies
+    /// (recursively)
+    fn sources(&self, crate_name: &str) -> Option<Vec<PathBuf>> {
+        let package = self
+            .packages
+            .iter()
+            .find(|p| p.name.replace('-', "_") == crate_name.replace('-', "_"))?;
+        let mut sources = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![package];
+        while let Some(package) = pending.pop() {
+            sources.push(package.manifest_path.clone());
+            for target in &package.targets {
+                if target.kind.iter().any(|k| k == "custom-build") {
+                    sources.push(target.src_path.clone());
+                } else if target
+                    .kind
+                    .iter()
+                    .any(|k| k.ends_with("lib") || k == "proc-macro")
+                {
+                    sources.push(target.src_path.parent()?.to_path_buf());
+                }
+            }
+            for dependency in &package.dependencies {
+                let Some(path) = &dependency.path else {
+                    continue;
+                };
+                if dependency.kind.as_deref() == Some("dev") || !visited.insert(path) {
+                    continue;
+                }
+                pending.extend(
+                    self.packages
+                        .iter()
+                        .filter(|p| p.manifest_path.parent() == Some(path.as_path())),
+                );
+            }
+        }
+        sources.sort();
+        sources.dedup();
+        Some(sources)
+    }
+}
+
+/// `--target {target}` or `--target={target}`
+fn target(rustdoc_flags: &[String]) -> Option<&str> {
+    let mut flags = rustdoc_flags.iter();
+    while let Some(flag) = flags.next() {
+        if flag == "--target" {
+            return flags.next().map(String::as_str);
+        }
+        if let Some(target) = flag.strip_prefix("--target=") {
+            return Some(target);
+        }
+    }
+    None
+}
+
+/// Hashes the path (relative to `root`) and content of each file in `directory`, without
+/// build output and hidden files
+fn hash_directory(
+    root: &Path,
+    directory: &Path,
+    hasher: &mut DefaultHasher,
+) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(directory)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().unwrap_or_default();
+        if name == OsStr::new("target") || name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            hash_directory(root, &path, hasher)?;
+        } else {
+            path.strip_prefix(root).unwrap_or(&path).hash(hasher);
+            std::fs::read(&path)?.hash(hasher);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directory_hash(directory: &Path) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hash_directory(directory, directory, &mut hasher).unwrap();
+        hasher.finish()
+    }
+
+    #[test]
+    fn changed_sources_change_the_hash() {
+        let directory = std::env::temp_dir().join("buffi_fingerprint");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("src")).unwrap();
+        std::fs::create_dir_all(directory.join("target")).unwrap();
+        std::fs::write(directory.join("src/lib.rs"), "pub fn a() {}").unwrap();
+        let original = directory_hash(&directory);
+
+        std::fs::write(directory.join("target/output"), "ignored").unwrap();
+        assert_eq!(directory_hash(&directory), original);
+
+        std::fs::write(directory.join("src/lib.rs"), "pub fn b() {}").unwrap();
+        assert_ne!(directory_hash(&directory), original);
+    }
+
+    #[test]
+    fn targets_are_read_from_the_rustdoc_flags() {
+        let flags = |flags: &[&str]| flags.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            target(&flags(&["--target", "wasm32-unknown-unknown"])),
+            Some("wasm32-unknown-unknown")
+        );
+        assert_eq!(
+            target(&flags(&["--locked", "--target=aarch64-apple-darwin"])),
+            Some("aarch64-apple-darwin")
+        );
+        assert_eq!(target(&flags(&["--locked"])), None);
+    }
+}
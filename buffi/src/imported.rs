@@ -0,0 +1,96 @@
+//! Call functions of the C++ side via traits implemented in C++
+//!
+//! A trait marked with `#[buffi_macro::imported]` is implemented by an abstract C++ class. The
+//! C++ side registers an implementation via the generated `buffi_set_{trait_name}` function,
+//! the Rust side gets it via `Imported{TraitName}::get()`. Arguments and results are serialized
+//! like those of exported functions, the C++ side allocates the result buffers via
+//! `buffi_alloc_byte_buffer` (see [`export_alloc_byte_buffer!`](crate::export_alloc_byte_buffer)).
+
+use std::ffi::c_void;
+
+/// The signature of the function that releases a C++ implementation
+pub type DropCallback = extern "C" fn(context: *mut c_void);
+
+/// A C++ object implementing an imported trait
+///
+/// The object is released via its drop callback once this value is dropped. As the Rust side
+/// may call the object from any thread, the C++ implementation needs to be thread-safe.
+pub struct ImportedObject {
+    context: *mut c_void,
+    drop: Option<DropCallback>,
+}
+
+// SAFETY: the C++ implementation is required to be thread-safe, see above
+unsafe impl Send for ImportedObject {}
+// SAFETY: the C++ implementation is required to be thread-safe, see above
+unsafe impl Sync for ImportedObject {}
+
+impl ImportedObject {
+    /// Wraps the `context` pointer registered by the C++ side
+    ///
+    /// # Safety
+    ///
+    /// `context` needs to stay valid until `drop` is called with it
+    pub unsafe fn new(context: *mut c_void, drop: Option<DropCallback>) -> Self {
+        Self { context, drop }
+    }
+
+    /// The pointer passed to every function of the implementation
+    pub fn context(&self) -> *mut c_void {
+        self.context
+    }
+}
+
+impl Drop for ImportedObject {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            drop(self.context);
+        }
+    }
+}
+
+/// Takes ownership of a result buffer written by the C++ side
+///
+/// Returns `None` if the C++ implementation did not write a buffer, which happens if it
+/// threw an exception.
+///
+/// # Safety
+///
+/// A non-null `ptr` needs to be allocated via `buffi_alloc_byte_buffer(size)`
+pub unsafe fn take_result(ptr: *mut u8, size: usize) -> Option<Vec<u8>> {
+    if ptr.is_null() {
+        return None;
+    }
+    crate::stats::record_free(size);
+    // SAFETY: the buffer was allocated as boxed slice of `size` bytes, see above
+    Some(unsafe { Vec::from_raw_parts(ptr, size, size) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn count_drop(_context: *mut c_void) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn objects_are_released_once() {
+        // SAFETY: the context is never dereferenced
+        let object = unsafe { ImportedObject::new(std::ptr::null_mut(), Some(count_drop)) };
+        drop(object);
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn missing_results_are_reported() {
+        // SAFETY: null pointers are never dereferenced
+        assert!(unsafe { take_result(std::ptr::null_mut(), 0) }.is_none());
+        let ptr = crate::buffer::alloc_byte_buffer(3);
+        // SAFETY: allocated via `alloc_byte_buffer` above
+        assert_eq!(unsafe { take_result(ptr, 3) }, Some(vec![0, 0, 0]));
+    }
+}
@@ -0,0 +1,165 @@
+//! Wrap the generated namespace in an inline namespace
+//!
+//! With [`Config::inline_namespace`](crate::Config::inline_namespace) set to e.g. `v2`, each
+//! `namespace api {` of the generated C++ code becomes `namespace api { inline namespace v2 {`
+//! (and `namespace api::geometry {` becomes `namespace api { inline namespace v2 { namespace
+//! geometry {`). Code using `api::Type` does not change, but the mangled names of the types and
+//! functions contain the version. This way two versions of the bindings can be linked into one
+//! binary during a migration. The extern functions still need to be distinct, e.g. by loading
+//! each version of the API library via the runtime loader.
+//!
+//! The generated files are rewritten after all of them are written, as the namespaces emitted
+//! by `serde_generate` need to be wrapped as well.
+
+use std::path::Path;
+
+/// Rewrites all C++ files in `files` that open `namespace`
+pub(crate) fn apply<'a>(
+    files: impl Iterator<Item = &'a Path>,
+    namespace: &str,
+    inline_namespace: &str,
+) -> std::io::Result<()> {
+    for path in files {
+        let is_cpp = path
+            .extension()
+            .is_some_and(|e| e == "hpp" || e == "cpp" || e == "h");
+        if !is_cpp {
+            continue;
+        }
+        let content = std::fs::read_to_string(path)?;
+        let wrapped = wrap(&content, namespace, inline_namespace);
+        if wrapped != content {
+            std::fs::write(path, wrapped)?;
+        }
+    }
+    Ok(())
+}
+
+/// An opened `namespace` and the number of braces opened since
+struct Open {
+    depth: usize,
+    /// `namespace {namespace}::{nested} {` opens a third namespace
+    nested: bool,
+}
+
+/// Wraps the content of each top level `namespace {namespace} {` (or `{namespace}::...`) in
+/// `inline_namespace`
+fn wrap(content: &str, namespace: &str, inline_namespace: &str) -> String {
+    let mut out = String::with_capacity(content.len() + 256);
+    let mut open: Option<Open> = None;
+    for line in content.split_inclusive('\n') {
+        let Some(current) = open.as_mut() else {
+            let trimmed = line.trim_end();
+            if trimmed == format!("namespace {namespace} {{") {
+                out +=
+                    &format!("namespace {namespace} {{ inline namespace {inline_namespace} {{\n");
+                open = Some(Open {
+                    depth: 1,
+                    nested: false,
+                });
+            } else if let Some(nested) = trimmed
+                .strip_prefix(&format!("namespace {namespace}::"))
+                .and_then(|rest| rest.strip_suffix(" {"))
+            {
+                out += &format!(
+                    "namespace {namespace} {{ inline namespace {inline_namespace} {{ namespace {nested} {{\n"
+                );
+                open = Some(Open {
+                    depth: 1,
+                    nested: true,
+                });
+            } else {
+                out += line;
+            }
+            continue;
+        };
+        match closing_brace(line, &mut current.depth) {
+            Some(idx) => {
+                let closing = if current.nested { "}}}" } else { "}}" };
+                let rest = line[idx + 1..].replace(
+                    &format!("// end of namespace {namespace}"),
+                    &format!("// end of namespace {namespace}::{inline_namespace}"),
+                );
+                out += &line[..idx];
+                out += closing;
+                out += &rest;
+                open = None;
+            }
+            None => out += line,
+        }
+    }
+    out
+}
+
+/// Updates `depth` with the braces of `line` (outside of comments, string and character
+/// literals) and returns the index of the brace closing the namespace
+fn closing_brace(line: &str, depth: &mut usize) -> Option<usize> {
+    let mut chars = line.char_indices().peekable();
+    let mut literal: Option<char> = None;
+    while let Some((idx, c)) = chars.next() {
+        match (literal, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(quote), c) if c == quote => literal = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => literal = Some(c),
+            (None, '/') if chars.peek().is_some_and(|(_, c)| *c == '/') => return None,
+            (None, '{') => *depth += 1,
+            (None, '}') => {
+                *depth -= 1;
+                if *depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wrap;
+
+    #[test]
+    fn namespaces_are_wrapped() {
+        let content = "\
+#pragma once
+
+namespace api {
+struct Point {
+    double x = 0.0; // in `{mm}`
+};
+inline void fail() { throw std::logic_error(\"a } in a string\"); }
+} // end of namespace api
+
+namespace api::geometry {
+using Point = api::Point;
+}  // end of namespace api::geometry
+
+namespace serde {
+template <> struct Serializable<api::Point> {};
+}
+";
+        let expected = "\
+#pragma once
+
+namespace api { inline namespace v2 {
+struct Point {
+    double x = 0.0; // in `{mm}`
+};
+inline void fail() { throw std::logic_error(\"a } in a string\"); }
+}} // end of namespace api::v2
+
+namespace api { inline namespace v2 { namespace geometry {
+using Point = api::Point;
+}}}  // end of namespace api::v2::geometry
+
+namespace serde {
+template <> struct Serializable<api::Point> {};
+}
+";
+        assert_eq!(wrap(content, "api", "v2"), expected);
+    }
+}
@@ -0,0 +1,522 @@
+//! C++ code that is called by the Rust side
+//!
+//! Each trait marked with `#[buffi_macro::imported]` gets a `{file_prefix}_{trait_name}.hpp` header with an abstract class and a
+//! `set_{trait_name}` function. It registers an implementation via the extern function
+//! generated by the macro, passing a trampoline for each function of the trait that
+//! deserializes the arguments, calls the implementation and serializes the result into a
+//! buffer allocated via `buffi_alloc_byte_buffer`.
+//!
+//! Each type marked with `#[buffi_macro::event]` gets a `{EventName}Subscription` class in
+//! `{file_prefix}_events.hpp`, which subscribes a `std::function` while it is alive.
+
+use crate::docs::to_cpp_doc;
+use crate::{
+    create_file, finish_file, get_name_without_path, is_event, is_imported_trait, last_format,
+    to_cpp_type_name, to_serde_reflect_type, write_function_footer, write_function_header,
+    write_wrapper_includes, Config, Error, ItemResolver, TypeCache,
+};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// `WordFilter` -> `word_filter`, like the macro names the extern function
+pub(crate) fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (idx, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if idx != 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// A function of an imported trait or a `#[buffi(callback)]` argument
+pub(crate) struct ImportedFunction {
+    pub(crate) name: String,
+    pub(crate) docs: Option<String>,
+    /// The names and C++ types of the arguments without `self`
+    pub(crate) inputs: Vec<(String, String)>,
+    /// `None` for functions returning `()`
+    pub(crate) output: Option<String>,
+}
+
+/// The functions of `trait_` in the order of the arguments of the extern `set_function`
+fn imported_functions(
+    res: &ItemResolver,
+    trait_: &rustdoc_types::Trait,
+    set_function: &rustdoc_types::Function,
+    type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+    config: &Config,
+) -> Result<Vec<ImportedFunction>, Error> {
+    let mut to_cpp_type = |tpe: &rustdoc_types::Type| {
+        let tpe = to_serde_reflect_type(
+            tpe,
+            res,
+            &mut None,
+            Vec::new(),
+            &config.parent_crate,
+            &config.namespace,
+            type_map,
+        )?;
+        to_cpp_type_name(&last_format(&tpe)?)
+    };
+    let mut functions = Vec::with_capacity(trait_.items.len());
+    for id in &trait_.items {
+        let item = res.resolve_index(None, id, &config.parent_crate)?;
+        let rustdoc_types::ItemEnum::Function(ref f) = item.inner else {
+            continue;
+        };
+        let inputs = f
+            .sig
+            .inputs
+            .iter()
+            .filter(|(name, _)| name != "self")
+            .map(|(name, tpe)| Ok((name.clone(), to_cpp_type(tpe)?)))
+            .collect::<Result<_, Error>>()?;
+        let output = match f.sig.output {
+            Some(rustdoc_types::Type::Tuple(ref t)) if t.is_empty() => None,
+            Some(ref tpe) => Some(to_cpp_type(tpe)?),
+            None => None,
+        };
+        functions.push(ImportedFunction {
+            name: item.name.clone().unwrap(),
+            docs: item.docs.clone(),
+            inputs,
+            output,
+        });
+    }
+    // the first two arguments are `context` and `drop`
+    let order = set_function
+        .sig
+        .inputs
+        .iter()
+        .skip(2)
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    functions.sort_by_key(|f| order.iter().position(|n| *n == f.name));
+    Ok(functions)
+}
+
+/// Writes the lambda that is passed to the extern function for `function`, which deserializes
+/// the arguments and calls `callee` with them
+pub(crate) fn write_trampoline(
+    out: &mut impl Write,
+    function: &ImportedFunction,
+    callee: &str,
+    prefix: &str,
+) {
+    let params = function
+        .inputs
+        .iter()
+        .map(|(name, _)| format!("const uint8_t* {name}, size_t {name}_size, "))
+        .collect::<Vec<_>>()
+        .join("");
+    writeln!(
+        out,
+        "[](void* context, {params}uint8_t** out_ptr) -> size_t {{"
+    )
+    .unwrap();
+    writeln!(out, "            try {{").unwrap();
+    for (name, tpe) in &function.inputs {
+        writeln!(
+            out,
+            "                auto deserializer_{name} = serde::BincodeDeserializer(std::vector<uint8_t>({name}, {name} + {name}_size));"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "                auto {name}_value = serde::Deserializable<{tpe}>::deserialize(deserializer_{name});"
+        )
+        .unwrap();
+    }
+    let args = function
+        .inputs
+        .iter()
+        .map(|(name, _)| format!("{name}_value"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let call = format!("{callee}({args})");
+    match function.output {
+        Some(ref output) => {
+            writeln!(out, "                auto result = {call};").unwrap();
+            writeln!(
+                out,
+                "                auto serializer = serde::BincodeSerializer();"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "                serde::Serializable<{output}>::serialize(result, serializer);"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "                std::vector<uint8_t> serialized_result = std::move(serializer).bytes();"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "                *out_ptr = {prefix}_alloc_byte_buffer(serialized_result.size());"
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "                std::copy(serialized_result.begin(), serialized_result.end(), *out_ptr);"
+            )
+            .unwrap();
+            writeln!(out, "                return serialized_result.size();").unwrap();
+        }
+        None => {
+            writeln!(out, "                {call};").unwrap();
+            // `()` is serialized as zero bytes
+            writeln!(
+                out,
+                "                *out_ptr = {prefix}_alloc_byte_buffer(0);"
+            )
+            .unwrap();
+            writeln!(out, "                return 0;").unwrap();
+        }
+    }
+    writeln!(out, "            }} catch (...) {{").unwrap();
+    writeln!(out, "                *out_ptr = nullptr;").unwrap();
+    writeln!(out, "                return 0;").unwrap();
+    writeln!(out, "            }}").unwrap();
+    write!(out, "        }}").unwrap();
+}
+
+/// Generates the header of each trait marked with `#[buffi_macro::imported]`
+pub(crate) fn generate_interfaces(
+    res: &ItemResolver,
+    out_dir: &Path,
+    type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+    function_prefix: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let mut traits = res
+        .doc_types
+        .index
+        .values()
+        .filter(is_imported_trait)
+        .collect::<Vec<_>>();
+    if traits.is_empty() {
+        return Ok(());
+    }
+    traits.sort_by_key(|t| t.name.as_ref());
+    let extern_function = move |name: &str| {
+        res.doc_types
+            .index
+            .values()
+            .find_map(|item| match item.inner {
+                rustdoc_types::ItemEnum::Function(ref f)
+                    if item.name.as_deref() == Some(name)
+                        && matches!(f.header.abi, rustdoc_types::Abi::C { .. }) =>
+                {
+                    Some(f)
+                }
+                _ => None,
+            })
+    };
+    if extern_function(&format!("{function_prefix}_alloc_byte_buffer")).is_none() {
+        return Err(Error::Generation(String::from(
+            "Traits marked with `#[buffi_macro::imported]` require a call to \
+             `buffi::export_alloc_byte_buffer!()` in the API crate",
+        )));
+    }
+
+    for item in traits {
+        let rustdoc_types::ItemEnum::Trait(ref trait_) = item.inner else {
+            unreachable!()
+        };
+        let name = get_name_without_path(item.name.as_deref().unwrap());
+        let snake_name = to_snake_case(name);
+        let set_name = format!("{function_prefix}_set_{snake_name}");
+        let set_function = extern_function(&set_name).ok_or_else(|| {
+            Error::Generation(format!(
+                "`{set_name}` of the imported trait `{name}` is missing"
+            ))
+        })?;
+        let functions = imported_functions(res, trait_, set_function, type_map, config)
+            .map_err(|e| e.in_function(name))?;
+
+        let path = out_dir.join(format!("{file_prefix}_{}.hpp", name.to_ascii_lowercase()));
+        let mut writer = create_file(&path)?;
+        write_function_header(&mut writer, config, &path, provenance)?;
+        writeln!(writer, "#include <algorithm>").unwrap();
+        writeln!(writer, "#include <memory>").unwrap();
+        write_wrapper_includes(&mut writer, false, file_prefix, namespace);
+        writeln!(writer).unwrap();
+        writeln!(writer, "namespace {namespace} {{").unwrap();
+        writeln!(writer).unwrap();
+        if let Some(ref docs) = item.docs {
+            for line in to_cpp_doc(docs).lines() {
+                writeln!(writer, "{}", format!("// {line}").trim_end()).unwrap();
+            }
+        }
+        writeln!(writer, "class {name} {{").unwrap();
+        writeln!(writer, "public:").unwrap();
+        writeln!(writer, "    virtual ~{name}() = default;").unwrap();
+        for function in &functions {
+            writeln!(writer).unwrap();
+            if let Some(ref docs) = function.docs {
+                for line in to_cpp_doc(docs).lines() {
+                    writeln!(writer, "{}", format!("    // {line}").trim_end()).unwrap();
+                }
+            }
+            let params = function
+                .inputs
+                .iter()
+                .map(|(name, tpe)| format!("const {tpe}& {name}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                writer,
+                "    virtual {} {}({params}) = 0;",
+                function.output.as_deref().unwrap_or("void"),
+                function.name
+            )
+            .unwrap();
+        }
+        writeln!(writer, "}};\n").unwrap();
+
+        writeln!(
+            writer,
+            "// Registers `implementation` as the implementation of `{name}` used by the Rust side,"
+        )
+        .unwrap();
+        writeln!(
+            writer,
+            "// a null pointer unregisters the current implementation"
+        )
+        .unwrap();
+        writeln!(
+            writer,
+            "inline void set_{snake_name}(std::shared_ptr<{name}> implementation) {{"
+        )
+        .unwrap();
+        writeln!(writer, "    if (implementation == nullptr) {{").unwrap();
+        let nulls = vec!["nullptr"; functions.len() + 2].join(", ");
+        writeln!(writer, "        {set_name}({nulls});").unwrap();
+        writeln!(writer, "        return;").unwrap();
+        writeln!(writer, "    }}").unwrap();
+        writeln!(writer, "    {set_name}(").unwrap();
+        writeln!(
+            writer,
+            "        new std::shared_ptr<{name}>(std::move(implementation)),"
+        )
+        .unwrap();
+        writeln!(writer, "        [](void* context) {{").unwrap();
+        writeln!(
+            writer,
+            "            delete static_cast<std::shared_ptr<{name}>*>(context);"
+        )
+        .unwrap();
+        write!(writer, "        }}").unwrap();
+        for function in &functions {
+            writeln!(writer, ",").unwrap();
+            let callee = format!(
+                "(*static_cast<std::shared_ptr<{name}>*>(context))->{}",
+                function.name
+            );
+            write!(writer, "        ").unwrap();
+            write_trampoline(&mut writer, function, &callee, function_prefix);
+        }
+        writeln!(writer, ");").unwrap();
+        writeln!(writer, "}}\n").unwrap();
+        writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+        write_function_footer(&mut writer, config)?;
+        finish_file(writer, &path)?;
+    }
+    Ok(())
+}
+
+/// Writes the `{name}Subscription` class for the event `name`, `tpe` is its C++ type
+fn write_event_subscription(out: &mut impl Write, name: &str, tpe: &str, prefix: &str) {
+    let snake_name = to_snake_case(name);
+    let handler = format!("std::function<void(const {tpe}&)>");
+    writeln!(
+        out,
+        "// Passes the `{name}` events of the Rust side to `handler` until it is destroyed,"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "// `handler` is called on the thread emitting the event and exceptions are ignored"
+    )
+    .unwrap();
+    writeln!(out, "class {name}Subscription {{").unwrap();
+    writeln!(out, "    uint64_t id;").unwrap();
+    writeln!(out, "public:").unwrap();
+    writeln!(out, "    explicit {name}Subscription({handler} handler) {{").unwrap();
+    writeln!(out, "        this->id = {prefix}_subscribe_{snake_name}(").unwrap();
+    writeln!(
+        out,
+        "            [](void* context, const uint8_t* event, size_t event_size) {{"
+    )
+    .unwrap();
+    writeln!(out, "                try {{").unwrap();
+    writeln!(
+        out,
+        "                    auto deserializer = serde::BincodeDeserializer(std::vector<uint8_t>(event, event + event_size));"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "                    auto value = serde::Deserializable<{tpe}>::deserialize(deserializer);"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "                    (*static_cast<{handler}*>(context))(value);"
+    )
+    .unwrap();
+    writeln!(out, "                }} catch (...) {{").unwrap();
+    writeln!(
+        out,
+        "                    // exceptions must not unwind into the Rust side"
+    )
+    .unwrap();
+    writeln!(out, "                }}").unwrap();
+    writeln!(out, "            }},").unwrap();
+    writeln!(out, "            new {handler}(std::move(handler)),").unwrap();
+    writeln!(out, "            [](void* context) {{").unwrap();
+    writeln!(
+        out,
+        "                delete static_cast<{handler}*>(context);"
+    )
+    .unwrap();
+    writeln!(out, "            }});").unwrap();
+    writeln!(
+        out,
+        "    }}
+"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    {name}Subscription({name}Subscription&& other) noexcept : id(other.id) {{"
+    )
+    .unwrap();
+    writeln!(out, "        other.id = 0;").unwrap();
+    writeln!(
+        out,
+        "    }}
+"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    {name}Subscription(const {name}Subscription&) = delete;"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    {name}Subscription& operator=(const {name}Subscription&) = delete;"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    {name}Subscription& operator=({name}Subscription&&) = delete;\n"
+    )
+    .unwrap();
+    writeln!(out, "    ~{name}Subscription() {{").unwrap();
+    writeln!(out, "        if (this->id != 0) {{").unwrap();
+    writeln!(
+        out,
+        "            {prefix}_unsubscribe_{snake_name}(this->id);"
+    )
+    .unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}};\n").unwrap();
+}
+
+/// Generates `events.hpp` for the types marked with `#[buffi_macro::event]`
+pub(crate) fn generate_event_subscriptions(
+    res: &ItemResolver,
+    out_dir: &Path,
+    type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+    function_prefix: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let mut events = res
+        .doc_types
+        .index
+        .values()
+        .filter(is_event)
+        .collect::<Vec<_>>();
+    if events.is_empty() {
+        return Ok(());
+    }
+    events.sort_by_key(|e| e.name.as_ref());
+
+    let path = out_dir.join(format!("{file_prefix}_events.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    write_wrapper_includes(&mut writer, false, file_prefix, namespace);
+    writeln!(writer).unwrap();
+    writeln!(writer, "namespace {namespace} {{").unwrap();
+    writeln!(writer).unwrap();
+    for item in events {
+        let name = item.name.as_deref().unwrap();
+        let tpe = event_type(item);
+        let tpe = to_serde_reflect_type(
+            &tpe,
+            res,
+            &mut None,
+            Vec::new(),
+            &config.parent_crate,
+            namespace,
+            type_map,
+        )
+        .and_then(|tpe| to_cpp_type_name(&last_format(&tpe)?))
+        .map_err(|e| e.in_function(name))?;
+        write_event_subscription(&mut writer, name, &tpe, function_prefix);
+    }
+    writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+/// The type of an event, as it is not referenced by any function
+pub(crate) fn event_type(item: &rustdoc_types::Item) -> rustdoc_types::Type {
+    rustdoc_types::Type::ResolvedPath(rustdoc_types::Path {
+        name: item.name.clone().unwrap(),
+        id: item.id,
+        args: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_snake_case, write_event_subscription};
+
+    #[test]
+    fn subscriptions_unsubscribe_when_destroyed() {
+        let mut out = Vec::new();
+        write_event_subscription(&mut out, "WordCounted", "WordCounted", "buffi");
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("this->id = buffi_subscribe_word_counted("));
+        assert!(out.contains("buffi_unsubscribe_word_counted(this->id);"));
+        assert!(out.contains(
+            "explicit WordCountedSubscription(std::function<void(const WordCounted&)> handler)"
+        ));
+    }
+
+    #[test]
+    fn trait_names_are_converted_like_the_macro_does() {
+        assert_eq!(to_snake_case("WordFilter"), "word_filter");
+        assert_eq!(to_snake_case("Logger"), "logger");
+    }
+}
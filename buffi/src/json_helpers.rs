@@ -0,0 +1,238 @@
+//! Call the JSON debug functions from C++
+//!
+//! If [`Config::debug_json`](crate::Config::debug_json) is set, an additional `debug_json.hpp`
+//! declares the `{prefix}_json_{function}` functions exported with the `debug_json` feature of
+//! `buffi_macro` (see [`debug_json`](crate::debug_json)). For each of them a function in the
+//! `debug_json` namespace takes the arguments as JSON strings and returns the JSON encoded
+//! result, so that payloads can be typed in a debugger or passed on from scripting languages.
+
+use crate::{create_file, finish_file, write_function_footer, write_function_header};
+use crate::{Config, Error, ExternFunction};
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// A function taking and returning JSON strings that calls a JSON debug function
+struct JsonHelper {
+    /// The name of the C++ function, the extern function without its prefix
+    name: String,
+    /// The name of the extern function using bincode
+    bincode_function: String,
+    /// The extern function, with the `{prefix}_json_` name
+    function: ExternFunction,
+    /// The declarations of the arguments of the C++ function
+    parameters: Vec<String>,
+    /// The arguments passed to the extern function, without `out_ptr`
+    arguments: Vec<String>,
+}
+
+/// The helper calling the JSON counterpart of `function`, `None` if the macro does not
+/// generate one for it (the result is not returned via `out_ptr` or it takes callbacks)
+fn json_helper(function: &ExternFunction, prefix: &str) -> Option<JsonHelper> {
+    let helper_name = function.name.strip_prefix(prefix)?.strip_prefix('_')?;
+    let (out_ptr, inputs) = function.inputs.split_last()?;
+    if function.output != "size_t"
+        || out_ptr.0 != "out_ptr"
+        || out_ptr.1 != "std::uint8_t** out_ptr"
+        || inputs
+            .iter()
+            .any(|(_, declaration)| declaration.contains('('))
+    {
+        return None;
+    }
+    let mut parameters = Vec::new();
+    let mut arguments = Vec::new();
+    let mut inputs = inputs.iter().peekable();
+    while let Some((name, declaration)) = inputs.next() {
+        let size = format!("{name}_size");
+        let serialized = *declaration == format!("const std::uint8_t* {name}")
+            && inputs
+                .next_if(|(n, d)| *n == size && *d == format!("size_t {size}"))
+                .is_some();
+        if serialized {
+            parameters.push(format!("const std::string& {name}"));
+            arguments.push(format!(
+                "reinterpret_cast<const std::uint8_t*>({name}.data())"
+            ));
+            arguments.push(format!("{name}.size()"));
+        } else {
+            parameters.push(declaration.clone());
+            arguments.push(name.clone());
+        }
+    }
+    Some(JsonHelper {
+        name: helper_name.to_owned(),
+        bincode_function: function.name.clone(),
+        function: ExternFunction {
+            name: format!("{prefix}_json_{helper_name}"),
+            output: function.output.clone(),
+            inputs: function.inputs.clone(),
+        },
+        parameters,
+        arguments,
+    })
+}
+
+/// Generates `debug_json.hpp` for the given extern functions
+pub(crate) fn generate_json_helpers(
+    functions: &[ExternFunction],
+    out_dir: &str,
+    prefix: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let free_byte_buffer = format!("{prefix}_free_byte_buffer");
+    let helpers = functions
+        .iter()
+        .filter_map(|function| json_helper(function, prefix))
+        .collect::<Vec<_>>();
+
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_debug_json.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include <string>\n").unwrap();
+    writeln!(
+        writer,
+        "#include \"{file_prefix}_api_functions.hpp\"  // IWYU pragma: export\n"
+    )
+    .unwrap();
+    for helper in &helpers {
+        writeln!(
+            writer,
+            "// `{}` with JSON instead of bincode",
+            helper.bincode_function
+        )
+        .unwrap();
+        writeln!(writer, "{}\n", helper.function.declaration()).unwrap();
+    }
+    writeln!(writer, "namespace {namespace} {{").unwrap();
+    writeln!(writer, "namespace debug_json {{\n").unwrap();
+    for helper in &helpers {
+        writeln!(
+            writer,
+            "// Calls `{}` with the JSON encoded arguments and returns the JSON encoded result",
+            helper.function.name
+        )
+        .unwrap();
+        writeln!(
+            writer,
+            "inline std::string {}({}) {{",
+            helper.name,
+            helper.parameters.join(", ")
+        )
+        .unwrap();
+        writeln!(writer, "    std::uint8_t* out_ptr = nullptr;").unwrap();
+        let mut arguments = helper.arguments.clone();
+        arguments.push(String::from("&out_ptr"));
+        writeln!(
+            writer,
+            "    size_t out_size = {}({});",
+            helper.function.name,
+            arguments.join(", ")
+        )
+        .unwrap();
+        writeln!(
+            writer,
+            "    std::string result(reinterpret_cast<const char*>(out_ptr), out_size);"
+        )
+        .unwrap();
+        writeln!(writer, "    {free_byte_buffer}(out_ptr, out_size);").unwrap();
+        writeln!(writer, "    return result;").unwrap();
+        writeln!(writer, "}}\n").unwrap();
+    }
+    writeln!(writer, "}}  // end of namespace debug_json").unwrap();
+    writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extern_function(name: &str, output: &str, inputs: &[(&str, &str)]) -> ExternFunction {
+        ExternFunction {
+            name: name.to_owned(),
+            output: output.to_owned(),
+            inputs: inputs
+                .iter()
+                .map(|(n, d)| (n.to_string(), d.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn serialized_arguments_are_passed_as_strings() {
+        let function = extern_function(
+            "buffi_scale_point",
+            "size_t",
+            &[
+                ("this_ptr", "TestClient* this_ptr"),
+                ("point", "const std::uint8_t* point"),
+                ("point_size", "size_t point_size"),
+                ("out_ptr", "std::uint8_t** out_ptr"),
+            ],
+        );
+        let helper = json_helper(&function, "buffi").unwrap();
+        assert_eq!(helper.name, "scale_point");
+        assert_eq!(helper.function.name, "buffi_json_scale_point");
+        assert_eq!(
+            helper.parameters,
+            ["TestClient* this_ptr", "const std::string& point"]
+        );
+        assert_eq!(
+            helper.arguments,
+            [
+                "this_ptr",
+                "reinterpret_cast<const std::uint8_t*>(point.data())",
+                "point.size()"
+            ]
+        );
+    }
+
+    #[test]
+    fn only_results_returned_via_out_ptr_have_a_json_function() {
+        let direct_return = extern_function(
+            "buffi_checked_add",
+            "size_t",
+            &[
+                ("value_out", "std::int64_t* value_out"),
+                ("error_out", "std::uint8_t** error_out"),
+            ],
+        );
+        let callback = extern_function(
+            "buffi_count_down",
+            "size_t",
+            &[
+                (
+                    "on_step",
+                    "size_t (*on_step)(void*, const std::uint8_t*, size_t, std::uint8_t**)",
+                ),
+                ("on_step_context", "void* on_step_context"),
+                ("out_ptr", "std::uint8_t** out_ptr"),
+            ],
+        );
+        let next_chunk = extern_function(
+            "buffi_next_chunk",
+            "size_t",
+            &[
+                ("chunks", "ChunkedResult* chunks"),
+                ("out_ptr", "const std::uint8_t** out_ptr"),
+            ],
+        );
+        let other_prefix = extern_function(
+            "other_stats",
+            "size_t",
+            &[("out_ptr", "std::uint8_t** out_ptr")],
+        );
+        for function in [direct_return, callback, next_chunk, other_prefix] {
+            assert!(
+                json_helper(&function, "buffi").is_none(),
+                "{}",
+                function.name
+            );
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Place the generated headers in subdirectories of the output directory
+//!
+//! By default all files are written to the output directory. With
+//! [`Config::layout`](crate::Config::layout), the extern declarations, the other headers and
+//! the serde/bincode runtime are moved into the configured subdirectories afterwards, and the
+//! `#include` directives between the generated files are rewritten to the relative paths, so
+//! that no additional include directories are required. All other files (like `embind.cpp` or
+//! `symbols.json`) stay in the output directory.
+
+use crate::summary::GeneratedFile;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// The headers of the serde and bincode runtime installed by `serde_generate`
+const RUNTIME_HEADERS: [&str; 3] = ["serde.hpp", "binary.hpp", "bincode.hpp"];
+
+/// The subdirectories (relative to the output directory) the generated headers are placed in,
+/// see [`Config::layout`](crate::Config::layout)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputLayout {
+    /// The directory of `api_functions.hpp`, which declares the extern functions
+    pub extern_declarations: Option<PathBuf>,
+    /// The directory of all other generated headers (types, holder classes, free-standing
+    /// functions, ...)
+    pub headers: Option<PathBuf>,
+    /// The directory of the serde and bincode runtime (`serde.hpp`, `binary.hpp` and
+    /// `bincode.hpp`)
+    pub runtime: Option<PathBuf>,
+}
+
+impl OutputLayout {
+    /// The directory `file_name` is placed in, relative to the output directory
+    fn directory(&self, file_name: &str, api_functions: &str) -> Option<&Path> {
+        let directory = if RUNTIME_HEADERS.contains(&file_name) {
+            &self.runtime
+        } else if file_name == api_functions {
+            &self.extern_declarations
+        } else if file_name.ends_with(".hpp") {
+            &self.headers
+        } else {
+            &None
+        };
+        directory.as_deref()
+    }
+}
+
+/// Moves the `files` written to `out_dir` to their directories and updates their paths,
+/// `api_functions` is the file name of the extern declarations
+pub(crate) fn apply(
+    files: &mut [GeneratedFile],
+    out_dir: &Path,
+    layout: &OutputLayout,
+    api_functions: &str,
+) -> std::io::Result<()> {
+    for directory in [
+        &layout.extern_declarations,
+        &layout.headers,
+        &layout.runtime,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if directory.is_absolute() || directory.components().any(|c| c == Component::ParentDir) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "`{}` needs to be a subdirectory of the output directory",
+                    directory.display()
+                ),
+            ));
+        }
+    }
+    // the new path of each generated file, relative to `out_dir`
+    let destinations = files
+        .iter()
+        .filter_map(|file| {
+            let name = file.path.file_name()?.to_str()?.to_owned();
+            let destination = match layout.directory(&name, api_functions) {
+                Some(directory) => directory.join(&name),
+                None => PathBuf::from(&name),
+            };
+            Some((name, destination))
+        })
+        .collect::<HashMap<_, _>>();
+
+    for file in files {
+        let Some(name) = file.path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let destination = out_dir.join(&destinations[name]);
+        let is_cpp = name.ends_with(".hpp") || name.ends_with(".cpp") || name.ends_with(".h");
+        if is_cpp {
+            let content = std::fs::read_to_string(&file.path)?;
+            let directory = destinations[name].parent().unwrap_or(Path::new(""));
+            let content = rewrite_includes(&content, directory, &destinations);
+            if let Some(parent) = destination.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&destination, content)?;
+            if destination != file.path {
+                std::fs::remove_file(&file.path)?;
+            }
+        } else if destination != file.path {
+            std::fs::rename(&file.path, &destination)?;
+        }
+        file.size = std::fs::metadata(&destination)?.len();
+        file.path = destination;
+    }
+    Ok(())
+}
+
+/// Points the includes of generated files in `content` (a file in `directory`) to their new
+/// location
+fn rewrite_includes(
+    content: &str,
+    directory: &Path,
+    destinations: &HashMap<String, PathBuf>,
+) -> String {
+    let mut out = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let included = line
+            .strip_prefix("#include \"")
+            .and_then(|rest| rest.split_once('"'));
+        match included {
+            Some((name, rest)) if destinations.contains_key(name) => {
+                let path = relative_path(directory, &destinations[name]);
+                out += &format!("#include \"{path}\"{rest}");
+            }
+            _ => out += line,
+        }
+    }
+    out
+}
+
+/// The path of `to` relative to `from`, both relative to the same directory
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    std::iter::repeat("..")
+        .take(from.len() - common)
+        .chain(
+            to[common..]
+                .iter()
+                .map(|c| c.as_os_str().to_str().unwrap_or_default()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_point_to_the_moved_headers() {
+        let destinations = HashMap::from([
+            (
+                String::from("api.hpp"),
+                PathBuf::from("include/api/api.hpp"),
+            ),
+            (
+                String::from("serde.hpp"),
+                PathBuf::from("include/runtime/serde.hpp"),
+            ),
+            (String::from("embind.cpp"), PathBuf::from("embind.cpp")),
+        ]);
+        let content = "#include \"serde.hpp\"\n#include \"api.hpp\"  // IWYU pragma: export\n#include <vector>\n";
+        assert_eq!(
+            rewrite_includes(content, Path::new("include/api"), &destinations),
+            "#include \"../runtime/serde.hpp\"\n#include \"api.hpp\"  // IWYU pragma: export\n#include <vector>\n"
+        );
+        assert_eq!(
+            rewrite_includes(content, Path::new(""), &destinations),
+            "#include \"include/runtime/serde.hpp\"\n#include \"include/api/api.hpp\"  // IWYU pragma: export\n#include <vector>\n"
+        );
+    }
+}
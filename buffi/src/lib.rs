@@ -9,6 +9,21 @@
 //! * types.hpp (includes all the types, name is dependent on the namespace)
 //! * testclient.hpp (C++ functions belonging to a struct such as `testclient`)
 //! * free_standing_functions.hpp (C++ functions not from an "impl" block)
+//! * wordfilter.hpp (abstract class for a trait implemented in C++, such as `WordFilter`)
+//! * events.hpp (subscriptions to the events pushed by the Rust side, if there are any)
+//! * error_category.hpp (`std::error_category` for `SerializableError`, optional)
+//! * module_namespaces.hpp (nested namespaces mirroring the Rust modules, optional)
+//! * qt_conversions.hpp (conversions between the generated and Qt types, optional)
+//! * embind.cpp and embind.d.ts (Emscripten bindings and TypeScript declarations, optional)
+//! * ffi.lua (LuaJIT FFI declarations and bincode helpers, optional)
+//! * adapter_traits.hpp and adapters.hpp (wrappers with pluggable string and vector types, optional)
+//! * loader.hpp and loader.cpp (load the API library at runtime via `dlopen`/`LoadLibrary`, optional)
+//! * exports.map and exports.def (linker export lists for GNU ld and MSVC, optional)
+//! * symbols.json (names and argument types of all extern functions, optional)
+//! * symbol_map.json (the extern function and Rust source location of each C++ function, optional)
+//! * smoke_test.cpp (checks the symbols and calling conventions of the API library, optional)
+//! * config.cmake and CMakeLists.txt (an `INTERFACE` library for CMake, optional)
+//! * debug_json.hpp (calls the JSON debug functions with JSON strings, optional)
 //!
 //! And these files to handle serde and bincode:
 //!
@@ -17,12 +32,16 @@
 //!
 #![doc = include_str!(concat!("../", env!("CARGO_PKG_README")))]
 
+use doc_source::{CargoRustdoc, DocSource};
+use docs::{to_cpp_doc, write_function_docs, FunctionDocs};
+use manifest::FunctionManifest;
 use serde::{Deserialize, Serialize};
 use serde_generate::SourceInstaller;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fmt::Write as _;
 use std::fs;
@@ -31,13 +50,77 @@ use std::io::BufWriter;
 use std::io::Write as _;
 use std::path::PathBuf;
 use std::path::{Component, Path};
-use std::process::{Output, Stdio};
+use std::process::Stdio;
+use std::rc::Rc;
+use std::time::SystemTime;
+use summary::{GenerationSummary, SkippedItem};
+use traced_types::SampleValues;
+use type_names::{to_type_name, TypeNames};
 
+mod abi_freeze;
+mod adapters;
+pub mod api_version;
+pub mod buffer;
+pub mod chunked;
+mod cmake;
+pub mod debug_json;
+pub mod doc_source;
+mod docs;
+mod embind;
+mod error;
+pub mod error_hook;
+pub mod events;
+mod fingerprint;
+pub mod imported;
+mod inline_namespace;
+mod interfaces;
+mod json_helpers;
+mod layout;
+mod loader;
+#[cfg(feature = "tracing")]
+pub mod log;
+mod lua;
+pub mod manifest;
+mod overloads;
+pub mod panic_hook;
+#[cfg(feature = "runtime")]
+pub mod runtime;
+mod smoke_test;
+pub mod stats;
+pub mod summary;
+pub mod testing;
+pub mod traced_types;
+mod type_names;
+mod verify;
+
+pub use cmake::{CMakeIntegration, LibraryKind};
+pub use docs::DocStyle;
+pub use error::Error;
+pub use layout::OutputLayout;
+pub use panic_hook::install_panic_hook;
+
+#[doc(hidden)]
+pub use buffi_macro::prefixed as __prefixed;
+
+/// The default prefix of the extern functions
 const FUNCTION_PREFIX: &str = "buffi";
 
 #[derive(Debug, serde::Deserialize)]
 struct WorkspaceMetadata {
     target_directory: String,
+    workspace_root: PathBuf,
+    packages: Vec<fingerprint::Package>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct WorkspacePackages {
+    packages: Vec<PackageMetadata>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct PackageMetadata {
+    name: String,
+    metadata: Option<serde_json::Value>,
 }
 
 /// A Config object that provides information for the generation of C/C++ code
@@ -55,12 +138,158 @@ pub struct Config {
     pub file_prefix: Option<String>,
     /// Copyright header to be included in every C/C++ file
     pub copyright_header: Option<String>,
+    /// A file containing a (multi-line) copyright header that is included verbatim in every C/C++ file,
+    /// so it needs to contain the comment markers as well. The placeholders `{year}`, `{file}` and
+    /// `{version}` (of buffi) are replaced. Takes precedence over `copyright_header`
+    pub copyright_header_file: Option<PathBuf>,
     /// Generated-by header to be included in every C/C++ file
     pub generated_by_header: Option<String>,
     /// In case you need to set any feature flags for build process of Rustdoc, add them here
     pub crate_feature_flags: Option<Vec<String>>,
     /// Add some additional rustdoc flags here, can be useful for debugging
     pub rustdoc_flags: Option<Vec<String>>,
+    /// Generate a `std::error_category` and error code constants for `SerializableError`
+    pub generate_error_category: Option<bool>,
+    /// A prefix for all generated C++ type names (e.g. `Gg`), in case they clash with other
+    /// types in the same namespace
+    pub type_name_prefix: Option<String>,
+    /// The prefix of the generated extern functions, needs to match the `prefix` argument of
+    /// `#[buffi_macro::exported]` and of the helper macros like
+    /// `buffi::export_chunked_results!(prefix = "...")` (defaults to `buffi`)
+    pub function_prefix: Option<String>,
+    /// Mirror the Rust modules as nested C++ namespaces (`crate::geometry::Point` is available as
+    /// `namespace::geometry::Point`) via type aliases in an additional `module_namespaces.hpp` file
+    pub module_namespaces: Option<bool>,
+    /// Generate inline conversions between the types used by the generated code and Qt types
+    /// (`QString`, `QByteArray`, `QStringList` and `QDateTime`) in an additional `qt_conversions.hpp` file
+    pub qt_conversions: Option<bool>,
+    /// Suppress warnings MSVC reports for the generated code (including the serde runtime)
+    /// via `#pragma warning`
+    pub msvc_compatibility: Option<bool>,
+    /// Wrap the generated code (including the serde runtime) in `// NOLINTBEGIN`/`// NOLINTEND`,
+    /// so that clang-tidy skips it
+    pub clang_tidy_nolint: Option<bool>,
+    /// GCC and Clang warnings (e.g. `-Wshadow`) to disable for the generated code (including the
+    /// serde runtime) via `#pragma GCC diagnostic`
+    pub suppressed_warnings: Option<Vec<String>>,
+    /// Generate the bindings for another target (e.g. `wasm32-unknown-unknown`) instead of the host
+    pub target: Option<String>,
+    /// Add the buffi version, a hash of this config and the versions of the source crates as
+    /// comments to every C/C++ file (no timestamps, so the output stays deterministic)
+    pub embed_provenance: Option<bool>,
+    /// The semantic version of your API (e.g. `1.2.0`), emitted as `constexpr` constants to compare
+    /// with the `{function_prefix}_api_version` function generated via `buffi::export_api_version!`
+    pub api_version: Option<String>,
+    /// Generate Emscripten bindings (`embind.cpp`) for the generated classes and functions
+    /// together with matching TypeScript declarations (`embind.d.ts`)
+    pub embind: Option<bool>,
+    /// Generate a Lua module (`ffi.lua`) with `ffi.cdef` declarations of the extern functions
+    /// and bincode encoders/decoders for all types, to call the API via the LuaJIT FFI
+    pub lua_ffi: Option<bool>,
+    /// Generate `*Adapter` class templates (`adapters.hpp`) whose signatures use the string and
+    /// vector types of a traits type (e.g. `FString`/`TArray`) instead of `std::string`/`std::vector`
+    pub stl_free_adapters: Option<bool>,
+    /// Generate `loader.hpp`/`loader.cpp`, which load the API library at runtime (via `dlopen` or
+    /// `LoadLibrary`) instead of linking it, for plugin-style deployments
+    pub runtime_loader: Option<bool>,
+    /// Generate a linker version script (`exports.map`, GNU ld) and a module-definition file
+    /// (`exports.def`, MSVC) listing exactly the extern functions of the API, to restrict the
+    /// symbols exported by the `cdylib` (e.g. via `-Wl,--version-script=` or `/DEF:`)
+    pub linker_exports: Option<bool>,
+    /// Generate `symbols.json`, which lists the names, return types and argument types of all
+    /// extern functions for tools that should not need to parse the headers
+    pub symbol_list: Option<bool>,
+    /// Print a summary of the generation (number of functions and types, skipped items, the
+    /// duration of each phase and the size of each file), see [`summary::GenerationSummary`]
+    pub print_summary: Option<bool>,
+    /// Skip functions whose types or signatures are not supported instead of aborting the
+    /// generation. They are reported as warnings and in the summary.
+    pub lenient: Option<bool>,
+    /// Emit `#line` directives in front of the C++ wrappers of each function, so that compiler
+    /// errors and debuggers point at the Rust function (relative to the workspace) instead
+    pub line_directives: Option<bool>,
+    /// Generate `symbol_map.json`, which maps each C++ wrapper function to the extern function it
+    /// calls and the source location of the Rust function, to symbolize native stack frames
+    pub symbol_map: Option<bool>,
+    /// Generate `smoke_test.cpp`, a program linked against the API library that references all
+    /// extern functions and checks that they report a null `this_ptr` or empty arguments as errors
+    pub abi_smoke_test: Option<bool>,
+    /// The order of the C++ wrapper functions within the generated headers (alphabetical by
+    /// default), e.g. to mirror a hand-written header that is replaced by the generated one
+    pub function_order: Option<FunctionOrder>,
+    /// Generate bindings for functions and constants marked with `#[doc(hidden)]` as well, which
+    /// are skipped by default (rustdoc is called with `--document-hidden-items` then)
+    pub include_hidden_items: Option<bool>,
+    /// Wrap the generated code in an inline namespace of this name (e.g. `v2` for
+    /// `namespace api { inline namespace v2 { ... }}`), so that two versions of the bindings can
+    /// be linked into one binary during a migration
+    pub inline_namespace: Option<String>,
+    /// A JSON snapshot of all types (usually committed next to the config). The generation fails
+    /// if the wire format of any type in it changes, unless the `BUFFI_BUMP_ABI_SNAPSHOT`
+    /// environment variable is set to update the snapshot. Created on the first run
+    pub abi_snapshot: Option<PathBuf>,
+    /// Place the extern declarations, the other headers and the serde/bincode runtime in
+    /// subdirectories of the output directory (e.g. `include/api` and `include/runtime`), all
+    /// files are placed in the output directory by default
+    pub layout: Option<OutputLayout>,
+    /// Generate `{file_prefix}-config.cmake` and a `CMakeLists.txt` including it, which define an
+    /// `INTERFACE` library for the generated headers that links the API library
+    pub cmake: Option<CMakeIntegration>,
+    /// Document all crates again, even if the fingerprints stored by [`generate_docs`] show that
+    /// the JSON output of the last run is still up to date
+    pub force_regenerate: Option<bool>,
+    /// Generate `debug_json.hpp`, which declares the JSON functions exported with the
+    /// `debug_json` feature of `buffi_macro` and wraps each of them in a function taking and
+    /// returning JSON strings
+    pub debug_json: Option<bool>,
+    /// How the docs of the C++ wrapper functions are written, `// line` comments by default.
+    /// `doxygen` writes `/** ... */` blocks with `@param`, `@return` and `@throws` tags
+    pub doc_style: Option<DocStyle>,
+}
+
+/// The order of the C++ wrapper functions within a header, see [`Config::function_order`]
+///
+/// The declarations in `api_functions.hpp` are always sorted alphabetically.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionOrder {
+    /// Sorted by the name of the Rust function
+    Alphabetical,
+    /// In the order in which the Rust functions are declared
+    Declaration,
+    /// The listed functions (either `name` or `Type::name`) come first in the given order, all
+    /// others follow alphabetically
+    Explicit(Vec<String>),
+}
+
+impl FunctionOrder {
+    /// Reorders the alphabetically sorted functions `items`, `location` returns the source
+    /// file and line of a function (if known)
+    fn reorder<T>(
+        &self,
+        items: &mut [T],
+        owner: Option<&str>,
+        name: impl Fn(&T) -> &str,
+        location: impl Fn(&T) -> Option<(PathBuf, usize)>,
+    ) {
+        match self {
+            FunctionOrder::Alphabetical => {}
+            // functions without a location keep their order at the end
+            FunctionOrder::Declaration => {
+                items.sort_by_key(|item| location(item).map_or((1, None), |l| (0, Some(l))))
+            }
+            FunctionOrder::Explicit(order) => items.sort_by_key(|item| {
+                let name = name(item);
+                order
+                    .iter()
+                    .position(|entry| match entry.split_once("::") {
+                        Some((tpe, entry)) => Some(tpe) == owner && entry == name,
+                        None => entry == name,
+                    })
+                    .unwrap_or(usize::MAX)
+            }),
+        }
+    }
 }
 
 impl Config {
@@ -78,10 +307,98 @@ impl Config {
             rustdoc_crates,
             file_prefix: None,
             copyright_header: None,
+            copyright_header_file: None,
             generated_by_header: None,
             crate_feature_flags: None,
             rustdoc_flags: None,
+            generate_error_category: None,
+            type_name_prefix: None,
+            function_prefix: None,
+            module_namespaces: None,
+            qt_conversions: None,
+            msvc_compatibility: None,
+            clang_tidy_nolint: None,
+            suppressed_warnings: None,
+            target: None,
+            embed_provenance: None,
+            api_version: None,
+            embind: None,
+            lua_ffi: None,
+            stl_free_adapters: None,
+            runtime_loader: None,
+            linker_exports: None,
+            symbol_list: None,
+            print_summary: None,
+            lenient: None,
+            line_directives: None,
+            symbol_map: None,
+            abi_smoke_test: None,
+            function_order: None,
+            include_hidden_items: None,
+            inline_namespace: None,
+            abi_snapshot: None,
+            layout: None,
+            cmake: None,
+            force_regenerate: None,
+            debug_json: None,
+            doc_style: None,
+        }
+    }
+
+    /// The prefix of the generated extern functions
+    fn function_prefix(&self) -> &str {
+        self.function_prefix.as_deref().unwrap_or(FUNCTION_PREFIX)
+    }
+
+    /// Read the config from the `[package.metadata.buffi]` table in the `Cargo.toml` of
+    /// `package`, which needs to be part of the workspace of the current directory
+    ///
+    /// `api_lib_name` and `parent_crate` default to the name of the package, `rustdoc_crates`
+    /// to only this package.
+    pub fn from_package_metadata(package: &str) -> Result<Self, Error> {
+        let metadata = std::process::Command::new("cargo")
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--no-deps")
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| Error::Generation(format!("Failed to run `cargo metadata`: {e}")))?;
+        if !metadata.status.success() {
+            return Err(Error::Generation(String::from(
+                "Failed to get workspace metadata",
+            )));
         }
+        let WorkspacePackages { packages } = serde_json::from_slice(&metadata.stdout)
+            .map_err(|e| Error::Generation(format!("Invalid workspace metadata: {e}")))?;
+        let package = packages
+            .into_iter()
+            .find(|p| p.name == package)
+            .ok_or_else(|| {
+                Error::Generation(format!("`{package}` is not part of the workspace"))
+            })?;
+        let Some(serde_json::Value::Object(mut config)) = package
+            .metadata
+            .and_then(|mut metadata| metadata.get_mut("buffi").map(serde_json::Value::take))
+        else {
+            return Err(Error::Generation(format!(
+                "`{}` has no `[package.metadata.buffi]` table",
+                package.name
+            )));
+        };
+        let name = serde_json::Value::from(package.name.clone());
+        config
+            .entry("parent_crate")
+            .or_insert_with(|| package.name.replace('-', "_").into());
+        config
+            .entry("rustdoc_crates")
+            .or_insert_with(|| serde_json::Value::Array(vec![name.clone()]));
+        config.entry("api_lib_name").or_insert(name);
+        serde_json::from_value(serde_json::Value::Object(config)).map_err(|e| {
+            Error::Generation(format!(
+                "Invalid `[package.metadata.buffi]` table of `{}`: {e}",
+                package.name
+            ))
+        })
     }
 
     /// Add some additional flags that should be passed when creating the rustdocs
@@ -94,21 +411,88 @@ impl Config {
     }
 }
 
+/// The id of a resolved item, the crate it was referenced in and the name it was referenced by
+type ResolvedKey = (rustdoc_types::Id, String, Option<String>);
+
 struct ItemResolver {
-    base_path: String,
+    source: Box<dyn DocSource>,
     doc_types: rustdoc_types::Crate,
     other_crates: RefCell<HashMap<String, rustdoc_types::Crate>>,
+    type_names: RefCell<TypeNames>,
+    samples: Option<Box<dyn SampleValues>>,
+    /// The functions skipped in lenient mode
+    skipped: RefCell<Vec<(rustdoc_types::Id, SkippedItem)>>,
+    /// The items returned by [`ItemResolver::resolve_index`], as the fields and variants of a
+    /// type are resolved for each function using it
+    resolved: RefCell<HashMap<ResolvedKey, Rc<rustdoc_types::Item>>>,
 }
 
 impl ItemResolver {
-    fn new(json_path: String, api_lib_name: &str) -> Self {
-        let content = std::fs::read_to_string(json_path.clone() + api_lib_name + ".json").unwrap();
-        let doc_types = serde_json::from_str(&content).unwrap();
-        Self {
-            base_path: json_path,
+    fn new(
+        source: Box<dyn DocSource>,
+        samples: Option<Box<dyn SampleValues>>,
+        api_lib_name: &str,
+        type_name_prefix: Option<&str>,
+    ) -> Result<Self, Error> {
+        let mut doc_types = source.load(api_lib_name).map_err(|e| {
+            Error::Rustdoc(format!("Failed to load the docs of `{api_lib_name}`: {e}"))
+        })?;
+        instantiate_generic_impls(&mut doc_types)?;
+        resolve_impl_trait_arguments(&mut doc_types);
+        Ok(Self {
+            source,
             doc_types,
             other_crates: RefCell::new(HashMap::new()),
+            type_names: RefCell::new(TypeNames::with_prefix(
+                type_name_prefix.unwrap_or_default().to_owned(),
+            )),
+            samples,
+            skipped: RefCell::new(Vec::new()),
+            resolved: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Runs `f` for `item` (called `name` in warnings and errors)
+    ///
+    /// With [`Config::lenient`] an [`Error::Unsupported`] skips the item instead of aborting
+    /// the generation. Otherwise the error is returned, naming the item.
+    fn lenient<T>(
+        &self,
+        config: &Config,
+        item: &rustdoc_types::Item,
+        name: &str,
+        type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+        f: impl FnOnce(&mut HashMap<rustdoc_types::Type, TypeCache>) -> Result<T, Error>,
+    ) -> Result<Option<T>, Error> {
+        let error = match f(type_map) {
+            Ok(result) => return Ok(Some(result)),
+            Err(e) => e.in_function(name),
+        };
+        let Error::Unsupported { ref message, .. } = error else {
+            return Err(error);
+        };
+        if config.lenient != Some(true) {
+            return Err(error);
         }
+        // the types that were being reflected are incomplete, so they need to be
+        // reflected again if another function uses them
+        type_map.retain(|_, cache| !matches!(cache, TypeCache::NeedToPopulate));
+        println!("Warning: skipping `{name}`: {message}");
+        self.skipped.borrow_mut().push((
+            item.id,
+            SkippedItem {
+                name: name.to_owned(),
+                reason: message.clone(),
+            },
+        ));
+        Ok(None)
+    }
+
+    fn is_skipped(&self, id: &rustdoc_types::Id) -> bool {
+        self.skipped
+            .borrow()
+            .iter()
+            .any(|(skipped, _)| skipped == id)
     }
 
     // this function expects a fully qualified path.
@@ -117,7 +501,7 @@ impl ItemResolver {
         path: &str,
         parent_crate: &str,
         requested_item: rustdoc_types::ItemKind,
-    ) -> rustdoc_types::Path {
+    ) -> Result<rustdoc_types::Path, Error> {
         let mut parts = path.split("::").collect::<Vec<_>>();
         if parts[0] == "crate" {
             parts[0] = parent_crate;
@@ -127,46 +511,74 @@ impl ItemResolver {
             let map = if parts[0] == parent_crate {
                 &self.doc_types
             } else {
-                other_crates.entry(parts[0].to_owned()).or_insert_with(|| {
-                    self.load_extern_crate_doc(parts[0], &format!("(needed for {path:?})"))
-                })
+                self.extern_crate_doc(
+                    &mut other_crates,
+                    parts[0],
+                    &format!("(needed for {path:?})"),
+                )?
             };
             let (id, summary) = map
                 .paths
                 .iter()
                 .find(|(_, i)| i.path == parts)
-                .expect("It's there");
-            if summary.kind == requested_item {
-                *id
-            } else {
-                panic!(
+                .ok_or_else(|| Error::unsupported(format!("Unknown path: `{path}`")))?;
+            if summary.kind != requested_item {
+                return Err(Error::unsupported(format!(
                     "Incompatible type: Expected {requested_item:?}, Got {:?}",
                     summary.kind
-                );
+                )));
             }
+            *id
         };
-        rustdoc_types::Path {
+        Ok(rustdoc_types::Path {
             name: parts[parts.len() - 1].to_owned(),
             id,
             args: None,
-        }
+        })
     }
 
+    /// The item `id` referenced (via the path `t`, if known) in `parent_crate`
+    ///
+    /// Each item is only resolved once, later lookups share it.
     fn resolve_index(
         &self,
         t: Option<&rustdoc_types::Path>,
         id: &rustdoc_types::Id,
         parent_crate: &str,
-    ) -> rustdoc_types::Item {
+    ) -> Result<Rc<rustdoc_types::Item>, Error> {
+        // only the name of `t` is used to resolve items of other crates
+        let key = (
+            *id,
+            parent_crate.to_owned(),
+            t.and_then(|t| t.name.split("::").last()).map(str::to_owned),
+        );
+        if let Some(item) = self.resolved.borrow().get(&key) {
+            return Ok(Rc::clone(item));
+        }
+        let item = Rc::new(self.resolve_index_uncached(t, id, parent_crate)?);
+        self.resolved.borrow_mut().insert(key, Rc::clone(&item));
+        Ok(item)
+    }
+
+    fn resolve_index_uncached(
+        &self,
+        t: Option<&rustdoc_types::Path>,
+        id: &rustdoc_types::Id,
+        parent_crate: &str,
+    ) -> Result<rustdoc_types::Item, Error> {
         let mut other_crates = self.other_crates.borrow_mut();
 
-        let candidates = std::iter::once(&self.doc_types)
+        let mut candidates = Vec::new();
+        for item in std::iter::once(&self.doc_types)
             .chain(other_crates.values())
             .filter_map(|c| c.index.get(id))
-            .filter(|i| extract_crate_from_span(i) == Some(parent_crate.into()))
-            .collect::<Vec<_>>();
+        {
+            if extract_crate_from_span(item)?.as_deref() == Some(parent_crate) {
+                candidates.push(item);
+            }
+        }
         match &candidates as &[&rustdoc_types::Item] {
-            [i] => return rustdoc_types::Item::clone(i),
+            [i] => return Ok(rustdoc_types::Item::clone(i)),
             [] => {
                 // handled by the code below
             }
@@ -175,15 +587,17 @@ impl ItemResolver {
                 //
                 // * There is a candidate coming from this crate (indicated by the parent_crate)
                 //   argument
-                let matches_parent_crate = items
-                    .iter()
-                    .position(|i| extract_crate_from_span(i) == Some(parent_crate.into()));
+                let matches_parent_crate = items.iter().position(
+                    |i| matches!(extract_crate_from_span(i), Ok(Some(c)) if c == parent_crate),
+                );
                 match matches_parent_crate {
                     Some(t) => {
-                        return rustdoc_types::Item::clone(items[t]);
+                        return Ok(rustdoc_types::Item::clone(items[t]));
                     }
                     _ => {
-                        panic!("Cannot decide what's the correct candidate")
+                        return Err(Error::unsupported(
+                            "Cannot decide what's the correct candidate",
+                        ));
                     }
                 }
             }
@@ -212,9 +626,11 @@ impl ItemResolver {
             // not by crate-id as these id's are not stable across
             // different crates
             let crate_name = crate_id.path.first().unwrap().clone();
-            let other_index = other_crates.entry(crate_name.clone()).or_insert_with(|| {
-                self.load_extern_crate_doc(&crate_name, &format!("(needed for {t:?})"))
-            });
+            let other_index = self.extern_crate_doc(
+                &mut other_crates,
+                &crate_name,
+                &format!("(needed for {t:?})"),
+            )?;
 
             // This is just guessing the right item at this point
             // This likely needs improvements
@@ -235,90 +651,379 @@ impl ItemResolver {
                     )
             });
             if let Some(item) = item {
-                return item.clone();
+                return Ok(item.clone());
             }
         }
-        panic!(
+        Err(Error::unsupported(format!(
             "Unknown id: {:?}, crate: {:?} (full type:{:?})",
             id, parent_crate, t
-        );
+        )))
+    }
+
+    /// The full path (`crate::module::Name`) of the item `id` referenced in `crate_name`
+    fn item_path(&self, id: &rustdoc_types::Id, crate_name: &str) -> Option<Vec<String>> {
+        let root_name = self
+            .doc_types
+            .index
+            .get(&self.doc_types.root)
+            .and_then(|root| root.name.as_deref());
+        if root_name == Some(crate_name) {
+            self.doc_types.paths.get(id).map(|s| s.path.clone())
+        } else {
+            self.other_crates
+                .borrow()
+                .get(crate_name)
+                .and_then(|c| c.paths.get(id).map(|s| s.path.clone()))
+        }
     }
 
-    fn load_extern_crate_doc(
+    /// The docs of `crate_name` in `other_crates`, loaded on first use
+    fn extern_crate_doc<'a>(
         &self,
+        other_crates: &'a mut HashMap<String, rustdoc_types::Crate>,
         crate_name: &str,
         additional_message: &str,
-    ) -> rustdoc_types::Crate {
-        let content = std::fs::read_to_string(self.base_path.clone() + crate_name + ".json")
-            .unwrap_or_else(|_| {
-                panic!(
-                    "Failed to find docs for `{}` {}",
-                    &crate_name, additional_message
-                );
-            });
-        serde_json::from_str(&content).unwrap()
+    ) -> Result<&'a rustdoc_types::Crate, Error> {
+        match other_crates.entry(crate_name.to_owned()) {
+            std::collections::hash_map::Entry::Occupied(e) => Ok(e.into_mut()),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                let docs = self.source.load(crate_name).map_err(|err| {
+                    Error::Rustdoc(format!(
+                        "Failed to find docs for `{crate_name}` {additional_message}: {err}"
+                    ))
+                })?;
+                Ok(e.insert(docs))
+            }
+        }
+    }
+
+    /// Comment lines describing which generator and inputs produced the bindings,
+    /// if enabled via [`Config::embed_provenance`]
+    fn provenance(&self, config: &Config) -> Result<Option<String>, Error> {
+        if config.embed_provenance != Some(true) {
+            return Ok(None);
+        }
+        let config_json = serde_json::to_string(config).expect("Config is serializable");
+        let mut crates = Vec::new();
+        for crate_name in std::iter::once(&config.api_lib_name).chain(&config.rustdoc_crates) {
+            let crate_name = crate_name.replace('-', "_");
+            if crates.iter().any(|(c, _)| *c == crate_name) {
+                continue;
+            }
+            let version = if crate_name == config.api_lib_name.replace('-', "_") {
+                self.doc_types.crate_version.clone()
+            } else {
+                self.extern_crate_doc(&mut self.other_crates.borrow_mut(), &crate_name, "")?
+                    .crate_version
+                    .clone()
+            };
+            crates.push((crate_name, version.unwrap_or_else(|| "unknown".into())));
+        }
+        let crates = crates
+            .iter()
+            .map(|(name, version)| format!("{name} {version}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(Some(format!(
+            "// buffi version: {}\n// config hash: {:016x}\n// source crates: {crates}\n",
+            env!("CARGO_PKG_VERSION"),
+            fnv1a_hash(config_json.as_bytes()),
+        )))
     }
 }
 
+/// A simple hash that is stable across Rust versions and platforms
+/// (unlike the hashers of `std`)
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Disables warnings MSVC reports for the generated code:
+///
+/// * C4100: unreferenced formal parameter (e.g. in the serde runtime)
+/// * C4244/C4267: conversions that might lose data (lengths are stored as `uint32_t` or `uint64_t`)
+/// * C4702: unreachable code (after `throw` in the deserializers)
+const MSVC_WARNING_PUSH: &str = "#ifdef _MSC_VER\n\
+    #pragma warning(push)\n\
+    #pragma warning(disable : 4100 4244 4267 4702)\n\
+    #endif";
+const MSVC_WARNING_POP: &str = "#ifdef _MSC_VER\n#pragma warning(pop)\n#endif";
+
+/// Added to the documentation of `#[non_exhaustive]` types on the C++ side
+const NON_EXHAUSTIVE_WARNING: &str = "WARNING: This type is `#[non_exhaustive]` on the Rust side. \
+     New variants or fields might be added in a compatible Rust release, which breaks \
+     deserializing this type in C++ code that was compiled against an older version. \
+     Regenerate and recompile the bindings whenever the Rust library is updated.";
+
+/// A format and, for named types, their container format
+///
+/// Containers are shared as they are repeated in the cache and in the result of every type
+/// referring to them.
+type ReflectedFormat = (
+    serde_reflection::Format,
+    Option<Rc<serde_reflection::ContainerFormat>>,
+);
+
 enum TypeCache {
     NeedToPopulate,
-    Cached(
-        Vec<(
-            serde_reflection::Format,
-            Option<serde_reflection::ContainerFormat>,
-        )>,
-    ),
+    Cached(Vec<ReflectedFormat>),
+}
+
+/// Runs rustdoc for the crates of `config` and writes the bindings to `out_dir`
+///
+/// If the API cannot be expressed in the bindings, the returned [`Error`] names the function
+/// and the types that were being reflected.
+pub fn generate_bindings(out_dir: &Path, config: Config) -> Result<GenerationSummary, Error> {
+    generate_bindings_with_source(out_dir, config, Box::new(CargoRustdoc::default()))
+}
+
+/// Like [`generate_bindings`], but reads the rustdoc JSON output from the given source
+/// instead of running rustdoc
+pub fn generate_bindings_with_source(
+    out_dir: &Path,
+    config: Config,
+    source: Box<dyn DocSource>,
+) -> Result<GenerationSummary, Error> {
+    run_generation(out_dir, config, source, None)
+}
+
+/// Like [`generate_bindings`], but traces the types known to `samples` via
+/// `serde_reflection::Tracer` instead of reflecting them via rustdoc, see [`traced_types`]
+pub fn generate_bindings_with_samples(
+    out_dir: &Path,
+    config: Config,
+    samples: impl SampleValues + 'static,
+) -> Result<GenerationSummary, Error> {
+    run_generation(
+        out_dir,
+        config,
+        Box::new(CargoRustdoc::default()),
+        Some(Box::new(samples)),
+    )
 }
 
-pub fn generate_bindings(out_dir: &Path, config: Config) {
+fn run_generation(
+    out_dir: &Path,
+    config: Config,
+    mut source: Box<dyn DocSource>,
+    samples: Option<Box<dyn SampleValues>>,
+) -> Result<GenerationSummary, Error> {
     if !out_dir.exists() {
-        panic!("Out directory does not exist");
+        return Err(Error::MissingOutputDirectory(out_dir.to_owned()));
     }
 
-    let (target_directory, handle) = generate_docs(
-        &config.api_lib_name,
-        &config.rustdoc_crates,
-        config.crate_feature_flags.as_ref().unwrap_or(&Vec::new()),
-        config.rustdoc_flags.as_ref().unwrap_or(&Vec::new()),
-    );
+    let started = SystemTime::now();
+    let mut summary = GenerationSummary::default();
+    summary
+        .time("rustdoc", || source.prepare(&config))
+        .map_err(|e| match e.downcast::<Error>() {
+            Ok(e) => *e,
+            Err(e) => Error::Rustdoc(e.to_string()),
+        })?;
+    let resolver = summary.time("loading docs", || {
+        ItemResolver::new(
+            source,
+            samples,
+            &config.api_lib_name,
+            config.type_name_prefix.as_deref(),
+        )
+    })?;
+    let mut type_map = HashMap::new();
+    let out_dir_str = out_dir.display().to_string();
+    let provenance = resolver.provenance(&config)?;
+    let registry = summary.time("types", || {
+        generate_type_definitions(&resolver, &out_dir_str, &mut type_map, &config)
+    })?;
+    let functions = summary.time("functions", || {
+        generate_function_definitions(
+            resolver,
+            &out_dir_str,
+            &mut type_map,
+            config.function_prefix(),
+            &config,
+        )
+    })?;
+    let skipped = summary.time("optional bindings", || {
+        generate_optional_bindings(
+            &registry,
+            &functions,
+            &out_dir_str,
+            &config,
+            provenance.as_deref(),
+        )
+    })?;
+    summary.functions = functions.extern_functions.len();
+    summary.types = registry.len();
+    summary.skipped = functions.skipped.clone();
+    summary.skipped.extend(skipped);
 
-    let mut failed = false;
-    if let Ok(handle) = handle {
-        if handle.status.success() {
-            let resolver = ItemResolver::new(target_directory + "/doc/", &config.api_lib_name);
-            let mut type_map = HashMap::new();
-            let out_dir = out_dir.display().to_string();
-            generate_type_definitions(&resolver, &out_dir, &mut type_map, &config);
-            generate_function_definitions(
-                resolver,
-                &out_dir,
-                &mut type_map,
-                FUNCTION_PREFIX,
-                &config,
-            );
-        } else {
-            failed = true;
-        }
-    } else {
-        failed = true;
+    println!("Finished, wrote bindings to `{}`", out_dir.display());
+    finish_summary(summary, out_dir, started, &config)
+}
+
+/// Adds the generated files to `summary` and prints it if enabled
+fn finish_summary(
+    mut summary: GenerationSummary,
+    out_dir: &Path,
+    started: SystemTime,
+    config: &Config,
+) -> Result<GenerationSummary, Error> {
+    let io_error = |source| Error::Io {
+        path: out_dir.to_owned(),
+        source,
+    };
+    summary.collect_files(out_dir, started);
+    if let Some(ref inline_namespace) = config.inline_namespace {
+        let files = summary.files.iter().map(|f| f.path.as_path());
+        inline_namespace::apply(files, &config.namespace, inline_namespace).map_err(io_error)?;
+        summary.collect_files(out_dir, started);
     }
+    if let Some(ref layout) = config.layout {
+        let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+        let api_functions = format!("{file_prefix}_api_functions.hpp");
+        layout::apply(&mut summary.files, out_dir, layout, &api_functions).map_err(io_error)?;
+    }
+    if config.print_summary == Some(true) {
+        print!("{summary}");
+    }
+    Ok(summary)
+}
 
-    if !failed {
-        println!("Finished, wrote bindings to `{}`", out_dir.display());
+/// Generates the bindings for the types of `registry` and the functions of `manifest` instead
+/// of reading them from the rustdoc JSON output, see [`manifest`] for the supported functions
+///
+/// Use this for types the rustdoc based pipeline cannot express, the registry can be built by
+/// hand or traced via `serde_reflection::Tracer`. It needs to contain the `Result` enum of
+/// each function.
+pub fn generate_from_registry(
+    out_dir: &Path,
+    config: Config,
+    registry: serde_reflection::Registry,
+    manifest: &FunctionManifest,
+) -> Result<GenerationSummary, Error> {
+    if !out_dir.exists() {
+        return Err(Error::MissingOutputDirectory(out_dir.to_owned()));
     }
 
-    if failed {
-        eprintln!("Failed to generate bindings");
-        std::process::exit(1);
+    let started = SystemTime::now();
+    let mut summary = GenerationSummary::default();
+    let out_dir_str = out_dir.display().to_string();
+    summary.time("types", || {
+        write_type_definitions(
+            &registry,
+            serde_generate::DocComments::new(),
+            &out_dir_str,
+            &config,
+            None,
+        )
+    })?;
+    let functions = summary.time("functions", || {
+        manifest::generate_function_definitions(
+            &registry,
+            manifest,
+            &out_dir_str,
+            config.function_prefix(),
+            &config,
+        )
+    })?;
+    let skipped = summary.time("optional bindings", || {
+        generate_optional_bindings(&registry, &functions, &out_dir_str, &config, None)
+    })?;
+    summary.functions = functions.extern_functions.len();
+    summary.types = registry.len();
+    summary.skipped = skipped;
+    println!("Finished, wrote bindings to `{}`", out_dir.display());
+    finish_summary(summary, out_dir, started, &config)
+}
+
+/// Generates the optional bindings for other languages and tools
+fn generate_optional_bindings(
+    registry: &serde_reflection::Registry,
+    functions: &GeneratedFunctions,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<Vec<SkippedItem>, Error> {
+    let mut skipped = Vec::new();
+    if config.embind == Some(true) {
+        skipped.extend(embind::generate_embind_bindings(
+            registry,
+            &functions.wrappers,
+            out_dir,
+            config,
+            provenance,
+        )?);
+    }
+    if config.stl_free_adapters == Some(true) {
+        adapters::generate_adapters(&functions.wrappers, out_dir, config, provenance)?;
+    }
+    if config.runtime_loader == Some(true) {
+        loader::generate_loader(functions, out_dir, config, provenance)?;
     }
+    if config.linker_exports == Some(true) {
+        generate_linker_exports(&functions.extern_functions, out_dir, config, provenance)?;
+    }
+    if config.symbol_list == Some(true) {
+        generate_symbol_list(&functions.extern_functions, out_dir, config)?;
+    }
+    if config.symbol_map == Some(true) {
+        generate_symbol_map(&functions.mapped_functions, out_dir, config)?;
+    }
+    if config.abi_smoke_test == Some(true) {
+        smoke_test::generate_smoke_test(
+            &functions.extern_functions,
+            out_dir,
+            config.function_prefix(),
+            config,
+            provenance,
+        )?;
+    }
+    if config.lua_ffi == Some(true) {
+        skipped.extend(lua::generate_lua_module(
+            registry,
+            functions,
+            out_dir,
+            config.function_prefix(),
+            config,
+            provenance,
+        )?);
+    }
+    if config.debug_json == Some(true) {
+        json_helpers::generate_json_helpers(
+            &functions.extern_functions,
+            out_dir,
+            config.function_prefix(),
+            config,
+            provenance,
+        )?;
+    }
+    if let Some(ref cmake) = config.cmake {
+        cmake::generate_cmake_files(cmake, out_dir, config, provenance)?;
+    }
+    Ok(skipped)
 }
 
+/// Runs `cargo rustdoc` with the JSON output for the API library and `rustdoc_crates` and
+/// returns the target directory containing the JSON files
+///
+/// Crates whose sources did not change since the last run are skipped, unless
+/// `force_regenerate` is set.
 pub fn generate_docs(
     api_lib_name: &String,
     rustdoc_crates: &[String],
     crate_flags: &[String],
     rustdoc_flags: &[String],
-) -> (String, Result<Output, std::io::Error>) {
+    document_hidden_items: bool,
+    force_regenerate: bool,
+) -> Result<String, Error> {
+    if rustdoc_crates.is_empty() {
+        return Err(Error::Rustdoc(String::from(
+            "Need at least one input crate to create bindings",
+        )));
+    }
+
     print!("Gather workspace metadata:");
     std::io::stdout().flush().expect("Flushing does not fail");
     let metadata = std::process::Command::new("cargo")
@@ -326,64 +1031,109 @@ pub fn generate_docs(
         .arg("--format-version=1")
         .stderr(Stdio::inherit())
         .output()
-        .expect("Failed to get workspace metadata");
+        .map_err(|e| Error::Rustdoc(format!("Failed to get workspace metadata: {e}")))?;
     println!(" OK");
 
-    let WorkspaceMetadata { target_directory } = serde_json::from_slice(&metadata.stdout).unwrap();
-    // remove all old json doc files (if any exist), important in case the configuration has changed
-    let doc_directory = target_directory.to_owned() + "/doc";
-    if matches!(fs::exists(&doc_directory), Ok(true)) {
-        for entry in fs::read_dir(doc_directory).unwrap() {
-            let file_path = entry.unwrap().path();
-            if file_path.extension().and_then(|s| s.to_str()) == Some("json") {
-                fs::remove_file(file_path).unwrap();
-            }
+    let WorkspaceMetadata {
+        target_directory,
+        workspace_root,
+        packages,
+    } = serde_json::from_slice(&metadata.stdout)
+        .map_err(|e| Error::Rustdoc(format!("Failed to read workspace metadata: {e}")))?;
+    // the json doc files of unchanged crates are reused, the fingerprints include the
+    // configuration as well
+    let fingerprints = fingerprint::Fingerprints::new(
+        &target_directory,
+        rustdoc_flags,
+        &workspace_root,
+        &packages,
+    );
+
+    let nightly = is_nightly_toolchain();
+
+    // document each crate on its own, so that the unstable JSON output is only requested
+    // for exactly these crates instead of every crate built along the way
+    let mut crates: Vec<&String> = Vec::new();
+    for crate_name in std::iter::once(api_lib_name).chain(rustdoc_crates) {
+        if !crates.contains(&crate_name) {
+            crates.push(crate_name);
         }
     }
-
-    if rustdoc_crates.is_empty() {
-        eprintln!("Need at least one input crate to create bindings!");
-        std::process::exit(1);
+    println!("Compile rustdocs:");
+    for crate_name in crates {
+        // cargo rejects features of packages that are neither selected nor dependencies
+        let crate_flag_args = crate_flags
+            .iter()
+            .filter(|crate_and_flag| match crate_and_flag.split_once('/') {
+                Some((flag_crate, _)) => {
+                    flag_crate.replace('-', "_") == crate_name.replace('-', "_")
+                }
+                None => true,
+            })
+            .flat_map(|crate_and_flag| ["-F", crate_and_flag]);
+        let mut rustdoc_command = std::process::Command::new("cargo");
+        rustdoc_command
+            .arg("rustdoc")
+            .args(["-p", crate_name])
+            .args(crate_flag_args)
+            .args(rustdoc_flags)
+            .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+            .args(document_hidden_items.then_some("--document-hidden-items"))
+            .env("CARGO_TARGET_DIR", &target_directory)
+            .stderr(Stdio::inherit())
+            .stdout(Stdio::inherit());
+        if !nightly {
+            // stable toolchains only accept `-Z` flags with `RUSTC_BOOTSTRAP`, which is
+            // limited to the documented crate
+            rustdoc_command.env("RUSTC_BOOTSTRAP", crate_name.replace('-', "_"));
+        }
+        let fingerprint = fingerprints.fingerprint(crate_name, &rustdoc_command);
+        if let Some(fingerprint) = &fingerprint {
+            if !force_regenerate && fingerprints.is_up_to_date(crate_name, fingerprint) {
+                println!("    {crate_name} is up to date");
+                continue;
+            }
+        }
+        fingerprints.invalidate(crate_name);
+        let output = rustdoc_command
+            .output()
+            .map_err(|e| Error::Rustdoc(format!("Failed to run `cargo rustdoc`: {e}")))?;
+        if !output.status.success() {
+            return Err(Error::Rustdoc(format!(
+                "Failed to generate the rustdoc JSON output of `{crate_name}`"
+            )));
+        }
+        if let Some(fingerprint) = &fingerprint {
+            fingerprints.store(crate_name, fingerprint);
+        }
     }
+    Ok(target_directory)
+}
 
-    // only build documentation for our own crates for now
-    let mut args = vec!["--no-deps"];
-    let crate_args: Vec<_> = rustdoc_crates
-        .iter()
-        .flat_map(|crate_name| vec!["-p", crate_name])
-        .collect();
-    let crate_flag_args: Vec<_> = crate_flags
-        .iter()
-        .flat_map(|crate_and_flag| vec!["-F", crate_and_flag])
-        .collect();
-    args.extend(crate_args);
-    args.extend(crate_flag_args);
-    args.extend(rustdoc_flags.iter().map(|s| s as &str));
-
-    let bootstrap_crates = vec![api_lib_name].into_iter().chain(rustdoc_crates).fold(
-        String::new(),
-        |cumulated, crate_name| {
-            let crate_name = crate_name.replace("-", "_");
-            cumulated + &format!(",{crate_name}")
-        },
-    );
-    // this works because `rustdoc_crates` has at least one entry
-    let bootstrap_crates = &bootstrap_crates[1..bootstrap_crates.len()];
-
-    println!("Compile rustdocs:");
-    let mut rustdoc_command = std::process::Command::new("cargo");
-
-    rustdoc_command
-        .arg("doc")
-        .args(args)
-        .env("RUSTC_BOOTSTRAP", bootstrap_crates)
-        .env("RUSTDOCFLAGS", "-Z unstable-options --output-format json ")
-        .env("CARGO_TARGET_DIR", &target_directory)
+/// Whether `rustc` is a nightly (or dev) toolchain, which supports unstable flags as is
+fn is_nightly_toolchain() -> bool {
+    std::process::Command::new("rustc")
+        .arg("--version")
         .stderr(Stdio::inherit())
-        .stdout(Stdio::inherit());
+        .output()
+        .map(|output| {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("-nightly") || version.contains("-dev")
+        })
+        .unwrap_or(false)
+}
 
-    let handle = rustdoc_command.output();
-    (target_directory, handle)
+/// The generated functions, as needed by the optional bindings for other languages
+struct GeneratedFunctions {
+    /// The functions declared in `api_functions.hpp`, in the same order
+    extern_functions: Vec<ExternFunction>,
+    /// Types that are only passed as pointers to the extern functions
+    opaque_types: Vec<String>,
+    wrappers: embind::WrapperFunctions,
+    /// The C++ wrapper functions and the extern functions they call
+    mapped_functions: Vec<MappedFunction>,
+    /// The functions that were skipped in lenient mode
+    skipped: Vec<SkippedItem>,
 }
 
 fn generate_function_definitions(
@@ -392,64 +1142,152 @@ fn generate_function_definitions(
     type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
     function_prefix: &str,
     config: &Config,
-) {
+) -> Result<GeneratedFunctions, Error> {
     let namespace = &config.namespace;
     let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let provenance = res.provenance(config)?;
+    let mut wrapper_functions = embind::WrapperFunctions::default();
+    let mut mapped_functions = Vec::new();
 
     let out_dir = PathBuf::from(out_dir);
-    let mut extern_c_functions = res
+    let mut has_by_value_arguments = false;
+    let mut extern_c_functions = Vec::new();
+    for item in res
         .doc_types
         .index
         .values()
-        .filter_map(|item| {
-            if let rustdoc_types::ItemEnum::Function(ref func) = item.inner {
-                if matches!(func.header.abi, rustdoc_types::Abi::C { .. }) {
-                    let s = generate_extern_c_function_def(item.name.as_deref().unwrap(), func);
-                    Some(s)
-                } else {
-                    None
+        .filter(|item| !is_doc_only(item) && !is_hidden(item, config))
+    {
+        let rustdoc_types::ItemEnum::Function(ref func) = item.inner else {
+            continue;
+        };
+        if !matches!(func.header.abi, rustdoc_types::Abi::C { .. }) {
+            continue;
+        }
+        let name = item.name.as_deref().unwrap();
+        let function = res.lenient(config, item, name, type_map, |type_map| {
+            let mut by_value = HashMap::new();
+            for (arg, tpe) in func
+                .sig
+                .inputs
+                .iter()
+                .filter(|(_, tpe)| is_by_value_type(tpe))
+            {
+                if let rustdoc_types::Type::ResolvedPath(p) = tpe {
+                    let t = res.resolve_index(Some(p), &p.id, &config.parent_crate)?;
+                    if !t.attrs.iter().any(|a| a.contains("repr(C)")) {
+                        return Err(Error::unsupported(format!(
+                            "`{arg}` of `{name}` is passed by value, but `{}` is not `#[repr(C)]`",
+                            p.name
+                        )));
+                    }
                 }
-            } else {
-                None
+                let tpe = to_serde_reflect_type(
+                    tpe,
+                    &res,
+                    &mut None,
+                    Vec::new(),
+                    &config.parent_crate,
+                    namespace,
+                    type_map,
+                )?;
+                let tpe = to_cpp_type_name(&last_format(&tpe)?)?;
+                by_value.insert(arg.clone(), format!("{namespace}::{tpe}"));
             }
-        })
-        .collect::<Vec<_>>();
+            has_by_value_arguments |= !by_value.is_empty();
+            let function = generate_extern_c_function_def(name, func, &by_value)?;
+            Ok((
+                function.declaration(),
+                item.docs.as_deref().map(to_cpp_doc),
+                function,
+            ))
+        })?;
+        extern_c_functions.extend(function);
+    }
     // ensure that we always emit these functions in the same order
-    extern_c_functions.sort();
+    extern_c_functions.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+    let extern_c_names = res
+        .doc_types
+        .index
+        .values()
+        .filter(|item| {
+            matches!(
+                item.inner,
+                rustdoc_types::ItemEnum::Function(rustdoc_types::Function {
+                    header: rustdoc_types::FunctionHeader {
+                        abi: rustdoc_types::Abi::C { .. },
+                        ..
+                    },
+                    ..
+                })
+            )
+        })
+        .filter_map(|item| item.name.clone())
+        .collect::<HashSet<_>>();
+    // functions exported with `#[buffi_macro::exported(completion_callback)]`
+    // have an additional non-blocking extern function
+    let has_completion_callback = |item: &rustdoc_types::Item| {
+        extern_c_names.contains(&format!(
+            "{function_prefix}_{}_with_callback",
+            item.name.as_deref().unwrap()
+        ))
+    };
 
     let mut free_standing_functions = res
         .doc_types
         .index
         .values()
         .filter(is_free_standing_impl)
+        .filter(|item| !res.is_skipped(&item.id) && !is_hidden(item, config) && is_built(item))
         .collect::<Vec<_>>();
 
     free_standing_functions.sort_by_key(|f| f.name.as_ref());
+    let function_order = config
+        .function_order
+        .as_ref()
+        .unwrap_or(&FunctionOrder::Alphabetical);
+    function_order.reorder(
+        &mut free_standing_functions,
+        None,
+        |f| f.name.as_deref().unwrap(),
+        |f| item_location(f),
+    );
 
-    let mut relevant_impls = res
+    let mut impl_functions = HashMap::<_, Vec<_>>::new();
+    for item in res
         .doc_types
         .index
         .values()
         .filter(is_relevant_impl)
-        .flat_map(|item| {
-            if let rustdoc_types::ItemEnum::Impl(ref impl_) = item.inner {
-                impl_
-                    .items
-                    .iter()
-                    .map(|id| res.resolve_index(None, id, &config.parent_crate))
-                    .filter(|item| matches!(item.inner, rustdoc_types::ItemEnum::Function(_)))
-                    .map(move |i| (&impl_.for_, i))
-            } else {
-                unreachable!()
+        .filter(|item| !is_hidden(item, config) && is_built(item))
+    {
+        let rustdoc_types::ItemEnum::Impl(ref impl_) = item.inner else {
+            unreachable!()
+        };
+        for id in &impl_.items {
+            let item = res.resolve_index(None, id, &config.parent_crate)?;
+            if matches!(item.inner, rustdoc_types::ItemEnum::Function(_))
+                && !is_hidden(&item, config)
+                && is_built(&item)
+            {
+                impl_functions.entry(&impl_.for_).or_default().push(item);
             }
-        })
-        .fold(HashMap::<_, Vec<_>>::new(), |mut acc, (t, i)| {
-            acc.entry(t).or_default().push(i);
-            acc
-        })
+        }
+    }
+    let mut relevant_impls = impl_functions
         .into_iter()
         .map(|(n, mut items)| {
             items.sort_by_key(|i| i.name.clone());
+            let owner = match n {
+                rustdoc_types::Type::ResolvedPath(p) => get_name_without_path(&p.name),
+                _ => unreachable!(),
+            };
+            function_order.reorder(
+                &mut items,
+                Some(owner),
+                |i| i.name.as_deref().unwrap(),
+                |i| item_location(i),
+            );
             (n, items)
         })
         .collect::<Vec<_>>();
@@ -462,46 +1300,112 @@ fn generate_function_definitions(
             unreachable!()
         }
     });
-    let extern_c_header = out_dir.join(format!("{file_prefix}_api_functions.hpp"));
-    let mut extern_c_header = BufWriter::new(File::create(extern_c_header).unwrap());
-    write_function_header(&mut extern_c_header, config);
-    writeln!(extern_c_header, "#include <cstdint>").unwrap();
-    writeln!(extern_c_header).unwrap();
+    verify::verify_extern_functions(
+        &res.doc_types.index,
+        relevant_impls
+            .iter()
+            .flat_map(|(t, impls)| {
+                let rustdoc_types::Type::ResolvedPath(p) = t else {
+                    unreachable!()
+                };
+                impls
+                    .iter()
+                    .map(|item| (Some(get_name_without_path(&p.name)), &**item))
+            })
+            .chain(free_standing_functions.iter().map(|item| (None, *item)))
+            .filter(|(_, item)| !res.is_skipped(&item.id)),
+        function_prefix,
+    )?;
+    let extern_c_header_path = out_dir.join(format!("{file_prefix}_api_functions.hpp"));
+    let mut extern_c_header = create_file(&extern_c_header_path)?;
+    write_function_header(
+        &mut extern_c_header,
+        config,
+        &extern_c_header_path,
+        provenance.as_deref(),
+    )?;
+    write_api_functions_prelude(&mut extern_c_header, config, has_by_value_arguments)?;
+    write_constants(&mut extern_c_header, &res, config)?;
+    let mut opaque_types = Vec::new();
     for (t, _) in relevant_impls.iter() {
         if let rustdoc_types::Type::ResolvedPath(p) = t {
             let name = get_name_without_path(&p.name);
             writeln!(extern_c_header, "struct {};\n", name).unwrap();
+            opaque_types.push(name.to_owned());
         } else {
             unreachable!()
         }
     }
-    for function in extern_c_functions {
+    // returned by functions marked with `#[buffi(chunked)]`, see `buffi::export_chunked_results`
+    if extern_c_names.contains(&format!("{function_prefix}_free_chunked_result")) {
+        writeln!(extern_c_header, "struct ChunkedResult;\n").unwrap();
+        opaque_types.push(String::from("ChunkedResult"));
+    }
+    let mut extern_functions = Vec::with_capacity(extern_c_functions.len());
+    for (idx, (function, docs, extern_function)) in extern_c_functions.into_iter().enumerate() {
+        extern_functions.push(extern_function);
+        if idx != 0 {
+            writeln!(extern_c_header).unwrap();
+        }
+        // the docs include the safety section generated by `#[buffi_macro::exported]`
+        if let Some(docs) = docs {
+            for line in docs.lines() {
+                writeln!(extern_c_header, "{}", format!("// {line}").trim_end()).unwrap();
+            }
+        }
         writeln!(extern_c_header, "{function}").unwrap();
     }
-    extern_c_header.flush().unwrap();
+    write_function_footer(&mut extern_c_header, config)?;
+    finish_file(extern_c_header, &extern_c_header_path)?;
 
     for (t, impls) in relevant_impls {
         if let rustdoc_types::Type::ResolvedPath(p) = t {
             let name = get_name_without_path(&p.name);
+            let constructor = constructor_functions(
+                &extern_c_names,
+                &res,
+                type_map,
+                name,
+                function_prefix,
+                config,
+            )
+            .map_err(|e| e.in_function(&format!("{name}::create")))?;
+            let mut factories = factory_functions(&res, name);
+            // the created value is owned by the holder returned by `create()` instead
+            factories.retain(|f| constructor.as_ref().is_none_or(|c| *f != c.create));
+            let mut holder_class = embind::HolderClass {
+                name: name.to_owned(),
+                factories,
+                // embind only binds `create()` without arguments
+                has_constructor: constructor.as_ref().is_some_and(|c| c.arguments.is_empty()),
+                functions: Vec::new(),
+            };
             let type_header =
                 out_dir.join(format!("{file_prefix}_{}.hpp", name.to_ascii_lowercase()));
-            let mut writer = BufWriter::new(File::create(type_header).unwrap());
-            write_function_header(&mut writer, config);
-            writeln!(writer, "#include \"{file_prefix}_api_functions.hpp\"\n").unwrap();
-            writeln!(writer, "#include \"{namespace}.hpp\"\n").unwrap();
+            let mut writer = create_file(&type_header)?;
+            write_function_header(&mut writer, config, &type_header, provenance.as_deref())?;
+            write_wrapper_includes(
+                &mut writer,
+                impls.iter().any(|item| has_completion_callback(item)),
+                file_prefix,
+                namespace,
+            );
 
-            writeln!(writer).unwrap();
-            writeln!(writer, "namespace {namespace} {{").unwrap();
-            writeln!(writer).unwrap();
-            writeln!(writer, "class {name}Holder {{").unwrap();
-            writeln!(writer, "    {name}* inner;").unwrap();
-            writeln!(writer, "public:").unwrap();
-            writeln!(writer, "    {name}Holder({name}* ptr) {{").unwrap();
-            writeln!(writer, "        this->inner = ptr;").unwrap();
-            writeln!(writer, "    }}\n").unwrap();
+            write_holder_class_start(
+                &mut writer,
+                name,
+                namespace,
+                function_prefix,
+                constructor.as_ref(),
+            );
+            let mut overloads = overloads::Overloads::default();
             for impl_ in impls {
+                if res.is_skipped(&impl_.id) {
+                    continue;
+                }
                 if let rustdoc_types::ItemEnum::Function(ref m) = impl_.inner {
-                    generate_function_def(
+                    write_line_directive(&mut writer, &impl_, config);
+                    let signature = generate_function_def(
                         m,
                         &res,
                         &impl_,
@@ -510,39 +1414,60 @@ fn generate_function_definitions(
                         function_prefix,
                         config,
                         Some(t),
-                    );
+                        has_completion_callback(&impl_),
+                        &mut overloads,
+                    )
+                    .map_err(|e| {
+                        e.in_function(&format!("{name}::{}", impl_.name.as_deref().unwrap()))
+                    })?;
+                    write_line_restore(&mut writer, config);
+                    holder_class.functions.extend(signature);
+                    mapped_functions.extend(MappedFunction::wrappers(
+                        &impl_,
+                        Some(name),
+                        function_prefix,
+                        namespace,
+                        has_completion_callback(&impl_),
+                    ));
                 }
             }
+            wrapper_functions.classes.push(holder_class);
             writeln!(writer, "}};\n").unwrap();
             writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
-            writer.flush().unwrap();
+            write_function_footer(&mut writer, config)?;
+            finish_file(writer, &type_header)?;
+            restore_line_numbers(&type_header, config)?;
         }
     }
 
-    let free_standing_function_header =
+    let free_standing_function_header_path =
         out_dir.join(format!("{file_prefix}_free_standing_functions.hpp"));
-    let mut free_standing_function_header =
-        BufWriter::new(File::create(free_standing_function_header).unwrap());
+    let mut free_standing_function_header = create_file(&free_standing_function_header_path)?;
 
-    write_function_header(&mut free_standing_function_header, config);
-    writeln!(
-        free_standing_function_header,
-        "#include \"{file_prefix}_api_functions.hpp\"\n"
-    )
-    .unwrap();
-    writeln!(
-        free_standing_function_header,
-        "#include \"{namespace}.hpp\"\n"
-    )
-    .unwrap();
+    write_function_header(
+        &mut free_standing_function_header,
+        config,
+        &free_standing_function_header_path,
+        provenance.as_deref(),
+    )?;
+    write_wrapper_includes(
+        &mut free_standing_function_header,
+        free_standing_functions
+            .iter()
+            .any(|item| has_completion_callback(item)),
+        file_prefix,
+        namespace,
+    );
 
     writeln!(free_standing_function_header).unwrap();
     writeln!(free_standing_function_header, "namespace {namespace} {{").unwrap();
     writeln!(free_standing_function_header).unwrap();
 
+    let mut overloads = overloads::Overloads::default();
     for item in &free_standing_functions {
         if let rustdoc_types::ItemEnum::Function(ref f) = item.inner {
-            generate_function_def(
+            write_line_directive(&mut free_standing_function_header, item, config);
+            let signature = generate_function_def(
                 f,
                 &res,
                 item,
@@ -551,7 +1476,19 @@ fn generate_function_definitions(
                 function_prefix,
                 config,
                 None,
-            );
+                has_completion_callback(item),
+                &mut overloads,
+            )
+            .map_err(|e| e.in_function(item.name.as_deref().unwrap()))?;
+            write_line_restore(&mut free_standing_function_header, config);
+            wrapper_functions.free_standing.extend(signature);
+            mapped_functions.extend(MappedFunction::wrappers(
+                item,
+                None,
+                function_prefix,
+                namespace,
+                has_completion_callback(item),
+            ));
             writeln!(free_standing_function_header).unwrap();
         }
     }
@@ -561,36 +1498,351 @@ fn generate_function_definitions(
         "}}  // end of namespace {namespace}"
     )
     .unwrap();
-    free_standing_function_header.flush().unwrap();
-}
+    write_function_footer(&mut free_standing_function_header, config)?;
+    finish_file(
+        free_standing_function_header,
+        &free_standing_function_header_path,
+    )?;
+    restore_line_numbers(&free_standing_function_header_path, config)?;
 
-fn write_function_header(out_functions: &mut BufWriter<File>, config: &Config) {
-    if let Some(copyright_header) = &config.copyright_header {
-        writeln!(out_functions, "// {copyright_header}").unwrap();
-    }
-    if let Some(generated_by) = &config.generated_by_header {
-        writeln!(out_functions, "// {generated_by}").unwrap();
-    }
-    if config.copyright_header.is_some() || config.generated_by_header.is_some() {
-        writeln!(out_functions).unwrap();
-    }
-    writeln!(out_functions, "#pragma once\n").unwrap();
-    writeln!(out_functions, "#include <cstddef>").unwrap();
-    writeln!(out_functions, "#include <limits>").unwrap();
+    interfaces::generate_interfaces(
+        &res,
+        &out_dir,
+        type_map,
+        function_prefix,
+        config,
+        provenance.as_deref(),
+    )?;
+    interfaces::generate_event_subscriptions(
+        &res,
+        &out_dir,
+        type_map,
+        function_prefix,
+        config,
+        provenance.as_deref(),
+    )?;
+    Ok(GeneratedFunctions {
+        extern_functions,
+        opaque_types,
+        wrappers: wrapper_functions,
+        mapped_functions,
+        skipped: res
+            .skipped
+            .into_inner()
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect(),
+    })
 }
 
-#[allow(clippy::too_many_arguments)]
-fn generate_function_def(
-    m: &rustdoc_types::Function,
-    res: &ItemResolver,
-    item: &rustdoc_types::Item,
-    out_functions: &mut BufWriter<File>,
+/// Everything in front of the type and function declarations of `api_functions.hpp`
+fn write_api_functions_prelude(
+    out: &mut impl std::io::Write,
+    config: &Config,
+    has_by_value_arguments: bool,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    writeln!(out, "#include <cstdint>").unwrap();
+    if has_by_value_arguments {
+        // the types of arguments passed by value
+        writeln!(out, "#include \"{namespace}.hpp\"").unwrap();
+    }
+    writeln!(out).unwrap();
+    // catch builds for a target that does not match the assumptions made during generation
+    let pointer_width = target_pointer_width(config.target.as_deref())?;
+    writeln!(
+        out,
+        "static_assert(sizeof(std::uint8_t) == 1, \"BuFFI requires 8 bit bytes\");"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "static_assert(sizeof(std::uint64_t) == 8, \"BuFFI requires 64 bit std::uint64_t\");"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "static_assert(sizeof(std::size_t) == {0}, \"The bindings were generated for a target with {1} bit usize, but size_t has a different width\");\n",
+        pointer_width / 8,
+        pointer_width,
+    )
+    .unwrap();
+    // the buffers are little-endian on every target, the serde runtime converts integers
+    // byte by byte but reinterprets floating point values as integers of the same size
+    writeln!(
+        out,
+        "#if defined(__FLOAT_WORD_ORDER__) && defined(__BYTE_ORDER__) && __FLOAT_WORD_ORDER__ != __BYTE_ORDER__"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "#error \"BuFFI requires floating point values to use the same byte order as integers\""
+    )
+    .unwrap();
+    writeln!(out, "#endif\n").unwrap();
+    if let Some(version) = &config.api_version {
+        write_api_version(out, version, namespace, config.function_prefix())?;
+    }
+    Ok(())
+}
+
+/// Generates the linker export lists for the extern functions
+///
+/// Both files list the symbols in the same order as `api_functions.hpp`.
+fn generate_linker_exports(
+    functions: &[ExternFunction],
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let out_dir = PathBuf::from(out_dir);
+
+    let path = out_dir.join(format!("{file_prefix}_exports.map"));
+    let mut writer = create_file(&path)?;
+    write_foreign_file_comment(&mut writer, config, &path, provenance, |text| {
+        format!("/*{text} */")
+    })?;
+    writeln!(writer, "{{").unwrap();
+    writeln!(writer, "  global:").unwrap();
+    for function in functions {
+        writeln!(writer, "    {};", function.name).unwrap();
+    }
+    writeln!(writer, "  local:").unwrap();
+    writeln!(writer, "    *;").unwrap();
+    writeln!(writer, "}};").unwrap();
+    finish_file(writer, &path)?;
+
+    let path = out_dir.join(format!("{file_prefix}_exports.def"));
+    let mut writer = create_file(&path)?;
+    write_foreign_file_comment(&mut writer, config, &path, provenance, |text| {
+        format!(";{text}")
+    })?;
+    // cargo replaces `-` in the name of the library
+    writeln!(writer, "LIBRARY {}", config.api_lib_name.replace('-', "_")).unwrap();
+    writeln!(writer, "EXPORTS").unwrap();
+    for function in functions {
+        writeln!(writer, "    {}", function.name).unwrap();
+    }
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+/// The content of `symbols.json`
+#[derive(Serialize)]
+struct SymbolList<'a> {
+    /// The name of the API library
+    library: &'a str,
+    symbols: Vec<Symbol<'a>>,
+}
+
+#[derive(Serialize)]
+struct Symbol<'a> {
+    name: &'a str,
+    return_type: &'a str,
+    arguments: Vec<SymbolArgument<'a>>,
+}
+
+#[derive(Serialize)]
+struct SymbolArgument<'a> {
+    name: &'a str,
+    /// The C type of the argument, as in `api_functions.hpp`
+    #[serde(rename = "type")]
+    tpe: String,
+}
+
+/// Generates `symbols.json` with the names and signatures of all extern functions
+fn generate_symbol_list(
+    functions: &[ExternFunction],
+    out_dir: &str,
+    config: &Config,
+) -> Result<(), Error> {
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let symbols = functions
+        .iter()
+        .map(|function| Symbol {
+            name: &function.name,
+            return_type: &function.output,
+            arguments: function
+                .inputs
+                .iter()
+                .map(|(name, declaration)| SymbolArgument {
+                    name,
+                    tpe: argument_type(name, declaration),
+                })
+                .collect(),
+        })
+        .collect();
+    let list = SymbolList {
+        library: &config.api_lib_name,
+        symbols,
+    };
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_symbols.json"));
+    let mut writer = create_file(&path)?;
+    serde_json::to_writer_pretty(&mut writer, &list).expect("Symbol list is serializable");
+    writeln!(writer).unwrap();
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+/// A C++ wrapper function and the extern function it calls, as listed in `symbol_map.json`
+#[derive(Serialize)]
+struct MappedFunction {
+    /// The qualified name of the C++ function
+    cpp_function: String,
+    /// The name of the called extern function
+    symbol: String,
+    /// The name of the Rust function, including the type of `self`
+    rust_function: String,
+    /// The source file of the Rust function, relative to the workspace
+    file: Option<PathBuf>,
+    line: Option<usize>,
+}
+
+impl MappedFunction {
+    /// The C++ wrappers generated for the Rust function `item` of `owner`
+    fn wrappers(
+        item: &rustdoc_types::Item,
+        owner: Option<&str>,
+        prefix: &str,
+        namespace: &str,
+        has_completion_callback: bool,
+    ) -> Vec<Self> {
+        let name = item.name.as_deref().unwrap();
+        let cpp_name = cpp_function_name(item);
+        let (scope, rust_function) = match owner {
+            Some(owner) => (
+                format!("{namespace}::{owner}Holder"),
+                format!("{owner}::{name}"),
+            ),
+            None => (namespace.to_owned(), name.to_owned()),
+        };
+        let mapped = |cpp_name: String, symbol: String| Self {
+            cpp_function: format!("{scope}::{cpp_name}"),
+            symbol,
+            rust_function: rust_function.clone(),
+            file: item.span.as_ref().map(|s| s.filename.clone()),
+            line: item.span.as_ref().map(|s| s.begin.0),
+        };
+        let mut wrappers = vec![mapped(cpp_name.to_owned(), format!("{prefix}_{name}"))];
+        if is_chunked(item) {
+            wrappers.push(mapped(
+                format!("{cpp_name}_chunked"),
+                format!("{prefix}_{name}"),
+            ));
+        }
+        if has_completion_callback {
+            wrappers.push(mapped(
+                format!("{cpp_name}_async"),
+                format!("{prefix}_{name}_with_callback"),
+            ));
+        }
+        wrappers
+    }
+}
+
+/// Generates `symbol_map.json`, which maps the C++ wrapper functions to the extern functions
+fn generate_symbol_map(
+    functions: &[MappedFunction],
+    out_dir: &str,
+    config: &Config,
+) -> Result<(), Error> {
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_symbol_map.json"));
+    let mut writer = create_file(&path)?;
+    serde_json::to_writer_pretty(&mut writer, functions).expect("Symbol map is serializable");
+    writeln!(writer).unwrap();
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+/// Removes the name from the C declaration of an argument
+fn argument_type(name: &str, declaration: &str) -> String {
+    match declaration.strip_suffix(name) {
+        Some(tpe) => tpe.trim_end().to_owned(),
+        // function pointers contain the name in the middle
+        None => declaration.replacen(&format!("(*{name})"), "(*)", 1),
+    }
+}
+
+/// The extern functions generated for the constructor of a type, see [`constructor_functions`]
+struct Constructor {
+    /// The extern function creating a value
+    create: String,
+    /// The extern function freeing a created value
+    free: String,
+    /// The names and C++ types of the serialized arguments of `create`
+    arguments: Vec<(String, String)>,
+    /// The C++ type of the error written to `error_out` by `create`, `None` if it returns the
+    /// pointer to the value (and null if the constructor panics)
+    error_type: Option<String>,
+}
+
+/// Functions exported as constructor by `#[buffi_macro::exported]` (either marked with
+/// `#[buffi(constructor)]` or returning `Self`) are moved into an impl block of their own
+fn is_constructor(item: &&rustdoc_types::Item) -> bool {
+    item.attrs
+        .contains(&String::from("#[cfg(not(generated_constructor_marker))]"))
+}
+
+/// The extern functions generated for the constructor of `type_name`, which create and free a
+/// value of this type
+fn constructor_functions(
+    extern_c_names: &HashSet<String>,
+    res: &ItemResolver,
     type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
-    prefix: &str,
+    type_name: &str,
+    function_prefix: &str,
     config: &Config,
-    impl_type: Option<&rustdoc_types::Type>,
-) {
-    let output_type = if let Some(ref tpe) = m.sig.output {
+) -> Result<Option<Constructor>, Error> {
+    let snake_name = interfaces::to_snake_case(type_name);
+    let create = format!("{function_prefix}_create_{snake_name}");
+    let free = format!("{function_prefix}_free_{snake_name}");
+    if !extern_c_names.contains(&create) || !extern_c_names.contains(&free) {
+        return Ok(None);
+    }
+    // constructors with arguments or returning a `Result` report errors via `error_out`
+    let function = res
+        .doc_types
+        .index
+        .values()
+        .filter_map(|item| match item.inner {
+            rustdoc_types::ItemEnum::Impl(ref impl_)
+                if impl_.trait_.is_none()
+                    && matches!(
+                        impl_.for_,
+                        rustdoc_types::Type::ResolvedPath(ref p)
+                            if get_name_without_path(&p.name) == type_name
+                    ) =>
+            {
+                Some(impl_)
+            }
+            _ => None,
+        })
+        .flat_map(|impl_| &impl_.items)
+        .filter_map(|id| res.doc_types.index.get(id))
+        .filter(is_constructor)
+        .find_map(|item| match item.inner {
+            rustdoc_types::ItemEnum::Function(ref m) => Some(m),
+            _ => None,
+        })
+        .filter(|m| {
+            !m.sig.inputs.is_empty()
+                || matches!(
+                    m.sig.output,
+                    Some(rustdoc_types::Type::ResolvedPath(ref p))
+                        if get_name_without_path(&p.name) == "Result"
+                )
+        });
+    let Some(function) = function else {
+        return Ok(Some(Constructor {
+            create,
+            free,
+            arguments: Vec::new(),
+            error_type: None,
+        }));
+    };
+    let mut to_cpp_type = |tpe: &rustdoc_types::Type| {
         let tpe = to_serde_reflect_type(
             tpe,
             res,
@@ -599,83 +1851,1444 @@ fn generate_function_def(
             &config.parent_crate,
             &config.namespace,
             type_map,
-        );
-        to_cpp_type_name(&tpe.last().unwrap().0)
-    } else {
-        unimplemented!()
+        )?;
+        to_cpp_type_name(&last_format(&tpe)?)
     };
-    let inputs = m
-        .sig
-        .inputs
-        .iter()
-        .map(|(name, tpe)| {
-            if name == "self" {
-                let impl_type_path = impl_type
-                    .map(|tpe| {
-                        let rustdoc_types::Type::ResolvedPath(path) = tpe else {
-                            panic!("Impl type must be a resolved path");
-                        };
-                        path
-                    })
-                    .expect("we have an impl type for impl functions");
-                return (name, get_name_without_path(&impl_type_path.name).to_owned());
+    let arguments = argument_names(function)
+        .into_iter()
+        .zip(&function.sig.inputs)
+        .map(|(name, (_, tpe))| Ok((name, to_cpp_type(tpe)?)))
+        .collect::<Result<_, Error>>()?;
+    // the error is converted into a `SerializableError` like for exported functions
+    let error_type = to_cpp_type(&serializable_error_type(res)?)?;
+    Ok(Some(Constructor {
+        create,
+        free,
+        arguments,
+        error_type: Some(error_type),
+    }))
+}
+
+/// Opens the namespace and the `*Holder` class for `name` including its constructor
+///
+/// With the extern functions of a `constructor`, the holder gets a static `create(...)`
+/// function taking the arguments of the constructor. Holders returned by it own the value, so
+/// they can only be moved and free the value when they are destroyed.
+fn write_holder_class_start(
+    writer: &mut impl std::io::Write,
+    name: &str,
+    namespace: &str,
+    prefix: &str,
+    constructor: Option<&Constructor>,
+) {
+    writeln!(writer).unwrap();
+    writeln!(writer, "namespace {namespace} {{").unwrap();
+    writeln!(writer).unwrap();
+    writeln!(writer, "class {name}Holder {{").unwrap();
+    writeln!(writer, "    {name}* inner;").unwrap();
+    if constructor.is_some() {
+        writeln!(
+            writer,
+            "    // Whether `inner` was created by `create()` and is freed by this holder"
+        )
+        .unwrap();
+        writeln!(writer, "    bool owned = false;").unwrap();
+    }
+    writeln!(writer, "public:").unwrap();
+    writeln!(writer, "    {name}Holder({name}* ptr) {{").unwrap();
+    writeln!(writer, "        this->inner = ptr;").unwrap();
+    writeln!(writer, "    }}\n").unwrap();
+    if let Some(Constructor {
+        create,
+        free,
+        arguments,
+        error_type,
+    }) = constructor
+    {
+        writeln!(
+            writer,
+            "    // Creates a `{name}` that is freed when the returned holder is destroyed"
+        )
+        .unwrap();
+        let parameters = arguments
+            .iter()
+            .map(|(argument, tpe)| format!("const {tpe}& {argument}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(writer, "    static {name}Holder create({parameters}) {{").unwrap();
+        if let Some(error_type) = error_type {
+            let inputs = arguments
+                .iter()
+                .map(|(argument, tpe)| (argument, tpe.clone()))
+                .collect::<Vec<_>>();
+            write_serialized_arguments(writer, &inputs, &HashSet::new());
+            writeln!(writer, "        {name}* inner = nullptr;").unwrap();
+            writeln!(writer, "        uint8_t* error_ptr = nullptr;").unwrap();
+            writeln!(writer).unwrap();
+            write!(writer, "        size_t error_size = {create}(").unwrap();
+            for (argument, _) in arguments {
+                write!(
+                    writer,
+                    "{argument}_serialized.data(), {argument}_serialized.size(), "
+                )
+                .unwrap();
+            }
+            writeln!(writer, "&inner, &error_ptr);").unwrap();
+            writeln!(writer).unwrap();
+            writeln!(writer, "        if (error_ptr != nullptr) {{").unwrap();
+            write_throw_serialized_error(writer, prefix, error_type, "            ");
+            writeln!(writer, "        }}").unwrap();
+        } else {
+            writeln!(writer, "        {name}* inner = {create}();").unwrap();
+            writeln!(writer, "        if (inner == nullptr) {{").unwrap();
+            writeln!(
+                writer,
+                "            throw std::runtime_error(\"Failed to create a `{name}`\");"
+            )
+            .unwrap();
+            writeln!(writer, "        }}").unwrap();
+        }
+        writeln!(writer, "        {name}Holder holder(inner);").unwrap();
+        writeln!(writer, "        holder.owned = true;").unwrap();
+        writeln!(writer, "        return holder;").unwrap();
+        writeln!(writer, "    }}\n").unwrap();
+        writeln!(writer, "    {name}Holder(const {name}Holder&) = delete;").unwrap();
+        writeln!(
+            writer,
+            "    {name}Holder& operator=(const {name}Holder&) = delete;\n"
+        )
+        .unwrap();
+        writeln!(writer, "    {name}Holder({name}Holder&& other) noexcept {{").unwrap();
+        writeln!(
+            writer,
+            "        this->inner = std::exchange(other.inner, nullptr);"
+        )
+        .unwrap();
+        writeln!(
+            writer,
+            "        this->owned = std::exchange(other.owned, false);"
+        )
+        .unwrap();
+        writeln!(writer, "    }}\n").unwrap();
+        writeln!(
+            writer,
+            "    {name}Holder& operator=({name}Holder&& other) noexcept {{"
+        )
+        .unwrap();
+        writeln!(writer, "        std::swap(this->inner, other.inner);").unwrap();
+        writeln!(writer, "        std::swap(this->owned, other.owned);").unwrap();
+        writeln!(writer, "        return *this;").unwrap();
+        writeln!(writer, "    }}\n").unwrap();
+        writeln!(writer, "    ~{name}Holder() {{").unwrap();
+        writeln!(writer, "        if (this->owned) {{").unwrap();
+        writeln!(writer, "            {free}(this->inner);").unwrap();
+        writeln!(writer, "        }}").unwrap();
+        writeln!(writer, "    }}\n").unwrap();
+    }
+    writeln!(
+        writer,
+        "    // Whether this holder points to a `{name}`, all other functions throw otherwise"
+    )
+    .unwrap();
+    writeln!(writer, "    bool is_valid() const {{").unwrap();
+    writeln!(writer, "        return this->inner != nullptr;").unwrap();
+    writeln!(writer, "    }}\n").unwrap();
+}
+
+/// Extern functions without arguments that return a pointer to `type_name`, like a
+/// `get_test_client` function returning `*mut TestClient`
+fn factory_functions(res: &ItemResolver, type_name: &str) -> Vec<String> {
+    let mut factories = res
+        .doc_types
+        .index
+        .values()
+        .filter_map(|item| {
+            let rustdoc_types::ItemEnum::Function(ref f) = item.inner else {
+                return None;
+            };
+            if !matches!(f.header.abi, rustdoc_types::Abi::C { .. }) || !f.sig.inputs.is_empty() {
+                return None;
+            }
+            match f.sig.output {
+                Some(rustdoc_types::Type::RawPointer {
+                    is_mutable: true,
+                    ref type_,
+                }) if matches!(**type_, rustdoc_types::Type::ResolvedPath(ref p) if get_name_without_path(&p.name) == type_name) => {
+                    item.name.clone()
+                }
+                _ => None,
             }
-            let reflect_type = to_serde_reflect_type(
-                tpe,
-                res,
-                &mut None,
-                Vec::new(),
-                &config.parent_crate,
-                &config.namespace,
-                type_map,
-            );
-            let type_string = reflect_type
-                .last()
-                .map(|(f, _)| to_cpp_type_name(f))
-                .unwrap_or_else(|| panic!("Unknown type: {:?}", tpe));
-            (name, type_string)
         })
         .collect::<Vec<_>>();
-    let return_output_type = match m.sig.output {
-        Some(rustdoc_types::Type::ResolvedPath(ref p))
-            if get_name_without_path(&p.name) == "Result" =>
+    factories.sort();
+    factories
+}
+
+/// Includes everything used by the generated C++ functions, so that the header is self-contained
+///
+/// The generated headers are marked as exported for include-what-you-use, as users of the
+/// functions need the types and the `*Holder` classes need the `extern "C"` declarations
+fn write_wrapper_includes(
+    out: &mut impl std::io::Write,
+    has_completion_callback: bool,
+    file_prefix: &str,
+    namespace: &str,
+) {
+    writeln!(out, "#include <array>").unwrap();
+    writeln!(out, "#include <cstdint>").unwrap();
+    if has_completion_callback {
+        writeln!(out, "#include <exception>").unwrap();
+        writeln!(out, "#include <future>").unwrap();
+    }
+    writeln!(out, "#include <functional>").unwrap();
+    writeln!(out, "#include <map>").unwrap();
+    writeln!(out, "#include <optional>").unwrap();
+    writeln!(out, "#include <stdexcept>").unwrap();
+    writeln!(out, "#include <string>").unwrap();
+    writeln!(out, "#include <tuple>").unwrap();
+    writeln!(out, "#include <utility>").unwrap();
+    writeln!(out, "#include <variant>").unwrap();
+    writeln!(out, "#include <vector>").unwrap();
+    writeln!(
+        out,
+        "#include \"{file_prefix}_api_functions.hpp\"  // IWYU pragma: export\n"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "#include \"{namespace}.hpp\"  // IWYU pragma: export\n"
+    )
+    .unwrap();
+}
+
+fn write_function_header(
+    out_functions: &mut impl std::io::Write,
+    config: &Config,
+    path: &Path,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    write_file_comment(out_functions, config, path, provenance)?;
+    writeln!(out_functions, "#pragma once\n").unwrap();
+    if let Some((push, _)) = warning_suppression(config)? {
+        writeln!(out_functions, "{push}").unwrap();
+    }
+    writeln!(out_functions, "#include <cstddef>").unwrap();
+    writeln!(out_functions, "#include <limits>").unwrap();
+    Ok(())
+}
+
+/// The copyright, generated-by and provenance comments at the top of every generated file
+fn write_file_comment(
+    out_functions: &mut impl std::io::Write,
+    config: &Config,
+    path: &Path,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    if let Some(template) = &config.copyright_header_file {
+        let template = fs::read_to_string(template).map_err(|source| Error::Io {
+            path: template.clone(),
+            source,
+        })?;
+        let file_name = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .expect("Generated files have an UTF-8 file name");
+        let header = template
+            .replace("{year}", &current_year().to_string())
+            .replace("{file}", file_name)
+            .replace("{version}", env!("CARGO_PKG_VERSION"));
+        write!(out_functions, "{header}").unwrap();
+        if !header.ends_with('\n') {
+            writeln!(out_functions).unwrap();
+        }
+    } else if let Some(copyright_header) = &config.copyright_header {
+        writeln!(out_functions, "// {copyright_header}").unwrap();
+    }
+    if let Some(generated_by) = &config.generated_by_header {
+        writeln!(out_functions, "// {generated_by}").unwrap();
+    }
+    if let Some(provenance) = provenance {
+        write!(out_functions, "{provenance}").unwrap();
+    }
+    if config.copyright_header_file.is_some()
+        || config.copyright_header.is_some()
+        || config.generated_by_header.is_some()
+        || provenance.is_some()
+    {
+        writeln!(out_functions).unwrap();
+    }
+    Ok(())
+}
+
+/// Creates one of the generated files
+pub(crate) fn create_file(path: &Path) -> Result<BufWriter<File>, Error> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+/// Flushes a generated file created by [`create_file`]
+pub(crate) fn finish_file(mut writer: BufWriter<File>, path: &Path) -> Result<(), Error> {
+    writer.flush().map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// Writes the comments of [`write_file_comment`] to a file that is not C/C++, `comment` turns
+/// the text of a `//` comment into a comment of that format
+fn write_foreign_file_comment(
+    out: &mut impl std::io::Write,
+    config: &Config,
+    path: &Path,
+    provenance: Option<&str>,
+    comment: impl Fn(&str) -> String,
+) -> Result<(), Error> {
+    let mut header = Vec::new();
+    write_file_comment(&mut header, config, path, provenance)?;
+    let header = String::from_utf8(header).expect("Generated comments are valid UTF-8");
+    for line in header.lines() {
+        match line.strip_prefix("//") {
+            Some(text) => writeln!(out, "{}", comment(text)).unwrap(),
+            None => writeln!(out, "{line}").unwrap(),
+        }
+    }
+    Ok(())
+}
+
+fn write_api_version(
+    out: &mut impl std::io::Write,
+    version: &str,
+    namespace: &str,
+    prefix: &str,
+) -> Result<(), Error> {
+    let (major, minor, patch) = api_version::parse(version).ok_or_else(|| {
+        Error::Generation(format!(
+            "Invalid API version `{version}`, expected a version like `1.2.0`"
+        ))
+    })?;
+    writeln!(out, "namespace {namespace} {{").unwrap();
+    writeln!(
+        out,
+        "// The version of the API these bindings were generated for, compare `API_VERSION` with"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "// the result of `{prefix}_api_version()` of the loaded library"
+    )
+    .unwrap();
+    writeln!(out, "constexpr std::uint32_t API_VERSION_MAJOR = {major};").unwrap();
+    writeln!(out, "constexpr std::uint16_t API_VERSION_MINOR = {minor};").unwrap();
+    writeln!(out, "constexpr std::uint16_t API_VERSION_PATCH = {patch};").unwrap();
+    writeln!(
+        out,
+        "constexpr std::uint64_t API_VERSION = {};",
+        api_version::encode(major, minor, patch)
+    )
+    .unwrap();
+    writeln!(out, "}}\n").unwrap();
+    Ok(())
+}
+
+/// Writes all constants marked with `#[buffi_macro::exported]` as `constexpr` variables
+fn write_constants(
+    out: &mut impl std::io::Write,
+    res: &ItemResolver,
+    config: &Config,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let mut constants = Vec::new();
+    for item in res.doc_types.index.values().filter(|item| {
+        item.attrs
+            .contains(&String::from("#[cfg(not(generated_extern_impl))]"))
+            && !is_hidden(item, config)
+    }) {
+        let rustdoc_types::ItemEnum::Constant {
+            ref type_,
+            ref const_,
+        } = item.inner
+        else {
+            continue;
+        };
+        let name = item.name.as_deref().unwrap();
+        constants.push((
+            name,
+            item.docs.as_deref(),
+            to_constexpr(name, type_, const_)?,
+        ));
+    }
+    if constants.is_empty() {
+        return Ok(());
+    }
+    constants.sort_by_key(|(name, ..)| *name);
+    writeln!(out, "namespace {namespace} {{").unwrap();
+    for (name, docs, (tpe, value)) in constants {
+        if let Some(docs) = docs {
+            for line in to_cpp_doc(docs).lines() {
+                writeln!(out, "{}", format!("// {line}").trim_end()).unwrap();
+            }
+        }
+        writeln!(out, "constexpr {tpe} {name} = {value};").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+    Ok(())
+}
+
+/// The C++ type and value of the constant `name`
+fn to_constexpr(
+    name: &str,
+    tpe: &rustdoc_types::Type,
+    constant: &rustdoc_types::Constant,
+) -> Result<(String, String), Error> {
+    let unsupported = || {
+        Err(Error::unsupported(format!(
+            "Cannot export the constant `{name}`, only integers, floats, `bool` and \
+             string literals are supported"
+        )))
+    };
+    match tpe {
+        rustdoc_types::Type::BorrowedRef { type_, .. }
+            if **type_ == rustdoc_types::Type::Primitive(String::from("str")) =>
         {
-            if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref()
+            // Rust and C++ share the common escape sequences, but not `\u{...}`
+            if !constant.is_literal
+                || !constant.expr.starts_with('"')
+                || constant.expr.contains("\\u{")
             {
-                if let rustdoc_types::GenericArg::Type(tpe) = &args[0] {
-                    let tpe = to_serde_reflect_type(
-                        tpe,
-                        res,
-                        &mut None,
-                        Vec::new(),
-                        &config.parent_crate,
-                        &config.namespace,
-                        type_map,
-                    );
-                    Cow::Owned(to_cpp_type_name(&tpe.last().unwrap().0))
-                } else {
-                    unreachable!()
-                }
-            } else {
-                unreachable!()
+                return unsupported();
             }
+            Ok((String::from("const char*"), constant.expr.clone()))
         }
-        Some(rustdoc_types::Type::ResolvedPath(ref p))
-            if get_name_without_path(&p.name) == "String" =>
+        rustdoc_types::Type::Primitive(p) if p == "bool" => {
+            if !matches!(constant.expr.as_str(), "true" | "false") {
+                return unsupported();
+            }
+            Ok((String::from("bool"), constant.expr.clone()))
+        }
+        rustdoc_types::Type::Primitive(p)
+            if p.starts_with('u') || p.starts_with('i') || p.starts_with('f') =>
         {
-            Cow::Owned(to_cpp_type_name(&serde_reflection::Format::Str))
+            if matches!(p.as_str(), "u128" | "i128" | "isize") {
+                return unsupported();
+            }
+            // evaluated values look like `1_000u32`, literals may contain separators as well
+            let value = constant
+                .value
+                .as_deref()
+                .or(constant.is_literal.then_some(constant.expr.as_str()))
+                .ok_or_else(|| {
+                    Error::unsupported(format!(
+                        "Cannot export the constant `{name}`, rustdoc does not know its value"
+                    ))
+                })?
+                .replace('_', "");
+            let mut value = value.strip_suffix(p.as_str()).unwrap_or(&value).to_owned();
+            if p.starts_with('u') {
+                value.push('u');
+            }
+            Ok((to_c_type(tpe)?, value))
         }
-        _ => Cow::Borrowed(&output_type as &str),
+        _ => unsupported(),
+    }
+}
+
+/// The width of `usize` in bits for the given target (or the host)
+fn target_pointer_width(target: Option<&str>) -> Result<usize, Error> {
+    let Some(target) = target else {
+        return Ok(usize::BITS as usize);
     };
-    if let Some(ref docs) = item.docs {
-        for line in docs.lines() {
-            writeln!(out_functions, "    // {line}").unwrap()
+    let output = std::process::Command::new("rustc")
+        .args(["--print", "cfg", "--target", target])
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| Error::Generation(format!("Failed to run rustc: {e}")))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix("target_pointer_width=\"")?
+                .strip_suffix('"')?
+                .parse()
+                .ok()
+        })
+        .ok_or_else(|| {
+            Error::Generation(format!(
+                "Could not determine the pointer width of `{target}`"
+            ))
+        })
+}
+
+/// Closes what was opened in [`write_function_header`]
+fn write_function_footer(
+    out_functions: &mut impl std::io::Write,
+    config: &Config,
+) -> Result<(), Error> {
+    if let Some((_, pop)) = warning_suppression(config)? {
+        writeln!(out_functions, "\n{pop}").unwrap();
+    }
+    Ok(())
+}
+
+/// The lines that start and end suppressing warnings for the generated code, see
+/// [`Config::msvc_compatibility`], [`Config::clang_tidy_nolint`] and
+/// [`Config::suppressed_warnings`]
+fn warning_suppression(config: &Config) -> Result<Option<(String, String)>, Error> {
+    let mut push = Vec::new();
+    let mut pop = Vec::new();
+    if config.msvc_compatibility == Some(true) {
+        push.push(MSVC_WARNING_PUSH.to_owned());
+        pop.push(MSVC_WARNING_POP.to_owned());
+    }
+    if let Some(warnings) = config
+        .suppressed_warnings
+        .as_ref()
+        .filter(|w| !w.is_empty())
+    {
+        // `__GNUC__` is defined by Clang as well, unknown warnings are reported by GCC
+        // via `-Wpragmas` and by Clang via `-Wunknown-warning-option`
+        let mut gcc_push = String::from(
+            "#if defined(__GNUC__)\n\
+             #pragma GCC diagnostic push\n\
+             #pragma GCC diagnostic ignored \"-Wpragmas\"\n\
+             #pragma GCC diagnostic ignored \"-Wunknown-warning-option\"\n",
+        );
+        for warning in warnings {
+            if !warning.starts_with("-W") || warning.contains('"') {
+                return Err(Error::Generation(format!(
+                    "Suppressed warnings need to look like `-Wshadow`, got `{warning}`"
+                )));
+            }
+            writeln!(gcc_push, "#pragma GCC diagnostic ignored \"{warning}\"").unwrap();
+        }
+        gcc_push.push_str("#endif");
+        push.push(gcc_push);
+        pop.push(String::from(
+            "#if defined(__GNUC__)\n#pragma GCC diagnostic pop\n#endif",
+        ));
+    }
+    if config.clang_tidy_nolint == Some(true) {
+        push.push(String::from("// NOLINTBEGIN"));
+        pop.push(String::from("// NOLINTEND"));
+    }
+    if push.is_empty() {
+        return Ok(None);
+    }
+    pop.reverse();
+    Ok(Some((push.join("\n"), pop.join("\n"))))
+}
+
+/// Adds the warning suppressions to a file generated by `serde_generate`
+fn add_warning_pragmas(path: &Path, push: &str, pop: &str) -> Result<(), Error> {
+    let io_error = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    let content = fs::read_to_string(path).map_err(io_error)?;
+    let content = match content.split_once("#pragma once\n") {
+        Some((before, after)) => {
+            format!("{before}#pragma once\n\n{push}\n{after}\n{pop}\n")
+        }
+        None => format!("{push}\n{content}\n{pop}\n"),
+    };
+    fs::write(path, content).map_err(io_error)
+}
+
+/// The source file and line of `item`, see [`FunctionOrder::reorder`]
+fn item_location(item: &rustdoc_types::Item) -> Option<(PathBuf, usize)> {
+    item.span
+        .as_ref()
+        .map(|span| (span.filename.clone(), span.begin.0))
+}
+
+/// Written after the wrappers of a function, replaced by [`restore_line_numbers`]
+const RESTORE_LINE_PLACEHOLDER: &str = "#line BUFFI_RESTORE_LINE";
+
+/// Points the following lines at the Rust definition of `item`, see [`Config::line_directives`]
+fn write_line_directive(
+    out: &mut impl std::io::Write,
+    item: &rustdoc_types::Item,
+    config: &Config,
+) {
+    if config.line_directives != Some(true) {
+        return;
+    }
+    if let Some(span) = &item.span {
+        // backslashes would start escape sequences
+        let file = span.filename.display().to_string().replace('\\', "/");
+        writeln!(out, "#line {} \"{file}\"", span.begin.0).unwrap();
+    }
+}
+
+/// Ends the lines written after [`write_line_directive`]
+fn write_line_restore(out: &mut impl std::io::Write, config: &Config) {
+    if config.line_directives == Some(true) {
+        writeln!(out, "{RESTORE_LINE_PLACEHOLDER}").unwrap();
+    }
+}
+
+/// Replaces the placeholders of [`write_line_restore`] in the file at `path` with directives
+/// that continue with the actual line numbers of the file
+fn restore_line_numbers(path: &Path, config: &Config) -> Result<(), Error> {
+    if config.line_directives != Some(true) {
+        return Ok(());
+    }
+    let io_error = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    let content = fs::read_to_string(path).map_err(io_error)?;
+    let mut restored = String::with_capacity(content.len());
+    for (idx, line) in content.lines().enumerate() {
+        if line == RESTORE_LINE_PLACEHOLDER {
+            // the directive sets the number of the next line, which is `idx + 2` as lines
+            // are counted from 1
+            writeln!(restored, "#line {} \"{file_name}\"", idx + 2).unwrap();
+        } else {
+            writeln!(restored, "{line}").unwrap();
+        }
+    }
+    fs::write(path, restored).map_err(io_error)
+}
+
+/// The current year (UTC), used for the `{year}` placeholder of the copyright header
+fn current_year() -> i64 {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("The system time is after 1970")
+        .as_secs() as i64
+        / 86_400;
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400;
+    if month <= 2 {
+        year + 1
+    } else {
+        year
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn generate_function_def(
+    m: &rustdoc_types::Function,
+    res: &ItemResolver,
+    item: &rustdoc_types::Item,
+    out_functions: &mut impl std::io::Write,
+    type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+    prefix: &str,
+    config: &Config,
+    impl_type: Option<&rustdoc_types::Type>,
+    with_completion_callback: bool,
+    overloads: &mut overloads::Overloads,
+) -> Result<Option<embind::FunctionSignature>, Error> {
+    let to_cpp_format =
+        |tpe: &rustdoc_types::Type, type_map: &mut HashMap<rustdoc_types::Type, TypeCache>| {
+            let tpe = to_serde_reflect_type(
+                tpe,
+                res,
+                &mut None,
+                Vec::new(),
+                &config.parent_crate,
+                &config.namespace,
+                type_map,
+            )?;
+            last_format(&tpe)
+        };
+    let Some(fn_name) = item.name.as_deref() else {
+        return Err(Error::unsupported(format!(
+            "exported functions need a name, `{:?}` has none",
+            item.id
+        )));
+    };
+    // chunked and raw bytes functions never transfer the whole `Result`, so it is not
+    // registered at all
+    let output_type = if is_chunked(item) || is_raw_bytes(item) {
+        String::new()
+    } else if let Some(ref tpe) = m.sig.output {
+        to_cpp_type_name(&to_cpp_format(
+            &to_wire_output_type(item, tpe, res)?,
+            type_map,
+        )?)?
+    } else {
+        return Err(Error::unsupported(format!(
+            "`{fn_name}` needs to return a value, functions without a return type are not \
+             supported"
+        )));
+    };
+    let by_value = by_value_arguments(res, &format!("{prefix}_{fn_name}"));
+    let mut input_formats = Vec::new();
+    let mut callbacks = Vec::new();
+    let names = argument_names(m);
+    let mut inputs = Vec::with_capacity(names.len());
+    for ((_, tpe), name) in m.sig.inputs.iter().zip(&names) {
+        if name == "self" {
+            let Some(rustdoc_types::Type::ResolvedPath(impl_type_path)) = impl_type else {
+                return Err(Error::unsupported(
+                    "`self` is only supported for impls of named types",
+                ));
+            };
+            inputs.push((name, get_name_without_path(&impl_type_path.name).to_owned()));
+            continue;
+        }
+        if let Some((callback_inputs, callback_output)) = callback_signature(tpe) {
+            let callback = interfaces::ImportedFunction {
+                name: name.clone(),
+                docs: None,
+                inputs: callback_inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, tpe)| {
+                        Ok((
+                            format!("arg{idx}"),
+                            to_cpp_type_name(&to_cpp_format(tpe, type_map)?)?,
+                        ))
+                    })
+                    .collect::<Result<_, Error>>()?,
+                output: callback_output
+                    .map(|tpe| to_cpp_type_name(&to_cpp_format(tpe, type_map)?))
+                    .transpose()?,
+            };
+            let type_string = callback_type(&callback);
+            callbacks.push(callback);
+            inputs.push((name, type_string));
+            continue;
+        }
+        let format = to_cpp_format(tpe, type_map)?;
+        let type_string = to_cpp_type_name(&format)?;
+        input_formats.push((name.clone(), format));
+        inputs.push((name, type_string));
+    }
+    overloads.insert(
+        fn_name,
+        cpp_function_name(item),
+        inputs
+            .iter()
+            .filter(|(n, _)| *n != "self")
+            .map(|(_, tpe)| tpe.clone()),
+    )?;
+    let mut return_format = None;
+    let return_output_type = match m.sig.output {
+        Some(rustdoc_types::Type::ResolvedPath(ref p))
+            if get_name_without_path(&p.name) == "Result" =>
+        {
+            if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref()
+            {
+                if let rustdoc_types::GenericArg::Type(tpe) = &args[0] {
+                    let format = to_cpp_format(tpe, type_map)?;
+                    let type_string = to_cpp_type_name(&format)?;
+                    return_format = Some(format);
+                    Cow::Owned(type_string)
+                } else {
+                    unreachable!()
+                }
+            } else {
+                unreachable!()
+            }
+        }
+        Some(rustdoc_types::Type::ResolvedPath(ref p))
+            if get_name_without_path(&p.name) == "String" =>
+        {
+            return_format = Some(serde_reflection::Format::Str);
+            Cow::Owned(to_cpp_type_name(&serde_reflection::Format::Str)?)
+        }
+        _ => Cow::Borrowed(&output_type as &str),
+    };
+    // `std::function` arguments are neither supported by embind nor by the adapters
+    let return_format = return_format.filter(|_| callbacks.is_empty());
+    let signature = return_format.map(|output| embind::FunctionSignature {
+        name: fn_name.to_owned(),
+        cpp_name: cpp_function_name(item).to_owned(),
+        inputs: input_formats,
+        output,
+    });
+    let doc_style = config.doc_style.unwrap_or_default();
+    if is_chunked(item) {
+        let error_type = direct_return_error_type(m, item, res, type_map, config)?;
+        generate_chunked_function_defs(
+            item,
+            out_functions,
+            prefix,
+            &inputs,
+            &by_value,
+            &return_output_type,
+            &error_type,
+            doc_style,
+        );
+        return Ok(signature);
+    }
+    let returns_result = matches!(m.sig.output, Some(rustdoc_types::Type::ResolvedPath(ref p)) if get_name_without_path(&p.name) == "Result");
+    // only the `@throws` tag needs the error type
+    let thrown_error = (doc_style == DocStyle::Doxygen && returns_result)
+        .then(|| direct_return_error_type(m, item, res, type_map, config))
+        .transpose()?;
+    write_function_docs(
+        out_functions,
+        doc_style,
+        "    ",
+        item.docs.as_deref(),
+        &[],
+        &FunctionDocs {
+            params: wrapper_params(&inputs),
+            returns: Some(&*return_output_type).filter(|r| *r != "void"),
+            throws: thrown_error.as_deref(),
+        },
+    );
+    write!(
+        out_functions,
+        "    inline {return_output_type} {}(",
+        cpp_function_name(item)
+    )
+    .unwrap();
+    for (idx, (name, tpe)) in inputs.iter().filter(|(n, _)| *n != "self").enumerate() {
+        if idx != 0 {
+            write!(out_functions, ", ").unwrap();
+        }
+        write!(out_functions, "const {tpe}& {name}").unwrap();
+    }
+    writeln!(out_functions, ") {{").unwrap();
+    write_null_check(out_functions, &inputs, cpp_function_name(item));
+    write_serialized_arguments(out_functions, &inputs, &by_value);
+    write_callback_trampolines(out_functions, res, &callbacks, prefix)?;
+    if is_raw_bytes(item) {
+        let error_type = direct_return_error_type(m, item, res, type_map, config)?;
+        write_raw_bytes_call(out_functions, prefix, item, &inputs, &by_value, &error_type);
+    } else if is_direct_return(item) {
+        let error_type = direct_return_error_type(m, item, res, type_map, config)?;
+        write_direct_return_call(
+            out_functions,
+            prefix,
+            item,
+            &inputs,
+            &by_value,
+            &return_output_type,
+            &error_type,
+        );
+    } else {
+        write_serialized_call(
+            out_functions,
+            prefix,
+            fn_name,
+            this_argument(item),
+            &inputs,
+            &by_value,
+            &output_type,
+            &return_output_type,
+            returns_result,
+        );
+    }
+    writeln!(out_functions, "    }}\n").unwrap();
+
+    if with_completion_callback {
+        generate_future_function_def(
+            item,
+            out_functions,
+            prefix,
+            &inputs,
+            &by_value,
+            &output_type,
+            &return_output_type,
+            doc_style,
+            thrown_error.as_deref(),
+        );
+    }
+    Ok(signature)
+}
+
+/// The parameters of a C++ wrapper taking `inputs`, as documented by Doxygen tags
+fn wrapper_params<'a>(inputs: &'a [(&String, String)]) -> Vec<(&'a str, Option<&'a str>)> {
+    inputs
+        .iter()
+        .filter(|(n, _)| *n != "self")
+        .map(|(name, _)| (name.as_str(), None))
+        .collect()
+}
+
+/// Calls the extern function, deserializes its result and throws a serialized error
+///
+/// `this` is the C++ expression passed as `this_ptr`, see [`this_argument`]
+#[allow(clippy::too_many_arguments)]
+fn write_serialized_call(
+    out_functions: &mut impl std::io::Write,
+    prefix: &str,
+    function_name: &str,
+    this: &str,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+    output_type: &str,
+    return_output_type: &str,
+    returns_result: bool,
+) {
+    writeln!(out_functions, "        uint8_t* out_ptr = nullptr;").unwrap();
+    writeln!(out_functions).unwrap();
+    write!(
+        out_functions,
+        "        size_t res_size = {}_{}(",
+        prefix, function_name,
+    )
+    .unwrap();
+    write_call_arguments(out_functions, this, inputs, by_value);
+    writeln!(out_functions, "&out_ptr);").unwrap();
+    writeln!(out_functions).unwrap();
+    writeln!(
+        out_functions,
+        "        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "        {output_type} out = {output_type}::bincodeDeserialize(serialized_result);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "        {}_free_byte_buffer(out_ptr, res_size);",
+        prefix
+    )
+    .unwrap();
+    writeln!(out_functions).unwrap();
+    if returns_result {
+        writeln!(
+            out_functions,
+            "        if (out.value.index() == 0) {{ // Ok"
+        )
+        .unwrap();
+        if return_output_type == "void" {
+            writeln!(out_functions, "            return;").unwrap();
+        } else {
+            writeln!(
+                out_functions,
+                "            auto ok = std::get<0>(out.value);"
+            )
+            .unwrap();
+            writeln!(out_functions, "            return std::get<0>(ok.value);").unwrap();
+        }
+        writeln!(out_functions, "        }} else {{ // Err").unwrap();
+        writeln!(
+            out_functions,
+            "            auto err = std::get<1>(out.value);"
+        )
+        .unwrap();
+        writeln!(
+            out_functions,
+            "            auto error = std::get<0>(err.value);"
+        )
+        .unwrap();
+        writeln!(out_functions, "            throw error;").unwrap();
+        writeln!(out_functions, "        }}").unwrap();
+    } else {
+        writeln!(out_functions, "        return out;").unwrap();
+    }
+}
+
+/// Functions marked with `#[buffi(direct_return)]` return a successful result directly
+/// and only serialize errors
+fn is_direct_return(item: &rustdoc_types::Item) -> bool {
+    item.attrs
+        .contains(&String::from("#[cfg(not(generated_direct_return_marker))]"))
+}
+
+/// The C++ type of the error that is transferred by a `#[buffi(direct_return)]` function
+fn direct_return_error_type(
+    m: &rustdoc_types::Function,
+    item: &rustdoc_types::Item,
+    res: &ItemResolver,
+    type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+    config: &Config,
+) -> Result<String, Error> {
+    let Some(ref tpe) = m.sig.output else {
+        unreachable!("Exported functions return a `Result`")
+    };
+    let rustdoc_types::Type::ResolvedPath(p) = to_wire_output_type(item, tpe, res)? else {
+        unreachable!("Exported functions return a `Result`")
+    };
+    let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref() else {
+        unreachable!()
+    };
+    let rustdoc_types::GenericArg::Type(ref error) = args[1] else {
+        unreachable!()
+    };
+    let tpe = to_serde_reflect_type(
+        error,
+        res,
+        &mut None,
+        Vec::new(),
+        &config.parent_crate,
+        &config.namespace,
+        type_map,
+    )?;
+    to_cpp_type_name(&last_format(&tpe)?)
+}
+
+/// Calls a `#[buffi(direct_return)]` function, which writes the value to `value` and only
+/// allocates a buffer for the serialized error
+fn write_direct_return_call(
+    out_functions: &mut impl std::io::Write,
+    prefix: &str,
+    item: &rustdoc_types::Item,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+    value_type: &str,
+    error_type: &str,
+) {
+    let function_name = item.name.as_deref().unwrap();
+    writeln!(out_functions, "        {value_type} value = {{}};").unwrap();
+    writeln!(out_functions, "        uint8_t* error_ptr = nullptr;").unwrap();
+    writeln!(out_functions).unwrap();
+    write!(
+        out_functions,
+        "        size_t error_size = {prefix}_{function_name}("
+    )
+    .unwrap();
+    write_call_arguments(out_functions, this_argument(item), inputs, by_value);
+    writeln!(out_functions, "&value, &error_ptr);").unwrap();
+    writeln!(out_functions).unwrap();
+    writeln!(out_functions, "        if (error_ptr == nullptr) {{").unwrap();
+    writeln!(out_functions, "            return value;").unwrap();
+    writeln!(out_functions, "        }}").unwrap();
+    write_throw_serialized_error(out_functions, prefix, error_type, "        ");
+}
+
+/// Functions marked with `#[buffi(raw_bytes)]` return the bytes of a successful result
+/// without serializing them and only serialize errors
+fn is_raw_bytes(item: &rustdoc_types::Item) -> bool {
+    item.attrs
+        .contains(&String::from("#[cfg(not(generated_raw_bytes_marker))]"))
+}
+
+/// Calls a `#[buffi(raw_bytes)]` function, which points `value_ptr` to the returned bytes
+/// on success, so they are returned without deserializing them
+fn write_raw_bytes_call(
+    out_functions: &mut impl std::io::Write,
+    prefix: &str,
+    item: &rustdoc_types::Item,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+    error_type: &str,
+) {
+    let function_name = item.name.as_deref().unwrap();
+    writeln!(out_functions, "        uint8_t* value_ptr = nullptr;").unwrap();
+    writeln!(out_functions, "        uint8_t* error_ptr = nullptr;").unwrap();
+    writeln!(out_functions).unwrap();
+    write!(
+        out_functions,
+        "        size_t res_size = {prefix}_{function_name}("
+    )
+    .unwrap();
+    write_call_arguments(out_functions, this_argument(item), inputs, by_value);
+    writeln!(out_functions, "&value_ptr, &error_ptr);").unwrap();
+    writeln!(out_functions).unwrap();
+    writeln!(out_functions, "        if (error_ptr == nullptr) {{").unwrap();
+    writeln!(
+        out_functions,
+        "            std::vector<uint8_t> value(value_ptr, value_ptr + res_size);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "            {prefix}_free_byte_buffer(value_ptr, res_size);"
+    )
+    .unwrap();
+    writeln!(out_functions, "            return value;").unwrap();
+    writeln!(out_functions, "        }}").unwrap();
+    writeln!(out_functions, "        size_t error_size = res_size;").unwrap();
+    write_throw_serialized_error(out_functions, prefix, error_type, "        ");
+}
+
+/// Deserializes and throws the error written to `error_ptr` by a function that only
+/// serializes errors
+fn write_throw_serialized_error(
+    out_functions: &mut impl std::io::Write,
+    prefix: &str,
+    error_type: &str,
+    indent: &str,
+) {
+    writeln!(
+        out_functions,
+        "{indent}std::vector<uint8_t> serialized_error(error_ptr, error_ptr + error_size);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "{indent}{prefix}_free_byte_buffer(error_ptr, error_size);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "{indent}auto error = {error_type}::bincodeDeserialize(serialized_error);"
+    )
+    .unwrap();
+    writeln!(out_functions, "{indent}throw error;").unwrap();
+}
+
+/// The name of the C++ wrapper of `item`, functions marked with `#[buffi(overload = "...")]`
+/// share the given name and are distinguished by their arguments
+fn cpp_function_name(item: &rustdoc_types::Item) -> &str {
+    item.attrs
+        .iter()
+        .find_map(|attr| {
+            attr.strip_prefix("#[cfg(not(generated_overload_marker = \"")?
+                .strip_suffix("\"))]")
+        })
+        .unwrap_or_else(|| item.name.as_deref().unwrap())
+}
+
+/// Functions marked with `#[buffi(chunked)]` return a pointer to their chunks instead of
+/// the serialized result
+fn is_chunked(item: &rustdoc_types::Item) -> bool {
+    item.attrs
+        .contains(&String::from("#[cfg(not(generated_chunked_marker))]"))
+}
+
+/// Generates two wrappers for a `#[buffi(chunked)]` function: `{name}_chunked` passes each
+/// chunk to a callback as soon as it is received, `{name}` collects all chunks into one vector
+#[allow(clippy::too_many_arguments)]
+fn generate_chunked_function_defs(
+    item: &rustdoc_types::Item,
+    out_functions: &mut impl std::io::Write,
+    prefix: &str,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+    vec_type: &str,
+    error_type: &str,
+    doc_style: DocStyle,
+) {
+    let name = item.name.as_deref().unwrap();
+    let cpp_name = cpp_function_name(item);
+    let params = inputs.iter().filter(|(n, _)| *n != "self").fold(
+        String::new(),
+        |mut params, (name, tpe)| {
+            write!(params, "const {tpe}& {name}, ").unwrap();
+            params
+        },
+    );
+    let args =
+        inputs
+            .iter()
+            .filter(|(n, _)| *n != "self")
+            .fold(String::new(), |mut args, (name, _)| {
+                write!(args, "{name}, ").unwrap();
+                args
+            });
+
+    let mut params_with_callback = wrapper_params(inputs);
+    params_with_callback.push(("on_chunk", Some("Called with each chunk of the result")));
+    write_function_docs(
+        out_functions,
+        doc_style,
+        "    ",
+        item.docs.as_deref(),
+        &["The result is passed to `on_chunk` in several chunks as soon as they are received"],
+        &FunctionDocs {
+            params: params_with_callback,
+            returns: None,
+            throws: Some(error_type),
+        },
+    );
+    writeln!(out_functions, "    template <typename F>").unwrap();
+    writeln!(
+        out_functions,
+        "    inline void {cpp_name}_chunked({params}F&& on_chunk) {{"
+    )
+    .unwrap();
+    write_null_check(out_functions, inputs, cpp_name);
+    write_serialized_arguments(out_functions, inputs, by_value);
+    writeln!(out_functions, "        ChunkedResult* chunks = nullptr;").unwrap();
+    writeln!(out_functions, "        uint8_t* error_ptr = nullptr;").unwrap();
+    writeln!(out_functions).unwrap();
+    write!(
+        out_functions,
+        "        size_t error_size = {prefix}_{name}("
+    )
+    .unwrap();
+    write_call_arguments(out_functions, this_argument(item), inputs, by_value);
+    writeln!(out_functions, "&chunks, &error_ptr);").unwrap();
+    writeln!(out_functions).unwrap();
+    writeln!(out_functions, "        if (error_ptr != nullptr) {{").unwrap();
+    write_throw_serialized_error(out_functions, prefix, error_type, "            ");
+    writeln!(out_functions, "        }}").unwrap();
+    writeln!(
+        out_functions,
+        "        size_t expected_len = {prefix}_chunked_result_len(chunks);"
+    )
+    .unwrap();
+    writeln!(out_functions, "        size_t received_len = 0;").unwrap();
+    writeln!(out_functions, "        try {{").unwrap();
+    writeln!(
+        out_functions,
+        "            const uint8_t* chunk_ptr = nullptr;"
+    )
+    .unwrap();
+    writeln!(out_functions, "            size_t chunk_size = 0;").unwrap();
+    writeln!(
+        out_functions,
+        "            while ((chunk_size = {prefix}_next_chunk(chunks, &chunk_ptr)) != 0) {{"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "                auto deserializer = serde::BincodeDeserializer(std::vector<uint8_t>(chunk_ptr, chunk_ptr + chunk_size));"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "                auto chunk = serde::Deserializable<{vec_type}>::deserialize(deserializer);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "                received_len += chunk.size();"
+    )
+    .unwrap();
+    writeln!(out_functions, "                on_chunk(std::move(chunk));").unwrap();
+    writeln!(out_functions, "            }}").unwrap();
+    writeln!(out_functions, "        }} catch (...) {{").unwrap();
+    writeln!(
+        out_functions,
+        "            {prefix}_free_chunked_result(chunks);"
+    )
+    .unwrap();
+    writeln!(out_functions, "            throw;").unwrap();
+    writeln!(out_functions, "        }}").unwrap();
+    writeln!(
+        out_functions,
+        "        {prefix}_free_chunked_result(chunks);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "        if (received_len != expected_len) {{"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "            throw std::runtime_error(\"Could not transfer all chunks of `{cpp_name}`\");"
+    )
+    .unwrap();
+    writeln!(out_functions, "        }}").unwrap();
+    writeln!(out_functions, "    }}\n").unwrap();
+
+    write_function_docs(
+        out_functions,
+        doc_style,
+        "    ",
+        item.docs.as_deref(),
+        &[],
+        &FunctionDocs {
+            params: wrapper_params(inputs),
+            returns: Some(vec_type),
+            throws: Some(error_type),
+        },
+    );
+    writeln!(
+        out_functions,
+        "    inline {vec_type} {cpp_name}({}) {{",
+        params.trim_end_matches(", ")
+    )
+    .unwrap();
+    writeln!(out_functions, "        {vec_type} out;").unwrap();
+    writeln!(
+        out_functions,
+        "        {cpp_name}_chunked({args}[&out]({vec_type}&& chunk) {{"
+    )
+    .unwrap();
+    writeln!(out_functions, "            for (auto& item : chunk) {{").unwrap();
+    writeln!(
+        out_functions,
+        "                out.push_back(std::move(item));"
+    )
+    .unwrap();
+    writeln!(out_functions, "            }}").unwrap();
+    writeln!(out_functions, "        }});").unwrap();
+    writeln!(out_functions, "        return out;").unwrap();
+    writeln!(out_functions, "    }}\n").unwrap();
+}
+
+/// Serializes all arguments that are not passed by value into `{name}_serialized`
+fn write_serialized_arguments(
+    out_functions: &mut impl std::io::Write,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+) {
+    for (name, tpe) in inputs {
+        if *name == "self" || by_value.contains(*name) || is_callback_type(tpe) {
+            continue;
+        }
+        writeln!(
+            out_functions,
+            "        auto serializer_{name} = serde::BincodeSerializer();"
+        )
+        .unwrap();
+        writeln!(
+            out_functions,
+            "        serde::Serializable<{tpe}>::serialize({name}, serializer_{name});"
+        )
+        .unwrap();
+        writeln!(out_functions, "        std::vector<uint8_t> {name}_serialized = std::move(serializer_{name}).bytes();").unwrap();
+    }
+}
+
+/// The `std::function` a `#[buffi(callback)]` argument is passed as
+fn callback_type(callback: &interfaces::ImportedFunction) -> String {
+    let inputs = callback
+        .inputs
+        .iter()
+        .map(|(_, tpe)| tpe.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let output = callback.output.as_deref().unwrap_or("void");
+    format!("std::function<{output}({inputs})>")
+}
+
+/// Whether `tpe` is the C++ type of a `#[buffi(callback)]` argument, see [`callback_type`]
+fn is_callback_type(tpe: &str) -> bool {
+    tpe.starts_with("std::function<")
+}
+
+/// Declares the `{name}_trampoline` that is passed to the extern function for each callback,
+/// it calls the `std::function` passed as context
+fn write_callback_trampolines(
+    out_functions: &mut impl std::io::Write,
+    res: &ItemResolver,
+    callbacks: &[interfaces::ImportedFunction],
+    prefix: &str,
+) -> Result<(), Error> {
+    if callbacks.is_empty() {
+        return Ok(());
+    }
+    let alloc_function = format!("{prefix}_alloc_byte_buffer");
+    let has_alloc_function = res.doc_types.index.values().any(|item| {
+        item.name.as_deref() == Some(alloc_function.as_str())
+            && matches!(item.inner, rustdoc_types::ItemEnum::Function(_))
+    });
+    if !has_alloc_function {
+        return Err(Error::Generation(String::from(
+            "Arguments marked with `#[buffi(callback)]` require a call to \
+             `buffi::export_alloc_byte_buffer!()` in the API crate",
+        )));
+    }
+    for callback in callbacks {
+        let callee = format!("(*static_cast<{}*>(context))", callback_type(callback));
+        write!(
+            out_functions,
+            "        auto {}_trampoline = ",
+            callback.name
+        )
+        .unwrap();
+        interfaces::write_trampoline(out_functions, callback, &callee, prefix);
+        writeln!(out_functions, ";").unwrap();
+    }
+    Ok(())
+}
+
+/// Whether the method takes `self: Box<Self>`, which frees the value after the call
+fn consumes_self(item: &rustdoc_types::Item) -> bool {
+    let rustdoc_types::ItemEnum::Function(ref function) = item.inner else {
+        return false;
+    };
+    function.sig.inputs.first().is_some_and(|(name, tpe)| {
+        name == "self"
+            && matches!(tpe, rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Box")
+    })
+}
+
+/// The C++ expression passed as `this_ptr`, the holder does not point to the value anymore if
+/// the method consumes it
+fn this_argument(item: &rustdoc_types::Item) -> &'static str {
+    if consumes_self(item) {
+        "std::exchange(this->inner, nullptr)"
+    } else {
+        "this->inner"
+    }
+}
+
+/// Passes the arguments to the extern function with `this` as `this_ptr`, the caller adds the
+/// out pointers
+fn write_call_arguments(
+    out_functions: &mut impl std::io::Write,
+    this: &str,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+) {
+    for (name, tpe) in inputs {
+        if *name == "self" {
+            write!(out_functions, "{this}, ").unwrap();
+        } else if by_value.contains(*name) {
+            write!(out_functions, "{name}, ").unwrap();
+        } else if is_callback_type(tpe) {
+            write!(
+                out_functions,
+                "{name}_trampoline, const_cast<{tpe}*>(&{name}), "
+            )
+            .unwrap();
+        } else {
+            write!(
+                out_functions,
+                "{name}_serialized.data(), {name}_serialized.size(), "
+            )
+            .unwrap();
         }
     }
+}
+
+/// Throws instead of passing a null pointer to the Rust side, which would crash
+fn write_null_check(
+    out_functions: &mut impl std::io::Write,
+    inputs: &[(&String, String)],
+    function_name: &str,
+) {
+    let Some((_, self_type)) = inputs.iter().find(|(n, _)| *n == "self") else {
+        return;
+    };
+    writeln!(out_functions, "        if (this->inner == nullptr) {{").unwrap();
+    writeln!(
+        out_functions,
+        "            throw std::logic_error(\"Called `{self_type}Holder::{function_name}` with a null `{self_type}`\");"
+    )
+    .unwrap();
+    writeln!(out_functions, "        }}").unwrap();
+}
+
+/// Generates a wrapper for `{prefix}_{name}_with_callback` that returns a `std::future`
+/// instead of blocking until the result is available
+#[allow(clippy::too_many_arguments)]
+fn generate_future_function_def(
+    item: &rustdoc_types::Item,
+    out_functions: &mut impl std::io::Write,
+    prefix: &str,
+    inputs: &[(&String, String)],
+    by_value: &HashSet<String>,
+    output_type: &str,
+    return_output_type: &str,
+    doc_style: DocStyle,
+    thrown_error: Option<&str>,
+) {
+    let name = item.name.as_deref().unwrap();
+    let cpp_name = cpp_function_name(item);
+    let future_type = format!("std::future<{return_output_type}>");
+    write_function_docs(
+        out_functions,
+        doc_style,
+        "    ",
+        item.docs.as_deref(),
+        &["This function does not block, the result is provided via the returned future"],
+        &FunctionDocs {
+            params: wrapper_params(inputs),
+            returns: Some(&future_type),
+            throws: thrown_error,
+        },
+    );
     write!(
         out_functions,
-        "    inline {return_output_type} {}(",
-        item.name.as_ref().unwrap()
+        "    inline std::future<{return_output_type}> {cpp_name}_async("
     )
     .unwrap();
     for (idx, (name, tpe)) in inputs.iter().filter(|(n, _)| *n != "self").enumerate() {
@@ -685,8 +3298,9 @@ fn generate_function_def(
         write!(out_functions, "const {tpe}& {name}").unwrap();
     }
     writeln!(out_functions, ") {{").unwrap();
-    for (name, tpe) in &inputs {
-        if *name == "self" {
+    write_null_check(out_functions, inputs, &format!("{cpp_name}_async"));
+    for (name, tpe) in inputs {
+        if *name == "self" || by_value.contains(*name) {
             continue;
         }
         writeln!(
@@ -701,18 +3315,23 @@ fn generate_function_def(
         .unwrap();
         writeln!(out_functions, "        std::vector<uint8_t> {name}_serialized = std::move(serializer_{name}).bytes();").unwrap();
     }
-    writeln!(out_functions, "        uint8_t* out_ptr = nullptr;").unwrap();
-    writeln!(out_functions).unwrap();
-    write!(
+    writeln!(
         out_functions,
-        "        size_t res_size = {}_{}(",
-        prefix,
-        item.name.as_deref().unwrap(),
+        "        auto promise = new std::promise<{return_output_type}>();"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "        auto future = promise->get_future();"
     )
     .unwrap();
+    writeln!(out_functions).unwrap();
+    write!(out_functions, "        {prefix}_{name}_with_callback(").unwrap();
     for (name, _) in inputs.iter() {
         if *name == "self" {
-            write!(out_functions, "this->inner, ").unwrap();
+            write!(out_functions, "{}, ", this_argument(item)).unwrap();
+        } else if by_value.contains(*name) {
+            write!(out_functions, "{name}, ").unwrap();
         } else {
             write!(
                 out_functions,
@@ -721,58 +3340,74 @@ fn generate_function_def(
             .unwrap();
         }
     }
-    writeln!(out_functions, "&out_ptr);").unwrap();
-    writeln!(out_functions).unwrap();
     writeln!(
         out_functions,
-        "        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);"
+        "[](void* context, uint8_t* out_ptr, size_t res_size) {{"
     )
     .unwrap();
     writeln!(
         out_functions,
-        "        {output_type} out = {output_type}::bincodeDeserialize(serialized_result);"
+        "            auto promise = static_cast<std::promise<{return_output_type}>*>(context);"
     )
     .unwrap();
     writeln!(
         out_functions,
-        "        {}_free_byte_buffer(out_ptr, res_size);",
-        prefix
+        "            std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);"
     )
     .unwrap();
-    writeln!(out_functions).unwrap();
-    if matches!(m.sig.output, Some(rustdoc_types::Type::ResolvedPath(ref p)) if get_name_without_path(&p.name) == "Result")
-    {
-        writeln!(
-            out_functions,
-            "        if (out.value.index() == 0) {{ // Ok"
-        )
-        .unwrap();
-        if return_output_type == "void" {
-            writeln!(out_functions, "            return;").unwrap();
-        } else {
-            writeln!(
-                out_functions,
-                "            auto ok = std::get<0>(out.value);"
-            )
-            .unwrap();
-            writeln!(out_functions, "            return std::get<0>(ok.value);").unwrap();
-        }
-        writeln!(out_functions, "        }} else {{ // Err").unwrap();
+    writeln!(
+        out_functions,
+        "            {prefix}_free_byte_buffer(out_ptr, res_size);"
+    )
+    .unwrap();
+    writeln!(out_functions, "            try {{").unwrap();
+    writeln!(
+        out_functions,
+        "                {output_type} out = {output_type}::bincodeDeserialize(serialized_result);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "                if (out.value.index() == 0) {{ // Ok"
+    )
+    .unwrap();
+    if return_output_type == "void" {
+        writeln!(out_functions, "                    promise->set_value();").unwrap();
+    } else {
         writeln!(
             out_functions,
-            "            auto err = std::get<1>(out.value);"
+            "                    auto ok = std::get<0>(out.value);"
         )
         .unwrap();
         writeln!(
             out_functions,
-            "            auto error = std::get<0>(err.value);"
+            "                    promise->set_value(std::get<0>(ok.value));"
         )
         .unwrap();
-        writeln!(out_functions, "            throw error;").unwrap();
-        writeln!(out_functions, "        }}").unwrap();
-    } else {
-        writeln!(out_functions, "        return out;").unwrap();
     }
+    writeln!(out_functions, "                }} else {{ // Err").unwrap();
+    writeln!(
+        out_functions,
+        "                    auto err = std::get<1>(out.value);"
+    )
+    .unwrap();
+    writeln!(
+        out_functions,
+        "                    promise->set_exception(std::make_exception_ptr(std::get<0>(err.value)));"
+    )
+    .unwrap();
+    writeln!(out_functions, "                }}").unwrap();
+    writeln!(out_functions, "            }} catch (...) {{").unwrap();
+    writeln!(
+        out_functions,
+        "                promise->set_exception(std::current_exception());"
+    )
+    .unwrap();
+    writeln!(out_functions, "            }}").unwrap();
+    writeln!(out_functions, "            delete promise;").unwrap();
+    writeln!(out_functions, "        }}, promise);").unwrap();
+    writeln!(out_functions).unwrap();
+    writeln!(out_functions, "        return future;").unwrap();
     writeln!(out_functions, "    }}\n").unwrap();
 }
 
@@ -781,53 +3416,92 @@ fn generate_type_definitions(
     out_types: &str,
     type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
     config: &Config,
-) {
+) -> Result<serde_reflection::Registry, Error> {
     let comments = serde_generate::DocComments::new();
     let mut comments = Some(comments);
-    let mut types_for_impls = res
+    let mut functions = Vec::new();
+    for item in res
         .doc_types
         .index
         .values()
-        .filter(|i| is_relevant_impl(i) || is_free_standing_impl(i))
-        .flat_map(|item| {
-            if let rustdoc_types::ItemEnum::Impl(ref impl_) = item.inner {
-                impl_
-                    .items
-                    .iter()
-                    .map(|id| res.resolve_index(None, id, &config.parent_crate))
-                    .filter(|item| matches!(item.inner, rustdoc_types::ItemEnum::Function(_)))
-                    .collect()
-            } else if let rustdoc_types::ItemEnum::Function(ref _f) = item.inner {
-                vec![item.clone()]
-            } else {
-                unreachable!()
+        .filter(|i| is_relevant_impl(i) || is_free_standing_impl(i) || is_imported_trait(i))
+    {
+        let (owner, items, exported) = match item.inner {
+            rustdoc_types::ItemEnum::Impl(ref impl_) => {
+                let owner = match impl_.for_ {
+                    rustdoc_types::Type::ResolvedPath(ref p) => get_name_without_path(&p.name),
+                    _ => unreachable!(),
+                };
+                (owner, &impl_.items, true)
             }
-        })
-        .flat_map(|m| {
-            if let rustdoc_types::ItemEnum::Function(ref m) = m.inner {
-                m.sig
-                    .inputs
-                    .iter()
-                    .map(|(_, t)| t.clone())
-                    .chain(
-                        m.sig
-                            .output
-                            .as_ref()
-                            .map(|e| vec![e.clone()])
-                            .unwrap_or_default(),
-                    )
-                    .collect::<Vec<_>>()
-            } else {
-                unreachable!()
+            // imported functions return their result as it is
+            rustdoc_types::ItemEnum::Trait(ref trait_) => {
+                (item.name.as_deref().unwrap(), &trait_.items, false)
             }
-        })
-        .collect::<Vec<_>>();
-    types_for_impls.dedup();
-    let registry = types_for_impls
-        .into_iter()
-        .map(|t| {
-            to_serde_reflect_type(
-                &t,
+            rustdoc_types::ItemEnum::Function(_) => {
+                functions.push((item.name.clone().unwrap(), Rc::new(item.clone()), true));
+                continue;
+            }
+            _ => unreachable!(),
+        };
+        for id in items {
+            let item = res.resolve_index(None, id, &config.parent_crate)?;
+            if matches!(item.inner, rustdoc_types::ItemEnum::Function(_)) {
+                let name = format!("{owner}::{}", item.name.as_deref().unwrap());
+                functions.push((name, item, exported));
+            }
+        }
+    }
+    let mut registry = serde_reflection::Registry::new();
+    for (name, item, exported) in &functions {
+        let rustdoc_types::ItemEnum::Function(ref m) = item.inner else {
+            unreachable!()
+        };
+        let types = res.lenient(config, item, name, type_map, |type_map| {
+            check_trait_types(name, m)?;
+            let output = match m.sig.output {
+                Some(ref e) if *exported => Some(Cow::Owned(to_wire_output_type(item, e, res)?)),
+                Some(ref e) => Some(Cow::Borrowed(e)),
+                None => None,
+            };
+            let mut types = Vec::new();
+            for t in m
+                .sig
+                .inputs
+                .iter()
+                .flat_map(|(_, t)| serialized_argument_types(t))
+                .map(Cow::Borrowed)
+                .chain(output)
+            {
+                types.extend(to_serde_reflect_type(
+                    &t,
+                    res,
+                    &mut comments,
+                    Vec::new(),
+                    &config.parent_crate,
+                    &config.namespace,
+                    type_map,
+                )?);
+            }
+            Ok(types)
+        })?;
+        for (format, container) in types.into_iter().flatten() {
+            if let (serde_reflection::Format::TypeName(n), Some(container)) = (format, container) {
+                // each name refers to one type, so the (shared) container is only cloned once
+                registry
+                    .entry(n)
+                    .or_insert_with(|| Rc::unwrap_or_clone(container));
+            }
+        }
+    }
+    // the result of a constructor is an opaque pointer, only its arguments are serialized
+    for item in res.doc_types.index.values().filter(is_constructor) {
+        let rustdoc_types::ItemEnum::Function(ref m) = item.inner else {
+            unreachable!()
+        };
+        for (_, tpe) in &m.sig.inputs {
+            let types = to_serde_reflect_type(
+                tpe,
                 res,
                 &mut comments,
                 Vec::new(),
@@ -835,93 +3509,455 @@ fn generate_type_definitions(
                 &config.namespace,
                 type_map,
             )
-        })
-        .flat_map(|types| {
-            types.into_iter().filter_map(|(format, container)| {
-                let container = container?;
-                if let serde_reflection::Format::TypeName(n) = format {
-                    Some((n, container))
-                } else {
-                    None
+            .map_err(|e| e.in_function(item.name.as_deref().unwrap()))?;
+            for (format, container) in types {
+                if let (serde_reflection::Format::TypeName(n), Some(container)) =
+                    (format, container)
+                {
+                    registry
+                        .entry(n)
+                        .or_insert_with(|| Rc::unwrap_or_clone(container));
                 }
-            })
-        })
-        .collect::<serde_reflection::Registry>();
+            }
+        }
+    }
+    for item in res.doc_types.index.values().filter(is_event) {
+        let types = to_serde_reflect_type(
+            &interfaces::event_type(item),
+            res,
+            &mut comments,
+            Vec::new(),
+            &config.parent_crate,
+            &config.namespace,
+            type_map,
+        )
+        .map_err(|e| e.in_function(item.name.as_deref().unwrap()))?;
+        for (format, container) in types {
+            if let (serde_reflection::Format::TypeName(n), Some(container)) = (format, container) {
+                // each name refers to one type, so the (shared) container is only cloned once
+                registry
+                    .entry(n)
+                    .or_insert_with(|| Rc::unwrap_or_clone(container));
+            }
+        }
+    }
+
+    let provenance = res.provenance(config)?;
+    if config.module_namespaces == Some(true) {
+        generate_module_namespaces(res, &registry, out_types, config, provenance.as_deref())?;
+    }
+    write_type_definitions(
+        &registry,
+        comments.unwrap(),
+        out_types,
+        config,
+        provenance.as_deref(),
+    )?;
+    Ok(registry)
+}
+
+/// Writes the types of `registry` via `serde_generate` together with the optional
+/// files that only depend on the types
+fn write_type_definitions(
+    registry: &serde_reflection::Registry,
+    comments: serde_generate::DocComments,
+    out_types: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    if let Some(ref snapshot) = config.abi_snapshot {
+        abi_freeze::check(registry, snapshot)?;
+    }
+    if config.generate_error_category == Some(true) {
+        generate_error_category(registry, out_types, config, provenance)?;
+    }
+    if config.qt_conversions == Some(true) {
+        generate_qt_conversions(out_types, config, provenance)?;
+    }
 
-    let config = serde_generate::CodeGeneratorConfig::new(config.namespace.to_owned())
-        .with_comments(comments.unwrap())
+    let warning_suppression = warning_suppression(config)?;
+    let namespace = &config.namespace;
+    let config = serde_generate::CodeGeneratorConfig::new(namespace.to_owned())
+        .with_comments(comments)
         .with_encodings([serde_generate::Encoding::Bincode]);
     let installer = serde_generate::cpp::Installer::new(PathBuf::from(out_types));
-    installer.install_module(&config, &registry).unwrap();
-    installer.install_serde_runtime().unwrap();
-    installer.install_bincode_runtime().unwrap();
+    let install_error = |e| Error::Generation(format!("Failed to write the types: {e}"));
+    installer
+        .install_module(&config, registry)
+        .map_err(install_error)?;
+    installer.install_serde_runtime().map_err(install_error)?;
+    installer.install_bincode_runtime().map_err(install_error)?;
+    if let Some((push, pop)) = warning_suppression {
+        let out_types = Path::new(out_types);
+        for file in [
+            format!("{namespace}.hpp"),
+            String::from("serde.hpp"),
+            String::from("binary.hpp"),
+            String::from("bincode.hpp"),
+        ] {
+            add_warning_pragmas(&out_types.join(file), &push, &pop)?;
+        }
+    }
+    Ok(())
+}
+
+/// Generates nested namespaces that mirror the Rust modules
+///
+/// The types themselves are still defined in the flat namespace generated by `serde_generate`,
+/// the nested namespaces only contain aliases. Types of the API crate are placed relative to the
+/// namespace, types of other crates in a namespace named after the crate.
+fn generate_module_namespaces(
+    res: &ItemResolver,
+    registry: &serde_reflection::Registry,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let parent_crate = config.parent_crate.replace('-', "_");
+    let type_names = res.type_names.borrow();
+    let mut modules = BTreeMap::<_, Vec<_>>::new();
+    for (name, module) in type_names.modules() {
+        // generic types only exist with their arguments
+        if !registry.contains_key(name) {
+            continue;
+        }
+        let module = if module.first() == Some(&parent_crate) {
+            &module[1..]
+        } else {
+            &module[..]
+        };
+        if !module.is_empty() {
+            modules.entry(module.join("::")).or_default().push(name);
+        }
+    }
+
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_module_namespaces.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(
+        writer,
+        "#include \"{namespace}.hpp\"  // IWYU pragma: export\n"
+    )
+    .unwrap();
+    for (module, names) in modules {
+        writeln!(writer, "namespace {namespace}::{module} {{").unwrap();
+        for name in names {
+            writeln!(writer, "using {name} = {namespace}::{name};").unwrap();
+        }
+        writeln!(writer, "}}  // end of namespace {namespace}::{module}\n").unwrap();
+    }
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+/// Generates conversions between the standard library types used by the generated code and
+/// the corresponding Qt types
+///
+/// Strings and byte buffers are converted via overloads of `to_qt` and `from_qt`, timestamps
+/// are expected as milliseconds since the Unix epoch (like `i64` fields storing a timestamp).
+fn generate_qt_conversions(
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_qt_conversions.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include <cstdint>").unwrap();
+    writeln!(writer, "#include <string>").unwrap();
+    writeln!(writer, "#include <vector>\n").unwrap();
+    writeln!(writer, "#include <QByteArray>").unwrap();
+    writeln!(writer, "#include <QDateTime>").unwrap();
+    writeln!(writer, "#include <QString>").unwrap();
+    writeln!(writer, "#include <QStringList>\n").unwrap();
+    writeln!(writer, "namespace {namespace}::qt {{\n").unwrap();
+    writeln!(writer, "inline QString to_qt(const std::string& value) {{").unwrap();
+    writeln!(writer, "    return QString::fromStdString(value);").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline std::string from_qt(const QString& value) {{"
+    )
+    .unwrap();
+    writeln!(writer, "    return value.toStdString();").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline QByteArray to_qt(const std::vector<uint8_t>& value) {{"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    return QByteArray(reinterpret_cast<const char*>(value.data()), static_cast<qsizetype>(value.size()));"
+    )
+    .unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline std::vector<uint8_t> from_qt(const QByteArray& value) {{"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    return std::vector<uint8_t>(value.constBegin(), value.constEnd());"
+    )
+    .unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline QStringList to_qt(const std::vector<std::string>& value) {{"
+    )
+    .unwrap();
+    writeln!(writer, "    QStringList list;").unwrap();
+    writeln!(
+        writer,
+        "    list.reserve(static_cast<qsizetype>(value.size()));"
+    )
+    .unwrap();
+    writeln!(writer, "    for (const auto& s : value) {{").unwrap();
+    writeln!(writer, "        list.append(to_qt(s));").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "    return list;").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline std::vector<std::string> from_qt(const QStringList& value) {{"
+    )
+    .unwrap();
+    writeln!(writer, "    std::vector<std::string> list;").unwrap();
+    writeln!(
+        writer,
+        "    list.reserve(static_cast<size_t>(value.size()));"
+    )
+    .unwrap();
+    writeln!(writer, "    for (const auto& s : value) {{").unwrap();
+    writeln!(writer, "        list.push_back(from_qt(s));").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "    return list;").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "// `millis` are the milliseconds since the Unix epoch, the result is in UTC"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "inline QDateTime datetime_from_unix_millis(std::int64_t millis) {{"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    return QDateTime::fromMSecsSinceEpoch(millis).toUTC();"
+    )
+    .unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "// The milliseconds since the Unix epoch").unwrap();
+    writeln!(
+        writer,
+        "inline std::int64_t unix_millis_from_datetime(const QDateTime& value) {{"
+    )
+    .unwrap();
+    writeln!(writer, "    return value.toMSecsSinceEpoch();").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "}}  // end of namespace {namespace}::qt").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+    Ok(())
+}
+
+/// Generates a `std::error_category` for `SerializableError`
+///
+/// Each variant of the error enum gets its own error code (starting at 1, as 0 means success for
+/// `std::error_code`). If the error type is a struct there is only a single error code.
+fn generate_error_category(
+    registry: &serde_reflection::Registry,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let prefix = config.type_name_prefix.as_deref().unwrap_or_default();
+    let error_type = format!("{prefix}SerializableError");
+    let variants = match registry.get(&error_type) {
+        Some(serde_reflection::ContainerFormat::Enum(variants)) => variants
+            .values()
+            .map(|v| v.name.clone())
+            .collect::<Vec<_>>(),
+        Some(_) => vec![String::from("SerializableError")],
+        None => {
+            return Err(Error::Generation(format!(
+                "Could not find `{error_type}` in the generated types"
+            )))
+        }
+    };
+    let is_enum = matches!(
+        registry.get(&error_type),
+        Some(serde_reflection::ContainerFormat::Enum(_))
+    );
+
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_error_category.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include <string>").unwrap();
+    writeln!(writer, "#include <system_error>").unwrap();
+    writeln!(
+        writer,
+        "#include \"{namespace}.hpp\"  // IWYU pragma: export\n"
+    )
+    .unwrap();
+    writeln!(writer).unwrap();
+    writeln!(writer, "namespace {namespace} {{").unwrap();
+    writeln!(writer).unwrap();
+    writeln!(writer, "enum class {prefix}SerializableErrorCode : int {{").unwrap();
+    for (idx, variant) in variants.iter().enumerate() {
+        writeln!(writer, "    {variant} = {},", idx + 1).unwrap();
+    }
+    writeln!(writer, "}};\n").unwrap();
+    writeln!(
+        writer,
+        "class {prefix}SerializableErrorCategory : public std::error_category {{"
+    )
+    .unwrap();
+    writeln!(writer, "public:").unwrap();
+    writeln!(
+        writer,
+        "    const char* name() const noexcept override {{ return \"{}\"; }}\n",
+        config.api_lib_name
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    std::string message(int code) const override {{"
+    )
+    .unwrap();
+    writeln!(writer, "        switch (code) {{").unwrap();
+    for (idx, variant) in variants.iter().enumerate() {
+        writeln!(
+            writer,
+            "            case {}: return \"{variant}\";",
+            idx + 1
+        )
+        .unwrap();
+    }
+    writeln!(writer, "            default: return \"Unknown error\";").unwrap();
+    writeln!(writer, "        }}").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "}};\n").unwrap();
+    writeln!(
+        writer,
+        "inline const std::error_category& serializable_error_category() {{"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    static {prefix}SerializableErrorCategory category;"
+    )
+    .unwrap();
+    writeln!(writer, "    return category;").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline std::error_code make_error_code({prefix}SerializableErrorCode code) {{"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    return {{static_cast<int>(code), serializable_error_category()}};"
+    )
+    .unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "inline std::error_code make_error_code(const {error_type}& error) {{"
+    )
+    .unwrap();
+    if is_enum {
+        writeln!(
+            writer,
+            "    return {{static_cast<int>(error.value.index()) + 1, serializable_error_category()}};"
+        )
+        .unwrap();
+    } else {
+        writeln!(writer, "    (void)error;").unwrap();
+        writeln!(
+            writer,
+            "    return make_error_code({prefix}SerializableErrorCode::SerializableError);"
+        )
+        .unwrap();
+    }
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "}}  // end of namespace {namespace}\n").unwrap();
+    writeln!(writer, "namespace std {{").unwrap();
+    writeln!(writer, "template <>").unwrap();
+    writeln!(
+        writer,
+        "struct is_error_code_enum<{namespace}::{prefix}SerializableErrorCode> : true_type {{}};"
+    )
+    .unwrap();
+    writeln!(writer, "}}  // end of namespace std").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+    Ok(())
 }
 
-fn to_cpp_type_name(f: &serde_reflection::Format) -> String {
-    match f {
-        serde_reflection::Format::Variable(_) => unimplemented!(),
-        serde_reflection::Format::TypeName(_) => to_type_name(f).into_owned(),
-        serde_reflection::Format::Unit => unimplemented!(),
+fn to_cpp_type_name(f: &serde_reflection::Format) -> Result<String, Error> {
+    Ok(match f {
+        serde_reflection::Format::TypeName(_) => to_type_name(f)?.into_owned(),
         serde_reflection::Format::Bool => String::from("bool"),
         serde_reflection::Format::I8 => String::from("int8_t"),
         serde_reflection::Format::I16 => String::from("int16_t"),
         serde_reflection::Format::I32 => String::from("int32_t"),
         serde_reflection::Format::I64 => String::from("int64_t"),
-        serde_reflection::Format::I128 => unimplemented!(),
         serde_reflection::Format::U8 => String::from("uint8_t"),
         serde_reflection::Format::U16 => String::from("uint16_t"),
         serde_reflection::Format::U32 => String::from("uint32_t"),
         serde_reflection::Format::U64 => String::from("uint64_t"),
-        serde_reflection::Format::U128 => unimplemented!(),
         serde_reflection::Format::F32 => String::from("float"),
         serde_reflection::Format::F64 => String::from("double"),
-        serde_reflection::Format::Char => unimplemented!(),
         serde_reflection::Format::Str => String::from("std::string"),
-        serde_reflection::Format::Bytes => unimplemented!(),
+        serde_reflection::Format::Bytes => String::from("std::vector<uint8_t>"),
         serde_reflection::Format::Option(t) => {
-            format!("std::optional<{}>", to_cpp_type_name(t))
+            format!("std::optional<{}>", to_cpp_type_name(t)?)
         }
         serde_reflection::Format::Seq(p) => {
-            format!("std::vector<{}>", to_cpp_type_name(p))
+            format!("std::vector<{}>", to_cpp_type_name(p)?)
+        }
+        // the serde runtime only supports `std::map`, so `HashMap`s are mapped to it as well
+        serde_reflection::Format::Map { key, value } => {
+            format!(
+                "std::map<{}, {}>",
+                to_cpp_type_name(key)?,
+                to_cpp_type_name(value)?
+            )
         }
-        serde_reflection::Format::Map { .. } => unimplemented!(),
         serde_reflection::Format::Tuple(d) if d.is_empty() => String::from("void"),
-        serde_reflection::Format::Tuple(_) => unimplemented!(),
-        serde_reflection::Format::TupleArray { .. } => unimplemented!(),
-    }
-}
-
-fn to_type_name(f: &serde_reflection::Format) -> Cow<str> {
-    match f {
-        serde_reflection::Format::Variable(_) => unimplemented!(),
-        serde_reflection::Format::TypeName(n) => Cow::Borrowed(n),
-        serde_reflection::Format::Unit => unimplemented!(),
-        serde_reflection::Format::Bool => Cow::Borrowed("bool"),
-        serde_reflection::Format::I8 => Cow::Borrowed("i8"),
-        serde_reflection::Format::I16 => Cow::Borrowed("i16"),
-        serde_reflection::Format::I32 => Cow::Borrowed("i32"),
-        serde_reflection::Format::I64 => Cow::Borrowed("i64"),
-        serde_reflection::Format::I128 => unimplemented!(),
-        serde_reflection::Format::U8 => Cow::Borrowed("u8"),
-        serde_reflection::Format::U16 => Cow::Borrowed("u16"),
-        serde_reflection::Format::U32 => Cow::Borrowed("u32"),
-        serde_reflection::Format::U64 => Cow::Borrowed("u64"),
-        serde_reflection::Format::U128 => unimplemented!(),
-        serde_reflection::Format::F32 => Cow::Borrowed("f32"),
-        serde_reflection::Format::F64 => Cow::Borrowed("f64"),
-        serde_reflection::Format::Char => unimplemented!(),
-        serde_reflection::Format::Str => Cow::Borrowed("String"),
-        serde_reflection::Format::Bytes => unimplemented!(),
-        serde_reflection::Format::Option(t) => Cow::Owned(format!("Option_{}", to_type_name(t))),
-        serde_reflection::Format::Seq(t) => Cow::Owned(format!("Vec_{}", to_type_name(t))),
-        serde_reflection::Format::Map { .. } => unimplemented!(),
-        serde_reflection::Format::Tuple(d) if d.is_empty() => Cow::Borrowed("void"),
-        serde_reflection::Format::Tuple(d) => {
-            dbg!(d);
-            unimplemented!()
-        }
-        serde_reflection::Format::TupleArray { .. } => unimplemented!(),
-    }
+        serde_reflection::Format::Tuple(elements) => {
+            let elements = elements
+                .iter()
+                .map(to_cpp_type_name)
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("std::tuple<{}>", elements.join(", "))
+        }
+        serde_reflection::Format::TupleArray { content, size } => {
+            format!("std::array<{}, {size}>", to_cpp_type_name(content)?)
+        }
+        serde_reflection::Format::Variable(_)
+        | serde_reflection::Format::Unit
+        | serde_reflection::Format::I128
+        | serde_reflection::Format::U128
+        | serde_reflection::Format::Char => {
+            return Err(Error::unsupported(format!(
+                "`{f:?}` has no C++ type in the bindings"
+            )))
+        }
+    })
 }
 
 fn to_serde_reflect_type(
@@ -932,71 +3968,38 @@ fn to_serde_reflect_type(
     parent_crate: &str,
     namespace: &str,
     type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
-) -> Vec<(
-    serde_reflection::Format,
-    Option<serde_reflection::ContainerFormat>,
-)> {
+) -> Result<Vec<ReflectedFormat>, Error> {
     use serde_reflection::{ContainerFormat, Format};
 
-    /// This is here for DRY (used by primitives and arrays.)
-    fn reflect_primitive(p: &rustdoc_types::Type) -> Vec<(Format, Option<ContainerFormat>)> {
-        let rustdoc_types::Type::Primitive(ref p) = p else {
-            unreachable!("Primitive!")
+    fn reflect_primitive(p: &str) -> Result<Vec<ReflectedFormat>, Error> {
+        let format = match p {
+            "i64" => Format::I64,
+            "i32" => Format::I32,
+            "i16" => Format::I16,
+            "i8" => Format::I8,
+            "bool" => Format::Bool,
+            "f64" => Format::F64,
+            "f32" => Format::F32,
+            "u8" => Format::U8,
+            "u16" => Format::U16,
+            "u32" => Format::U32,
+            "u64" => Format::U64,
+            // `str` is only reachable behind a reference and represented as an owned string
+            "str" => Format::Str,
+            // serde serializes `usize` and `isize` as 64 bit values on every target
+            "usize" => Format::U64,
+            "isize" => Format::I64,
+            _ => return Err(Error::unsupported(format!("`{p}` is not supported"))),
         };
-        match p.as_ref() {
-            "i64" => {
-                vec![(Format::I64, None)]
-            }
-            "i32" => {
-                vec![(Format::I32, None)]
-            }
-            "i16" => {
-                vec![(Format::I16, None)]
-            }
-            "i8" => {
-                vec![(Format::I8, None)]
-            }
-            "bool" => {
-                vec![(Format::Bool, None)]
-            }
-            "f64" => {
-                vec![(Format::F64, None)]
-            }
-            "f32" => {
-                vec![(Format::F32, None)]
-            }
-            "u8" => {
-                vec![(Format::U8, None)]
-            }
-            "u16" => {
-                vec![(Format::U16, None)]
-            }
-            "u32" => {
-                vec![(Format::U32, None)]
-            }
-            "u64" => {
-                vec![(Format::U64, None)]
-            }
-            "usize" if size_of::<usize>() == 8 => {
-                // TODO: This, properly.
-                vec![(Format::U64, None)]
-            }
-            "usize" if size_of::<usize>() == 4 => {
-                // TODO: This, properly.
-                vec![(Format::U32, None)]
-            }
-            "usize" => {
-                panic!("Invalid size of usize.");
-            }
-            _ => {
-                dbg!(p);
-                unimplemented!()
-            }
-        }
+        Ok(vec![(format, None)])
     }
 
+    // a generic parameter stands for a different type in each instance of its parent type,
+    // so it is not cached
+    let is_generic = matches!(t, rustdoc_types::Type::Generic(_));
     let recursive_type = match type_map.get(t) {
-        Some(TypeCache::Cached(t)) => return t.clone(),
+        _ if is_generic => false,
+        Some(TypeCache::Cached(t)) => return Ok(t.clone()),
         Some(TypeCache::NeedToPopulate) => true,
         None => {
             type_map.insert(t.clone(), TypeCache::NeedToPopulate);
@@ -1007,80 +4010,55 @@ fn to_serde_reflect_type(
     let r = match t {
         rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Result" => {
             let mut out = Vec::new();
-            let (ok, error) = if let Some(rustdoc_types::GenericArgs::AngleBracketed {
-                args, ..
-            }) = p.args.as_deref()
-            {
-                let ok = &args[0];
-                let ok = if let rustdoc_types::GenericArg::Type(tpe) = ok {
-                    to_serde_reflect_type(
-                        tpe,
-                        crate_map,
-                        comment_map,
-                        Vec::new(),
-                        parent_crate,
-                        namespace,
-                        type_map,
-                    )
-                } else {
-                    unreachable!()
-                };
-                let err = if let Some((id, _)) =
-                    crate_map.doc_types.index.iter().find(|(_, item)| {
-                        item.name.as_deref().map(get_name_without_path) == Some("SerializableError")
-                    }) {
-                    let t = rustdoc_types::Type::ResolvedPath(rustdoc_types::Path {
-                        name: "SerializableError".into(),
-                        id: *id,
-                        args: None,
-                    });
-                    to_serde_reflect_type(
-                        &t,
-                        crate_map,
-                        comment_map,
-                        Vec::new(),
-                        parent_crate,
-                        namespace,
-                        type_map,
-                    )
-                } else {
-                    unreachable!("Could not find docs for `SerializableError`! Maybe the `errors` module or the type itself is still private?")
-                };
-                (ok, err)
-            } else {
-                unreachable!()
+            let ok = to_serde_reflect_type(
+                type_argument(p, 0)?,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            // `Result` aliases like `type Result<T> = …` don't name their error type
+            let err = match type_argument(p, 1) {
+                Ok(tpe) => Cow::Borrowed(tpe),
+                Err(_) => Cow::Owned(serializable_error_type(crate_map)?),
             };
+            let error = to_serde_reflect_type(
+                &err,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            let ok_format = last_format(&ok)?;
+            let error_format = last_format(&error)?;
             let mut result_enum = BTreeMap::new();
             result_enum.insert(
                 0,
                 serde_reflection::Named {
                     name: "Ok".into(),
-                    value: serde_reflection::VariantFormat::Tuple(vec![ok
-                        .last()
-                        .unwrap()
-                        .0
-                        .clone()]),
+                    value: serde_reflection::VariantFormat::Tuple(vec![ok_format.clone()]),
                 },
             );
             result_enum.insert(
                 1,
                 serde_reflection::Named {
                     name: "Err".into(),
-                    value: serde_reflection::VariantFormat::Tuple(vec![error
-                        .last()
-                        .unwrap()
-                        .0
-                        .clone()]),
+                    value: serde_reflection::VariantFormat::Tuple(vec![error_format.clone()]),
                 },
             );
-            let ok_name = to_type_name(&ok.last().unwrap().0);
-            let err_name = to_type_name(&error.last().unwrap().0);
-            let name = format!("Result_{ok_name}_{err_name}");
+            let name = crate_map
+                .type_names
+                .borrow_mut()
+                .composite_name("Result", &[ok_format, error_format])?;
             out.extend(ok);
             out.extend(error);
             out.push((
                 Format::TypeName(name),
-                Some(ContainerFormat::Enum(result_enum)),
+                Some(Rc::new(ContainerFormat::Enum(result_enum))),
             ));
 
             out
@@ -1088,177 +4066,136 @@ fn to_serde_reflect_type(
         rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "String" => {
             vec![(Format::Str, None)]
         }
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "ByteBuf" => {
+            vec![(Format::Bytes, None)]
+        }
         rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Vec" => {
-            if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref()
-            {
-                if let rustdoc_types::GenericArg::Type(tpe) = &args[0] {
-                    let mut inner = to_serde_reflect_type(
-                        tpe,
-                        crate_map,
-                        comment_map,
-                        Vec::new(),
-                        parent_crate,
-                        namespace,
-                        type_map,
-                    );
-                    let last = inner.last().unwrap().0.clone();
-                    inner.push((Format::Seq(Box::new(last)), None));
-                    inner
-                } else {
-                    unreachable!()
-                }
-            } else {
-                unreachable!()
-            }
+            let mut inner = to_serde_reflect_type(
+                type_argument(p, 0)?,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            let last = last_format(&inner)?;
+            inner.push((Format::Seq(Box::new(last)), None));
+            inner
+        }
+        rustdoc_types::Type::ResolvedPath(p)
+            if matches!(get_name_without_path(&p.name), "HashMap" | "BTreeMap") =>
+        {
+            let mut out = to_serde_reflect_type(
+                type_argument(p, 0)?,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            let key = last_format(&out)?;
+            out.extend(to_serde_reflect_type(
+                type_argument(p, 1)?,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?);
+            let value = last_format(&out)?;
+            out.push((
+                Format::Map {
+                    key: Box::new(key),
+                    value: Box::new(value),
+                },
+                None,
+            ));
+            out
         }
         rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Option" => {
-            if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref()
-            {
-                if let rustdoc_types::GenericArg::Type(tpe) = &args[0] {
-                    let mut inner = to_serde_reflect_type(
-                        tpe,
-                        crate_map,
-                        comment_map,
-                        Vec::new(),
-                        parent_crate,
-                        namespace,
-                        type_map,
-                    );
-                    let last = inner.last().unwrap().0.clone();
-                    inner.push((Format::Option(Box::new(last)), None));
-                    inner
-                } else {
-                    unreachable!()
-                }
-            } else {
-                unreachable!()
-            }
+            let mut inner = to_serde_reflect_type(
+                type_argument(p, 0)?,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            let last = last_format(&inner)?;
+            inner.push((Format::Option(Box::new(last)), None));
+            inner
         }
-        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Box" => {
-            let t = match p.args.as_deref() {
-                Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. })
-                    if args.len() == 1 =>
-                {
-                    if let Some(rustdoc_types::GenericArg::Type(t)) = args.first() {
-                        t
-                    } else {
-                        unreachable!()
-                    }
-                }
-                Some(_) | None => unreachable!(),
-            };
+        // `Cow` is serialized like its owned counterpart, its first argument is a lifetime
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Cow" => {
+            to_serde_reflect_type(
+                type_argument(p, 0)?,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?
+        }
+        // smart pointers are serialized like the value they point to
+        rustdoc_types::Type::ResolvedPath(p)
+            if matches!(get_name_without_path(&p.name), "Box" | "Arc" | "Rc") =>
+        {
+            let inner = to_serde_reflect_type(
+                type_argument(p, 0)?,
+                crate_map,
+                comment_map,
+                parent_args,
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
             if recursive_type {
-                let name = match t {
-                    rustdoc_types::Type::ResolvedPath(p) => get_name_without_path(&p.name),
-                    _ => unreachable!(),
-                };
+                // the pointee is already being populated further up the stack,
+                // so we only need its name here
+                let name = last_format(&inner)?;
                 // we need an explicit early return here as we **don't** want to
                 // update the type map with the preliminary result
-                return vec![(Format::TypeName(name.to_owned()), None)];
-            } else {
-                to_serde_reflect_type(
-                    t,
-                    crate_map,
-                    comment_map,
-                    parent_args,
-                    parent_crate,
-                    namespace,
-                    type_map,
-                )
+                return Ok(vec![(name, None)]);
             }
+            inner
         }
         rustdoc_types::Type::ResolvedPath(p) => {
-            let t = crate_map.resolve_index(Some(p), &p.id, parent_crate);
-            let parent_crate = extract_crate_from_span(&t).expect("parent crate is set");
-            if let Some(comment_map) = comment_map {
-                if let Some(ref doc) = t.docs {
-                    comment_map.insert(vec![namespace.to_owned(), p.name.clone()], doc.clone());
-                }
-            }
-            if let rustdoc_types::ItemEnum::Struct(rustdoc_types::Struct {
-                kind: rustdoc_types::StructKind::Plain { ref fields, .. },
-                ..
-            }) = t.inner
-            {
-                return generate_exported_struct(
-                    fields,
-                    crate_map,
-                    comment_map,
-                    p,
-                    parent_args,
-                    &parent_crate,
-                    namespace,
-                    type_map,
-                    recursive_type,
-                );
-            }
-            if let rustdoc_types::ItemEnum::Struct(rustdoc_types::Struct {
-                kind: rustdoc_types::StructKind::Unit {},
-                ..
-            }) = t.inner
-            {
-                return generate_exported_struct(
-                    &[],
-                    crate_map,
-                    comment_map,
-                    p,
-                    parent_args,
-                    &parent_crate,
-                    namespace,
-                    type_map,
-                    recursive_type,
-                );
-            }
-            if let rustdoc_types::ItemEnum::Enum(ref e) = t.inner {
-                return generate_exported_enum(
-                    e,
-                    crate_map,
-                    comment_map,
-                    p,
-                    &parent_crate,
-                    namespace,
-                    type_map,
-                    recursive_type,
-                );
-            }
-            if let rustdoc_types::ItemEnum::TypeAlias(ref t) = t.inner {
-                return to_serde_reflect_type(
-                    &t.type_,
-                    crate_map,
-                    comment_map,
-                    parent_args,
-                    &parent_crate,
-                    namespace,
-                    type_map,
-                );
-            }
-            dbg!(t);
-            unimplemented!()
-        }
-        rustdoc_types::Type::DynTrait(_) => unimplemented!(),
-        rustdoc_types::Type::Generic(p) => {
-            if parent_args.len() == 1 {
-                if let rustdoc_types::GenericArg::Type(ref t) = &parent_args[0] {
-                    to_serde_reflect_type(
-                        t,
-                        crate_map,
-                        comment_map,
-                        Vec::new(),
-                        parent_crate,
-                        namespace,
-                        type_map,
-                    )
-                } else {
-                    unimplemented!("Only types are accepted here?")
-                }
-            } else {
-                dbg!(parent_args);
-                dbg!(p);
-                unimplemented!("Unsure how to resolve multiple args here??")
-            }
+            return reflect_named_type(
+                t,
+                p,
+                crate_map,
+                comment_map,
+                parent_args,
+                parent_crate,
+                namespace,
+                type_map,
+                recursive_type,
+            )
+            .map_err(|e| e.reflecting(&p.name));
         }
-        rustdoc_types::Type::Primitive(_) => reflect_primitive(t),
-        rustdoc_types::Type::FunctionPointer(_) => unimplemented!(),
+        rustdoc_types::Type::Generic(p) => match &parent_args as &[_] {
+            [rustdoc_types::GenericArg::Type(t)] => to_serde_reflect_type(
+                t,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?,
+            _ => {
+                return Err(Error::unsupported(format!(
+                    "the generic parameter `{p}` cannot be resolved, only types with a \
+                     single type parameter are supported"
+                )))
+            }
+        },
+        rustdoc_types::Type::Primitive(p) => reflect_primitive(p)?,
         rustdoc_types::Type::Tuple(tup) => {
             let mut out = Vec::new();
             let mut fields = Vec::with_capacity(tup.len());
@@ -1271,49 +4208,273 @@ fn to_serde_reflect_type(
                     parent_crate,
                     namespace,
                     type_map,
-                );
-                let f = r.last().map(|a| a.0.clone()).unwrap();
+                )?;
+                fields.push(last_format(&r)?);
                 out.extend(r);
-                fields.push(f);
             }
             out.push((Format::Tuple(fields), None));
             out
         }
-        rustdoc_types::Type::Slice(_) => unimplemented!(),
+        // slices are only reachable behind a reference and represented as a sequence
+        rustdoc_types::Type::Slice(tpe) => {
+            let mut inner = to_serde_reflect_type(
+                tpe,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            let last = last_format(&inner)?;
+            inner.push((Format::Seq(Box::new(last)), None));
+            inner
+        }
         rustdoc_types::Type::Array { type_, len } => {
-            let size = len.parse::<usize>().expect("Array len should be a number");
-            let t = reflect_primitive(type_)[0].0.clone();
-            vec![(
+            let size = len.parse::<usize>().map_err(|_| {
+                Error::unsupported(format!("the array length `{len}` is not a number"))
+            })?;
+            let mut inner = to_serde_reflect_type(
+                type_,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?;
+            let last = last_format(&inner)?;
+            inner.push((
                 Format::TupleArray {
-                    content: Box::new(t),
+                    content: Box::new(last),
                     size,
                 },
                 None,
-            )]
+            ));
+            inner
         }
-        rustdoc_types::Type::ImplTrait(_) => unimplemented!(),
-        rustdoc_types::Type::Infer => unimplemented!(),
-        rustdoc_types::Type::RawPointer { .. } => unimplemented!(),
-        rustdoc_types::Type::Pat { .. } => unimplemented!(),
-
         rustdoc_types::Type::BorrowedRef { type_, .. } => {
             if let rustdoc_types::Type::Generic(s) = &**type_ {
                 if s == "Self" {
-                    return Vec::new();
+                    return Ok(Vec::new());
                 }
             }
-            dbg!(t);
-            unimplemented!()
+            // references are passed like the owned value on the wire
+            to_serde_reflect_type(
+                type_,
+                crate_map,
+                comment_map,
+                Vec::new(),
+                parent_crate,
+                namespace,
+                type_map,
+            )?
+        }
+        rustdoc_types::Type::DynTrait(_) => {
+            return Err(Error::unsupported(
+                "`dyn Trait` types cannot be (de)serialized",
+            ))
         }
-        rustdoc_types::Type::QualifiedPath { .. } => unimplemented!(),
+        rustdoc_types::Type::ImplTrait(_) => {
+            return Err(Error::unsupported(
+                "`impl Trait` types cannot be (de)serialized",
+            ))
+        }
+        rustdoc_types::Type::FunctionPointer(_)
+        | rustdoc_types::Type::Infer
+        | rustdoc_types::Type::RawPointer { .. }
+        | rustdoc_types::Type::Pat { .. }
+        | rustdoc_types::Type::QualifiedPath { .. } => {
+            return Err(Error::unsupported(format!(
+                "`{t:?}` cannot be (de)serialized"
+            )))
+        }
+    };
+
+    if !is_generic {
+        type_map.insert(t.clone(), TypeCache::Cached(r.clone()));
+    }
+    Ok(r)
+}
+
+/// The `idx`th type argument of `p` (like `T` of `Vec<T>`), lifetimes are skipped
+fn type_argument(p: &rustdoc_types::Path, idx: usize) -> Result<&rustdoc_types::Type, Error> {
+    let args = match p.args.as_deref() {
+        Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) => &args[..],
+        _ => &[],
     };
+    args.iter()
+        .filter_map(|a| match a {
+            rustdoc_types::GenericArg::Type(t) => Some(t),
+            _ => None,
+        })
+        .nth(idx)
+        .ok_or_else(|| {
+            Error::unsupported(format!(
+                "`{}` is missing its type argument {}",
+                p.name,
+                idx + 1
+            ))
+        })
+}
+
+/// The format of the reflected type itself, which comes after the types it refers to
+pub(crate) fn last_format(formats: &[ReflectedFormat]) -> Result<serde_reflection::Format, Error> {
+    formats
+        .last()
+        .map(|(format, _)| format.clone())
+        .ok_or_else(|| Error::unsupported("`Self` cannot be (de)serialized"))
+}
 
-    type_map.insert(t.clone(), TypeCache::Cached(r.clone()));
-    r
+/// Reflects a struct, enum or type alias of the API crate or one of its dependencies
+#[allow(clippy::too_many_arguments)]
+fn reflect_named_type(
+    t: &rustdoc_types::Type,
+    p: &rustdoc_types::Path,
+    crate_map: &ItemResolver,
+    comment_map: &mut Option<serde_generate::DocComments>,
+    parent_args: Vec<rustdoc_types::GenericArg>,
+    parent_crate: &str,
+    namespace: &str,
+    type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
+    recursive_type: bool,
+) -> Result<Vec<ReflectedFormat>, Error> {
+    let traced = match crate_map.samples.as_deref() {
+        Some(samples) => traced_types::trace_type(samples, get_name_without_path(&p.name))?,
+        None => None,
+    };
+    if let Some(traced) = traced {
+        let traced = traced
+            .into_iter()
+            .map(|(format, container)| (format, container.map(Rc::new)))
+            .collect::<Vec<_>>();
+        type_map.insert(t.clone(), TypeCache::Cached(traced.clone()));
+        return Ok(traced);
+    }
+    let t = crate_map.resolve_index(Some(p), &p.id, parent_crate)?;
+    let item_path = crate_map.item_path(&p.id, parent_crate);
+    let parent_crate = extract_crate_from_span(&t)?
+        .ok_or_else(|| Error::unsupported(format!("`{}` has no source location", p.name)))?;
+    if let (Some(span), rustdoc_types::ItemEnum::Struct(_) | rustdoc_types::ItemEnum::Enum(_)) =
+        (&t.span, &t.inner)
+    {
+        let origin = format!(
+            "{parent_crate} ({}:{})",
+            span.filename.display(),
+            span.begin.0
+        );
+        let mut type_names = crate_map.type_names.borrow_mut();
+        let name = get_name_without_path(&p.name);
+        type_names.register_type(name, origin)?;
+        if let Some(mut module) = item_path {
+            module.pop();
+            type_names.set_module(name, module);
+        }
+    }
+    let non_exhaustive = t.attrs.iter().any(|a| a == "#[non_exhaustive]");
+    if let Some(comment_map) = comment_map {
+        let mut doc = t.docs.as_deref().map(to_cpp_doc);
+        if non_exhaustive {
+            let doc = doc.get_or_insert_with(String::new);
+            if !doc.is_empty() {
+                doc.push_str("\n\n");
+            }
+            doc.push_str(NON_EXHAUSTIVE_WARNING);
+            if !recursive_type {
+                println!(
+                    "Warning: `{}` is `#[non_exhaustive]`, adding variants or fields \
+                     breaks already compiled C++ code",
+                    p.name
+                );
+            }
+        }
+        if let Some(doc) = doc {
+            comment_map.insert(
+                vec![
+                    namespace.to_owned(),
+                    crate_map
+                        .type_names
+                        .borrow()
+                        .prefixed(get_name_without_path(&p.name)),
+                ],
+                doc,
+            );
+        }
+    }
+    if let rustdoc_types::ItemEnum::Struct(rustdoc_types::Struct {
+        kind: rustdoc_types::StructKind::Plain { ref fields, .. },
+        ..
+    }) = t.inner
+    {
+        return generate_exported_struct(
+            fields,
+            crate_map,
+            comment_map,
+            p,
+            parent_args,
+            &parent_crate,
+            namespace,
+            type_map,
+            recursive_type,
+        );
+    }
+    if let rustdoc_types::ItemEnum::Struct(rustdoc_types::Struct {
+        kind: rustdoc_types::StructKind::Unit {},
+        ..
+    }) = t.inner
+    {
+        return generate_exported_struct(
+            &[],
+            crate_map,
+            comment_map,
+            p,
+            parent_args,
+            &parent_crate,
+            namespace,
+            type_map,
+            recursive_type,
+        );
+    }
+    if let rustdoc_types::ItemEnum::Enum(ref e) = t.inner {
+        return generate_exported_enum(
+            e,
+            crate_map,
+            comment_map,
+            p,
+            &parent_crate,
+            namespace,
+            type_map,
+            recursive_type,
+        );
+    }
+    if let rustdoc_types::ItemEnum::TypeAlias(ref t) = t.inner {
+        return to_serde_reflect_type(
+            &t.type_,
+            crate_map,
+            comment_map,
+            parent_args,
+            &parent_crate,
+            namespace,
+            type_map,
+        );
+    }
+    Err(Error::unsupported(format!(
+        "`{}` is neither a struct, an enum nor a type alias",
+        p.name
+    )))
 }
 
-fn extract_crate_from_span(t: &rustdoc_types::Item) -> Option<String> {
-    let p = &t.span.as_ref()?.filename;
+fn extract_crate_from_span(t: &rustdoc_types::Item) -> Result<Option<String>, Error> {
+    let Some(span) = t.span.as_ref() else {
+        return Ok(None);
+    };
+    let p = &span.filename;
+    let unresolved = |reason: &str| {
+        Error::unsupported(format!(
+            "Cannot find the crate of `{}`: {reason}",
+            p.display()
+        ))
+    };
     let mut components = p.components().peekable();
     let crate_name = match components.next() {
         Some(Component::Normal(el)) => {
@@ -1333,9 +4494,11 @@ fn extract_crate_from_span(t: &rustdoc_types::Item) -> Option<String> {
                 .skip(1); // need to skip "src" itself
             let Component::Normal(next) = rev_components.next().unwrap_or(Component::Normal(el))
             else {
-                panic!("Could not resolve source path");
+                return Err(unresolved("could not resolve the source path"));
             };
-            let s = next.to_str().expect("We expect an UTF-8 Path");
+            let s = next
+                .to_str()
+                .ok_or_else(|| unresolved("the path is not valid UTF-8"))?;
             // crate names do not contain `-` but `_`
             s.replace('-', "_")
         }
@@ -1353,7 +4516,7 @@ fn extract_crate_from_span(t: &rustdoc_types::Item) -> Option<String> {
                     {
                         break
                     }
-                    None => panic!("Unexpected end of path: {}", p.display()),
+                    None => return Err(unresolved("it is not in the cargo registry")),
                     _ => {}
                 }
             }
@@ -1364,20 +4527,24 @@ fn extract_crate_from_span(t: &rustdoc_types::Item) -> Option<String> {
             // "github.com-*"
             components.next();
             let Some(Component::Normal(el)) = components.next() else {
-                panic!("Expect a normal path element")
+                return Err(unresolved(
+                    "expected a crate directory in the cargo registry",
+                ));
             };
             // that's cratename-version
-            let s = el.to_str().expect("We expect an UTF-8 Path");
+            let s = el
+                .to_str()
+                .ok_or_else(|| unresolved("the path is not valid UTF-8"))?;
             // split from the back as the crate name might contain a `-` as well
             let Some((s, _)) = s.rsplit_once('-') else {
-                panic!("Expect a versioned crate name")
+                return Err(unresolved("expected a versioned crate name"));
             };
             // crate names do not contain `-` but `_`
             s.replace('-', "_")
         }
-        _ => panic!("We expect a relative or absolute path here"),
+        _ => return Err(unresolved("expected a relative or absolute path")),
     };
-    Some(crate_name)
+    Ok(Some(crate_name))
 }
 
 // we can't simply replace `parent_crate` and `namespace` by `config` because this function will
@@ -1393,10 +4560,7 @@ fn generate_exported_enum(
     namespace: &str,
     type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
     recursive_type: bool,
-) -> Vec<(
-    serde_reflection::Format,
-    Option<serde_reflection::ContainerFormat>,
-)> {
+) -> Result<Vec<ReflectedFormat>, Error> {
     use serde_reflection::{ContainerFormat, Format};
 
     let mut out = Vec::new();
@@ -1406,16 +4570,19 @@ fn generate_exported_enum(
     } else {
         let mut enum_def = BTreeMap::new();
         for (id, variant) in e.variants.iter().enumerate() {
-            let v = crate_map.resolve_index(None, variant, parent_crate);
+            let v = crate_map.resolve_index(None, variant, parent_crate)?;
             if let Some(comment_map) = comment_map {
                 if let Some(ref docs) = v.docs {
                     comment_map.insert(
                         vec![
                             namespace.to_owned(),
-                            p.name.clone(),
+                            crate_map
+                                .type_names
+                                .borrow()
+                                .prefixed(get_name_without_path(&p.name)),
                             v.name.clone().unwrap(),
                         ],
-                        docs.clone(),
+                        to_cpp_doc(docs),
                     );
                 }
             }
@@ -1441,13 +4608,17 @@ fn generate_exported_enum(
                         if let Some(t) = id
                             .as_ref()
                             .map(|id| crate_map.resolve_index(None, id, parent_crate))
+                            .transpose()?
                         {
                             if let rustdoc_types::ItemEnum::StructField(ref tpe) = t.inner {
                                 // check for a custom serde attribute here
                                 // this allows us to specify different types for the c++ side
                                 // we expect that we always set a fully qualified path to an type there
                                 // (we control that, as it's our source, so that shouldn't be an problem)
-                                if let Some(serde_type) = t.attrs.iter().find_map(|a| {
+                                if is_serde_bytes(&t.attrs) {
+                                    variants.push(Format::Bytes);
+                                    out.push((Format::Bytes, None));
+                                } else if let Some(serde_type) = t.attrs.iter().find_map(|a| {
                                     let pref = a.strip_prefix("#[serde(with = \"")?;
                                     Some(&pref[..pref.len() - 3])
                                 }) {
@@ -1455,7 +4626,7 @@ fn generate_exported_enum(
                                         serde_type,
                                         parent_crate,
                                         rustdoc_types::ItemKind::Struct,
-                                    );
+                                    )?;
                                     let tpe = rustdoc_types::Type::ResolvedPath(item);
                                     let tps = to_serde_reflect_type(
                                         &tpe,
@@ -1465,20 +4636,20 @@ fn generate_exported_enum(
                                         parent_crate,
                                         namespace,
                                         type_map,
-                                    );
-                                    variants.push(tps.last().unwrap().0.clone());
+                                    )?;
+                                    variants.push(last_format(&tps)?);
                                     out.extend(tps);
                                 } else {
                                     let tps = to_serde_reflect_type(
-                                        tpe,
+                                        &replace_self_type(tpe, p),
                                         crate_map,
                                         comment_map,
                                         Vec::new(),
                                         parent_crate,
                                         namespace,
                                         type_map,
-                                    );
-                                    variants.push(tps.last().unwrap().0.clone());
+                                    )?;
+                                    variants.push(last_format(&tps)?);
                                     out.extend(tps);
                                 }
                             }
@@ -1509,20 +4680,20 @@ fn generate_exported_enum(
                 }) => {
                     let mut variants = Vec::new();
                     for id in fields {
-                        let t = crate_map.resolve_index(None, id, parent_crate);
+                        let t = crate_map.resolve_index(None, id, parent_crate)?;
                         if let rustdoc_types::ItemEnum::StructField(ref tpe) = t.inner {
                             let tps = to_serde_reflect_type(
-                                tpe,
+                                &replace_self_type(tpe, p),
                                 crate_map,
                                 comment_map,
                                 Vec::new(),
                                 parent_crate,
                                 namespace,
                                 type_map,
-                            );
+                            )?;
                             variants.push(serde_reflection::Named {
-                                name: t.name.unwrap(),
-                                value: tps.last().unwrap().0.clone(),
+                                name: t.name.clone().unwrap(),
+                                value: last_format(&tps)?,
                             });
                             out.extend(tps);
                         }
@@ -1536,14 +4707,30 @@ fn generate_exported_enum(
                         },
                     );
                 }
-                _ => unimplemented!(),
+                _ => {
+                    return Err(Error::unsupported(format!(
+                        "`{}` is not an enum variant",
+                        v.name.as_deref().unwrap_or_default()
+                    )))
+                }
             }
         }
-        Some(ContainerFormat::Enum(enum_def))
+        Some(Rc::new(ContainerFormat::Enum(enum_def)))
     };
-    let name = get_name_without_path(&p.name);
-    out.push((Format::TypeName(name.to_owned()), container_format));
-    out
+    let name = crate_map
+        .type_names
+        .borrow()
+        .prefixed(get_name_without_path(&p.name));
+    out.push((Format::TypeName(name), container_format));
+    Ok(out)
+}
+
+/// Whether a field is (de)serialized via `#[serde(with = "serde_bytes")]`, which encodes it as
+/// bytes (`std::vector<uint8_t>` in C++) instead of a sequence
+fn is_serde_bytes(attrs: &[String]) -> bool {
+    attrs
+        .iter()
+        .any(|a| a == "#[serde(with = \"serde_bytes\")]")
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1557,18 +4744,17 @@ fn generate_exported_struct(
     namespace: &str,
     type_map: &mut HashMap<rustdoc_types::Type, TypeCache>,
     recursive_type: bool,
-) -> Vec<(
-    serde_reflection::Format,
-    Option<serde_reflection::ContainerFormat>,
-)> {
+) -> Result<Vec<ReflectedFormat>, Error> {
     use serde_reflection::{ContainerFormat, Format};
 
     let mut out = Vec::new();
-    let mut name = get_name_without_path(&p.name).to_owned();
+    let base_name = get_name_without_path(&p.name);
+    let mut name = crate_map.type_names.borrow().prefixed(base_name);
     if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref() {
+        let mut type_args = Vec::new();
         for arg in args {
             if let rustdoc_types::GenericArg::Type(ref t) = arg {
-                let tpe = to_serde_reflect_type(
+                let formats = to_serde_reflect_type(
                     t,
                     crate_map,
                     comment_map,
@@ -1576,86 +4762,537 @@ fn generate_exported_struct(
                     parent_crate,
                     namespace,
                     type_map,
-                )
-                .pop()
-                .unwrap()
-                .0;
-                name = format!("{name}_{}", to_type_name(&tpe));
+                )?;
+                type_args.push(last_format(&formats)?);
+                // the argument is only reflected once, so its definition needs to be kept even
+                // if the fields refer to it (e.g. `Wrapper<Wrapper<i32>>`)
+                out.extend(formats);
             }
         }
+        if !type_args.is_empty() {
+            name = crate_map
+                .type_names
+                .borrow_mut()
+                .composite_name(base_name, &type_args)?;
+        }
     }
     let container_format = if recursive_type {
         // we don't need that for a recursive type
         None
     } else {
-        let fields = fields
-            .iter()
-            .map(|id| crate_map.resolve_index(None, id, parent_crate))
-            .filter_map(|s| {
-                if let Some(ref mut comment_map) = comment_map {
-                    if let Some(ref doc) = s.docs {
-                        comment_map.insert(
-                            vec![
-                                namespace.to_owned(),
-                                p.name.clone(),
-                                s.name.clone().unwrap(),
-                            ],
-                            doc.clone(),
-                        );
-                    }
+        let mut struct_fields = Vec::with_capacity(fields.len());
+        for id in fields {
+            let s = crate_map.resolve_index(None, id, parent_crate)?;
+            let rustdoc_types::ItemEnum::StructField(ref tpe) = s.inner else {
+                continue;
+            };
+            // `PhantomData` markers don't contain any data on the wire
+            if is_phantom_data(tpe) {
+                continue;
+            }
+            if let Some(ref mut comment_map) = comment_map {
+                if let Some(ref doc) = s.docs {
+                    comment_map.insert(
+                        vec![
+                            namespace.to_owned(),
+                            crate_map
+                                .type_names
+                                .borrow()
+                                .prefixed(get_name_without_path(&p.name)),
+                            s.name.clone().unwrap(),
+                        ],
+                        to_cpp_doc(doc),
+                    );
                 }
-                if let rustdoc_types::ItemEnum::StructField(ref tpe) = s.inner {
-                    let parent_args = if let Some(rustdoc_types::GenericArgs::AngleBracketed {
-                        args,
-                        constraints,
-                    }) = p.args.as_deref()
+            }
+            let parent_args =
+                if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, constraints }) =
+                    p.args.as_deref()
+                {
+                    if args.is_empty() && constraints.is_empty() {
+                        Vec::new()
+                    } else if parent_args.len() == 1
+                        && args.len() == 1
+                        && matches!(
+                            &args[0],
+                            rustdoc_types::GenericArg::Type(rustdoc_types::Type::Generic(_))
+                        )
                     {
-                        if args.is_empty() && constraints.is_empty() {
-                            Vec::new()
-                        } else if parent_args.len() == 1
-                            && args.len() == 1
-                            && matches!(
-                                &args[0],
-                                rustdoc_types::GenericArg::Type(rustdoc_types::Type::Generic(_))
-                            )
-                        {
-                            parent_args.clone()
-                        } else {
-                            args.clone()
-                        }
+                        parent_args.clone()
                     } else {
-                        Vec::new()
-                    };
-                    Some((
-                        s.name.clone().unwrap(),
-                        to_serde_reflect_type(
-                            tpe,
-                            crate_map,
-                            comment_map,
-                            parent_args,
-                            parent_crate,
-                            namespace,
-                            type_map,
-                        ),
-                    ))
+                        args.clone()
+                    }
                 } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        let mut struct_fields = Vec::with_capacity(fields.len());
-        for (name, tpe) in fields {
-            let format = tpe.last().unwrap().0.clone();
+                    Vec::new()
+                };
+            let formats = if is_serde_bytes(&s.attrs) {
+                vec![(Format::Bytes, None)]
+            } else {
+                to_serde_reflect_type(
+                    &replace_self_type(tpe, p),
+                    crate_map,
+                    comment_map,
+                    parent_args,
+                    parent_crate,
+                    namespace,
+                    type_map,
+                )?
+            };
             struct_fields.push(serde_reflection::Named {
-                name,
-                value: format,
+                name: s.name.clone().unwrap(),
+                value: last_format(&formats)?,
             });
-            out.extend(tpe);
+            out.extend(formats);
         }
-        Some(ContainerFormat::Struct(struct_fields))
+        Some(Rc::new(ContainerFormat::Struct(struct_fields)))
     };
     out.push((Format::TypeName(name), container_format));
-    out
+    Ok(out)
+}
+
+/// Replaces `Self` in field types (e.g. `Option<Box<Self>>`) with the type that is defined
+///
+/// This allows the usual recursive type handling to pick up self referential types
+fn replace_self_type(
+    tpe: &rustdoc_types::Type,
+    self_type: &rustdoc_types::Path,
+) -> rustdoc_types::Type {
+    replace_generics(tpe, &|name| {
+        (name == "Self").then(|| rustdoc_types::Type::ResolvedPath(self_type.clone()))
+    })
+}
+
+/// Replaces each generic parameter for which `substitute` returns a type
+fn replace_generics(
+    tpe: &rustdoc_types::Type,
+    substitute: &dyn Fn(&str) -> Option<rustdoc_types::Type>,
+) -> rustdoc_types::Type {
+    match tpe {
+        rustdoc_types::Type::Generic(s) => {
+            substitute(s).unwrap_or_else(|| rustdoc_types::Type::Generic(s.clone()))
+        }
+        rustdoc_types::Type::ResolvedPath(p) => {
+            let args = p.args.as_deref().map(|args| match args {
+                rustdoc_types::GenericArgs::AngleBracketed { args, constraints } => {
+                    rustdoc_types::GenericArgs::AngleBracketed {
+                        args: args
+                            .iter()
+                            .map(|a| match a {
+                                rustdoc_types::GenericArg::Type(t) => {
+                                    rustdoc_types::GenericArg::Type(replace_generics(t, substitute))
+                                }
+                                a => a.clone(),
+                            })
+                            .collect(),
+                        constraints: constraints.clone(),
+                    }
+                }
+                args => args.clone(),
+            });
+            rustdoc_types::Type::ResolvedPath(rustdoc_types::Path {
+                args: args.map(Box::new),
+                ..p.clone()
+            })
+        }
+        rustdoc_types::Type::Tuple(tup) => rustdoc_types::Type::Tuple(
+            tup.iter()
+                .map(|t| replace_generics(t, substitute))
+                .collect(),
+        ),
+        rustdoc_types::Type::Slice(t) => {
+            rustdoc_types::Type::Slice(Box::new(replace_generics(t, substitute)))
+        }
+        rustdoc_types::Type::Array { type_, len } => rustdoc_types::Type::Array {
+            type_: Box::new(replace_generics(type_, substitute)),
+            len: len.clone(),
+        },
+        rustdoc_types::Type::BorrowedRef {
+            lifetime,
+            is_mutable,
+            type_,
+        } => rustdoc_types::Type::BorrowedRef {
+            lifetime: lifetime.clone(),
+            is_mutable: *is_mutable,
+            type_: Box::new(replace_generics(type_, substitute)),
+        },
+        t => t.clone(),
+    }
+}
+
+fn is_phantom_data(tpe: &rustdoc_types::Type) -> bool {
+    matches!(
+        tpe,
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "PhantomData"
+    )
+}
+
+fn serializable_error_type(crate_map: &ItemResolver) -> Result<rustdoc_types::Type, Error> {
+    let Some((id, _)) = crate_map.doc_types.index.iter().find(|(_, item)| {
+        item.name.as_deref().map(get_name_without_path) == Some("SerializableError")
+    }) else {
+        return Err(Error::unsupported("Could not find docs for `SerializableError`! Maybe the `errors` module or the type itself is still private?"));
+    };
+    Ok(rustdoc_types::Type::ResolvedPath(rustdoc_types::Path {
+        name: "SerializableError".into(),
+        id: *id,
+        args: None,
+    }))
+}
+
+/// The type that is transferred over the FFI boundary for the return type of an exported function
+///
+/// The macro converts the error type of the returned `Result` into `SerializableError`
+/// unless the function is marked with `#[buffi(custom_error)]`
+fn to_wire_output_type(
+    item: &rustdoc_types::Item,
+    tpe: &rustdoc_types::Type,
+    crate_map: &ItemResolver,
+) -> Result<rustdoc_types::Type, Error> {
+    if item
+        .attrs
+        .contains(&String::from("#[cfg(not(generated_custom_error_marker))]"))
+    {
+        return Ok(tpe.clone());
+    }
+    match tpe {
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Result" => {
+            let Some(rustdoc_types::GenericArgs::AngleBracketed { args, constraints }) =
+                p.args.as_deref()
+            else {
+                unreachable!()
+            };
+            let mut args = args.clone();
+            args.truncate(1);
+            args.push(rustdoc_types::GenericArg::Type(serializable_error_type(
+                crate_map,
+            )?));
+            Ok(rustdoc_types::Type::ResolvedPath(rustdoc_types::Path {
+                args: Some(Box::new(rustdoc_types::GenericArgs::AngleBracketed {
+                    args,
+                    constraints: constraints.clone(),
+                })),
+                ..p.clone()
+            }))
+        }
+        t => Ok(t.clone()),
+    }
+}
+
+/// The instances of an impl block exported with `#[buffi_macro::exported(instances(...))]`
+fn instance_names(item: &rustdoc_types::Item) -> Vec<String> {
+    item.attrs
+        .iter()
+        .filter_map(|attr| {
+            attr.strip_prefix("#[cfg(not(generated_instance_marker = \"")?
+                .strip_suffix("\"))]")
+        })
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Replaces each generic impl block exported with `instances(...)` by one impl block per
+/// instance, implemented for its type alias and with the generic parameters replaced by the
+/// concrete types
+///
+/// The macro names the extern functions of an instance `{prefix}_{instance}_{function}`, so the
+/// methods are renamed to `{instance}_{function}` and keep their name on the C++ side via an
+/// overload marker. The remaining generation handles the instances like any other impl block.
+fn instantiate_generic_impls(doc_types: &mut rustdoc_types::Crate) -> Result<(), Error> {
+    let generic_impls = doc_types
+        .index
+        .values()
+        .filter(is_relevant_impl)
+        .map(|item| (item.id, instance_names(item)))
+        .filter(|(_, instances)| !instances.is_empty())
+        .collect::<Vec<_>>();
+    let mut next_id = doc_types
+        .index
+        .keys()
+        .map(|id| id.0)
+        .max()
+        .unwrap_or_default();
+    let mut new_id = || {
+        next_id += 1;
+        rustdoc_types::Id(next_id)
+    };
+    for (impl_id, instances) in generic_impls {
+        // only the instances are exported
+        let generic_impl = doc_types.index.get_mut(&impl_id).unwrap();
+        generic_impl
+            .attrs
+            .retain(|a| a != "#[cfg(not(generated_extern_impl))]");
+        let generic_impl = generic_impl.clone();
+        let rustdoc_types::ItemEnum::Impl(ref impl_) = generic_impl.inner else {
+            unreachable!()
+        };
+        let generic_args = match impl_.for_ {
+            rustdoc_types::Type::ResolvedPath(rustdoc_types::Path { ref args, .. }) => {
+                match args.as_deref() {
+                    Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) => args.clone(),
+                    _ => Vec::new(),
+                }
+            }
+            _ => unreachable!(),
+        };
+        for instance in instances {
+            let Some(alias) = doc_types.index.values().find(|item| {
+                item.name.as_deref() == Some(&instance)
+                    && matches!(item.inner, rustdoc_types::ItemEnum::TypeAlias(_))
+            }) else {
+                return Err(Error::Generation(format!(
+                    "Could not find docs for the instance `{instance}`! Maybe it is not reachable from the crate root?"
+                )));
+            };
+            let rustdoc_types::ItemEnum::TypeAlias(ref alias_type) = alias.inner else {
+                unreachable!()
+            };
+            let concrete_args = match alias_type.type_ {
+                rustdoc_types::Type::ResolvedPath(rustdoc_types::Path { ref args, .. }) => {
+                    match args.as_deref() {
+                        Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) => {
+                            args.clone()
+                        }
+                        _ => Vec::new(),
+                    }
+                }
+                _ => Vec::new(),
+            };
+            let substitutions = generic_args
+                .iter()
+                .zip(concrete_args)
+                .filter_map(|(generic, concrete)| match (generic, concrete) {
+                    (
+                        rustdoc_types::GenericArg::Type(rustdoc_types::Type::Generic(name)),
+                        rustdoc_types::GenericArg::Type(concrete),
+                    ) => Some((name.clone(), concrete)),
+                    _ => None,
+                })
+                .collect::<HashMap<_, _>>();
+            let substitute = |name: &str| substitutions.get(name).cloned();
+            let for_ = rustdoc_types::Type::ResolvedPath(rustdoc_types::Path {
+                name: instance.clone(),
+                id: alias.id,
+                args: None,
+            });
+            let method_prefix = interfaces::to_snake_case(&instance);
+
+            let mut items = Vec::with_capacity(impl_.items.len());
+            for id in &impl_.items {
+                let Some(mut method) = doc_types.index.get(id).cloned() else {
+                    continue;
+                };
+                if let rustdoc_types::ItemEnum::Function(ref mut f) = method.inner {
+                    for (_, tpe) in &mut f.sig.inputs {
+                        *tpe = replace_generics(tpe, &substitute);
+                    }
+                    f.sig.output = f
+                        .sig
+                        .output
+                        .as_ref()
+                        .map(|tpe| replace_generics(tpe, &substitute));
+                    let name = method.name.clone().unwrap_or_default();
+                    if !method
+                        .attrs
+                        .iter()
+                        .any(|a| a.starts_with("#[cfg(not(generated_overload_marker = "))
+                    {
+                        method.attrs.push(format!(
+                            "#[cfg(not(generated_overload_marker = \"{name}\"))]"
+                        ));
+                    }
+                    method.name = Some(format!("{method_prefix}_{name}"));
+                }
+                method.id = new_id();
+                items.push(method.id);
+                doc_types.index.insert(method.id, method);
+            }
+
+            let mut instance_impl = generic_impl.clone();
+            instance_impl.id = new_id();
+            instance_impl
+                .attrs
+                .push(String::from("#[cfg(not(generated_extern_impl))]"));
+            instance_impl.inner = rustdoc_types::ItemEnum::Impl(rustdoc_types::Impl {
+                generics: rustdoc_types::Generics {
+                    params: Vec::new(),
+                    where_predicates: Vec::new(),
+                },
+                for_,
+                items,
+                ..impl_.clone()
+            });
+            doc_types.index.insert(instance_impl.id, instance_impl);
+        }
+    }
+    Ok(())
+}
+
+/// The type `#[buffi_macro::exported]` deserializes an `impl Trait` argument as: `T` for
+/// `impl Into<T>`, `&str` for `impl AsRef<str>` and `&[T]` for `impl AsRef<[T]>`
+fn resolve_impl_trait(tpe: &rustdoc_types::Type) -> Option<rustdoc_types::Type> {
+    let rustdoc_types::Type::ImplTrait(bounds) = tpe else {
+        return None;
+    };
+    // lifetime bounds like `+ 'static` do not change the wire type
+    let mut traits = bounds.iter().filter_map(|bound| match bound {
+        rustdoc_types::GenericBound::TraitBound { trait_, .. } => Some(trait_),
+        _ => None,
+    });
+    let (Some(trait_), None) = (traits.next(), traits.next()) else {
+        return None;
+    };
+    let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = trait_.args.as_deref()
+    else {
+        return None;
+    };
+    let [rustdoc_types::GenericArg::Type(arg)] = args.as_slice() else {
+        return None;
+    };
+    match (get_name_without_path(&trait_.name), arg) {
+        ("Into", t) => Some(t.clone()),
+        ("AsRef", t)
+            if matches!(t, rustdoc_types::Type::Slice(_))
+                || *t == rustdoc_types::Type::Primitive(String::from("str")) =>
+        {
+            Some(rustdoc_types::Type::BorrowedRef {
+                lifetime: None,
+                is_mutable: false,
+                type_: Box::new(t.clone()),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The arguments and the result of a `#[buffi(callback)]` argument like `impl Fn(u64) -> bool`,
+/// the result is `None` for callbacks returning `()`
+fn callback_signature(
+    tpe: &rustdoc_types::Type,
+) -> Option<(&[rustdoc_types::Type], Option<&rustdoc_types::Type>)> {
+    let rustdoc_types::Type::ImplTrait(bounds) = tpe else {
+        return None;
+    };
+    let mut traits = bounds.iter().filter_map(|bound| match bound {
+        rustdoc_types::GenericBound::TraitBound { trait_, .. } => Some(trait_),
+        _ => None,
+    });
+    let (Some(trait_), None) = (traits.next(), traits.next()) else {
+        return None;
+    };
+    match (get_name_without_path(&trait_.name), trait_.args.as_deref()) {
+        ("Fn" | "FnMut", Some(rustdoc_types::GenericArgs::Parenthesized { inputs, output })) => {
+            let output = output
+                .as_ref()
+                .filter(|t| !matches!(t, rustdoc_types::Type::Tuple(t) if t.is_empty()));
+            Some((inputs.as_slice(), output))
+        }
+        _ => None,
+    }
+}
+
+/// The types that are serialized for an argument, which are the arguments and the result for
+/// a `#[buffi(callback)]`
+fn serialized_argument_types(tpe: &rustdoc_types::Type) -> Vec<&rustdoc_types::Type> {
+    match callback_signature(tpe) {
+        Some((inputs, output)) => inputs.iter().chain(output).collect(),
+        None => vec![tpe],
+    }
+}
+
+/// Replaces the `impl Trait` arguments of all exported functions by the type they are
+/// transferred as (see [`resolve_impl_trait`]), so that the remaining generation only sees
+/// supported types
+fn resolve_impl_trait_arguments(doc_types: &mut rustdoc_types::Crate) {
+    let exported = doc_types
+        .index
+        .values()
+        .filter(|item| {
+            is_relevant_impl(item) || is_free_standing_impl(item) || is_constructor(item)
+        })
+        .flat_map(|item| match item.inner {
+            rustdoc_types::ItemEnum::Impl(ref impl_) => impl_.items.clone(),
+            _ => vec![item.id],
+        })
+        .collect::<Vec<_>>();
+    for id in exported {
+        if let Some(rustdoc_types::Item {
+            inner: rustdoc_types::ItemEnum::Function(ref mut function),
+            ..
+        }) = doc_types.index.get_mut(&id)
+        {
+            for (_, tpe) in &mut function.sig.inputs {
+                if let Some(resolved) = resolve_impl_trait(tpe) {
+                    *tpe = resolved;
+                }
+            }
+        }
+    }
+}
+
+/// The traits of an `impl Trait` or `dyn Trait` type (also behind a reference or `Box`)
+fn trait_type_name(tpe: &rustdoc_types::Type) -> Option<String> {
+    let join = |names: Vec<&str>| names.join(" + ");
+    match tpe {
+        rustdoc_types::Type::ImplTrait(bounds) => Some(format!(
+            "impl {}",
+            join(
+                bounds
+                    .iter()
+                    .filter_map(|bound| match bound {
+                        rustdoc_types::GenericBound::TraitBound { trait_, .. } => {
+                            Some(get_name_without_path(&trait_.name))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            )
+        )),
+        rustdoc_types::Type::DynTrait(d) => Some(format!(
+            "dyn {}",
+            join(
+                d.traits
+                    .iter()
+                    .map(|t| get_name_without_path(&t.trait_.name))
+                    .collect()
+            )
+        )),
+        rustdoc_types::Type::BorrowedRef { type_, .. } => trait_type_name(type_),
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Box" => {
+            match p.args.as_deref() {
+                Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) => {
+                    match args.first() {
+                        Some(rustdoc_types::GenericArg::Type(t)) => trait_type_name(t),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Panics with the function and argument if the signature of `function` (called `name`)
+/// contains a trait type that was not resolved by [`resolve_impl_trait_arguments`]
+fn check_trait_types(name: &str, function: &rustdoc_types::Function) -> Result<(), Error> {
+    for (arg, tpe) in &function.sig.inputs {
+        if callback_signature(tpe).is_some() {
+            continue;
+        }
+        if let Some(trait_type) = trait_type_name(tpe) {
+            return Err(Error::unsupported(format!(
+                "the argument `{arg}` of `{name}` has the type `{trait_type}`, which cannot be \
+                 deserialized (only `impl Into<T>`, `impl AsRef<str>` and `impl AsRef<[T]>` are \
+                 supported), use a concrete type instead"
+            )));
+        }
+    }
+    if let Some(trait_type) = function.sig.output.as_ref().and_then(trait_type_name) {
+        return Err(Error::unsupported(format!(
+            "`{name}` returns `{trait_type}`, which cannot be serialized, use a concrete type instead"
+        )));
+    }
+    Ok(())
 }
 
 fn is_relevant_impl(item: &&rustdoc_types::Item) -> bool {
@@ -1668,6 +5305,57 @@ fn is_relevant_impl(item: &&rustdoc_types::Item) -> bool {
     matches!(item.inner, rustdoc_types::ItemEnum::Impl(_))
 }
 
+/// Whether `item` is marked with `#[doc(hidden)]` and therefore skipped, see
+/// [`Config::include_hidden_items`]
+fn is_hidden(item: &rustdoc_types::Item, config: &Config) -> bool {
+    config.include_hidden_items != Some(true) && item.attrs.iter().any(|a| a == "#[doc(hidden)]")
+}
+
+/// Whether `item` is only enabled while documenting (e.g. `#[cfg(any(doc, feature = "x"))]`)
+///
+/// Rustdoc is run with the features of [`Config::crate_feature_flags`], so every other item
+/// matches the API library built with these features.
+fn is_doc_only(item: &rustdoc_types::Item) -> bool {
+    item.attrs.iter().any(|attr| {
+        // every other part is a string literal like a feature name
+        attr.starts_with("#[cfg(")
+            && attr.split('"').step_by(2).any(|part| {
+                part.split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .any(|token| token == "doc")
+            })
+    })
+}
+
+/// Whether the wrapper of `item` can be generated, warns about items that only exist while
+/// documenting, as the API library does not contain their extern functions
+fn is_built(item: &rustdoc_types::Item) -> bool {
+    if is_doc_only(item) {
+        println!(
+            "Warning: skipping `{}`, it is only enabled while documenting",
+            item.name.as_deref().unwrap_or("impl block")
+        );
+        return false;
+    }
+    true
+}
+
+/// Types marked with `#[buffi_macro::event]`, which are pushed to listeners on the C++ side
+fn is_event(item: &&rustdoc_types::Item) -> bool {
+    item.attrs
+        .contains(&String::from("#[cfg(not(generated_event_marker))]"))
+}
+
+/// Traits marked with `#[buffi_macro::imported]`, which are implemented on the C++ side
+fn is_imported_trait(item: &&rustdoc_types::Item) -> bool {
+    if !item
+        .attrs
+        .contains(&String::from("#[cfg(not(generated_imported_trait))]"))
+    {
+        return false;
+    }
+    matches!(item.inner, rustdoc_types::ItemEnum::Trait(_))
+}
+
 fn is_free_standing_impl(item: &&rustdoc_types::Item) -> bool {
     if !item
         .attrs
@@ -1678,78 +5366,219 @@ fn is_free_standing_impl(item: &&rustdoc_types::Item) -> bool {
     matches!(item.inner, rustdoc_types::ItemEnum::Function(_))
 }
 
-fn to_c_type(tpe: &rustdoc_types::Type) -> String {
-    match tpe {
+fn to_c_type(tpe: &rustdoc_types::Type) -> Result<String, Error> {
+    Ok(match tpe {
         rustdoc_types::Type::ResolvedPath(p) => {
             let mut ret = get_name_without_path(&p.name).trim().to_string();
             if ret == "c_char" {
                 String::from("char")
+            } else if ret == "c_void" {
+                String::from("void")
             } else {
                 if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) =
                     p.args.as_deref()
                 {
                     for arg in args {
                         if let rustdoc_types::GenericArg::Type(t) = arg {
-                            write!(ret, "_{}", to_c_type(t)).unwrap();
+                            ret.push('_');
+                            ret.push_str(&to_c_type(t)?);
                         }
                     }
                 }
                 ret
             }
         }
-        rustdoc_types::Type::DynTrait(_) => unimplemented!(),
-        rustdoc_types::Type::Generic(_) => unimplemented!(),
         rustdoc_types::Type::Primitive(p) if p == "u8" => String::from("std::uint8_t"),
         rustdoc_types::Type::Primitive(p) if p == "usize" => String::from("size_t"),
         rustdoc_types::Type::Primitive(p) if p == "u16" => String::from("std::uint16_t"),
+        rustdoc_types::Type::Primitive(p) if p == "u32" => String::from("std::uint32_t"),
+        rustdoc_types::Type::Primitive(p) if p == "u64" => String::from("std::uint64_t"),
+        rustdoc_types::Type::Primitive(p) if p == "i8" => String::from("std::int8_t"),
+        rustdoc_types::Type::Primitive(p) if p == "i16" => String::from("std::int16_t"),
+        rustdoc_types::Type::Primitive(p) if p == "i32" => String::from("std::int32_t"),
+        rustdoc_types::Type::Primitive(p) if p == "i64" => String::from("std::int64_t"),
+        rustdoc_types::Type::Primitive(p) if p == "f32" => String::from("float"),
+        rustdoc_types::Type::Primitive(p) if p == "f64" => String::from("double"),
         rustdoc_types::Type::Primitive(p) => p.clone(),
         rustdoc_types::Type::FunctionPointer(_) => String::new(),
-        rustdoc_types::Type::Tuple(_) => unimplemented!(),
-        rustdoc_types::Type::Slice(_) => unimplemented!(),
-        rustdoc_types::Type::Array { .. } => unimplemented!(),
-        rustdoc_types::Type::ImplTrait(_) => unimplemented!(),
-        rustdoc_types::Type::Infer => unimplemented!(),
         rustdoc_types::Type::RawPointer { is_mutable, type_ } => {
-            let mut out = if *is_mutable {
-                String::new()
-            } else {
-                String::from("const ")
-            };
-            write!(out, "{}*", to_c_type(type_)).unwrap();
-            out
+            let constness = if *is_mutable { "" } else { "const " };
+            format!("{constness}{}*", to_c_type(type_)?)
         }
         rustdoc_types::Type::BorrowedRef { .. } => String::new(),
-        rustdoc_types::Type::QualifiedPath { .. } => unimplemented!(),
-        rustdoc_types::Type::Pat { .. } => unimplemented!(),
-    }
+        rustdoc_types::Type::DynTrait(_)
+        | rustdoc_types::Type::Generic(_)
+        | rustdoc_types::Type::Tuple(_)
+        | rustdoc_types::Type::Slice(_)
+        | rustdoc_types::Type::Array { .. }
+        | rustdoc_types::Type::ImplTrait(_)
+        | rustdoc_types::Type::Infer
+        | rustdoc_types::Type::QualifiedPath { .. }
+        | rustdoc_types::Type::Pat { .. } => {
+            return Err(Error::unsupported(format!(
+                "`{tpe:?}` cannot be passed to an extern function, only primitives, pointers \
+                 and `#[repr(C)]` types are supported"
+            )))
+        }
+    })
 }
 
-fn generate_extern_c_function_def(name: &str, func: &rustdoc_types::Function) -> String {
-    let mut out = String::from("extern \"C\" ");
-    write!(
-        out,
-        "{} ",
-        func.sig
-            .output
-            .as_ref()
-            .map(to_c_type)
-            .unwrap_or_else(|| "void".into())
+/// Arguments marked with `#[buffi(by_value)]` are passed as they are instead of a pointer
+/// to a serialized buffer (function pointers are wrapped in an `Option`)
+fn is_by_value_type(tpe: &rustdoc_types::Type) -> bool {
+    matches!(
+        tpe,
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) != "Option"
     )
-    .unwrap();
+}
+
+/// The names of the arguments of `function` as used by its extern function
+///
+/// `#[buffi_macro::exported]` names arguments bound by a pattern like `(x, y)` or `_` (which
+/// are not valid C++ names either) `arg{idx}`, where `idx` does not count `self`.
+fn argument_names(function: &rustdoc_types::Function) -> Vec<String> {
+    let mut idx = 0;
+    function
+        .sig
+        .inputs
+        .iter()
+        .map(|(name, _)| {
+            if name == "self" {
+                return name.clone();
+            }
+            let is_identifier = name != "_"
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            let name = if is_identifier {
+                name.clone()
+            } else {
+                format!("arg{idx}")
+            };
+            idx += 1;
+            name
+        })
+        .collect()
+}
+
+/// The arguments of `{prefix}_{name}` that are passed by value
+fn by_value_arguments(res: &ItemResolver, extern_name: &str) -> HashSet<String> {
+    res.doc_types
+        .index
+        .values()
+        .filter(|item| item.name.as_deref() == Some(extern_name))
+        .find_map(|item| match item.inner {
+            rustdoc_types::ItemEnum::Function(ref f)
+                if matches!(f.header.abi, rustdoc_types::Abi::C { .. }) =>
+            {
+                Some(f)
+            }
+            _ => None,
+        })
+        .map(|f| {
+            f.sig
+                .inputs
+                .iter()
+                .filter(|(_, tpe)| is_by_value_type(tpe))
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// An `extern "C"` function of the API library
+struct ExternFunction {
+    name: String,
+    /// The C return type
+    output: String,
+    /// The name and the C declaration (including the name) of each argument
+    inputs: Vec<(String, String)>,
+}
+
+impl ExternFunction {
+    /// The declaration as written to `api_functions.hpp`
+    fn declaration(&self) -> String {
+        let args = self
+            .inputs
+            .iter()
+            .map(|(_, declaration)| declaration.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("extern \"C\" {} {}({args});", self.output, self.name)
+    }
+}
 
-    let args = func
+/// `by_value` maps arguments passed by value to their C++ type
+fn generate_extern_c_function_def(
+    name: &str,
+    func: &rustdoc_types::Function,
+    by_value: &HashMap<String, String>,
+) -> Result<ExternFunction, Error> {
+    let output = func
+        .sig
+        .output
+        .as_ref()
+        .map(to_c_type)
+        .transpose()?
+        .unwrap_or_else(|| "void".into());
+    let inputs = func
         .sig
         .inputs
         .iter()
         .map(|(name, tpe)| {
-            let mut out = to_c_type(tpe);
-            write!(out, " {name}").unwrap();
-            out
+            let declaration = match by_value.get(name) {
+                Some(tpe) => format!("{tpe} {name}"),
+                None => to_c_declaration(name, tpe)?,
+            };
+            Ok((name.clone(), declaration))
         })
-        .collect::<Vec<_>>()
-        .join(", ");
-    write!(out, "{name}({args});").unwrap();
-    out
+        .collect::<Result<_, Error>>()?;
+    Ok(ExternFunction {
+        name: name.to_owned(),
+        output,
+        inputs,
+    })
+}
+
+fn to_c_declaration(name: &str, tpe: &rustdoc_types::Type) -> Result<String, Error> {
+    // function pointers are nullable on the C side, so an `Option` around
+    // them does not change the declaration
+    let fn_ptr = match tpe {
+        rustdoc_types::Type::FunctionPointer(f) => Some(f),
+        rustdoc_types::Type::ResolvedPath(p) if get_name_without_path(&p.name) == "Option" => {
+            if let Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) = p.args.as_deref()
+            {
+                if let [rustdoc_types::GenericArg::Type(rustdoc_types::Type::FunctionPointer(f))] =
+                    &args[..]
+                {
+                    Some(f)
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+    if let Some(f) = fn_ptr {
+        let output = f
+            .sig
+            .output
+            .as_ref()
+            .map(to_c_type)
+            .transpose()?
+            .unwrap_or_else(|| "void".into());
+        let args = f
+            .sig
+            .inputs
+            .iter()
+            .map(|(_, tpe)| to_c_type(tpe))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(", ");
+        Ok(format!("{output} (*{name})({args})"))
+    } else {
+        Ok(format!("{} {name}", to_c_type(tpe)?))
+    }
 }
 
 fn get_name_without_path(name: &str) -> &str {
@@ -0,0 +1,228 @@
+//! Load the API library at runtime
+//!
+//! If [`Config::runtime_loader`](crate::Config::runtime_loader) is set, an additional
+//! `loader.hpp`/`loader.cpp` pair loads the API library via `dlopen` (`LoadLibrary` on Windows)
+//! and resolves all extern functions into a function table. `loader.cpp` defines the extern
+//! functions declared in `api_functions.hpp` by forwarding to this table, so the `*Holder`
+//! classes work unchanged when compiling `loader.cpp` instead of linking the API library.
+
+use crate::{create_file, finish_file, Error, ExternFunction, GeneratedFunctions};
+use crate::{write_file_comment, write_function_footer, write_function_header, Config};
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// The arguments of `function` without their types, to forward them to another function
+fn forwarded_arguments(function: &ExternFunction) -> String {
+    function
+        .inputs
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn argument_declarations(function: &ExternFunction) -> String {
+    function
+        .inputs
+        .iter()
+        .map(|(_, declaration)| declaration.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Generates `loader.hpp` and `loader.cpp` for the given extern functions
+pub(crate) fn generate_loader(
+    functions: &GeneratedFunctions,
+    out_dir: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let out_dir = PathBuf::from(out_dir);
+
+    let path = out_dir.join(format!("{file_prefix}_loader.hpp"));
+    let mut writer = create_file(&path)?;
+    write_function_header(&mut writer, config, &path, provenance)?;
+    writeln!(
+        writer,
+        "#include \"{file_prefix}_api_functions.hpp\"  // IWYU pragma: export\n"
+    )
+    .unwrap();
+    writeln!(writer, "namespace {namespace} {{\n").unwrap();
+    writeln!(
+        writer,
+        "// The functions of the API library, resolved by `load_library`"
+    )
+    .unwrap();
+    writeln!(writer, "struct FunctionTable {{").unwrap();
+    for function in &functions.extern_functions {
+        writeln!(
+            writer,
+            "    {} (*{})({}) = nullptr;",
+            function.output,
+            function.name,
+            argument_declarations(function)
+        )
+        .unwrap();
+    }
+    writeln!(writer, "}};\n").unwrap();
+    writeln!(
+        writer,
+        "// Loads the API library from `path` and resolves all functions, needs to be called\n\
+         // before any other function of the API. Throws `std::runtime_error` if the library\n\
+         // cannot be loaded or does not contain all functions"
+    )
+    .unwrap();
+    writeln!(writer, "void load_library(const char* path);\n").unwrap();
+    writeln!(
+        writer,
+        "// Unloads the API library, all objects created by it are invalid afterwards"
+    )
+    .unwrap();
+    writeln!(writer, "void unload_library();\n").unwrap();
+    writeln!(writer, "// Whether the API library is currently loaded").unwrap();
+    writeln!(writer, "bool is_library_loaded();\n").unwrap();
+    writeln!(
+        writer,
+        "// The resolved functions, all of them are `nullptr` if the library is not loaded"
+    )
+    .unwrap();
+    writeln!(writer, "const FunctionTable& function_table();\n").unwrap();
+    writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &path)?;
+
+    let path = out_dir.join(format!("{file_prefix}_loader.cpp"));
+    let mut writer = create_file(&path)?;
+    write_file_comment(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include \"{file_prefix}_loader.hpp\"\n").unwrap();
+    writeln!(writer, "#include <cstdio>").unwrap();
+    writeln!(writer, "#include <cstdlib>").unwrap();
+    writeln!(writer, "#include <stdexcept>").unwrap();
+    writeln!(writer, "#include <string>\n").unwrap();
+    writeln!(writer, "#ifdef _WIN32").unwrap();
+    writeln!(writer, "#include <windows.h>").unwrap();
+    writeln!(writer, "#else").unwrap();
+    writeln!(writer, "#include <dlfcn.h>").unwrap();
+    writeln!(writer, "#endif\n").unwrap();
+    writeln!(writer, "namespace {namespace} {{\n").unwrap();
+    writeln!(writer, "namespace {{\n").unwrap();
+    writeln!(writer, "#ifdef _WIN32").unwrap();
+    writeln!(writer, "HMODULE library = nullptr;").unwrap();
+    writeln!(writer, "#else").unwrap();
+    writeln!(writer, "void* library = nullptr;").unwrap();
+    writeln!(writer, "#endif").unwrap();
+    writeln!(writer, "FunctionTable table;\n").unwrap();
+    writeln!(writer, "template <typename F>").unwrap();
+    writeln!(writer, "void resolve(F& function, const char* name) {{").unwrap();
+    writeln!(writer, "#ifdef _WIN32").unwrap();
+    writeln!(
+        writer,
+        "    function = reinterpret_cast<F>(GetProcAddress(library, name));"
+    )
+    .unwrap();
+    writeln!(writer, "#else").unwrap();
+    writeln!(
+        writer,
+        "    function = reinterpret_cast<F>(dlsym(library, name));"
+    )
+    .unwrap();
+    writeln!(writer, "#endif").unwrap();
+    writeln!(writer, "    if (function == nullptr) {{").unwrap();
+    writeln!(
+        writer,
+        "        throw std::runtime_error(std::string(\"The API library does not contain `\") + name + \"`\");"
+    )
+    .unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(
+        writer,
+        "// the extern functions cannot throw, so calling them without a library is fatal"
+    )
+    .unwrap();
+    writeln!(writer, "[[noreturn]] void not_loaded(const char* name) {{").unwrap();
+    writeln!(
+        writer,
+        "    std::fprintf(stderr, \"Called `%s` before loading the API library\\n\", name);"
+    )
+    .unwrap();
+    writeln!(writer, "    std::abort();").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "}}  // end of anonymous namespace\n").unwrap();
+    writeln!(writer, "void load_library(const char* path) {{").unwrap();
+    writeln!(writer, "    if (library != nullptr) {{").unwrap();
+    writeln!(
+        writer,
+        "        throw std::logic_error(\"The API library is already loaded\");"
+    )
+    .unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "#ifdef _WIN32").unwrap();
+    writeln!(writer, "    library = LoadLibraryA(path);").unwrap();
+    writeln!(writer, "#else").unwrap();
+    writeln!(writer, "    library = dlopen(path, RTLD_NOW | RTLD_LOCAL);").unwrap();
+    writeln!(writer, "#endif").unwrap();
+    writeln!(writer, "    if (library == nullptr) {{").unwrap();
+    writeln!(
+        writer,
+        "        throw std::runtime_error(std::string(\"Could not load the API library `\") + path + \"`\");"
+    )
+    .unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "    try {{").unwrap();
+    for function in &functions.extern_functions {
+        writeln!(
+            writer,
+            "        resolve(table.{0}, \"{0}\");",
+            function.name
+        )
+        .unwrap();
+    }
+    writeln!(writer, "    }} catch (...) {{").unwrap();
+    writeln!(writer, "        unload_library();").unwrap();
+    writeln!(writer, "        throw;").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "void unload_library() {{").unwrap();
+    writeln!(writer, "    if (library != nullptr) {{").unwrap();
+    writeln!(writer, "#ifdef _WIN32").unwrap();
+    writeln!(writer, "        FreeLibrary(library);").unwrap();
+    writeln!(writer, "#else").unwrap();
+    writeln!(writer, "        dlclose(library);").unwrap();
+    writeln!(writer, "#endif").unwrap();
+    writeln!(writer, "        library = nullptr;").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "    table = FunctionTable();").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "bool is_library_loaded() {{").unwrap();
+    writeln!(writer, "    return library != nullptr;").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "const FunctionTable& function_table() {{").unwrap();
+    writeln!(writer, "    return table;").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+    for function in &functions.extern_functions {
+        let name = &function.name;
+        writeln!(writer).unwrap();
+        writeln!(
+            writer,
+            "{} {{",
+            function.declaration().trim_end_matches(';')
+        )
+        .unwrap();
+        writeln!(writer, "    if ({namespace}::table.{name} == nullptr) {{").unwrap();
+        writeln!(writer, "        {namespace}::not_loaded(\"{name}\");").unwrap();
+        writeln!(writer, "    }}").unwrap();
+        writeln!(
+            writer,
+            "    return {namespace}::table.{name}({});",
+            forwarded_arguments(function)
+        )
+        .unwrap();
+        writeln!(writer, "}}").unwrap();
+    }
+    finish_file(writer, &path)?;
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Forward Rust log messages to the C++ side
+//!
+//! The C++ side registers a callback via the `buffi_set_log_callback` extern function
+//! (generated by [`export_log_callback!`](crate::export_log_callback)) and receives every
+//! `tracing` event up to the configured level. The Rust side needs to install the
+//! subscriber provided here via [`install`] or add [`layer`] to its own subscriber.
+
+use std::ffi::{c_char, CString};
+use std::fmt::Write as _;
+use std::sync::RwLock;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::Layer;
+
+/// The signature of the callback that receives log messages
+///
+/// The arguments are the log level (see [`set_log_callback`]), the target
+/// (usually the module path) and the formatted message. Both strings are only
+/// valid for the duration of the call.
+pub type LogCallback = extern "C" fn(level: u8, target: *const c_char, message: *const c_char);
+
+static LOG_CALLBACK: RwLock<Option<(LogCallback, u8)>> = RwLock::new(None);
+
+/// Register the callback that receives log messages
+///
+/// `max_level` is the most verbose level that is forwarded: 0 = off, 1 = error,
+/// 2 = warn, 3 = info, 4 = debug, 5 = trace. Passing no callback disables forwarding.
+pub fn set_log_callback(callback: Option<LogCallback>, max_level: u8) {
+    let mut log_callback = LOG_CALLBACK.write().unwrap_or_else(|e| e.into_inner());
+    *log_callback = callback.map(|c| (c, max_level));
+}
+
+/// Install a global `tracing` subscriber that only forwards events to the registered callback
+///
+/// Fails if there is already a global subscriber, use [`layer`] to extend that one instead.
+pub fn install() -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer()))
+}
+
+/// A `tracing_subscriber` layer that forwards events to the registered callback
+pub fn layer() -> CallbackLayer {
+    CallbackLayer { _priv: () }
+}
+
+/// See [`layer`]
+pub struct CallbackLayer {
+    _priv: (),
+}
+
+impl<S: Subscriber> Layer<S> for CallbackLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let log_callback = LOG_CALLBACK.read().unwrap_or_else(|e| e.into_inner());
+        let Some((callback, max_level)) = *log_callback else {
+            return;
+        };
+        let metadata = event.metadata();
+        let level = level_to_u8(metadata.level());
+        if level > max_level {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        // interior nul bytes cannot be represented in a C string
+        let target = CString::new(metadata.target().replace('\0', "")).unwrap_or_default();
+        let message = visitor.message + &visitor.fields;
+        let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+        callback(level, target.as_ptr(), message.as_ptr());
+    }
+}
+
+fn level_to_u8(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        Level::TRACE => 5,
+    }
+}
+
+/// Formats the message of an event followed by all other fields as `name=value`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            write!(self.message, "{value:?}").unwrap();
+        } else {
+            write!(self.fields, " {}={value:?}", field.name()).unwrap();
+        }
+    }
+}
+
+/// Generates the `buffi_set_log_callback` extern function
+///
+/// This macro needs to be called in your API crate, so that the function is picked up
+/// by the code generation. Pass `prefix = "myapi"` for a custom function prefix.
+#[macro_export]
+macro_rules! export_log_callback {
+    () => {
+        $crate::export_log_callback!(prefix = "buffi");
+    };
+    (prefix = $prefix:literal) => {
+        $crate::__prefixed! {
+            prefix = $prefix;
+
+            /// Register a callback that receives the log messages of the Rust side
+            ///
+            /// * `callback`: Called with the log level, the target and the message of each log
+            ///   event, pass a null pointer to disable logging
+            /// * `max_level`: The most verbose level that is forwarded: 0 = off, 1 = error,
+            ///   2 = warn, 3 = info, 4 = debug, 5 = trace
+            #[no_mangle]
+            pub extern "C" fn set_log_callback(
+                callback: Option<
+                    extern "C" fn(
+                        level: u8,
+                        target: *const std::ffi::c_char,
+                        message: *const std::ffi::c_char,
+                    ),
+                >,
+                max_level: u8,
+            ) {
+                $crate::log::set_log_callback(callback, max_level)
+            }
+        }
+    };
+}
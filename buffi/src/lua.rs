@@ -0,0 +1,492 @@
+//! LuaJIT FFI bindings for the extern functions
+//!
+//! If [`Config::lua_ffi`](crate::Config::lua_ffi) is set, an additional `ffi.lua` module declares
+//! all extern functions via `ffi.cdef` and contains bincode encoders and decoders for every type
+//! of the API. This allows scripting layers (game engines, test harnesses) to drive the API
+//! without going through the generated C++ code:
+//!
+//! ```lua
+//! local api = require("buffi_example_ffi")
+//! local lib = api.load()
+//! local client = lib.get_test_client()
+//! local input, input_size = api.encode(api.codec.str, "hello")
+//! local result = api.call(
+//!     lib.buffi_client_function,
+//!     api.types.Result_String_SerializableError,
+//!     client, input, input_size
+//! )
+//! assert(result.tag == "Ok")
+//! ```
+//!
+//! Enums are represented as `{ tag = "Variant", value = ... }` tables, 128 bit integers and
+//! nested options are not supported.
+
+use crate::summary::SkippedItem;
+use crate::{create_file, finish_file, Config, Error, GeneratedFunctions};
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// The encoders/decoders shared by all generated modules, `{function_prefix}` is replaced
+const RUNTIME: &str = r##"local M = { types = {} }
+
+local Encoder = {}
+Encoder.__index = Encoder
+
+function Encoder:bytes(s)
+    self.parts[#self.parts + 1] = s
+end
+
+local Decoder = {}
+Decoder.__index = Decoder
+
+-- Returns a pointer to the next `n` bytes and skips them
+function Decoder:take(n)
+    if self.pos + n > self.size then
+        error("Unexpected end of the serialized data", 0)
+    end
+    local ptr = self.ptr + self.pos
+    self.pos = self.pos + n
+    return ptr
+end
+
+local function scalar(ctype)
+    local size = ffi.sizeof(ctype)
+    local value = ffi.new(ctype .. "[1]")
+    return {
+        encode = function(enc, v)
+            value[0] = v
+            enc:bytes(ffi.string(value, size))
+        end,
+        decode = function(dec)
+            ffi.copy(value, dec:take(size), size)
+            return value[0]
+        end,
+    }
+end
+
+-- Encoders and decoders for all types, as used by `M.types`
+local codec = {
+    i8 = scalar("int8_t"),
+    i16 = scalar("int16_t"),
+    i32 = scalar("int32_t"),
+    i64 = scalar("int64_t"),
+    u8 = scalar("uint8_t"),
+    u16 = scalar("uint16_t"),
+    u32 = scalar("uint32_t"),
+    u64 = scalar("uint64_t"),
+    f32 = scalar("float"),
+    f64 = scalar("double"),
+}
+M.codec = codec
+
+codec.unit = {
+    encode = function() end,
+    decode = function() return nil end,
+}
+
+codec.bool = {
+    encode = function(enc, v)
+        codec.u8.encode(enc, v and 1 or 0)
+    end,
+    decode = function(dec)
+        local v = codec.u8.decode(dec)
+        if v > 1 then
+            error("Invalid bool " .. v, 0)
+        end
+        return v == 1
+    end,
+}
+
+-- A single UTF-8 encoded character as string
+codec.char = {
+    encode = function(enc, v)
+        enc:bytes(v)
+    end,
+    decode = function(dec)
+        local first = dec:take(1)[0]
+        dec.pos = dec.pos - 1
+        local len = (first < 0x80 and 1) or (first < 0xE0 and 2) or (first < 0xF0 and 3) or 4
+        return ffi.string(dec:take(len), len)
+    end,
+}
+
+codec.str = {
+    encode = function(enc, v)
+        codec.u64.encode(enc, #v)
+        enc:bytes(v)
+    end,
+    decode = function(dec)
+        local len = tonumber(codec.u64.decode(dec))
+        return ffi.string(dec:take(len), len)
+    end,
+}
+
+-- `nil` is encoded as `None`, so options cannot be nested
+function codec.option(inner)
+    return {
+        encode = function(enc, v)
+            codec.bool.encode(enc, v ~= nil)
+            if v ~= nil then
+                inner.encode(enc, v)
+            end
+        end,
+        decode = function(dec)
+            if codec.bool.decode(dec) then
+                return inner.decode(dec)
+            end
+            return nil
+        end,
+    }
+end
+
+function codec.seq(inner)
+    return {
+        encode = function(enc, v)
+            codec.u64.encode(enc, #v)
+            for i = 1, #v do
+                inner.encode(enc, v[i])
+            end
+        end,
+        decode = function(dec)
+            local v = {}
+            for i = 1, tonumber(codec.u64.decode(dec)) do
+                v[i] = inner.decode(dec)
+            end
+            return v
+        end,
+    }
+end
+
+function codec.array(inner, size)
+    return {
+        encode = function(enc, v)
+            for i = 1, size do
+                inner.encode(enc, v[i])
+            end
+        end,
+        decode = function(dec)
+            local v = {}
+            for i = 1, size do
+                v[i] = inner.decode(dec)
+            end
+            return v
+        end,
+    }
+end
+
+function codec.tuple(elements)
+    return {
+        encode = function(enc, v)
+            for i, element in ipairs(elements) do
+                element.encode(enc, v[i])
+            end
+        end,
+        decode = function(dec)
+            local v = {}
+            for i, element in ipairs(elements) do
+                v[i] = element.decode(dec)
+            end
+            return v
+        end,
+    }
+end
+
+function codec.map(key, value)
+    return {
+        encode = function(enc, v)
+            local len = 0
+            for _ in pairs(v) do
+                len = len + 1
+            end
+            codec.u64.encode(enc, len)
+            for k, e in pairs(v) do
+                key.encode(enc, k)
+                value.encode(enc, e)
+            end
+        end,
+        decode = function(dec)
+            local v = {}
+            for _ = 1, tonumber(codec.u64.decode(dec)) do
+                local k = key.decode(dec)
+                v[k] = value.decode(dec)
+            end
+            return v
+        end,
+    }
+end
+
+-- `fields` is a list of `{ name, codec }` pairs in declaration order
+function codec.struct(fields)
+    return {
+        encode = function(enc, v)
+            for _, field in ipairs(fields) do
+                field[2].encode(enc, v[field[1]])
+            end
+        end,
+        decode = function(dec)
+            local v = {}
+            for _, field in ipairs(fields) do
+                v[field[1]] = field[2].decode(dec)
+            end
+            return v
+        end,
+    }
+end
+
+-- `variants` is a list of `{ name, codec }` pairs in declaration order,
+-- values are represented as `{ tag = name, value = ... }`
+function codec.enum(variants)
+    local indices = {}
+    for i, variant in ipairs(variants) do
+        indices[variant[1]] = i
+    end
+    return {
+        encode = function(enc, v)
+            local i = indices[v.tag]
+            if i == nil then
+                error("Unknown variant " .. tostring(v.tag), 0)
+            end
+            codec.u32.encode(enc, i - 1)
+            variants[i][2].encode(enc, v.value)
+        end,
+        decode = function(dec)
+            local i = codec.u32.decode(dec) + 1
+            local variant = variants[i]
+            if variant == nil then
+                error("Unknown variant index " .. (i - 1), 0)
+            end
+            return { tag = variant[1], value = variant[2].decode(dec) }
+        end,
+    }
+end
+
+-- Refers to `M.types[name]`, so types can be recursive
+function codec.named(name)
+    return {
+        encode = function(enc, v)
+            M.types[name].encode(enc, v)
+        end,
+        decode = function(dec)
+            return M.types[name].decode(dec)
+        end,
+    }
+end
+
+function codec.unsupported(name)
+    local function fail()
+        error(name .. " cannot be represented in Lua", 0)
+    end
+    return { encode = fail, decode = fail }
+end
+
+-- Serializes `v` via `c`, returns a buffer and its size to pass to an extern function
+--
+-- The buffer needs to be kept alive until the function returns
+function M.encode(c, v)
+    local enc = setmetatable({ parts = {} }, Encoder)
+    c.encode(enc, v)
+    local s = table.concat(enc.parts)
+    local buffer = ffi.new("uint8_t[?]", #s)
+    ffi.copy(buffer, s, #s)
+    return buffer, #s
+end
+
+-- Deserializes the `size` bytes at `ptr` via `c`
+function M.decode(c, ptr, size)
+    local dec = setmetatable({ ptr = ffi.cast("const uint8_t*", ptr), size = tonumber(size), pos = 0 }, Decoder)
+    local v = c.decode(dec)
+    if dec.pos ~= dec.size then
+        error("Serialized data contains trailing bytes", 0)
+    end
+    return v
+end
+
+-- Deserializes a result buffer via `c` and frees it afterwards
+function M.take_result(c, ptr, size)
+    local ok, v = pcall(M.decode, c, ptr, size)
+    M.lib.{function_prefix}_free_byte_buffer(ptr, size)
+    if not ok then
+        error(v, 2)
+    end
+    return v
+end
+
+-- Calls `fn` with the given arguments and an additional `out_ptr`, the result is
+-- deserialized via `c`
+function M.call(fn, c, ...)
+    local out_ptr = ffi.new("uint8_t*[1]")
+    local n = select("#", ...)
+    local args = { ... }
+    args[n + 1] = out_ptr
+    local size = fn(unpack(args, 1, n + 1))
+    return M.take_result(c, out_ptr[0], size)
+end
+"##;
+
+/// The Lua expression of the codec for the given type
+fn lua_codec(format: &Format) -> String {
+    match format {
+        Format::Variable(_) => unreachable!("Registry is complete"),
+        Format::TypeName(name) => format!("codec.named(\"{name}\")"),
+        Format::Unit => String::from("codec.unit"),
+        Format::Bool => String::from("codec.bool"),
+        Format::I8 => String::from("codec.i8"),
+        Format::I16 => String::from("codec.i16"),
+        Format::I32 => String::from("codec.i32"),
+        Format::I64 => String::from("codec.i64"),
+        Format::U8 => String::from("codec.u8"),
+        Format::U16 => String::from("codec.u16"),
+        Format::U32 => String::from("codec.u32"),
+        Format::U64 => String::from("codec.u64"),
+        Format::I128 => String::from("codec.unsupported(\"i128\")"),
+        Format::U128 => String::from("codec.unsupported(\"u128\")"),
+        Format::F32 => String::from("codec.f32"),
+        Format::F64 => String::from("codec.f64"),
+        Format::Char => String::from("codec.char"),
+        // bincode encodes bytes like strings
+        Format::Str | Format::Bytes => String::from("codec.str"),
+        Format::Option(t) => format!("codec.option({})", lua_codec(t)),
+        Format::Seq(t) => format!("codec.seq({})", lua_codec(t)),
+        Format::Map { key, value } => {
+            format!("codec.map({}, {})", lua_codec(key), lua_codec(value))
+        }
+        Format::Tuple(elements) => tuple_codec(elements),
+        Format::TupleArray { content, size } => {
+            format!("codec.array({}, {size})", lua_codec(content))
+        }
+    }
+}
+
+fn tuple_codec(elements: &[Format]) -> String {
+    let elements = elements.iter().map(lua_codec).collect::<Vec<_>>();
+    format!("codec.tuple({{ {} }})", elements.join(", "))
+}
+
+fn struct_codec(fields: &[Named<Format>], indent: &str) -> String {
+    let mut out = String::from("codec.struct({\n");
+    for field in fields {
+        out += &format!(
+            "{indent}    {{ \"{}\", {} }},\n",
+            field.name,
+            lua_codec(&field.value)
+        );
+    }
+    out + indent + "})"
+}
+
+fn container_codec(format: &ContainerFormat) -> String {
+    match format {
+        ContainerFormat::UnitStruct => String::from("codec.unit"),
+        // bincode does not add anything for newtypes
+        ContainerFormat::NewTypeStruct(t) => lua_codec(t),
+        ContainerFormat::TupleStruct(elements) => tuple_codec(elements),
+        ContainerFormat::Struct(fields) => struct_codec(fields, ""),
+        ContainerFormat::Enum(variants) => {
+            let mut out = String::from("codec.enum({\n");
+            for variant in variants.values() {
+                let codec = match &variant.value {
+                    VariantFormat::Variable(_) => unreachable!("Registry is complete"),
+                    VariantFormat::Unit => String::from("codec.unit"),
+                    VariantFormat::NewType(t) => lua_codec(t),
+                    VariantFormat::Tuple(elements) => tuple_codec(elements),
+                    VariantFormat::Struct(fields) => struct_codec(fields, "    "),
+                };
+                out += &format!("    {{ \"{}\", {codec} }},\n", variant.name);
+            }
+            out + "})"
+        }
+    }
+}
+
+/// Generates `ffi.lua` for the given extern functions and types
+pub(crate) fn generate_lua_module(
+    registry: &Registry,
+    functions: &GeneratedFunctions,
+    out_dir: &str,
+    function_prefix: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<Vec<SkippedItem>, Error> {
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_ffi.lua"));
+
+    // the declarations use the `<cstdint>` types, LuaJIT knows them without the namespace
+    let mut declarations = Vec::new();
+    let mut skipped = Vec::new();
+    for function in &functions.extern_functions {
+        let declaration = function
+            .declaration()
+            .trim_start_matches("extern \"C\" ")
+            .replace("std::", "");
+        if declaration.contains("::") {
+            // arguments passed by value are C++ types, which LuaJIT does not know
+            skipped.push((function.name.clone(), declaration));
+        } else {
+            declarations.push(declaration);
+        }
+    }
+
+    let mut writer = create_file(&path)?;
+    crate::write_foreign_file_comment(&mut writer, config, &path, provenance, |text| {
+        format!("--{text}")
+    })?;
+    writeln!(writer, "local ffi = require(\"ffi\")\n").unwrap();
+    writeln!(
+        writer,
+        "assert(ffi.abi(\"le\"), \"bincode data is encoded as little endian\")\n"
+    )
+    .unwrap();
+    if !skipped.is_empty() {
+        writeln!(
+            writer,
+            "-- The following functions are not declared, as their arguments are passed by value:"
+        )
+        .unwrap();
+        for (_, declaration) in &skipped {
+            writeln!(writer, "-- * `{declaration}`").unwrap();
+        }
+        writeln!(writer).unwrap();
+    }
+    writeln!(writer, "ffi.cdef[[").unwrap();
+    for name in &functions.opaque_types {
+        writeln!(writer, "typedef struct {name} {name};").unwrap();
+    }
+    for declaration in &declarations {
+        writeln!(writer, "{declaration}").unwrap();
+    }
+    writeln!(writer, "]]\n").unwrap();
+    writeln!(
+        writer,
+        "{}",
+        RUNTIME.replace("{function_prefix}", function_prefix)
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "-- Loads the API library, which is required by `M.call`"
+    )
+    .unwrap();
+    writeln!(writer, "function M.load(path)").unwrap();
+    writeln!(
+        writer,
+        "    M.lib = ffi.load(path or \"{}\")",
+        config.api_lib_name
+    )
+    .unwrap();
+    writeln!(writer, "    return M.lib").unwrap();
+    writeln!(writer, "end\n").unwrap();
+    for (name, format) in registry {
+        writeln!(writer, "M.types.{name} = {}\n", container_codec(format)).unwrap();
+    }
+    writeln!(writer, "return M").unwrap();
+    finish_file(writer, &path)?;
+
+    Ok(skipped
+        .into_iter()
+        .map(|(name, _)| SkippedItem {
+            name,
+            reason: String::from("LuaJIT cannot declare arguments passed by value"),
+        })
+        .collect())
+}
@@ -0,0 +1,371 @@
+//! Generate bindings without the rustdoc JSON output
+//!
+//! [`generate_from_registry`](crate::generate_from_registry) takes the types as a
+//! `serde_reflection::Registry` (built by hand or traced via `serde_reflection::Tracer`) and the
+//! exported functions as a [`FunctionManifest`]. This covers types the rustdoc based pipeline
+//! cannot express, the generated files are the same as for
+//! [`generate_bindings`](crate::generate_bindings).
+//!
+//! Only functions using the default calling convention of `#[buffi_macro::exported]` are
+//! supported, so no `completion_callback`, `direct_return`, `chunked`, `raw_bytes` or arguments
+//! passed by value.
+
+use crate::docs::to_cpp_doc;
+use crate::embind::{FunctionSignature, HolderClass, WrapperFunctions};
+use crate::{
+    create_file, finish_file, to_cpp_type_name, write_api_functions_prelude, write_function_footer,
+    write_function_header, write_holder_class_start, write_null_check, write_serialized_arguments,
+    write_serialized_call, write_wrapper_includes, Config, Error, ExternFunction, FunctionOrder,
+    GeneratedFunctions, MappedFunction,
+};
+use serde::{Deserialize, Serialize};
+use serde_reflection::{ContainerFormat, Format, Registry, VariantFormat};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write as _};
+use std::path::PathBuf;
+
+/// The functions exported by the API library
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FunctionManifest {
+    pub functions: Vec<ManifestFunction>,
+}
+
+/// A function exported via `#[buffi_macro::exported]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFunction {
+    /// The name of the Rust function, the extern function is called `buffi_{name}`
+    pub name: String,
+    /// The type of `self`, `None` for free-standing functions
+    pub owner: Option<String>,
+    /// The arguments without `self`
+    pub inputs: Vec<(String, Format)>,
+    /// The name of the returned `Result` in the registry, an enum with the variants `Ok` and
+    /// `Err` that contain a single value each (as registered by the rustdoc based pipeline)
+    pub result_type: String,
+    /// The documentation of the function, written to the generated C++ function
+    pub docs: Option<String>,
+    /// The name of the C++ function if it is an overload (see `#[buffi(overload = "...")]`)
+    #[serde(default)]
+    pub overload: Option<String>,
+}
+
+/// The type contained in the `Ok` variant of `result_type`
+fn ok_format(registry: &Registry, result_type: &str) -> Result<Format, Error> {
+    let Some(ContainerFormat::Enum(variants)) = registry.get(result_type) else {
+        return Err(Error::unsupported(format!(
+            "`{result_type}` needs to be an enum contained in the registry"
+        )));
+    };
+    match variants.get(&0).map(|v| &v.value) {
+        Some(VariantFormat::Tuple(values)) if values.len() == 1 => match &values[0] {
+            Format::Unit => Ok(Format::Tuple(Vec::new())),
+            format => Ok(format.clone()),
+        },
+        _ => Err(Error::unsupported(format!(
+            "The first variant of `{result_type}` needs to be `Ok` with a single value"
+        ))),
+    }
+}
+
+fn extern_function(function: &ManifestFunction, function_prefix: &str) -> ExternFunction {
+    let mut inputs = Vec::new();
+    if let Some(owner) = &function.owner {
+        inputs.push((String::from("this_ptr"), format!("{owner}* this_ptr")));
+    }
+    for (name, _) in &function.inputs {
+        inputs.push((name.clone(), format!("const std::uint8_t* {name}")));
+        inputs.push((format!("{name}_size"), format!("size_t {name}_size")));
+    }
+    inputs.push((
+        String::from("out_ptr"),
+        String::from("std::uint8_t** out_ptr"),
+    ));
+    ExternFunction {
+        name: format!("{function_prefix}_{}", function.name),
+        output: String::from("size_t"),
+        inputs,
+    }
+}
+
+/// The entry of `symbol_map.json` for `function`, there is no source location to report
+fn mapped_function(
+    function: &ManifestFunction,
+    signature: &FunctionSignature,
+    function_prefix: &str,
+    namespace: &str,
+) -> MappedFunction {
+    let (cpp_function, rust_function) = match &function.owner {
+        Some(owner) => (
+            format!("{namespace}::{owner}Holder::{}", signature.cpp_name),
+            format!("{owner}::{}", function.name),
+        ),
+        None => (
+            format!("{namespace}::{}", signature.cpp_name),
+            function.name.clone(),
+        ),
+    };
+    MappedFunction {
+        cpp_function,
+        symbol: format!("{function_prefix}_{}", function.name),
+        rust_function,
+        file: None,
+        line: None,
+    }
+}
+
+/// Writes the C++ wrapper of `function` and returns its signature
+fn write_function_def(
+    registry: &Registry,
+    function: &ManifestFunction,
+    out_functions: &mut BufWriter<File>,
+    prefix: &str,
+) -> Result<FunctionSignature, Error> {
+    let output = ok_format(registry, &function.result_type)?;
+    let return_output_type = to_cpp_type_name(&output)?;
+    let self_name = String::from("self");
+    let cpp_name = function.overload.as_ref().unwrap_or(&function.name);
+    let inputs = function
+        .owner
+        .iter()
+        .map(|owner| Ok((&self_name, owner.clone())))
+        .chain(
+            function
+                .inputs
+                .iter()
+                .map(|(name, format)| Ok((name, to_cpp_type_name(format)?))),
+        )
+        .collect::<Result<Vec<_>, Error>>()?;
+    if let Some(ref docs) = function.docs {
+        for line in to_cpp_doc(docs).lines() {
+            writeln!(out_functions, "{}", format!("    // {line}").trim_end()).unwrap()
+        }
+    }
+    write!(out_functions, "    inline {return_output_type} {cpp_name}(").unwrap();
+    for (idx, (name, tpe)) in inputs.iter().filter(|(n, _)| *n != "self").enumerate() {
+        if idx != 0 {
+            write!(out_functions, ", ").unwrap();
+        }
+        write!(out_functions, "const {tpe}& {name}").unwrap();
+    }
+    writeln!(out_functions, ") {{").unwrap();
+    write_null_check(out_functions, &inputs, cpp_name);
+    let by_value = HashSet::new();
+    write_serialized_arguments(out_functions, &inputs, &by_value);
+    write_serialized_call(
+        out_functions,
+        prefix,
+        &function.name,
+        "this->inner",
+        &inputs,
+        &by_value,
+        &function.result_type,
+        &return_output_type,
+        true,
+    );
+    writeln!(out_functions, "    }}\n").unwrap();
+    Ok(FunctionSignature {
+        name: function.name.clone(),
+        cpp_name: cpp_name.clone(),
+        inputs: function.inputs.clone(),
+        output,
+    })
+}
+
+/// Generates `api_functions.hpp`, the `*Holder` classes and the free-standing functions for
+/// the functions of `manifest`
+pub(crate) fn generate_function_definitions(
+    registry: &Registry,
+    manifest: &FunctionManifest,
+    out_dir: &str,
+    function_prefix: &str,
+    config: &Config,
+) -> Result<GeneratedFunctions, Error> {
+    let namespace = &config.namespace;
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let out_dir = PathBuf::from(out_dir);
+
+    let mut owners = BTreeMap::<&str, Vec<&ManifestFunction>>::new();
+    let mut free_standing_functions = Vec::new();
+    for function in &manifest.functions {
+        match &function.owner {
+            Some(owner) => owners.entry(owner.as_str()).or_default().push(function),
+            None => free_standing_functions.push(function),
+        }
+    }
+    // the manifest has no source locations, so its order is the declaration order
+    let function_order = config
+        .function_order
+        .as_ref()
+        .unwrap_or(&FunctionOrder::Alphabetical);
+    let sorted = |functions: &mut Vec<&ManifestFunction>, owner: Option<&str>| {
+        if *function_order != FunctionOrder::Declaration {
+            functions.sort_by(|a, b| a.name.cmp(&b.name));
+            function_order.reorder(functions, owner, |f| f.name.as_str(), |_| None);
+        }
+    };
+    for (owner, functions) in owners.iter_mut() {
+        sorted(functions, Some(*owner));
+    }
+    sorted(&mut free_standing_functions, None);
+
+    let mut extern_functions = manifest
+        .functions
+        .iter()
+        .map(|f| extern_function(f, function_prefix))
+        .collect::<Vec<_>>();
+    extern_functions.push(ExternFunction {
+        name: format!("{function_prefix}_free_byte_buffer"),
+        output: String::from("void"),
+        inputs: vec![
+            (String::from("ptr"), String::from("std::uint8_t* ptr")),
+            (String::from("size"), String::from("size_t size")),
+        ],
+    });
+    // ensure that we always emit these functions in the same order
+    extern_functions.sort_by_key(|f| f.declaration());
+
+    let extern_c_header_path = out_dir.join(format!("{file_prefix}_api_functions.hpp"));
+    let mut extern_c_header = create_file(&extern_c_header_path)?;
+    write_function_header(&mut extern_c_header, config, &extern_c_header_path, None)?;
+    write_api_functions_prelude(&mut extern_c_header, config, false)?;
+    for owner in owners.keys() {
+        writeln!(extern_c_header, "struct {owner};\n").unwrap();
+    }
+    for (idx, function) in extern_functions.iter().enumerate() {
+        if idx != 0 {
+            writeln!(extern_c_header).unwrap();
+        }
+        writeln!(extern_c_header, "{}", function.declaration()).unwrap();
+    }
+    write_function_footer(&mut extern_c_header, config)?;
+    finish_file(extern_c_header, &extern_c_header_path)?;
+
+    let mut wrapper_functions = WrapperFunctions::default();
+    let mut mapped_functions = Vec::new();
+    for (name, functions) in &owners {
+        let mut holder_class = HolderClass {
+            name: (*name).to_owned(),
+            factories: Vec::new(),
+            has_constructor: false,
+            functions: Vec::new(),
+        };
+        let type_header = out_dir.join(format!("{file_prefix}_{}.hpp", name.to_ascii_lowercase()));
+        let mut writer = create_file(&type_header)?;
+        write_function_header(&mut writer, config, &type_header, None)?;
+        write_wrapper_includes(&mut writer, false, file_prefix, namespace);
+        write_holder_class_start(&mut writer, name, namespace, function_prefix, None);
+        for function in functions {
+            let signature = write_function_def(registry, function, &mut writer, function_prefix)
+                .map_err(|e| e.in_function(&format!("{name}::{}", function.name)))?;
+            mapped_functions.push(mapped_function(
+                function,
+                &signature,
+                function_prefix,
+                namespace,
+            ));
+            holder_class.functions.push(signature);
+        }
+        wrapper_functions.classes.push(holder_class);
+        writeln!(writer, "}};\n").unwrap();
+        writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+        write_function_footer(&mut writer, config)?;
+        finish_file(writer, &type_header)?;
+    }
+
+    let free_standing_function_header_path =
+        out_dir.join(format!("{file_prefix}_free_standing_functions.hpp"));
+    let mut writer = create_file(&free_standing_function_header_path)?;
+    write_function_header(
+        &mut writer,
+        config,
+        &free_standing_function_header_path,
+        None,
+    )?;
+    write_wrapper_includes(&mut writer, false, file_prefix, namespace);
+    writeln!(writer).unwrap();
+    writeln!(writer, "namespace {namespace} {{").unwrap();
+    writeln!(writer).unwrap();
+    for function in free_standing_functions {
+        let signature = write_function_def(registry, function, &mut writer, function_prefix)
+            .map_err(|e| e.in_function(&function.name))?;
+        mapped_functions.push(mapped_function(
+            function,
+            &signature,
+            function_prefix,
+            namespace,
+        ));
+        wrapper_functions.free_standing.push(signature);
+        writeln!(writer).unwrap();
+    }
+    writeln!(writer, "}}  // end of namespace {namespace}").unwrap();
+    write_function_footer(&mut writer, config)?;
+    finish_file(writer, &free_standing_function_header_path)?;
+
+    Ok(GeneratedFunctions {
+        extern_functions,
+        opaque_types: owners.keys().map(|o| (*o).to_owned()).collect(),
+        wrappers: wrapper_functions,
+        mapped_functions,
+        skipped: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> Registry {
+        let mut variants = BTreeMap::new();
+        variants.insert(
+            0,
+            serde_reflection::Named {
+                name: String::from("Ok"),
+                value: VariantFormat::Tuple(vec![Format::Str]),
+            },
+        );
+        variants.insert(
+            1,
+            serde_reflection::Named {
+                name: String::from("Err"),
+                value: VariantFormat::Tuple(vec![Format::Str]),
+            },
+        );
+        let mut registry = Registry::new();
+        registry.insert(
+            String::from("Result_String_String"),
+            ContainerFormat::Enum(variants),
+        );
+        registry
+    }
+
+    #[test]
+    fn ok_format_is_read_from_the_registry() {
+        assert_eq!(
+            ok_format(&registry(), "Result_String_String").unwrap(),
+            Format::Str
+        );
+    }
+
+    #[test]
+    fn missing_result_types_are_reported() {
+        let error = ok_format(&registry(), "Result_i64_String").unwrap_err();
+        assert!(error.to_string().contains("contained in the registry"));
+    }
+
+    #[test]
+    fn extern_functions_use_the_default_calling_convention() {
+        let function = ManifestFunction {
+            name: String::from("get_name"),
+            owner: Some(String::from("Client")),
+            inputs: vec![(String::from("id"), Format::I64)],
+            result_type: String::from("Result_String_String"),
+            docs: None,
+            overload: None,
+        };
+        assert_eq!(
+            extern_function(&function, "buffi").declaration(),
+            "extern \"C\" size_t buffi_get_name(Client* this_ptr, const std::uint8_t* id, size_t id_size, std::uint8_t** out_ptr);"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Detect C++ overloads that can not be distinguished
+//!
+//! Functions marked with `#[buffi(overload = "...")]` share the name of their C++ wrapper, so
+//! their arguments need to differ. Different Rust types can map to the same C++ type though
+//! (e.g. `String` and `&str` both become `std::string`), which would only be reported by the
+//! C++ compiler. The wrappers of each class (and the free-standing functions) are collected
+//! here instead, so that such overloads are reported during the generation.
+
+use crate::Error;
+use std::collections::HashMap;
+
+/// The C++ wrappers generated for one class or the free-standing functions
+#[derive(Default)]
+pub(crate) struct Overloads {
+    /// The Rust function for each C++ name and its argument types
+    wrappers: HashMap<(String, Vec<String>), String>,
+}
+
+impl Overloads {
+    /// Adds the wrapper `cpp_name` of the Rust function `name` taking arguments of the C++
+    /// `types`, fails if another function already has the same C++ signature
+    pub(crate) fn insert(
+        &mut self,
+        name: &str,
+        cpp_name: &str,
+        types: impl IntoIterator<Item = String>,
+    ) -> Result<(), Error> {
+        let types = types.into_iter().collect::<Vec<_>>();
+        let key = (cpp_name.to_owned(), types);
+        if let Some(other) = self.wrappers.get(&key) {
+            return Err(Error::unsupported(format!(
+                "`{other}` and `{name}` are both exported as `{cpp_name}({})`, C++ can not \
+                 distinguish these overloads",
+                key.1.join(", ")
+            )));
+        }
+        self.wrappers.insert(key, name.to_owned());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Overloads;
+    use crate::Error;
+
+    #[test]
+    fn identical_argument_types_are_ambiguous() {
+        let mut overloads = Overloads::default();
+        overloads
+            .insert("find_by_id", "find", [String::from("int64_t")])
+            .unwrap();
+        overloads
+            .insert("find_by_name", "find", [String::from("std::string")])
+            .unwrap();
+        // a different name is no overload
+        overloads
+            .insert("find_by_title", "find_title", [String::from("std::string")])
+            .unwrap();
+        let error = overloads
+            .insert("find_by_str", "find", [String::from("std::string")])
+            .unwrap_err();
+        let Error::Unsupported { message, .. } = error else {
+            panic!("expected an unsupported item");
+        };
+        assert_eq!(
+            message,
+            "`find_by_name` and `find_by_str` are both exported as `find(std::string)`, C++ can \
+             not distinguish these overloads"
+        );
+    }
+}
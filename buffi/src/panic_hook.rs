@@ -0,0 +1,89 @@
+//! Record details about panics for error messages
+//!
+//! The panic payload caught at the FFI boundary only contains the panic message. The
+//! hook installed via [`install_panic_hook`] additionally records the location (and
+//! optionally a backtrace) of the last panic of the current thread. Functions exported via
+//! `#[buffi_macro::exported(panic_details)]` add these details to the `SerializableError`
+//! that is returned for a panic.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fmt::Write as _;
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Install a panic hook that records the location of each panic
+///
+/// If `capture_backtrace` is set, a backtrace is recorded as well (independent of
+/// `RUST_BACKTRACE`). The previously installed hook is still called afterwards.
+pub fn install_panic_hook(capture_backtrace: bool) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let mut details = match info.location() {
+            Some(location) => format!("panicked at {location}"),
+            None => String::from("panicked at an unknown location"),
+        };
+        if capture_backtrace {
+            write!(details, "\nBacktrace:\n{}", Backtrace::force_capture()).unwrap();
+        }
+        LAST_PANIC.with(|last_panic| *last_panic.borrow_mut() = Some(details));
+        previous(info);
+    }));
+}
+
+/// Returns the details recorded for the last panic of the current thread (if any)
+///
+/// The details are removed, so they are only reported once.
+pub fn take_last_panic() -> Option<String> {
+    LAST_PANIC.with(|last_panic| last_panic.borrow_mut().take())
+}
+
+/// Adds the recorded details of the last panic to a panic payload
+///
+/// The returned payload is a `String`, so that it can be converted into a
+/// `SerializableError` like any other panic payload. If there are no recorded
+/// details, the payload is returned unchanged.
+pub fn with_panic_details(payload: Box<dyn Any + Send>) -> Box<dyn Any + Send> {
+    let Some(details) = take_last_panic() else {
+        return payload;
+    };
+    let message = payload_message(&*payload);
+    Box::new(format!("{message} ({details})"))
+}
+
+/// The message of a panic payload, empty if the payload is neither a `&str` nor a `String`
+pub(crate) fn payload_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&'static str>()
+        .map(|c| String::from(*c))
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+/// Generates the `buffi_install_panic_hook` extern function
+///
+/// This macro needs to be called in your API crate, so that the function is picked up
+/// by the code generation. Pass `prefix = "myapi"` for a custom function prefix.
+#[macro_export]
+macro_rules! export_panic_hook {
+    () => {
+        $crate::export_panic_hook!(prefix = "buffi");
+    };
+    (prefix = $prefix:literal) => {
+        $crate::__prefixed! {
+            prefix = $prefix;
+
+            /// Install a panic hook that records the location of each panic, so that it is
+            /// included in the returned error
+            ///
+            /// * `capture_backtrace`: Record a backtrace as well
+            #[no_mangle]
+            pub extern "C" fn install_panic_hook(capture_backtrace: bool) {
+                $crate::install_panic_hook(capture_backtrace)
+            }
+        }
+    };
+}
@@ -0,0 +1,77 @@
+//! Support for a global async runtime
+//!
+//! Async functions exported via `#[buffi_macro::exported(global_runtime)]` do not
+//! use a `runtime` field of their `Self` type but the runtime registered here. This
+//! also allows to export async functions that are not part of an impl block.
+//!
+//! In addition this module contains the reentrancy detection used by
+//! `#[buffi_macro::exported(detect_reentrancy)]`.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+use tokio::runtime::Handle;
+
+static GLOBAL_RUNTIME: OnceLock<Handle> = OnceLock::new();
+
+/// Register the runtime that should be used to execute exported async functions
+///
+/// The runtime can only be registered once, if there is already a registered runtime
+/// the passed handle is returned as error.
+pub fn set_global(handle: Handle) -> Result<(), Handle> {
+    GLOBAL_RUNTIME.set(handle)
+}
+
+/// Get the globally registered runtime (if any)
+pub fn global() -> Option<&'static Handle> {
+    GLOBAL_RUNTIME.get()
+}
+
+thread_local! {
+    static INSIDE_BLOCKING_CALL: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as waiting for the result of an exported function
+///
+/// The marker is removed as soon as the guard is dropped.
+pub struct BlockingCallGuard {
+    // the marker is thread local, so the guard must not be moved to another thread
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for BlockingCallGuard {
+    fn drop(&mut self) {
+        INSIDE_BLOCKING_CALL.set(false);
+    }
+}
+
+/// Called by exported functions before they block the current thread on a runtime
+///
+/// Blocking a thread that is already waiting for another exported function (for example
+/// if C++ code calls back into the API from a callback) or a thread of the async runtime
+/// itself either panics or deadlocks. This function returns a descriptive error message
+/// instead. `allow_runtime_thread` is set for functions that can handle being called on a
+/// runtime thread (`blocking_strategy = "block_in_place"`).
+pub fn enter_blocking_call(
+    function: &str,
+    allow_runtime_thread: bool,
+) -> Result<BlockingCallGuard, String> {
+    if INSIDE_BLOCKING_CALL.get() {
+        return Err(format!(
+            "Reentrant call to `{function}`: This thread is already waiting for the result of \
+             another API function, waiting again would deadlock. Call `{function}` from a \
+             different thread instead"
+        ));
+    }
+    if !allow_runtime_thread && Handle::try_current().is_ok() {
+        return Err(format!(
+            "`{function}` was called from a thread of the async runtime, blocking this thread \
+             would panic or deadlock. Call `{function}` from a different thread or use \
+             `blocking_strategy = \"block_in_place\"`"
+        ));
+    }
+    INSIDE_BLOCKING_CALL.set(true);
+    Ok(BlockingCallGuard {
+        _not_send: PhantomData,
+    })
+}
@@ -0,0 +1,189 @@
+//! Check the calling conventions of the API library from C++
+//!
+//! If [`Config::abi_smoke_test`](crate::Config::abi_smoke_test) is set, an additional
+//! `smoke_test.cpp` is generated. Linked against the API library, it references every extern
+//! function, so that missing symbols fail to link, and calls each generated function that can
+//! be called without valid inputs: a null `this_ptr` or empty serialized arguments need to
+//! be reported as serialized error without touching anything else. The program returns a
+//! non-zero exit code if any of these calls does not return an error.
+//!
+//! Free-standing functions are only called if they take serialized arguments. If all of their
+//! types can be deserialized from an empty buffer (like `()`), the function is executed.
+
+use crate::{create_file, finish_file, write_file_comment, Config, Error, ExternFunction};
+use std::io::Write as _;
+use std::path::PathBuf;
+
+/// A call of `function` with invalid inputs
+struct InvalidCall {
+    /// The local variables passed as out pointers
+    locals: Vec<String>,
+    arguments: Vec<String>,
+    /// What makes the inputs invalid
+    reason: &'static str,
+    /// The variables containing the error and its size
+    error: &'static str,
+}
+
+/// Removes the name from the C declaration of an argument, `None` for function pointers
+fn declared_type<'a>(name: &str, declaration: &'a str) -> Option<&'a str> {
+    declaration
+        .strip_suffix(name)
+        .map(str::trim_end)
+        .filter(|tpe| !tpe.contains('('))
+}
+
+/// How to call `function` with invalid inputs, `None` if it cannot be called safely
+fn invalid_call(function: &ExternFunction) -> Option<InvalidCall> {
+    if function.output != "size_t" {
+        return None;
+    }
+    let mut call = InvalidCall {
+        locals: Vec::new(),
+        arguments: Vec::new(),
+        reason: "",
+        error: "",
+    };
+    let mut inputs = function.inputs.iter().peekable();
+    while let Some((name, declaration)) = inputs.next() {
+        let tpe = declared_type(name, declaration)?;
+        match (name.as_str(), tpe) {
+            ("this_ptr", _) if call.arguments.is_empty() => {
+                call.arguments.push(String::from("nullptr"));
+                call.reason = "`this_ptr` is null";
+            }
+            ("out_ptr", "std::uint8_t**") if inputs.peek().is_none() => {
+                call.locals
+                    .push(String::from("std::uint8_t* out_ptr = nullptr;"));
+                call.arguments.push(String::from("&out_ptr"));
+                call.error = "out_ptr";
+            }
+            ("value_out", _) => {
+                let value = tpe.strip_suffix('*')?;
+                call.locals.push(format!("{value} value_out{{}};"));
+                call.arguments.push(String::from("&value_out"));
+            }
+            ("error_out", "std::uint8_t**") if inputs.peek().is_none() => {
+                call.locals
+                    .push(String::from("std::uint8_t* error_out = nullptr;"));
+                call.arguments.push(String::from("&error_out"));
+                call.error = "error_out";
+            }
+            (_, "const std::uint8_t*") => {
+                inputs.next_if(|(n, d)| {
+                    *n == format!("{name}_size") && *d == format!("size_t {n}")
+                })?;
+                call.arguments.push(String::from("nullptr"));
+                call.arguments.push(String::from("0"));
+                if call.reason.is_empty() {
+                    call.reason = "the serialized arguments are empty";
+                }
+            }
+            _ => return None,
+        }
+    }
+    (!call.reason.is_empty() && !call.error.is_empty()).then_some(call)
+}
+
+/// Generates `smoke_test.cpp` for the given extern functions
+pub(crate) fn generate_smoke_test(
+    functions: &[ExternFunction],
+    out_dir: &str,
+    function_prefix: &str,
+    config: &Config,
+    provenance: Option<&str>,
+) -> Result<(), Error> {
+    let file_prefix = config.file_prefix.as_ref().unwrap_or(&config.api_lib_name);
+    let path = PathBuf::from(out_dir).join(format!("{file_prefix}_smoke_test.cpp"));
+    let mut writer = create_file(&path)?;
+    write_file_comment(&mut writer, config, &path, provenance)?;
+    writeln!(writer, "#include \"{file_prefix}_api_functions.hpp\"\n").unwrap();
+    writeln!(writer, "#include <cstdio>\n").unwrap();
+    writeln!(writer, "namespace {{\n").unwrap();
+    writeln!(writer, "int failures = 0;\n").unwrap();
+    writeln!(
+        writer,
+        "// Frees the error returned by `function` for invalid inputs, a missing error is a failure"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "void expect_error(const char* function, const char* reason, size_t size, std::uint8_t* error) {{"
+    )
+    .unwrap();
+    writeln!(writer, "    if (size == 0 || error == nullptr) {{").unwrap();
+    writeln!(
+        writer,
+        "        std::fprintf(stderr, \"`%s` did not return an error although %s\\n\", function, reason);"
+    )
+    .unwrap();
+    writeln!(writer, "        ++failures;").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "    if (error != nullptr) {{").unwrap();
+    writeln!(
+        writer,
+        "        {function_prefix}_free_byte_buffer(error, size);"
+    )
+    .unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "}}\n").unwrap();
+    writeln!(writer, "}}  // end of anonymous namespace\n").unwrap();
+    writeln!(writer, "int main() {{").unwrap();
+    writeln!(
+        writer,
+        "    // every function is referenced, so that a missing symbol fails to link"
+    )
+    .unwrap();
+    writeln!(writer, "    void (*const symbols[])() = {{").unwrap();
+    for function in functions {
+        writeln!(
+            writer,
+            "        reinterpret_cast<void (*)()>(&{}),",
+            function.name
+        )
+        .unwrap();
+    }
+    writeln!(writer, "    }};").unwrap();
+    writeln!(
+        writer,
+        "    std::printf(\"Linked %zu functions\\n\", sizeof(symbols) / sizeof(symbols[0]));"
+    )
+    .unwrap();
+    for function in functions {
+        let Some(call) = invalid_call(function) else {
+            continue;
+        };
+        writeln!(writer).unwrap();
+        writeln!(writer, "    {{").unwrap();
+        for local in &call.locals {
+            writeln!(writer, "        {local}").unwrap();
+        }
+        writeln!(
+            writer,
+            "        size_t size = {}({});",
+            function.name,
+            call.arguments.join(", ")
+        )
+        .unwrap();
+        writeln!(
+            writer,
+            "        expect_error(\"{}\", \"{}\", size, {});",
+            function.name, call.reason, call.error
+        )
+        .unwrap();
+        writeln!(writer, "    }}").unwrap();
+    }
+    writeln!(writer).unwrap();
+    writeln!(writer, "    if (failures != 0) {{").unwrap();
+    writeln!(
+        writer,
+        "        std::fprintf(stderr, \"%d functions did not report invalid inputs\\n\", failures);"
+    )
+    .unwrap();
+    writeln!(writer, "        return 1;").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "    return 0;").unwrap();
+    writeln!(writer, "}}").unwrap();
+    finish_file(writer, &path)?;
+    Ok(())
+}
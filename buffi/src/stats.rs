@@ -0,0 +1,88 @@
+//! Statistics about calls and byte buffers
+//!
+//! If the `with_stats` feature of `buffi_macro` is enabled, each exported function counts
+//! its calls and the result buffers it allocates. Frees need to be recorded by calling
+//! [`record_free`] from your `buffi_free_byte_buffer` implementation. Comparing both numbers
+//! allows to detect buffers that are never freed by the C++ side.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static CALLS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BUFFERS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+static FREED_BUFFERS: AtomicU64 = AtomicU64::new(0);
+static FREED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// A snapshot of the recorded statistics
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferStatistics {
+    /// The number of calls to exported functions
+    pub calls: u64,
+    /// The number of result buffers allocated by exported functions
+    pub allocated_buffers: u64,
+    /// The total size of all allocated result buffers
+    pub allocated_bytes: u64,
+    /// The number of result buffers freed via `buffi_free_byte_buffer`
+    pub freed_buffers: u64,
+    /// The total size of all freed result buffers
+    pub freed_bytes: u64,
+    /// The number of result buffers that were not freed yet
+    pub outstanding_buffers: u64,
+    /// The total size of all result buffers that were not freed yet
+    pub outstanding_bytes: u64,
+}
+
+/// Record a call to an exported function
+pub fn record_call() {
+    CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Record the allocation of a result buffer with the given size
+pub fn record_allocation(size: usize) {
+    ALLOCATED_BUFFERS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+/// Record that a result buffer with the given size was freed
+pub fn record_free(size: usize) {
+    FREED_BUFFERS.fetch_add(1, Ordering::Relaxed);
+    FREED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+/// Get the statistics recorded so far
+pub fn snapshot() -> BufferStatistics {
+    let calls = CALLS.load(Ordering::Relaxed);
+    let allocated_buffers = ALLOCATED_BUFFERS.load(Ordering::Relaxed);
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let freed_buffers = FREED_BUFFERS.load(Ordering::Relaxed);
+    let freed_bytes = FREED_BYTES.load(Ordering::Relaxed);
+    BufferStatistics {
+        calls,
+        allocated_buffers,
+        allocated_bytes,
+        freed_buffers,
+        freed_bytes,
+        outstanding_buffers: allocated_buffers.saturating_sub(freed_buffers),
+        outstanding_bytes: allocated_bytes.saturating_sub(freed_bytes),
+    }
+}
+
+/// Generates an exported `stats` function (`buffi_stats` on the C side)
+///
+/// This macro needs to be called in your API crate. As the returned type is defined
+/// in `buffi`, `buffi` needs to be part of the `rustdoc_crates` in your config. Pass
+/// `prefix = "myapi"` for a custom function prefix.
+#[macro_export]
+macro_rules! export_stats {
+    () => {
+        $crate::export_stats!(prefix = "buffi");
+    };
+    (prefix = $prefix:literal) => {
+        /// Statistics about the calls to exported functions and their result buffers
+        #[buffi_macro::exported(prefix = $prefix)]
+        pub fn stats() -> Result<$crate::stats::BufferStatistics, String> {
+            Ok($crate::stats::snapshot())
+        }
+    };
+}
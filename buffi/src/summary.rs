@@ -0,0 +1,140 @@
+//! A summary of a generation run
+//!
+//! All `generate_*` functions return a [`GenerationSummary`], which is printed as well if
+//! [`Config::print_summary`](crate::Config::print_summary) is set. It is serializable, so CI
+//! jobs can store it (e.g. as JSON) to track the growth of an API and the generation time.
+
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// An item that is not available in some of the generated files
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedItem {
+    /// The name of the function or type
+    pub name: String,
+    /// Why it was skipped
+    pub reason: String,
+}
+
+/// How long a phase of the generation took
+#[derive(Debug, Clone, Serialize)]
+pub struct Phase {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// A file written by the generation
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    /// The size in bytes
+    pub size: u64,
+}
+
+/// What a generation run produced and how long it took
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GenerationSummary {
+    /// The number of extern functions declared in `api_functions.hpp`
+    pub functions: usize,
+    /// The number of types generated for the registry (structs and enums)
+    pub types: usize,
+    pub skipped: Vec<SkippedItem>,
+    /// The phases in the order they ran
+    pub phases: Vec<Phase>,
+    /// The files written to the output directory, ordered by path
+    pub files: Vec<GeneratedFile>,
+}
+
+impl GenerationSummary {
+    /// Runs `phase` and records how long it took
+    pub(crate) fn time<T>(&mut self, name: &str, phase: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = phase();
+        self.phases.push(Phase {
+            name: name.to_owned(),
+            duration: start.elapsed(),
+        });
+        result
+    }
+
+    /// Records all files in `out_dir` that were modified since `started`
+    pub(crate) fn collect_files(&mut self, out_dir: &Path, started: SystemTime) {
+        let Ok(entries) = std::fs::read_dir(out_dir) else {
+            return;
+        };
+        let mut files = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                let modified = metadata.modified().ok()?;
+                (metadata.is_file() && modified >= started).then(|| GeneratedFile {
+                    path: entry.path(),
+                    size: metadata.len(),
+                })
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        self.files = files;
+    }
+}
+
+impl fmt::Display for GenerationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Generated {} extern functions and {} types",
+            self.functions, self.types
+        )?;
+        writeln!(f, "Phases:")?;
+        for phase in &self.phases {
+            writeln!(f, "  {}: {:.2?}", phase.name, phase.duration)?;
+        }
+        let total = self.files.iter().map(|file| file.size).sum::<u64>();
+        writeln!(f, "Files ({total} bytes):")?;
+        for file in &self.files {
+            let name = file.path.file_name().unwrap_or(file.path.as_os_str());
+            writeln!(f, "  {}: {} bytes", name.to_string_lossy(), file.size)?;
+        }
+        if !self.skipped.is_empty() {
+            writeln!(f, "Skipped:")?;
+            for item in &self.skipped {
+                writeln!(f, "  `{}`: {}", item.name, item.reason)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_are_recorded_in_order() {
+        let mut summary = GenerationSummary::default();
+        assert_eq!(summary.time("first", || 1), 1);
+        summary.time("second", || ());
+        let names = summary
+            .phases
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["first", "second"]);
+    }
+
+    #[test]
+    fn skipped_items_are_printed() {
+        let summary = GenerationSummary {
+            skipped: vec![SkippedItem {
+                name: String::from("TestClient::async_function"),
+                reason: String::from("embind cannot represent its types"),
+            }],
+            ..Default::default()
+        };
+        assert!(summary
+            .to_string()
+            .contains("`TestClient::async_function`: embind cannot represent its types"));
+    }
+}
@@ -0,0 +1,89 @@
+//! Check the generated C++ code in the Rust test suite
+//!
+//! [`compile_generated`] includes all headers of an output directory in a single translation
+//! unit and runs a C++ compiler on it, so that invalid generated code fails `cargo test`
+//! instead of a separate C++ build:
+//!
+//! ```no_run
+//! # let out_dir = std::path::Path::new("bindings");
+//! let compiler = std::env::var("CXX").unwrap_or_else(|_| String::from("c++"));
+//! if let Err(e) = buffi::testing::compile_generated(out_dir, &compiler) {
+//!     panic!("{e}");
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Command;
+
+/// The translation unit that includes all headers, removed after compiling it
+const TEST_SOURCE: &str = "buffi_compile_generated.cpp";
+
+/// Compiles all headers in `out_dir` and its subdirectories (except `qt_conversions.hpp`, which
+/// needs Qt) with `compiler`
+///
+/// `compiler` is either MSVC (`cl` or `cl.exe`) or a compiler accepting GCC style flags (like
+/// `g++` or `clang++`). Only the syntax is checked, nothing is linked. The error contains the
+/// diagnostics of the compiler.
+pub fn compile_generated(out_dir: &Path, compiler: &str) -> Result<(), Box<dyn Error>> {
+    let mut headers = Vec::new();
+    collect_headers(out_dir, Path::new(""), &mut headers)?;
+    if headers.is_empty() {
+        return Err(format!("`{}` contains no generated headers", out_dir.display()).into());
+    }
+    headers.sort();
+    let mut source = String::new();
+    for header in &headers {
+        writeln!(source, "#include \"{header}\"")?;
+    }
+    writeln!(source, "\nint main() {{ return 0; }}")?;
+    std::fs::write(out_dir.join(TEST_SOURCE), source)?;
+
+    let is_msvc = Path::new(compiler)
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("cl"));
+    let mut command = Command::new(compiler);
+    if is_msvc {
+        command.args(["/nologo", "/std:c++17", "/EHsc", "/W4", "/Zs", "/I."]);
+    } else {
+        command.args(["-std=c++17", "-fsyntax-only", "-Wall", "-Wextra", "-I."]);
+    }
+    let output = command.arg(TEST_SOURCE).current_dir(out_dir).output();
+    let _ = std::fs::remove_file(out_dir.join(TEST_SOURCE));
+    let output = output.map_err(|e| format!("Failed to run `{compiler}`: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Compiling the generated code with `{compiler}` failed:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into())
+    }
+}
+
+/// Adds the headers in `out_dir.join(directory)` and its subdirectories to `headers`, as paths
+/// relative to `out_dir` (with `/` as separator, like the `#include` directives)
+///
+/// The subdirectories are created by [`Config::layout`](crate::Config::layout).
+fn collect_headers(
+    out_dir: &Path,
+    directory: &Path,
+    headers: &mut Vec<String>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(out_dir.join(directory))? {
+        let entry = entry?;
+        let path = directory.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_headers(out_dir, &path, headers)?;
+        } else {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".hpp") && !name.ends_with("qt_conversions.hpp") {
+                headers.push(path.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Reflect types via `serde_reflection::Tracer` instead of the rustdoc JSON output
+//!
+//! Some types cannot be reflected based on rustdoc, for example types with complex generics or
+//! types serialized via a remote derive (`#[serde(remote = "…")]`), where the serialized format
+//! differs from the fields rustdoc sees. Pass a [`SampleValues`] hook to
+//! [`generate_bindings_with_samples`](crate::generate_bindings_with_samples) to trace such types
+//! at generation time instead of aborting.
+//!
+//! The hook is asked for every named type (except standard types like `Vec` or `Option`) before
+//! it is reflected via rustdoc. Types it traces are taken from the traced registry as they are,
+//! so they don't get the [`Config::type_name_prefix`](crate::Config::type_name_prefix) and
+//! different instances of a generic type share one name (as `serde` names them after the type
+//! without its arguments).
+
+use crate::Error;
+use serde_reflection::{ContainerFormat, Format, Samples, Tracer, TracerConfig};
+
+/// Provides sample values for types that are traced instead of being reflected via rustdoc
+pub trait SampleValues {
+    /// Traces the type called `type_name` (the Rust name without its path)
+    ///
+    /// Implementations call `tracer.trace_value(samples, &value)` with a sample value (and
+    /// `tracer.trace_type::<T>(samples)` for enums, so that all variants are known) and return
+    /// `Ok(true)`, or return `Ok(false)` for types that should be reflected via rustdoc.
+    fn trace(
+        &self,
+        type_name: &str,
+        tracer: &mut Tracer,
+        samples: &mut Samples,
+    ) -> serde_reflection::Result<bool>;
+}
+
+impl<F> SampleValues for F
+where
+    F: Fn(&str, &mut Tracer, &mut Samples) -> serde_reflection::Result<bool>,
+{
+    fn trace(
+        &self,
+        type_name: &str,
+        tracer: &mut Tracer,
+        samples: &mut Samples,
+    ) -> serde_reflection::Result<bool> {
+        self(type_name, tracer, samples)
+    }
+}
+
+/// The traced containers, like the formats returned by the rustdoc based reflection
+type TracedFormats = Vec<(Format, Option<ContainerFormat>)>;
+
+/// Traces `type_name` via `hook`, returns `None` if the hook does not know the type
+///
+/// Like the rustdoc based reflection, the result contains all traced containers with the
+/// format of `type_name` itself as last entry.
+pub(crate) fn trace_type(
+    hook: &dyn SampleValues,
+    type_name: &str,
+) -> Result<Option<TracedFormats>, Error> {
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let mut samples = Samples::new();
+    let traced = hook
+        .trace(type_name, &mut tracer, &mut samples)
+        .map_err(|e| Error::Generation(format!("Failed to trace `{type_name}`: {e}")))?;
+    if !traced {
+        return Ok(None);
+    }
+    let mut registry = tracer
+        .registry()
+        .map_err(|e| Error::Generation(format!("Failed to trace `{type_name}`: {e}")))?;
+    let container = registry.remove(type_name).ok_or_else(|| {
+        Error::Generation(format!(
+            "Tracing `{type_name}` did not record a type with this name, is it renamed via serde?"
+        ))
+    })?;
+    let mut out = registry
+        .into_iter()
+        .map(|(name, container)| (Format::TypeName(name), Some(container)))
+        .collect::<Vec<_>>();
+    out.push((Format::TypeName(type_name.to_owned()), Some(container)));
+    Ok(Some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Serialize)]
+    struct Inner {
+        value: i64,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Outer {
+        inner: Inner,
+    }
+
+    fn hook(
+        type_name: &str,
+        tracer: &mut Tracer,
+        samples: &mut Samples,
+    ) -> serde_reflection::Result<bool> {
+        if type_name != "Outer" {
+            return Ok(false);
+        }
+        tracer.trace_value(
+            samples,
+            &Outer {
+                inner: Inner { value: 42 },
+            },
+        )?;
+        Ok(true)
+    }
+
+    #[test]
+    fn traced_type_is_last() {
+        let traced = trace_type(&hook, "Outer").unwrap().unwrap();
+        assert_eq!(traced.len(), 2);
+        assert_eq!(traced[0].0, Format::TypeName(String::from("Inner")));
+        assert_eq!(traced[1].0, Format::TypeName(String::from("Outer")));
+    }
+
+    #[test]
+    fn unknown_types_are_not_traced() {
+        assert!(trace_type(&hook, "Inner").unwrap().is_none());
+    }
+}
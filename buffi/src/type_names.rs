@@ -0,0 +1,304 @@
+//! Names of the C++ types generated for composite Rust types
+//!
+//! Types like `Result<Vec<Option<Foo>>, SerializableError>` or generic structs like `Wrapper<i64>`
+//! do not have a name on their own. They are named by joining the name of the type and all its
+//! arguments with `_`, e.g. `Result_Vec_Option_Foo_SerializableError`. As this is ambiguous
+//! (`Result<Foo_Bar, Baz>` vs `Result<Foo, Bar_Baz>`), [`TypeNames`] remembers which type
+//! a name was generated for and appends a counter to colliding names.
+//!
+//! Structs and enums keep their Rust name. As C++ does not know about the crate or module a type
+//! is defined in, [`TypeNames`] also checks that there are no two different Rust types with the
+//! same name.
+//!
+//! All names can be prefixed (see `Config::type_name_prefix`) to avoid clashes with other types
+//! in the same namespace.
+
+use crate::Error;
+use serde_reflection::Format;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+/// Keeps track of all generated composite type names
+///
+/// The same instance needs to be used for all passes of the generator, otherwise the
+/// type definitions and the functions might disagree on a name
+#[derive(Default)]
+pub(crate) struct TypeNames {
+    /// Prepended to each generated type name
+    prefix: String,
+    /// Maps a generated name to the base name and arguments it was generated for
+    names: HashMap<String, (String, Vec<Format>)>,
+    /// Maps the name of a struct or enum to the location of its definition
+    types: HashMap<String, String>,
+    /// Maps the name of a struct or enum to the Rust module it is defined in
+    modules: BTreeMap<String, Vec<String>>,
+}
+
+impl TypeNames {
+    pub(crate) fn with_prefix(prefix: String) -> Self {
+        Self {
+            prefix,
+            names: HashMap::new(),
+            types: HashMap::new(),
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// Records the Rust module (including the crate name) the struct or enum `name` is defined in
+    pub(crate) fn set_module(&mut self, name: &str, module: Vec<String>) {
+        self.modules.insert(self.prefixed(name), module);
+    }
+
+    /// The Rust modules of all types registered via [`TypeNames::set_module`], by C++ name
+    pub(crate) fn modules(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.modules
+    }
+
+    /// Registers the struct or enum `name` defined at `origin`
+    ///
+    /// Fails if a different type with the same name was registered before, as
+    /// both types would end up as the same C++ type
+    pub(crate) fn register_type(&mut self, name: &str, origin: String) -> Result<(), Error> {
+        match self.types.get(name) {
+            Some(existing) if *existing != origin => {
+                return Err(Error::unsupported(format!(
+                    "The types `{name}` defined at {existing} and `{name}` defined at {origin} \
+                     would both be named `{}` in C++, please rename one of them",
+                    self.prefixed(name)
+                )));
+            }
+            Some(_) => {}
+            None => {
+                self.types.insert(name.to_owned(), origin);
+            }
+        }
+        Ok(())
+    }
+
+    /// The name of a type that does not have any arguments
+    pub(crate) fn prefixed(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix)
+    }
+
+    /// The name for the type `base` instantiated with `args`
+    ///
+    /// Calling this function again with the same arguments returns the same name
+    pub(crate) fn composite_name(&mut self, base: &str, args: &[Format]) -> Result<String, Error> {
+        let mut name = self.prefixed(base);
+        for arg in args {
+            name.push('_');
+            name.push_str(&to_type_name(arg)?);
+        }
+        let mut candidate = name.clone();
+        let mut counter = 1;
+        loop {
+            match self.names.get(&candidate) {
+                Some((b, a)) if b == base && a == args => return Ok(candidate),
+                Some(_) => {
+                    counter += 1;
+                    candidate = format!("{name}_{counter}");
+                }
+                None => {
+                    self.names
+                        .insert(candidate.clone(), (base.to_owned(), args.to_vec()));
+                    return Ok(candidate);
+                }
+            }
+        }
+    }
+}
+
+/// The name of a type used as part of a composite type name
+pub(crate) fn to_type_name(f: &Format) -> Result<Cow<str>, Error> {
+    Ok(match f {
+        Format::TypeName(n) => Cow::Borrowed(n),
+        Format::Bool => Cow::Borrowed("bool"),
+        Format::I8 => Cow::Borrowed("i8"),
+        Format::I16 => Cow::Borrowed("i16"),
+        Format::I32 => Cow::Borrowed("i32"),
+        Format::I64 => Cow::Borrowed("i64"),
+        Format::U8 => Cow::Borrowed("u8"),
+        Format::U16 => Cow::Borrowed("u16"),
+        Format::U32 => Cow::Borrowed("u32"),
+        Format::U64 => Cow::Borrowed("u64"),
+        Format::F32 => Cow::Borrowed("f32"),
+        Format::F64 => Cow::Borrowed("f64"),
+        Format::Str => Cow::Borrowed("String"),
+        Format::Bytes => Cow::Borrowed("Bytes"),
+        Format::Option(t) => Cow::Owned(format!("Option_{}", to_type_name(t)?)),
+        Format::Seq(t) => Cow::Owned(format!("Vec_{}", to_type_name(t)?)),
+        Format::Map { key, value } => Cow::Owned(format!(
+            "Map_{}_{}",
+            to_type_name(key)?,
+            to_type_name(value)?
+        )),
+        Format::Tuple(d) if d.is_empty() => Cow::Borrowed("void"),
+        Format::Tuple(d) => {
+            let elements = d.iter().map(to_type_name).collect::<Result<Vec<_>, _>>()?;
+            Cow::Owned(format!("Tuple_{}", elements.join("_")))
+        }
+        Format::TupleArray { content, size } => {
+            Cow::Owned(format!("Array_{size}_{}", to_type_name(content)?))
+        }
+        Format::Variable(_) | Format::Unit | Format::I128 | Format::U128 | Format::Char => {
+            return Err(Error::unsupported(format!(
+                "`{f:?}` cannot be part of a type name in the bindings"
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> Format {
+        Format::TypeName(name.to_owned())
+    }
+
+    #[test]
+    fn nested_option_vec_result() {
+        let mut names = TypeNames::default();
+        let ok = Format::Seq(Box::new(Format::Option(Box::new(named("Foo")))));
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Vec_Option_Foo_SerializableError"
+        );
+        let ok = Format::Option(Box::new(Format::Seq(Box::new(Format::I64))));
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Option_Vec_i64_SerializableError"
+        );
+        assert_eq!(
+            names
+                .composite_name(
+                    "Result",
+                    &[Format::Tuple(Vec::new()), named("SerializableError")]
+                )
+                .unwrap(),
+            "Result_void_SerializableError"
+        );
+        let ok = Format::TupleArray {
+            content: Box::new(Format::Option(Box::new(Format::F64))),
+            size: 3,
+        };
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Array_3_Option_f64_SerializableError"
+        );
+        let ok = Format::Map {
+            key: Box::new(Format::Str),
+            value: Box::new(Format::Seq(Box::new(Format::I64))),
+        };
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Map_String_Vec_i64_SerializableError"
+        );
+        let ok = Format::Tuple(vec![Format::I64, Format::Option(Box::new(Format::Str))]);
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Tuple_i64_Option_String_SerializableError"
+        );
+        let ok = named(&names.composite_name("Wrapper", &[Format::Str]).unwrap());
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Wrapper_String_SerializableError"
+        );
+        let ok = Format::Option(Box::new(Format::Bytes));
+        assert_eq!(
+            names
+                .composite_name("Result", &[ok, named("SerializableError")])
+                .unwrap(),
+            "Result_Option_Bytes_SerializableError"
+        );
+    }
+
+    #[test]
+    fn same_type_same_name() {
+        let mut names = TypeNames::default();
+        let args = [
+            Format::Seq(Box::new(Format::Str)),
+            named("SerializableError"),
+        ];
+        let first = names.composite_name("Result", &args).unwrap();
+        let second = names.composite_name("Result", &args).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn colliding_names_are_unique() {
+        let mut names = TypeNames::default();
+        let first = names
+            .composite_name("Result", &[named("Foo_Bar"), named("Baz")])
+            .unwrap();
+        let second = names
+            .composite_name("Result", &[named("Foo"), named("Bar_Baz")])
+            .unwrap();
+        let third = names
+            .composite_name("Result_Foo", &[named("Bar"), named("Baz")])
+            .unwrap();
+        assert_eq!(first, "Result_Foo_Bar_Baz");
+        assert_eq!(second, "Result_Foo_Bar_Baz_2");
+        assert_eq!(third, "Result_Foo_Bar_Baz_3");
+        // the first registration keeps its name
+        assert_eq!(
+            names
+                .composite_name("Result", &[named("Foo"), named("Bar_Baz")])
+                .unwrap(),
+            "Result_Foo_Bar_Baz_2"
+        );
+    }
+
+    #[test]
+    fn same_type_can_be_registered_again() {
+        let mut names = TypeNames::default();
+        names
+            .register_type("Config", String::from("crate_a (src/lib.rs:3)"))
+            .unwrap();
+        names
+            .register_type("Config", String::from("crate_a (src/lib.rs:3)"))
+            .unwrap();
+        names
+            .register_type("Other", String::from("crate_b (src/lib.rs:3)"))
+            .unwrap();
+    }
+
+    #[test]
+    fn colliding_types_are_detected() {
+        let mut names = TypeNames::default();
+        names
+            .register_type("Config", String::from("crate_a (src/lib.rs:3)"))
+            .unwrap();
+        let error = names
+            .register_type("Config", String::from("crate_b (src/config.rs:10)"))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("would both be named `Config` in C++"));
+    }
+
+    #[test]
+    fn prefixed_names() {
+        let mut names = TypeNames::with_prefix(String::from("Gg"));
+        assert_eq!(names.prefixed("Foo"), "GgFoo");
+        assert_eq!(
+            names
+                .composite_name("Result", &[named("GgFoo"), named("GgSerializableError")])
+                .unwrap(),
+            "GgResult_GgFoo_GgSerializableError"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Cross-check the extern functions generated by `#[buffi_macro::exported]` with the functions
+//! they wrap
+//!
+//! The generator derives the extern function called by each C++ wrapper from the wrapped Rust
+//! function (`{prefix}_{name}`, a pointer/size pair per serialized argument, a function and a
+//! context pointer per callback and an `out_ptr` or `value_out`/`error_out`). If the macro
+//! output does not match, e.g. because the macro is disabled by a feature flag or uses another
+//! prefix, the generated C++ code would not link or call the function with the wrong arguments.
+//! All mismatches are reported at once before any C++ code is written.
+
+use crate::{
+    argument_names, callback_signature, is_by_value_type, is_chunked, is_direct_return,
+    is_raw_bytes, Error,
+};
+use rustdoc_types::{Abi, Function, Id, Item, ItemEnum, Type};
+use std::collections::HashMap;
+
+fn is_primitive(tpe: &Type, name: &str) -> bool {
+    matches!(tpe, Type::Primitive(p) if p == name)
+}
+
+/// `*const u8` (`is_mutable == false`) or `*mut u8`
+fn is_byte_pointer(tpe: &Type, is_mutable: bool) -> bool {
+    matches!(
+        tpe,
+        Type::RawPointer { is_mutable: m, type_ } if *m == is_mutable && is_primitive(type_, "u8")
+    )
+}
+
+fn is_mut_pointer(tpe: &Type) -> bool {
+    matches!(
+        tpe,
+        Type::RawPointer {
+            is_mutable: true,
+            ..
+        }
+    )
+}
+
+/// `Option<extern "C" fn(..)>`, which is passed for `#[buffi(callback)]` arguments
+fn is_function_pointer(tpe: &Type) -> bool {
+    let Type::ResolvedPath(p) = tpe else {
+        return false;
+    };
+    match p.args.as_deref() {
+        Some(rustdoc_types::GenericArgs::AngleBracketed { args, .. }) => matches!(
+            args.as_slice(),
+            [rustdoc_types::GenericArg::Type(Type::FunctionPointer(_))]
+        ),
+        _ => false,
+    }
+}
+
+/// `*mut *mut u8`
+fn is_out_pointer(tpe: &Type) -> bool {
+    matches!(tpe, Type::RawPointer { is_mutable: true, type_ } if is_byte_pointer(type_, true))
+}
+
+/// The problems of `extern_function` as the extern function of `wrapped`
+fn signature_mismatches(
+    item: &Item,
+    wrapped: &Function,
+    extern_function: &Function,
+    is_method: bool,
+) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    let mut extern_inputs = extern_function.sig.inputs.iter();
+    let mut expect = |name: &str, is_valid: &dyn Fn(&Type) -> bool, description: &str| {
+        match extern_inputs.next() {
+            Some((n, tpe)) if n == name && is_valid(tpe) => {}
+            Some((n, _)) if n == name => {
+                mismatches.push(format!("`{name}` needs to be {description}"))
+            }
+            Some((n, _)) => mismatches.push(format!("expected `{name}` but found `{n}`")),
+            None => mismatches.push(format!("`{name}` is missing")),
+        }
+    };
+    if is_method {
+        expect("this_ptr", &is_mut_pointer, "a `*mut` pointer");
+    }
+    for (name, (_, tpe)) in argument_names(wrapped)
+        .iter()
+        .zip(&wrapped.sig.inputs)
+        .filter(|(name, _)| *name != "self")
+    {
+        if callback_signature(tpe).is_some() {
+            expect(name, &is_function_pointer, "an optional function pointer");
+            expect(
+                &format!("{name}_context"),
+                &is_mut_pointer,
+                "a `*mut` pointer",
+            );
+            continue;
+        }
+        let by_value = extern_function
+            .sig
+            .inputs
+            .iter()
+            .any(|(n, tpe)| n == name && is_by_value_type(tpe));
+        if by_value {
+            expect(name, &|_| true, "");
+        } else {
+            expect(name, &|tpe| is_byte_pointer(tpe, false), "`*const u8`");
+            expect(
+                &format!("{name}_size"),
+                &|tpe| is_primitive(tpe, "usize"),
+                "`usize`",
+            );
+        }
+    }
+    if is_direct_return(item) || is_chunked(item) || is_raw_bytes(item) {
+        expect("value_out", &is_mut_pointer, "a `*mut` pointer");
+        expect("error_out", &is_out_pointer, "`*mut *mut u8`");
+    } else {
+        expect("out_ptr", &is_out_pointer, "`*mut *mut u8`");
+    }
+    if let Some((n, _)) = extern_inputs.next() {
+        mismatches.push(format!("unexpected argument `{n}`"));
+    }
+    if !extern_function
+        .sig
+        .output
+        .as_ref()
+        .is_some_and(|tpe| is_primitive(tpe, "usize"))
+    {
+        mismatches.push(String::from("the return type needs to be `usize`"));
+    }
+    mismatches
+}
+
+/// Checks that there is a matching extern function for each of the `wrapped` functions (with
+/// the type of `self` for methods) in `index`, returns a list of all mismatches otherwise
+pub(crate) fn verify_extern_functions<'a>(
+    index: &HashMap<Id, Item>,
+    wrapped: impl Iterator<Item = (Option<&'a str>, &'a Item)>,
+    function_prefix: &str,
+) -> Result<(), Error> {
+    let extern_functions = index
+        .values()
+        .filter_map(|item| match item.inner {
+            ItemEnum::Function(ref f) if matches!(f.header.abi, Abi::C { .. }) => {
+                Some((item.name.as_deref()?, f))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut errors = Vec::new();
+    for (owner, item) in wrapped {
+        let ItemEnum::Function(ref function) = item.inner else {
+            continue;
+        };
+        let Some(name) = item.name.as_deref() else {
+            continue;
+        };
+        let qualified_name = match owner {
+            Some(owner) => format!("{owner}::{name}"),
+            None => name.to_owned(),
+        };
+        let extern_name = format!("{function_prefix}_{name}");
+        match extern_functions.iter().find(|(n, _)| *n == extern_name) {
+            Some((_, extern_function)) => {
+                for mismatch in
+                    signature_mismatches(item, function, extern_function, owner.is_some())
+                {
+                    errors.push(format!(
+                        "`{extern_name}` (for `{qualified_name}`): {mismatch}"
+                    ));
+                }
+            }
+            None => {
+                // the same function with another prefix
+                let suffix = format!("_{name}");
+                let hint = match extern_functions.iter().find(|(n, _)| n.ends_with(&suffix)) {
+                    Some((other, _)) => format!(
+                        "found `{other}` instead, the macro needs to use the prefix `{function_prefix}`"
+                    ),
+                    None => String::from(
+                        "is `#[buffi_macro::exported]` disabled, e.g. by a feature flag?",
+                    ),
+                };
+                errors.push(format!(
+                    "`{qualified_name}` is wrapped, but `{extern_name}` does not exist: {hint}"
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(Error::Generation(format!(
+            "The extern functions do not match the wrapped functions:\n  {}",
+            errors.join("\n  ")
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustdoc_types::{FunctionHeader, FunctionSignature, Generics, Visibility};
+
+    fn byte_pointer(is_mutable: bool) -> Type {
+        Type::RawPointer {
+            is_mutable,
+            type_: Box::new(Type::Primitive(String::from("u8"))),
+        }
+    }
+
+    fn function(id: u32, name: &str, abi: Abi, inputs: Vec<(&str, Type)>, output: Type) -> Item {
+        Item {
+            id: Id(id),
+            crate_id: 0,
+            name: Some(name.to_owned()),
+            span: None,
+            visibility: Visibility::Public,
+            docs: None,
+            links: HashMap::new(),
+            attrs: Vec::new(),
+            deprecation: None,
+            inner: ItemEnum::Function(Function {
+                sig: FunctionSignature {
+                    inputs: inputs
+                        .into_iter()
+                        .map(|(name, tpe)| (name.to_owned(), tpe))
+                        .collect(),
+                    output: Some(output),
+                    is_c_variadic: false,
+                },
+                generics: Generics {
+                    params: Vec::new(),
+                    where_predicates: Vec::new(),
+                },
+                header: FunctionHeader {
+                    is_const: false,
+                    is_unsafe: false,
+                    is_async: false,
+                    abi,
+                },
+                has_body: true,
+            }),
+        }
+    }
+
+    /// `fn get(id: u64)`, the output is not checked
+    fn wrapped() -> Item {
+        function(
+            1,
+            "get",
+            Abi::Rust,
+            vec![("id", Type::Primitive(String::from("u64")))],
+            Type::Primitive(String::from("str")),
+        )
+    }
+
+    fn extern_function(name: &str, inputs: Vec<(&str, Type)>) -> Item {
+        function(
+            2,
+            name,
+            Abi::C { unwind: false },
+            inputs,
+            Type::Primitive(String::from("usize")),
+        )
+    }
+
+    fn verify(extern_function: Item) -> Result<(), Error> {
+        let wrapped = wrapped();
+        let index = HashMap::from([
+            (wrapped.id, wrapped.clone()),
+            (extern_function.id, extern_function),
+        ]);
+        verify_extern_functions(&index, std::iter::once((None, &wrapped)), "buffi")
+    }
+
+    #[test]
+    fn matching_extern_functions_are_accepted() {
+        let extern_function = extern_function(
+            "buffi_get",
+            vec![
+                ("id", byte_pointer(false)),
+                ("id_size", Type::Primitive(String::from("usize"))),
+                (
+                    "out_ptr",
+                    Type::RawPointer {
+                        is_mutable: true,
+                        type_: Box::new(byte_pointer(true)),
+                    },
+                ),
+            ],
+        );
+        verify(extern_function).unwrap();
+    }
+
+    #[test]
+    fn another_prefix_is_reported() {
+        let error = verify(extern_function("other_get", Vec::new())).unwrap_err();
+        assert!(matches!(error, Error::Generation(_)));
+        assert!(error.to_string().contains(
+            "`get` is wrapped, but `buffi_get` does not exist: found `other_get` instead, \
+             the macro needs to use the prefix `buffi`"
+        ));
+    }
+
+    #[test]
+    fn missing_extern_functions_are_reported() {
+        let error = verify(extern_function("buffi_other", Vec::new())).unwrap_err();
+        assert!(error.to_string().contains(
+            "`get` is wrapped, but `buffi_get` does not exist: \
+             is `#[buffi_macro::exported]` disabled, e.g. by a feature flag?"
+        ));
+    }
+
+    #[test]
+    fn wrong_signatures_are_reported() {
+        let extern_function = extern_function(
+            "buffi_get",
+            vec![
+                ("id", byte_pointer(false)),
+                ("id_size", Type::Primitive(String::from("u32"))),
+                ("out_ptr", byte_pointer(true)),
+            ],
+        );
+        let message = verify(extern_function).unwrap_err().to_string();
+        assert!(message.contains("`buffi_get` (for `get`): `id_size` needs to be `usize`"));
+        assert!(message.contains("`buffi_get` (for `get`): `out_ptr` needs to be `*mut *mut u8`"));
+    }
+}
@@ -0,0 +1,86 @@
+//! This file contains the implementation of the `#[buffi_macro::event]` attribute macro
+use syn::spanned::Spanned;
+
+use crate::imported::to_snake_case;
+
+pub(crate) fn expand(
+    item: syn::Item,
+    prefix: &str,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let (name, generics) = match item {
+        syn::Item::Struct(ref s) => (&s.ident, &s.generics),
+        syn::Item::Enum(ref e) => (&e.ident, &e.generics),
+        _ => {
+            return Err(syn::Error::new(
+                item.span(),
+                "only structs and enums can be events",
+            ))
+        }
+    };
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new(generics.span(), "events cannot be generic"));
+    }
+    let snake_name = to_snake_case(&name.to_string());
+    let subscribe_name = quote::format_ident!("{}_subscribe_{}", prefix, snake_name);
+    let unsubscribe_name = quote::format_ident!("{}_unsubscribe_{}", prefix, snake_name);
+    let serialize_error = format!("Failed to serialize the event `{name}`");
+    let subscribe_docs = [
+        format!(" Subscribe to `{name}` events"),
+        String::new(),
+        String::from(" `callback` is called with `context` and each serialized event, the buffer"),
+        String::from(" is only valid during the call. Returns the id of the subscription, which"),
+        String::from(" is passed to the unsubscribe function, or 0 if `callback` is null."),
+        String::new(),
+        String::from(" # Safety"),
+        String::new(),
+        String::from(" `context` needs to stay valid until `drop` is called with it after"),
+        String::from(" unsubscribing. `callback` may be called from any thread and must not"),
+        String::from(" unwind."),
+    ];
+    let unsubscribe_docs = [
+        format!(" Unsubscribe from `{name}` events"),
+        String::new(),
+        String::from(
+            " * `subscription`: The id returned when subscribing, unknown ids are ignored",
+        ),
+    ];
+
+    Ok(quote::quote! {
+        #[cfg(not(generated_event_marker))]
+        #item
+
+        impl buffi::events::Event for #name {
+            fn sink() -> &'static buffi::events::EventSink<Self> {
+                static SINK: buffi::events::EventSink<#name> =
+                    buffi::events::EventSink::new(|event| {
+                        bincode::serialize(event).expect(#serialize_error)
+                    });
+                &SINK
+            }
+        }
+
+        #(#[doc = #subscribe_docs])*
+        #[no_mangle]
+        pub unsafe extern "C" fn #subscribe_name(
+            callback: Option<
+                extern "C" fn(context: *mut std::ffi::c_void, event: *const u8, event_size: usize),
+            >,
+            context: *mut std::ffi::c_void,
+            drop: Option<extern "C" fn(context: *mut std::ffi::c_void)>,
+        ) -> u64 {
+            match callback {
+                // SAFETY: guaranteed by the caller
+                Some(callback) => unsafe {
+                    <#name as buffi::events::Event>::sink().subscribe(callback, context, drop)
+                },
+                None => 0,
+            }
+        }
+
+        #(#[doc = #unsubscribe_docs])*
+        #[no_mangle]
+        pub extern "C" fn #unsubscribe_name(subscription: u64) {
+            <#name as buffi::events::Event>::sink().unsubscribe(subscription)
+        }
+    })
+}
@@ -0,0 +1,225 @@
+//! This file contains the implementation of the `#[buffi_macro::imported]` attribute macro
+use syn::spanned::Spanned;
+
+/// `WordFilter` -> `word_filter`
+pub(crate) fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (idx, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if idx != 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// The name of each argument of `sig` without the receiver, which needs to be `&self`
+fn argument_names(sig: &syn::Signature) -> Result<Vec<&syn::Ident>, syn::Error> {
+    let mut names = Vec::new();
+    for (idx, arg) in sig.inputs.iter().enumerate() {
+        match arg {
+            syn::FnArg::Receiver(r)
+                if idx == 0 && r.reference.is_some() && r.mutability.is_none() => {}
+            syn::FnArg::Typed(t) if idx != 0 => match &*t.pat {
+                syn::Pat::Ident(i) => names.push(&i.ident),
+                pat => {
+                    return Err(syn::Error::new(
+                        pat.span(),
+                        "arguments of imported functions need to be plain identifiers",
+                    ))
+                }
+            },
+            _ => {
+                return Err(syn::Error::new(
+                    arg.span(),
+                    format!(
+                        "imported function '{}' needs to take `&self` as first argument",
+                        sig.ident
+                    ),
+                ))
+            }
+        }
+    }
+    if sig.inputs.is_empty() {
+        return Err(syn::Error::new(
+            sig.span(),
+            format!(
+                "imported function '{}' needs to take `&self` as first argument",
+                sig.ident
+            ),
+        ));
+    }
+    Ok(names)
+}
+
+pub(crate) fn expand(
+    mut item: syn::ItemTrait,
+    prefix: &str,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item.generics.span(),
+            "imported traits cannot be generic",
+        ));
+    }
+    let trait_name = &item.ident;
+    let snake_name = to_snake_case(&trait_name.to_string());
+    let imported_name = quote::format_ident!("Imported{}", trait_name);
+    let static_name = quote::format_ident!("IMPORTED_{}", snake_name.to_ascii_uppercase());
+    let set_name = quote::format_ident!("{}_set_{}", prefix, snake_name);
+
+    let mut fields = Vec::new();
+    let mut methods = Vec::new();
+    let mut set_args = Vec::new();
+    let mut function_names = Vec::new();
+    for trait_item in &item.items {
+        let syn::TraitItem::Fn(f) = trait_item else {
+            return Err(syn::Error::new(
+                trait_item.span(),
+                "imported traits can only contain functions",
+            ));
+        };
+        let sig = &f.sig;
+        if sig.asyncness.is_some() || !sig.generics.params.is_empty() {
+            return Err(syn::Error::new(
+                sig.span(),
+                format!(
+                    "imported function '{}' cannot be async or generic",
+                    sig.ident
+                ),
+            ));
+        }
+        let names = argument_names(sig)?;
+        let fn_name = &sig.ident;
+        let sizes = names
+            .iter()
+            .map(|n| quote::format_ident!("{}_size", n))
+            .collect::<Vec<_>>();
+        let arg_ptrs = names.iter().map(|_| quote::quote!(*const u8));
+        fields.push(quote::quote! {
+            #fn_name: extern "C" fn(*mut std::ffi::c_void, #(#arg_ptrs, usize,)* *mut *mut u8) -> usize
+        });
+        set_args.push(quote::quote! {
+            #fn_name: Option<
+                extern "C" fn(
+                    context: *mut std::ffi::c_void,
+                    #(#names: *const u8, #sizes: usize,)*
+                    out_ptr: *mut *mut u8,
+                ) -> usize,
+            >
+        });
+        function_names.push(fn_name);
+
+        let qualified_name = format!("{trait_name}::{fn_name}");
+        let serialize_errors = names
+            .iter()
+            .map(|n| format!("Failed to serialize `{n}` of `{qualified_name}`"));
+        let missing_result =
+            format!("The C++ implementation of `{qualified_name}` threw an exception");
+        let deserialize_error = format!("Failed to deserialize the result of `{qualified_name}`");
+        methods.push(quote::quote! {
+            #sig {
+                #(let #names = bincode::serialize(&#names).expect(#serialize_errors);)*
+                let mut out_ptr = std::ptr::null_mut();
+                let size = (self.#fn_name)(
+                    self.object.context(),
+                    #(#names.as_ptr(), #names.len(),)*
+                    &mut out_ptr,
+                );
+                // SAFETY: the C++ side allocates the result via `{prefix}_alloc_byte_buffer`
+                let result = unsafe { buffi::imported::take_result(out_ptr, size) }
+                    .unwrap_or_else(|| panic!(#missing_result));
+                bincode::deserialize(&result).expect(#deserialize_error)
+            }
+        });
+    }
+
+    let struct_docs =
+        format!("The C++ implementation of [`{trait_name}`], registered via `{set_name}`");
+    let set_docs = [
+        format!(" Register the C++ implementation of `{trait_name}`"),
+        String::new(),
+        String::from(" Passing a null pointer for any function unregisters the current"),
+        String::from(" implementation."),
+        String::new(),
+        String::from(" # Safety"),
+        String::new(),
+        String::from(
+            " `context` needs to stay valid until `drop` is called with it. Each function",
+        ),
+        String::from(
+            " is called with `context` and the serialized arguments and writes its serialized",
+        ),
+        format!(" result to a buffer allocated via `{prefix}_alloc_byte_buffer`. If it does not"),
+        String::from(" write a buffer, the Rust side panics."),
+    ];
+    item.attrs
+        .push(syn::parse_quote!(#[cfg(not(generated_imported_trait))]));
+    let vis = &item.vis;
+
+    Ok(quote::quote! {
+        #item
+
+        #[doc = #struct_docs]
+        #vis struct #imported_name {
+            object: buffi::imported::ImportedObject,
+            #(#fields,)*
+        }
+
+        impl #imported_name {
+            /// The implementation currently registered by the C++ side
+            #vis fn get() -> Option<std::sync::Arc<Self>> {
+                #static_name
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone()
+            }
+        }
+
+        static #static_name: std::sync::RwLock<Option<std::sync::Arc<#imported_name>>> =
+            std::sync::RwLock::new(None);
+
+        impl #trait_name for #imported_name {
+            #(#methods)*
+        }
+
+        #(#[doc = #set_docs])*
+        #[no_mangle]
+        pub unsafe extern "C" fn #set_name(
+            context: *mut std::ffi::c_void,
+            drop: Option<extern "C" fn(context: *mut std::ffi::c_void)>,
+            #(#set_args,)*
+        ) {
+            let imported = match (#(#function_names,)*) {
+                (#(Some(#function_names),)*) => Some(std::sync::Arc::new(#imported_name {
+                    // SAFETY: guaranteed by the caller
+                    object: unsafe { buffi::imported::ImportedObject::new(context, drop) },
+                    #(#function_names,)*
+                })),
+                _ => None,
+            };
+            let previous = std::mem::replace(
+                &mut *#static_name.write().unwrap_or_else(|e| e.into_inner()),
+                imported,
+            );
+            // the previous implementation is released without holding the lock, so that its
+            // destructor can register another implementation
+            std::mem::drop(previous);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_snake_case;
+
+    #[test]
+    fn trait_names_are_converted_to_snake_case() {
+        assert_eq!(to_snake_case("WordFilter"), "word_filter");
+        assert_eq!(to_snake_case("Logger"), "logger");
+    }
+}
@@ -1,15 +1,48 @@
+mod events;
+mod imported;
+mod prefixed;
 mod proc_macro;
 use ::proc_macro::TokenStream;
 
 const FUNCTION_PREFIX: &str = "buffi";
 
+/// Parses the `prefix = "..."` argument, which replaces `buffi` in the names of the extern functions
+fn parse_prefix(meta: &syn::meta::ParseNestedMeta) -> Result<String, syn::Error> {
+    let prefix = meta.value()?.parse::<syn::LitStr>()?;
+    if syn::parse_str::<syn::Ident>(&prefix.value()).is_err() {
+        return Err(syn::Error::new(
+            prefix.span(),
+            "`prefix` needs to be a valid identifier",
+        ));
+    }
+    Ok(prefix.value())
+}
+
+/// Parses the arguments of `imported` and `event`, which only accept a `prefix`
+fn parse_prefix_only(att: TokenStream, macro_name: &str) -> Result<String, syn::Error> {
+    let mut prefix = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("prefix") {
+            prefix = Some(parse_prefix(&meta)?);
+            Ok(())
+        } else {
+            Err(meta.error(format!("unsupported `{macro_name}` argument")))
+        }
+    });
+    syn::parse::Parser::parse(parser, att)?;
+    Ok(prefix.unwrap_or_else(|| FUNCTION_PREFIX.to_owned()))
+}
+
 /// This macro generates a compatible c function for each function in the current impl block
 ///
 /// The generated c function accepts arguments as bincode serialized byte buffers and returns
 /// a bincode serialized byte buffer as well. The generated function handles (de)serialization of
 /// those buffers internally and converts the arguments/results of each function internally. In
 /// addition the generated function contains code to handle panics before reaching the FFI boundary,
-/// blocking async funtcions and converting `color_eyre::Report` error types to a FFI compatible version
+/// blocking async funtcions and converting error types to a FFI compatible version. Errors of the
+/// wrapped function as well as internal errors (like null pointers or panics) are converted into
+/// the `SerializableError` of your crate via `From`, so the generated code does not depend on any
+/// error handling crate
 ///
 /// The generated c function will be named `buffi_{function_name}`. It accepts a pointer to the current
 /// type (`Self`) as first argument. For each other argument of the rust function, two arguments for
@@ -19,13 +52,133 @@ const FUNCTION_PREFIX: &str = "buffi";
 /// returns a `usize` indicating the size of the allocated buffer. This buffer needs to be freed
 /// via `buffi_free_byte_buffer`
 ///
+/// Methods can take `&self`, `&mut self`, `self: Pin<&Self>` or `self: Pin<&mut Self>`. Methods
+/// taking `self: Arc<Self>` get a clone of the `Arc`, the pointer passed to them needs to be
+/// created via `Arc::into_raw`. Methods taking `self: Box<Self>` consume the value, the C++
+/// holder is empty afterwards
+///
+/// Exported functions cannot have type or const parameters, as the extern functions need concrete
+/// types (use `impl Trait` arguments, `instantiate(...)` for generic functions or `instances(...)`
+/// for generic impl blocks instead). Lifetime parameters and where clauses are supported
+///
 /// In addition this macro prepends a `#[tracing::instrument]` attribute to each function
 /// in the current impl block
 ///
+/// If the `with_stats` feature is enabled, the generated functions record their calls and
+/// allocated buffers via `buffi::stats`
+///
+/// If the `debug_json` feature is enabled, an additional `buffi_json_{function_name}` function is
+/// generated for each function that returns its result via `out_ptr` and takes no callbacks. It
+/// encodes the arguments and the result as JSON via `buffi::debug_json` instead of bincode, so
+/// that payloads can be inspected in a debugger or passed from scripting languages
+///
+/// The following arguments are supported:
+///
+/// * `completion_callback`: For each `async` function an additional `buffi_{function_name}_with_callback`
+///   function is generated. Instead of blocking until the future resolves it returns immediately and
+///   later calls the provided `callback` with the given `context` pointer and the serialized result. The
+///   result buffer needs to be freed via `buffi_free_byte_buffer` as well
+/// * `global_runtime`: Async functions are executed on the runtime registered via
+///   `buffi::runtime::set_global` (requires the `runtime` feature of `buffi`) instead of the
+///   `runtime` field of `Self`. This is required for async functions that are not part of an
+///   impl block
+/// * `runtime = "self.rt"`: Async and `blocking` functions are executed on the runtime the given
+///   expression evaluates to instead of `self.runtime`. `self` refers to the value behind `this_ptr`,
+///   the expression needs to evaluate to an `Arc<tokio::runtime::Runtime>` or a `tokio::runtime::Handle`
+///   (or a reference to one of them), e.g. `"self.handle"` or `"self.inner.runtime.handle()"`
+/// * `blocking_strategy = "..."`: How the extern function waits for async functions. `block_on` (default)
+///   calls `block_on` on the runtime, which panics if the extern function is called from a runtime thread.
+///   `block_in_place` additionally wraps that call in `tokio::task::block_in_place` when called from a
+///   runtime thread. `spawn` spawns the future on the runtime and waits for the result on a channel
+/// * `blocking`: Synchronous functions are executed via `spawn_blocking` on the runtime (see
+///   `global_runtime`) while the extern function still waits for the result, so CPU heavy functions
+///   do not occupy the worker threads that execute async functions
+/// * `detect_reentrancy`: Before blocking the calling thread the extern function checks whether that thread
+///   is already waiting for another exported function or belongs to the async runtime. Instead of a
+///   deadlock or panic, such calls return a descriptive error (requires the `runtime` feature of `buffi`)
+/// * `panic_details`: Errors caused by a panic include the location (and backtrace) recorded by the hook
+///   installed via `buffi::install_panic_hook`
+/// * `error_hook = path::to::function`: Panics and internal failures (like null pointers) are passed
+///   to the given `fn(buffi::error_hook::InternalFailure) -> SerializableError` instead of being
+///   converted via `From`, so that they can be mapped to custom error codes
+/// * `prefix = "myapi"`: The extern functions are named `myapi_{function_name}` instead of
+///   `buffi_{function_name}`, so that independently generated APIs can be linked into the same
+///   binary. The generator needs the same prefix via `Config::function_prefix`, all exported items
+///   of a crate need to use the same prefix
+/// * `instances(Name = Type<Concrete>, ...)`: Required for generic impl blocks like
+///   `impl<T: Serialize> Repository<T>`. Each instance gets a `pub type Name = Type<Concrete>` alias,
+///   its own extern functions named `buffi_{name}_{function_name}` (with `name` in snake case) and its
+///   own holder class `Name` on the C++ side
+/// * `instantiate(T = Concrete, ...)`: Required for generic functions like
+///   `fn identity<T: Serialize>(value: T)`. Each instantiation assigns a type to every type
+///   parameter, a parameter that is assigned again starts the next instantiation (e.g.
+///   `instantiate(K = i64, V = String, K = String, V = i64)`). Each instantiation gets a
+///   `pub fn {function_name}_{types}` (with the concrete types in snake case) calling the generic
+///   function, which is exported like any other function. On the C++ side they are overloads named
+///   like the generic function, so the type parameters need to be used by the arguments
+///
+/// Single functions can be configured with a `#[buffi(...)]` attribute. It supports:
+///
+/// * `blocking_strategy = "..."`: Overwrites the blocking strategy for this function
+/// * `blocking`: Execute this synchronous function on the blocking thread pool
+/// * `custom_error`: Transfer the error type of the returned `Result` as it is instead of converting
+///   it to `SerializableError`. The error type needs to implement `Serialize` and
+///   `From<SerializableError>`, which is used to report internal errors like panics
+/// * `direct_return`: For functions returning a primitive type like `Result<i64, E>`. A successful
+///   result is returned directly instead of being serialized into a byte buffer, only errors are
+///   serialized. Use this for latency sensitive functions that are called frequently
+/// * `chunked` or `chunked = <len>`: For functions returning a `Result<Vec<T>, E>`. The returned
+///   elements are serialized in chunks of `len` elements once the C++ side requests them, instead
+///   of serializing everything into a single buffer. The C++ side gets an additional
+///   `{name}_chunked` function that passes each chunk to a callback. Requires a call to
+///   `buffi::export_chunked_results!()` in your API crate
+/// * `raw_bytes`: For functions returning a `Result<Vec<u8>, E>`. The returned bytes are moved
+///   into the output buffer as they are instead of serializing them, only errors are serialized.
+///   The C++ function returns the buffer as `std::vector<uint8_t>` without deserializing it, which
+///   avoids encoding and decoding functions that return a binary blob (like an image)
+/// * `overload = "..."`: The C++ function gets the given name instead of the name of the Rust
+///   function. Functions of the same impl block (or free-standing functions) can share a name
+///   this way, e.g. `find_by_id` and `find_by_name` become overloads of `find`. Their C++
+///   argument types need to differ (`String` and `&str` are both `std::string`), otherwise the
+///   generation fails. The extern functions keep their names. Bindings for languages without
+///   overloads (like embind) still use the Rust name
+/// * `constructor`: For a function like `fn new() -> Self` in an impl block. Instead of a method,
+///   it gets an extern function `buffi_create_{type_name}` returning a pointer to a new `Self`
+///   and `buffi_free_{type_name}` to free it again (with `type_name` in snake case). The C++
+///   holder class gets a static `create()` function, the holder returned by it frees the value
+///   when it is destroyed. A public associated function returning `Self` or `Result<Self, E>`
+///   is exported as constructor without the attribute. Each type has at most one constructor,
+///   with several such functions the attribute selects it and the others are exported as
+///   regular functions. Generic impl blocks do not support constructors. Constructors can take
+///   serialized arguments and return errors like `fn connect(url: String) -> Result<Self, E>`,
+///   the extern function then writes the pointer to a `value_out` argument like a
+///   `direct_return` function and `create(...)` takes the arguments and throws the error
+///
+/// Arguments can be marked with `#[buffi(by_value)]` to pass them as they are instead of
+/// serializing them. This is only supported for owned `#[repr(C)]` types implementing `Copy`
+/// that contain only primitive fields, the generated C++ declaration uses the corresponding
+/// struct of the namespace header.
+///
+/// Arguments like `progress: impl Fn(u64)` or `impl FnMut(Row) -> bool` can be marked with
+/// `#[buffi(callback)]`. The extern function takes a function pointer and a `{name}_context`
+/// pointer for them, the C++ wrapper takes a `std::function` instead. Arguments and results of
+/// the callback are serialized like those of exported functions, so the API crate needs to call
+/// `buffi::export_alloc_byte_buffer!()`. Callbacks are only called while the function runs and
+/// are only supported for synchronous functions that are neither `blocking` nor `chunked`.
+///
+/// `pub const` items can be marked with `#[buffi_macro::exported]` as well, they are emitted as
+/// `constexpr` constants in `api_functions.hpp`. Integers, floats, `bool` and string literals
+/// are supported.
+///
 /// Modules containing a `#[buffi_macro::exported]` call needs to be public!
 #[proc_macro_attribute]
-pub fn exported(_att: TokenStream, item: TokenStream) -> TokenStream {
-    match syn::parse(item.clone()).and_then(|parsed_item| proc_macro::expand(parsed_item, None)) {
+pub fn exported(att: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = proc_macro::ExportArgs::default();
+    let parser = syn::meta::parser(|meta| args.parse(meta));
+    match syn::parse::Parser::parse(parser, att)
+        .and_then(|()| syn::parse(item.clone()))
+        .and_then(|parsed_item| proc_macro::expand(parsed_item, &args))
+    {
         Ok(tokenstream) => tokenstream,
         Err(e) => {
             let mut out = proc_macro2::TokenStream::from(item);
@@ -35,3 +188,99 @@ pub fn exported(_att: TokenStream, item: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+/// This macro makes a trait implementable on the C++ side
+///
+/// The C++ side gets an abstract class with a pure virtual function for each function of the
+/// trait and a `set_{trait_name}` function to register an implementation. The Rust side gets
+/// an `Imported{TraitName}` type implementing the trait, the registered implementation is
+/// returned by `Imported{TraitName}::get()`:
+///
+/// ```rust,ignore
+/// #[buffi_macro::imported]
+/// pub trait WordFilter {
+///     fn accept(&self, word: String) -> bool;
+/// }
+///
+/// if let Some(filter) = ImportedWordFilter::get() {
+///     filter.accept(String::from("word"));
+/// }
+/// ```
+///
+/// Each function needs to take `&self` and can not be async or generic. The arguments and the
+/// result are serialized like those of exported functions, so they need to implement
+/// `Serialize` and `Deserialize`. The C++ implementation needs to be thread-safe, as it may be
+/// called from any thread. If it throws an exception, the Rust side panics.
+///
+/// The registration happens via a generated `buffi_set_{trait_name}` extern function, the
+/// result buffers of the C++ side are allocated via the function generated by
+/// `buffi::export_alloc_byte_buffer!()`, which needs to be called in your API crate as well.
+///
+/// Like `exported`, this macro accepts a `prefix = "myapi"` argument for the extern functions.
+#[proc_macro_attribute]
+pub fn imported(att: TokenStream, item: TokenStream) -> TokenStream {
+    let result = parse_prefix_only(att, "imported").and_then(|prefix| {
+        syn::parse(item.clone()).and_then(|parsed_item| imported::expand(parsed_item, &prefix))
+    });
+    match result {
+        Ok(tokenstream) => tokenstream,
+        Err(e) => {
+            let mut out = proc_macro2::TokenStream::from(item);
+            out.extend(e.to_compile_error());
+            out
+        }
+    }
+    .into()
+}
+
+/// This macro makes a type an event that the Rust side can push to listeners on the C++ side
+///
+/// It implements `buffi::events::Event` for the type, the listeners are notified via
+/// `event.emit()` (or `Type::sink().emit(&event)`):
+///
+/// ```rust,ignore
+/// #[buffi_macro::event]
+/// #[derive(Serialize, Deserialize)]
+/// pub struct Progress {
+///     pub done: u64,
+/// }
+///
+/// Progress { done: 42 }.emit();
+/// ```
+///
+/// The C++ side subscribes via the generated `buffi_subscribe_{event_name}` and
+/// `buffi_unsubscribe_{event_name}` extern functions, usually via the `{EventName}Subscription`
+/// class generated into `events.hpp`, which passes the deserialized events to a
+/// `std::function`. Listeners are called on the thread that emits the event.
+///
+/// Like `exported`, this macro accepts a `prefix = "myapi"` argument for the extern functions.
+#[proc_macro_attribute]
+pub fn event(att: TokenStream, item: TokenStream) -> TokenStream {
+    let result = parse_prefix_only(att, "event").and_then(|prefix| {
+        syn::parse(item.clone()).and_then(|parsed_item| events::expand(parsed_item, &prefix))
+    });
+    match result {
+        Ok(tokenstream) => tokenstream,
+        Err(e) => {
+            let mut out = proc_macro2::TokenStream::from(item);
+            out.extend(e.to_compile_error());
+            out
+        }
+    }
+    .into()
+}
+
+/// Prepends `prefix` and an underscore to the names of the given functions and replaces
+/// `{prefix}` in their docs
+///
+/// This is used by the helper macros of buffi like `buffi::export_chunked_results!()`, so that
+/// their extern functions use the same prefix as the exported functions.
+#[doc(hidden)]
+#[proc_macro]
+pub fn prefixed(item: TokenStream) -> TokenStream {
+    match syn::parse(item) {
+        Ok(input) => prefixed::expand(input),
+        Err(e) => e.to_compile_error(),
+    }
+    .into()
+}
@@ -0,0 +1,81 @@
+//! This file contains the implementation of the `buffi_macro::prefixed!` macro, which is used by
+//! the helper macros of buffi like `buffi::export_chunked_results!()`
+
+/// `prefix = "..."; fn ...` as passed to `buffi_macro::prefixed!`
+pub(crate) struct PrefixedFunctions {
+    prefix: String,
+    functions: Vec<syn::ItemFn>,
+}
+
+impl syn::parse::Parse for PrefixedFunctions {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key = input.parse::<syn::Ident>()?;
+        if key != "prefix" {
+            return Err(syn::Error::new(key.span(), "expected `prefix = \"...\"`"));
+        }
+        input.parse::<syn::Token![=]>()?;
+        let prefix = input.parse::<syn::LitStr>()?;
+        if syn::parse_str::<syn::Ident>(&prefix.value()).is_err() {
+            return Err(syn::Error::new(
+                prefix.span(),
+                "`prefix` needs to be a valid identifier",
+            ));
+        }
+        input.parse::<syn::Token![;]>()?;
+        let mut functions = Vec::new();
+        while !input.is_empty() {
+            functions.push(input.parse()?);
+        }
+        Ok(Self {
+            prefix: prefix.value(),
+            functions,
+        })
+    }
+}
+
+/// Prepends the prefix to the name of each function and replaces `{prefix}` in their docs
+pub(crate) fn expand(input: PrefixedFunctions) -> proc_macro2::TokenStream {
+    let PrefixedFunctions {
+        prefix,
+        mut functions,
+    } = input;
+    for function in &mut functions {
+        function.sig.ident = quote::format_ident!("{}_{}", prefix, function.sig.ident);
+        for attr in &mut function.attrs {
+            if let syn::Meta::NameValue(ref mut doc) = attr.meta {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(ref mut text),
+                    ..
+                }) = doc.value
+                {
+                    *text =
+                        syn::LitStr::new(&text.value().replace("{prefix}", &prefix), text.span());
+                }
+            }
+        }
+    }
+    quote::quote!(#(#functions)*)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn functions_and_docs_get_the_prefix() {
+        let input: PrefixedFunctions = syn::parse_quote! {
+            prefix = "myapi";
+            /// Needs to be freed via `{prefix}_free_byte_buffer`
+            #[no_mangle]
+            pub extern "C" fn alloc_byte_buffer(size: usize) -> *mut u8 {
+                std::ptr::null_mut()
+            }
+        };
+        let expanded = expand(input).to_string();
+        assert!(expanded.contains("fn myapi_alloc_byte_buffer"));
+        assert!(expanded.contains("Needs to be freed via `myapi_free_byte_buffer`"));
+
+        let invalid = syn::parse_str::<PrefixedFunctions>("prefix = \"my-api\"; fn f() {}");
+        assert!(invalid.is_err());
+    }
+}
@@ -5,41 +5,1400 @@ use syn::spanned::Spanned;
 
 use crate::FUNCTION_PREFIX;
 
-// the prefix parameter is here in preparation for whenever we want to customize that as well
-pub(crate) fn expand(
-    impl_item: syn::Item,
+/// Arguments passed to `#[buffi_macro::exported(...)]`
+#[derive(Default)]
+pub(crate) struct ExportArgs {
+    /// Generate an additional non-blocking extern function for each `async` function
+    completion_callback: bool,
+    /// Use the runtime registered via `buffi::runtime::set_global` instead of `self.runtime`
+    global_runtime: bool,
+    /// The runtime of `self` (an `Arc<Runtime>` or a `Handle`) instead of `self.runtime`
+    runtime: Option<syn::Expr>,
+    /// Default blocking strategy for all async functions
+    blocking_strategy: Option<BlockingStrategy>,
+    /// Run all synchronous functions on the blocking thread pool of the runtime
+    blocking: bool,
+    /// Return an error instead of blocking a thread that is not allowed to block
+    detect_reentrancy: bool,
+    /// Add the details recorded by `buffi::install_panic_hook` to errors caused by a panic
+    panic_details: bool,
+    /// The concrete types a generic impl block is exported for, each with the name of the
+    /// type alias that is generated for it
+    instances: Vec<(syn::Ident, syn::Type)>,
+    /// The concrete types a generic function is exported for, each instantiation assigns a
+    /// type to every type parameter
+    instantiations: Vec<Vec<(syn::Ident, syn::Type)>>,
+    /// A function converting a `buffi::error_hook::InternalFailure` into the
+    /// `SerializableError` of the crate
+    error_hook: Option<syn::Path>,
+    /// The prefix of the generated extern functions instead of `buffi`
     prefix: Option<String>,
+}
+
+impl ExportArgs {
+    pub(crate) fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> Result<(), syn::Error> {
+        if meta.path.is_ident("completion_callback") {
+            self.completion_callback = true;
+            Ok(())
+        } else if meta.path.is_ident("global_runtime") {
+            self.global_runtime = true;
+            Ok(())
+        } else if meta.path.is_ident("runtime") {
+            let expr = meta.value()?.parse::<syn::LitStr>()?;
+            self.runtime = Some(expr.parse::<syn::Expr>()?);
+            Ok(())
+        } else if meta.path.is_ident("blocking_strategy") {
+            self.blocking_strategy = Some(BlockingStrategy::parse(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("blocking") {
+            self.blocking = true;
+            Ok(())
+        } else if meta.path.is_ident("detect_reentrancy") {
+            self.detect_reentrancy = true;
+            Ok(())
+        } else if meta.path.is_ident("panic_details") {
+            self.panic_details = true;
+            Ok(())
+        } else if meta.path.is_ident("error_hook") {
+            self.error_hook = Some(meta.value()?.parse::<syn::Path>()?);
+            Ok(())
+        } else if meta.path.is_ident("prefix") {
+            self.prefix = Some(crate::parse_prefix(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("instances") {
+            meta.parse_nested_meta(|instance| {
+                let name = instance
+                    .path
+                    .get_ident()
+                    .cloned()
+                    .ok_or_else(|| instance.error("expected the name of the instance"))?;
+                let tpe = instance.value()?.parse::<syn::Type>()?;
+                self.instances.push((name, tpe));
+                Ok(())
+            })
+        } else if meta.path.is_ident("instantiate") {
+            // `T = i64, T = String` are two instantiations, a parameter that is already
+            // assigned starts the next one
+            meta.parse_nested_meta(|param| {
+                let name = param
+                    .path
+                    .get_ident()
+                    .cloned()
+                    .ok_or_else(|| param.error("expected the name of a type parameter"))?;
+                let tpe = param.value()?.parse::<syn::Type>()?;
+                match self.instantiations.last_mut() {
+                    Some(current) if current.iter().all(|(n, _)| *n != name) => {
+                        current.push((name, tpe));
+                    }
+                    _ => self.instantiations.push(vec![(name, tpe)]),
+                }
+                Ok(())
+            })
+        } else {
+            Err(meta.error("unsupported `exported` argument"))
+        }
+    }
+}
+
+/// Arguments passed to `#[buffi(...)]` on a single function
+#[derive(Default)]
+struct FunctionArgs {
+    /// Overwrites the blocking strategy passed to `#[buffi_macro::exported(...)]`
+    blocking_strategy: Option<BlockingStrategy>,
+    /// Run this synchronous function on the blocking thread pool of the runtime
+    blocking: bool,
+    /// Transfer the error type of the returned `Result` instead of converting it
+    /// to `SerializableError`
+    custom_error: bool,
+    /// Return the primitive value of a successful call directly instead of serializing it
+    direct_return: bool,
+    /// Transfer the returned `Vec` in chunks of the given number of elements
+    chunked: Option<proc_macro2::TokenStream>,
+    /// Write the bytes of a successful call to the output buffer without serializing them
+    raw_bytes: bool,
+    /// The name of the C++ function, shared by all overloads
+    overload: Option<String>,
+    /// This `fn new() -> Self` gets an extern function that creates `Self` on the heap and
+    /// one that frees it again
+    constructor: bool,
+}
+
+impl FunctionArgs {
+    fn from_attributes(attrs: &[syn::Attribute]) -> Result<Self, syn::Error> {
+        let mut args = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("buffi")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("blocking_strategy") {
+                    args.blocking_strategy = Some(BlockingStrategy::parse(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("blocking") {
+                    args.blocking = true;
+                    Ok(())
+                } else if meta.path.is_ident("custom_error") {
+                    args.custom_error = true;
+                    Ok(())
+                } else if meta.path.is_ident("direct_return") {
+                    args.direct_return = true;
+                    Ok(())
+                } else if meta.path.is_ident("chunked") {
+                    args.chunked = Some(if meta.input.peek(syn::Token![=]) {
+                        let chunk_len = meta.value()?.parse::<syn::LitInt>()?;
+                        if chunk_len.base10_parse::<usize>()? == 0 {
+                            return Err(syn::Error::new(
+                                chunk_len.span(),
+                                "chunks need to contain at least one element",
+                            ));
+                        }
+                        quote::quote!(#chunk_len)
+                    } else {
+                        quote::quote!(buffi::chunked::DEFAULT_CHUNK_LEN)
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("raw_bytes") {
+                    args.raw_bytes = true;
+                    Ok(())
+                } else if meta.path.is_ident("overload") {
+                    let name = meta.value()?.parse::<syn::LitStr>()?;
+                    if syn::parse_str::<syn::Ident>(&name.value()).is_err() {
+                        return Err(syn::Error::new(
+                            name.span(),
+                            "the overload name needs to be a valid identifier",
+                        ));
+                    }
+                    args.overload = Some(name.value());
+                    Ok(())
+                } else if meta.path.is_ident("constructor") {
+                    args.constructor = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported `buffi` argument"))
+                }
+            })?;
+        }
+        Ok(args)
+    }
+}
+
+/// How the extern function waits for the result of an async function
+#[derive(Clone, Copy, Default)]
+enum BlockingStrategy {
+    /// Call `block_on` on the runtime, this panics if called from within a runtime thread
+    #[default]
+    BlockOn,
+    /// Like `BlockOn`, but wrapped in `tokio::task::block_in_place` if called from within
+    /// a runtime thread (requires a multi-threaded runtime)
+    BlockInPlace,
+    /// Spawn the future on the runtime and wait on a channel for the result
+    Spawn,
+}
+
+impl BlockingStrategy {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> Result<Self, syn::Error> {
+        let value = meta.value()?.parse::<syn::LitStr>()?;
+        match value.value().as_str() {
+            "block_on" => Ok(Self::BlockOn),
+            "block_in_place" => Ok(Self::BlockInPlace),
+            "spawn" => Ok(Self::Spawn),
+            _ => Err(syn::Error::new(
+                value.span(),
+                "expected one of `block_on`, `block_in_place` or `spawn`",
+            )),
+        }
+    }
+}
+
+/// An argument that is passed by reference or as `impl Trait` to the rust function
+///
+/// Such arguments are deserialized into an owned value first
+enum BorrowedArgument {
+    /// `&T` is deserialized as owned `T` and passed as `&value`
+    Ref(proc_macro2::TokenStream),
+    /// `Option<&T>` is deserialized as `Option<T>`. The flag indicates whether the
+    /// value needs to be passed via `as_deref` (`str` and slices) instead of `as_ref`
+    OptionRef(proc_macro2::TokenStream, bool),
+    /// `impl Into<T>`, `impl AsRef<str>` and `impl AsRef<[T]>` are deserialized as `T`,
+    /// `String` and `Vec<T>` and passed as they are
+    ImplTrait(proc_macro2::TokenStream),
+}
+
+impl BorrowedArgument {
+    fn from_type(tpe: &syn::Type) -> Option<Self> {
+        match tpe {
+            syn::Type::Reference(r) if r.mutability.is_none() => {
+                Some(Self::Ref(owned_type(&r.elem).0))
+            }
+            syn::Type::ImplTrait(i) => impl_trait_type(i).map(Self::ImplTrait),
+            syn::Type::Path(p) => {
+                let segment = p.path.segments.last()?;
+                if segment.ident != "Option" {
+                    return None;
+                }
+                let syn::PathArguments::AngleBracketed(ref args) = segment.arguments else {
+                    return None;
+                };
+                match args.args.first()? {
+                    syn::GenericArgument::Type(syn::Type::Reference(r))
+                        if r.mutability.is_none() =>
+                    {
+                        let (owned, deref) = owned_type(&r.elem);
+                        Some(Self::OptionRef(owned, deref))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn owned_type(&self) -> proc_macro2::TokenStream {
+        match self {
+            Self::Ref(t) => t.clone(),
+            Self::OptionRef(t, _) => quote::quote!(Option<#t>),
+            Self::ImplTrait(t) => t.clone(),
+        }
+    }
+
+    fn pass(&self, n: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            Self::Ref(_) => quote::quote!(&#n),
+            Self::OptionRef(_, true) => quote::quote!(#n.as_deref()),
+            Self::OptionRef(_, false) => quote::quote!(#n.as_ref()),
+            Self::ImplTrait(_) => quote::quote!(#n),
+        }
+    }
+}
+
+/// The type an `impl Trait` argument is deserialized as, `None` if there is no such type
+fn impl_trait_type(tpe: &syn::TypeImplTrait) -> Option<proc_macro2::TokenStream> {
+    // lifetime bounds like `+ 'static` do not change the type
+    let mut traits = tpe.bounds.iter().filter_map(|bound| match bound {
+        syn::TypeParamBound::Trait(t) => Some(t),
+        _ => None,
+    });
+    let (Some(trait_), None) = (traits.next(), traits.next()) else {
+        return None;
+    };
+    let segment = trait_.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(ref args) = segment.arguments else {
+        return None;
+    };
+    let mut args = args.args.iter();
+    let (Some(syn::GenericArgument::Type(arg)), None) = (args.next(), args.next()) else {
+        return None;
+    };
+    if segment.ident == "Into" {
+        return Some(quote::quote!(#arg));
+    }
+    // `String` and `Vec<T>` implement `AsRef<str>` and `AsRef<[T]>`
+    let (owned, is_borrowed) = owned_type(arg);
+    (segment.ident == "AsRef" && is_borrowed).then_some(owned)
+}
+
+/// Rejects argument types that cannot be deserialized, like `dyn Trait` or unsupported `impl Trait`
+fn check_trait_type(
+    sig: &syn::Signature,
+    name: &syn::Ident,
+    tpe: &syn::Type,
+) -> Result<(), syn::Error> {
+    let message = match tpe {
+        syn::Type::ImplTrait(i) if impl_trait_type(i).is_none() => {
+            "only `impl Into<T>`, `impl AsRef<str>` and `impl AsRef<[T]>` are supported"
+        }
+        syn::Type::TraitObject(_) => "`dyn Trait` cannot be deserialized",
+        syn::Type::Reference(r) if matches!(*r.elem, syn::Type::TraitObject(_)) => {
+            "`dyn Trait` cannot be deserialized"
+        }
+        _ => return Ok(()),
+    };
+    Err(syn::Error::new(
+        tpe.span(),
+        format!(
+            "argument `{name}` of API function '{}' has an unsupported type: {message}, \
+             use a concrete type instead",
+            sig.ident
+        ),
+    ))
+}
+
+/// The name of the extern argument for `arg` of `sig`
+///
+/// Bindings like `mut data` keep their name, the mutability only matters inside the exported
+/// function. Arguments bound by a pattern like `(x, y)` or `_` are named `arg{idx}`, where `idx` counts
+/// the arguments without the receiver. The generator derives the same names.
+fn argument_name(sig: &syn::Signature, arg: &syn::PatType) -> syn::Ident {
+    if let syn::Pat::Ident(ref i) = *arg.pat {
+        return i.ident.clone();
+    }
+    let idx = sig
+        .inputs
+        .iter()
+        .filter_map(|a| match a {
+            syn::FnArg::Typed(t) => Some(t),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .position(|t| std::ptr::eq(t, arg))
+        .expect("`arg` is an argument of `sig`");
+    syn::Ident::new(&format!("arg{idx}"), arg.pat.span())
+}
+
+/// How an argument is passed to the extern function, see `#[buffi(...)]` on arguments
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum ArgumentKind {
+    /// As pointer to a serialized buffer and its size
+    #[default]
+    Serialized,
+    /// `#[buffi(by_value)]`: As it is
+    ByValue,
+    /// `#[buffi(callback)]`: As function pointer and a context pointer passed back to it
+    Callback,
+}
+
+/// How `arg` is passed, depending on the `#[buffi(...)]` attribute of the argument
+fn argument_kind(arg: &syn::PatType) -> Result<ArgumentKind, syn::Error> {
+    let mut kind = ArgumentKind::Serialized;
+    for attr in arg.attrs.iter().filter(|a| a.path().is_ident("buffi")) {
+        attr.parse_nested_meta(|meta| {
+            let next = if meta.path.is_ident("by_value") {
+                ArgumentKind::ByValue
+            } else if meta.path.is_ident("callback") {
+                ArgumentKind::Callback
+            } else {
+                return Err(meta.error("expected `by_value` or `callback`"));
+            };
+            if kind != ArgumentKind::Serialized && kind != next {
+                return Err(meta.error("cannot use `by_value` and `callback` at the same time"));
+            }
+            kind = next;
+            Ok(())
+        })?;
+    }
+    Ok(kind)
+}
+
+/// The arguments and the result of a callback like `impl Fn(u64) -> bool` or `impl FnMut(u64)`
+fn callback_signature(tpe: &syn::Type) -> Option<&syn::ParenthesizedGenericArguments> {
+    let syn::Type::ImplTrait(i) = tpe else {
+        return None;
+    };
+    // lifetime bounds like `+ '_` do not change the signature
+    let mut traits = i.bounds.iter().filter_map(|bound| match bound {
+        syn::TypeParamBound::Trait(t) => Some(t),
+        _ => None,
+    });
+    let (Some(trait_), None) = (traits.next(), traits.next()) else {
+        return None;
+    };
+    let segment = trait_.path.segments.last()?;
+    match segment.arguments {
+        syn::PathArguments::Parenthesized(ref args)
+            if segment.ident == "Fn" || segment.ident == "FnMut" =>
+        {
+            Some(args)
+        }
+        _ => None,
+    }
+}
+
+/// Wraps the function pointer `name` (and `{name}_context`) passed for a `#[buffi(callback)]`
+/// argument in a closure, which serializes the arguments and deserializes the result
+fn callback_closure(
+    sig: &syn::Signature,
+    name: &syn::Ident,
+    callback: &syn::ParenthesizedGenericArguments,
+    null_callback: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let context = quote::format_ident!("{}_context", name);
+    let args = (0..callback.inputs.len())
+        .map(|idx| quote::format_ident!("arg{}", idx))
+        .collect::<Vec<_>>();
+    let types = callback.inputs.iter();
+    let output = match callback.output {
+        syn::ReturnType::Default => quote::quote!(()),
+        syn::ReturnType::Type(_, ref tpe) => quote::quote!(#tpe),
+    };
+    let qualified_name = format!("`{name}` of `{}`", sig.ident);
+    let serialize_errors = args
+        .iter()
+        .map(|arg| format!("Failed to serialize `{arg}` for the callback {qualified_name}"));
+    let missing_result = format!("The C++ callback {qualified_name} threw an exception");
+    let deserialize_error =
+        format!("Failed to deserialize the result of the callback {qualified_name}");
+    quote::quote! {
+        let Some(#name) = #name else {
+            return Err(#null_callback);
+        };
+        let #name = move |#(#args: #types),*| {
+            #(let #args = bincode::serialize(&#args).expect(#serialize_errors);)*
+            let mut out_ptr = std::ptr::null_mut();
+            let size = #name(#context, #(#args.as_ptr(), #args.len(),)* &mut out_ptr);
+            // SAFETY: the C++ side allocates the result via `{prefix}_alloc_byte_buffer`
+            let result = unsafe { buffi::imported::take_result(out_ptr, size) }
+                .unwrap_or_else(|| panic!(#missing_result));
+            bincode::deserialize::<#output>(&result).expect(#deserialize_error)
+        };
+    }
+}
+
+/// The `T` of a returned `Result<T, E>`
+fn result_ok_type(output: &syn::ReturnType) -> Option<&syn::Path> {
+    let syn::ReturnType::Type(_, tpe) = output else {
+        return None;
+    };
+    let syn::Type::Path(p) = &**tpe else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(ref args) = p.path.segments.last()?.arguments else {
+        return None;
+    };
+    let Some(syn::GenericArgument::Type(syn::Type::Path(ok))) = args.args.first() else {
+        return None;
+    };
+    Some(&ok.path)
+}
+
+/// The `T` of a returned `Result<T, E>` if it is a primitive type that can be returned
+/// without serializing it
+fn direct_return_type(output: &syn::ReturnType) -> Option<&syn::Ident> {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64",
+    ];
+    let ident = result_ok_type(output)?.get_ident()?;
+    PRIMITIVES.iter().any(|p| ident == p).then_some(ident)
+}
+
+/// Whether the function returns a `Result<Vec<T>, E>`, which can be transferred in chunks
+fn returns_vec(output: &syn::ReturnType) -> bool {
+    result_ok_type(output)
+        .and_then(|p| p.segments.last())
+        .is_some_and(|s| s.ident == "Vec")
+}
+
+/// Whether the function returns a `Result<Vec<u8>, E>`, which can be transferred as raw bytes
+fn returns_bytes(output: &syn::ReturnType) -> bool {
+    let Some(vec) = result_ok_type(output)
+        .and_then(|p| p.segments.last())
+        .filter(|s| s.ident == "Vec")
+    else {
+        return false;
+    };
+    let syn::PathArguments::AngleBracketed(ref args) = vec.arguments else {
+        return false;
+    };
+    matches!(
+        args.args.first(),
+        Some(syn::GenericArgument::Type(syn::Type::Path(p))) if p.path.is_ident("u8")
+    )
+}
+
+/// The owned counterpart of a borrowed type and whether it needs to be dereferenced
+/// to get the borrowed type again
+fn owned_type(tpe: &syn::Type) -> (proc_macro2::TokenStream, bool) {
+    match tpe {
+        syn::Type::Path(p) if p.path.is_ident("str") => (quote::quote!(String), true),
+        syn::Type::Slice(s) => {
+            let elem = &s.elem;
+            (quote::quote!(Vec<#elem>), true)
+        }
+        t => (quote::quote!(#t), false),
+    }
+}
+
+/// `Self` or `Wrapper<Self>` (with the given wrapper, like `Arc`)
+fn is_wrapped_self(tpe: &syn::Type, wrapper: Option<&str>) -> bool {
+    let syn::Type::Path(p) = tpe else {
+        return false;
+    };
+    let Some(wrapper) = wrapper else {
+        return p.path.is_ident("Self");
+    };
+    let Some(last) = p.path.segments.last() else {
+        return false;
+    };
+    let syn::PathArguments::AngleBracketed(ref args) = last.arguments else {
+        return false;
+    };
+    last.ident == wrapper
+        && matches!(
+            args.args.first(),
+            Some(syn::GenericArgument::Type(inner)) if args.args.len() == 1 && is_wrapped_self(inner, None)
+        )
+}
+
+/// How the extern function turns `this_ptr` into the receiver of the wrapped method
+///
+/// Next to references, `self: Arc<Self>` (the pointer needs to be created via
+/// `Arc::into_raw`, the reference count is incremented for the call), `self: Box<Self>` (the
+/// value is consumed by the call) and pinned references are supported.
+fn receiver_from_pointer(receiver: &syn::Receiver) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let tpe = &*receiver.ty;
+    let pinned = |tpe: &syn::Type| -> Option<Option<syn::token::Mut>> {
+        let syn::Type::Path(p) = tpe else {
+            return None;
+        };
+        let last = p.path.segments.last().filter(|s| s.ident == "Pin")?;
+        let syn::PathArguments::AngleBracketed(ref args) = last.arguments else {
+            return None;
+        };
+        match args.args.first() {
+            Some(syn::GenericArgument::Type(syn::Type::Reference(r)))
+                if args.args.len() == 1 && is_wrapped_self(&r.elem, None) =>
+            {
+                Some(r.mutability)
+            }
+            _ => None,
+        }
+    };
+    match tpe {
+        syn::Type::Reference(r) if is_wrapped_self(&r.elem, None) => {
+            let mutability = r.mutability;
+            Ok(quote::quote!(&#mutability *this_ptr))
+        }
+        _ if is_wrapped_self(tpe, Some("Arc")) => Ok(quote::quote! {{
+            std::sync::Arc::increment_strong_count(this_ptr);
+            std::sync::Arc::from_raw(this_ptr)
+        }}),
+        _ if is_wrapped_self(tpe, Some("Box")) => Ok(quote::quote!(Box::from_raw(this_ptr))),
+        _ => match pinned(tpe) {
+            Some(mutability) => {
+                Ok(quote::quote!(std::pin::Pin::new_unchecked(&#mutability *this_ptr)))
+            }
+            None => Err(syn::Error::new(
+                receiver.span(),
+                "unsupported receiver, expected `&self`, `&mut self`, `self: Arc<Self>`, \
+                 `self: Box<Self>` or `self: Pin<&mut Self>`",
+            )),
+        },
+    }
+}
+
+pub(crate) fn expand(
+    mut impl_item: syn::Item,
+    args: &ExportArgs,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
-    let prefix = prefix.unwrap_or_else(|| FUNCTION_PREFIX.to_string());
+    if let (true, Some(runtime)) = (args.global_runtime, &args.runtime) {
+        return Err(syn::Error::new(
+            runtime.span(),
+            "cannot use `global_runtime` and `runtime` at the same time",
+        ));
+    }
+    let prefix = args.prefix.as_deref().unwrap_or(FUNCTION_PREFIX).to_owned();
     let mut exports = Vec::new();
-    if cfg!(feature = "with_c_api") {
-        if let syn::Item::Impl(ref impl_item) = impl_item {
-            generate_exported_functions_for_impl_block(impl_item, &mut exports, prefix)?;
-        } else if let syn::Item::Fn(ref fn_item) = impl_item {
-            let docs = fn_item.attrs.iter().filter(|a| a.path().is_ident("doc"));
+    let mut constructors = None;
+    let mut instantiations = Vec::new();
+    if let syn::Item::Impl(ref mut impl_item) = impl_item {
+        if let Some((param, _)) = args.instantiations.first().and_then(|i| i.first()) {
+            return Err(syn::Error::new(
+                param.span(),
+                "`instantiate` is only supported for generic functions, \
+                 use `instances` for generic impl blocks",
+            ));
+        }
+        constructors = generate_constructors(impl_item, &mut exports, args, &prefix)?;
+        if !args.instances.is_empty() || !impl_item.generics.params.is_empty() {
+            generate_instances(impl_item, &mut exports, args, &prefix)?;
+        } else if cfg!(feature = "with_c_api") {
+            generate_exported_functions_for_impl_block(impl_item, &mut exports, args, prefix)?;
+        }
+    } else if let (syn::Item::Fn(ref fn_item), false) = (&impl_item, args.instantiations.is_empty())
+    {
+        instantiations = generate_instantiations(fn_item, &mut exports, args, &prefix)?;
+    } else if cfg!(feature = "with_c_api") {
+        if let syn::Item::Fn(ref fn_item) = impl_item {
             generate_exported_function(
                 &fn_item.sig,
                 Vec::new(),
                 &mut exports,
-                docs,
+                &fn_item.attrs,
                 fn_item.span(),
+                args,
                 prefix,
             )?;
+        } else if let syn::Item::Const(_) = impl_item {
+            // constants are only marked, so that the generator emits them as `constexpr`
         } else {
-            panic!("Unknown")
+            return Err(syn::Error::new(
+                impl_item.span(),
+                "`#[buffi_macro::exported]` is not supported here, only functions, impl blocks \
+                 and constants can be exported",
+            ));
         };
     }
+    strip_buffi_attributes(&mut impl_item);
+    // only the instantiations of a generic function are exported
+    let extern_impl_marker = instantiations
+        .is_empty()
+        .then(|| quote::quote!(#[cfg(not(generated_extern_impl))]));
     Ok(quote::quote! {
-        #[cfg(not(generated_extern_impl))]
+        #extern_impl_marker
         #impl_item
 
+        #constructors
+
+        #(#instantiations)*
+
         #(#exports)*
     })
 }
 
+/// Whether `output` is `Self` (`Some(false)`) or a `Result<Self, E>` (`Some(true)`)
+fn constructor_output(output: &syn::ReturnType, self_ty: &syn::Type) -> Option<bool> {
+    let is_self = |tpe: &syn::Type| {
+        matches!(tpe, syn::Type::Path(p) if p.path.is_ident("Self")) || tpe == self_ty
+    };
+    let syn::ReturnType::Type(_, tpe) = output else {
+        return None;
+    };
+    if is_self(tpe) {
+        return Some(false);
+    }
+    let syn::Type::Path(p) = &**tpe else {
+        return None;
+    };
+    let segment = p.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(ref args) = segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(t) if segment.ident == "Result" && is_self(t) => Some(true),
+        _ => None,
+    }
+}
+
+/// Whether `item` is marked with `#[buffi(constructor)]`
+fn is_marked_constructor(item: &syn::ImplItem) -> bool {
+    let syn::ImplItem::Fn(m) = item else {
+        return false;
+    };
+    FunctionArgs::from_attributes(&m.attrs).is_ok_and(|args| args.constructor)
+}
+
+/// Whether `item` is a public associated function returning `Self` (or a `Result<Self, E>`),
+/// which is exported as constructor without being marked
+fn is_implicit_constructor(item: &syn::ImplItem, self_ty: &syn::Type) -> bool {
+    let syn::ImplItem::Fn(m) = item else {
+        return false;
+    };
+    matches!(m.vis, syn::Visibility::Public(_))
+        && !matches!(m.sig.inputs.first(), Some(syn::FnArg::Receiver(_)))
+        && constructor_output(&m.sig.output, self_ty).is_some()
+}
+
+/// Moves the constructor of `impl_item` out of it into an impl block of its own, which is not
+/// exported, and generates the extern functions creating and freeing `Self` for it
+///
+/// The constructor is the function marked with `#[buffi(constructor)]`, otherwise the only
+/// public associated function returning `Self` (or a `Result<Self, E>`). With a marked function
+/// the other functions returning `Self` are exported as regular functions, without it several
+/// of them are an error. Functions of generic impl blocks are only constructors if they are
+/// marked, which is not supported.
+///
+/// For a type `Name` these are `{prefix}_create_{name}` and `{prefix}_free_{name}` (with `name`
+/// in snake case), the generator emits a static `create(...)` function and a destructor freeing
+/// the created value for the holder class. A constructor like `fn new() -> Self` is called as
+/// it is and returns null if it panics. Constructors with arguments or returning a
+/// `Result<Self, E>` get the serialized arguments and report errors like functions marked with
+/// `#[buffi(direct_return)]`, they write the created value to `value_out`.
+fn generate_constructors(
+    impl_item: &mut syn::ItemImpl,
+    exports: &mut Vec<proc_macro2::TokenStream>,
+    args: &ExportArgs,
+    prefix: &str,
+) -> Result<Option<syn::ItemImpl>, syn::Error> {
+    let self_ty = impl_item.self_ty.clone();
+    let mut marked = impl_item
+        .items
+        .iter()
+        .filter(|item| is_marked_constructor(item));
+    let has_marked = marked.next().is_some();
+    if let Some(other) = marked.next() {
+        return Err(syn::Error::new(
+            other.span(),
+            "only one function of a type can be marked with `#[buffi(constructor)]`",
+        ));
+    }
+    let is_generic = !impl_item.generics.params.is_empty();
+    if has_marked && is_generic {
+        return Err(syn::Error::new(
+            impl_item.generics.span(),
+            "constructors of generic impl blocks are not supported",
+        ));
+    }
+    let is_constructor = |item: &syn::ImplItem| {
+        if has_marked {
+            is_marked_constructor(item)
+        } else {
+            !is_generic && is_implicit_constructor(item, &self_ty)
+        }
+    };
+    let (constructors, items) = std::mem::take(&mut impl_item.items)
+        .into_iter()
+        .partition::<Vec<_>, _>(is_constructor);
+    impl_item.items = items;
+    let mut constructors = constructors.into_iter().filter_map(|item| match item {
+        syn::ImplItem::Fn(m) => Some(m),
+        _ => None,
+    });
+    let Some(mut constructor) = constructors.next() else {
+        return Ok(None);
+    };
+    if let Some(error) = constructors
+        .map(|other| {
+            syn::Error::new(
+                other.sig.span(),
+                "only one function of a type returning `Self` is exported as constructor, \
+                 mark it with `#[buffi(constructor)]` to export the others as functions",
+            )
+        })
+        .reduce(|mut error, other| {
+            error.combine(other);
+            error
+        })
+    {
+        return Err(error);
+    }
+    let sig = &constructor.sig;
+    let has_receiver = matches!(sig.inputs.first(), Some(syn::FnArg::Receiver(_)));
+    let Some(returns_result) = constructor_output(&sig.output, &self_ty)
+        .filter(|_| !has_receiver && sig.asyncness.is_none() && sig.generics.params.is_empty())
+    else {
+        return Err(syn::Error::new(
+            sig.span(),
+            "constructors need to be synchronous functions like `fn new() -> Self` or \
+             `fn connect(url: String) -> Result<Self, E>`",
+        ));
+    };
+    for arg in &sig.inputs {
+        if let syn::FnArg::Typed(t) = arg {
+            if argument_kind(t)? != ArgumentKind::Serialized {
+                return Err(syn::Error::new(
+                    t.span(),
+                    "arguments of constructors are always serialized",
+                ));
+            }
+        }
+    }
+    // `self: Arc<Self>` methods expect a pointer created via `Arc::into_raw`
+    let arc_method = impl_item.items.iter().find_map(|item| match item {
+        syn::ImplItem::Fn(m) => match m.sig.inputs.first() {
+            Some(syn::FnArg::Receiver(r)) if is_wrapped_self(&r.ty, Some("Arc")) => Some(r),
+            _ => None,
+        },
+        _ => None,
+    });
+    if let Some(receiver) = arc_method {
+        return Err(syn::Error::new(
+            receiver.span(),
+            "constructors are not supported for types with `self: Arc<Self>` methods",
+        ));
+    }
+    let Some(type_name) = (match &*self_ty {
+        syn::Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }) else {
+        return Err(syn::Error::new(
+            self_ty.span(),
+            "constructors are only supported for named types",
+        ));
+    };
+
+    let snake_name = crate::imported::to_snake_case(&type_name);
+    let create_name = quote::format_ident!("{}_create_{}", prefix, snake_name);
+    let free_name = quote::format_ident!("{}_free_{}", prefix, snake_name);
+    let fn_name = &sig.ident;
+    // `#[cfg]` attributes of the impl block apply to the constructor as well
+    let cfgs = impl_item
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .cloned()
+        .collect::<Vec<_>>();
+    let free_docs = [
+        format!(" Frees a `{type_name}` created via `{create_name}`, null is ignored"),
+        String::new(),
+        String::from(" # Safety"),
+        format!(" `ptr` needs to be returned by `{create_name}` and must not be used afterwards"),
+    ];
+    let create = if sig.inputs.is_empty() && !returns_result {
+        let create_docs = [
+            format!(" Creates a `{type_name}` via `{type_name}::{fn_name}`"),
+            String::new(),
+            format!(" It needs to be freed via `{free_name}`, null is returned if the constructor"),
+            String::from(" panics."),
+        ];
+        quote::quote! {
+            #(#[doc = #create_docs])*
+            #(#cfgs)*
+            #[no_mangle]
+            pub extern "C" fn #create_name() -> *mut #self_ty {
+                std::panic::catch_unwind(|| Box::into_raw(Box::new(<#self_ty>::#fn_name())))
+                    .unwrap_or(std::ptr::null_mut())
+            }
+        }
+    } else {
+        let create_docs = [
+            format!(" Creates a `{type_name}` via `{type_name}::{fn_name}`"),
+            String::new(),
+            String::from(
+                " On success `value_out` points to the created value, which needs to be freed",
+            ),
+            format!(" via `{free_name}`. Otherwise `error_out` points to the serialized error,"),
+            format!(" which needs to be freed via `{prefix}_free_byte_buffer`."),
+            String::new(),
+            String::from(" # Safety"),
+            String::from(" Each argument needs to point to a serialized buffer of the given size."),
+        ];
+        let deserialize_error = args.error_hook.as_ref().map(|_| {
+            let error = to_internal_error(args, "Serialization", quote::quote!(e));
+            quote::quote!(.map_err(|e| #error))
+        });
+        let mut parameters = Vec::new();
+        let mut deserialized_args = Vec::new();
+        let mut call_args = Vec::new();
+        for arg in &sig.inputs {
+            let syn::FnArg::Typed(t) = arg else {
+                unreachable!("constructors do not have a receiver")
+            };
+            let n = argument_name(sig, t);
+            let n_size = syn::Ident::new(&format!("{n}_size"), n.span());
+            let borrowed = BorrowedArgument::from_type(&t.ty);
+            let owned_type = borrowed.as_ref().map(|b| {
+                let owned = b.owned_type();
+                quote::quote!(: #owned)
+            });
+            parameters.push(quote::quote!(#n: *const u8, #n_size: usize));
+            deserialized_args.push(quote::quote! {
+                let slice = if #n.is_null() {
+                    &[]
+                } else {
+                    unsafe {
+                        std::slice::from_raw_parts(#n, #n_size)
+                    }
+                };
+                let #n #owned_type = bincode::deserialize(slice)#deserialize_error?;
+            });
+            call_args.push(
+                borrowed
+                    .map(|b| b.pass(&n))
+                    .unwrap_or_else(|| quote::quote!(#n)),
+            );
+        }
+        let call = if returns_result {
+            quote::quote! {
+                <#self_ty>::#fn_name(#(#call_args,)*).map_err(crate::errors::SerializableError::from)
+            }
+        } else {
+            quote::quote!(Ok(<#self_ty>::#fn_name(#(#call_args,)*)))
+        };
+        let null_out = to_internal_error(
+            args,
+            "NullPointer",
+            quote::quote!("Out pointer is null".to_string()),
+        );
+        let panic_payload = if args.panic_details {
+            quote::quote!(buffi::panic_hook::with_panic_details(e))
+        } else {
+            quote::quote!(e)
+        };
+        let panic_error = to_internal_error(args, "Panic", panic_payload);
+        let serialization_error = to_internal_error(args, "Serialization", quote::quote!(e));
+        let stats_allocation = cfg!(feature = "with_stats")
+            .then(|| quote::quote!(buffi::stats::record_allocation(len);));
+        quote::quote! {
+            #(#[doc = #create_docs])*
+            #(#cfgs)*
+            #[no_mangle]
+            pub unsafe extern "C" fn #create_name(
+                #(#parameters,)*
+                value_out: *mut *mut #self_ty,
+                error_out: *mut *mut u8,
+            ) -> usize {
+                let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                    || -> Result<#self_ty, crate::errors::SerializableError> {
+                        if value_out.is_null() || error_out.is_null() {
+                            return Err(#null_out);
+                        }
+                        #(#deserialized_args)*
+                        #call
+                    },
+                ));
+                let res = match r {
+                    Ok(o) => o,
+                    Err(e) => Err(#panic_error),
+                };
+                match res {
+                    Ok(value) => {
+                        unsafe {
+                            *value_out = Box::into_raw(Box::new(value));
+                            *error_out = std::ptr::null_mut();
+                        }
+                        0
+                    }
+                    Err(error) => {
+                        if error_out.is_null() {
+                            return 0;
+                        }
+                        let bytes = match bincode::serialize(&error) {
+                            Ok(bytes) => bytes,
+                            Err(e) => bincode::serialize(&#serialization_error).unwrap_or_default(),
+                        };
+                        let bytes = bytes.into_boxed_slice();
+                        let len = bytes.len();
+                        #stats_allocation
+                        unsafe {
+                            *error_out = Box::into_raw(bytes) as *mut u8;
+                        }
+                        len
+                    }
+                }
+            }
+        }
+    };
+    if cfg!(feature = "with_c_api") {
+        exports.push(quote::quote! {
+            #create
+
+            #(#[doc = #free_docs])*
+            #(#cfgs)*
+            #[no_mangle]
+            pub unsafe extern "C" fn #free_name(ptr: *mut #self_ty) {
+                if !ptr.is_null() {
+                    // SAFETY: guaranteed by the caller
+                    drop(unsafe { Box::from_raw(ptr) });
+                }
+            }
+        });
+    }
+
+    // lets the generator find the constructor and its arguments
+    constructor
+        .attrs
+        .push(syn::parse_quote!(#[cfg(not(generated_constructor_marker))]));
+    Ok(Some(constructor_impl(impl_item, constructor)))
+}
+
+/// The impl block containing the `constructor` moved out of `impl_item` by
+/// [`generate_constructors`], without the `#[buffi(...)]` attributes
+fn constructor_impl(impl_item: &syn::ItemImpl, mut constructor: syn::ImplItemFn) -> syn::ItemImpl {
+    // the functions are only available if the impl block is
+    let cfgs = impl_item
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .cloned()
+        .collect::<Vec<_>>();
+    constructor.attrs.retain(|a| !a.path().is_ident("buffi"));
+    for arg in &mut constructor.sig.inputs {
+        if let syn::FnArg::Typed(t) = arg {
+            t.attrs.retain(|a| !a.path().is_ident("buffi"));
+        }
+    }
+    syn::ItemImpl {
+        attrs: cfgs,
+        items: vec![syn::ImplItem::Fn(constructor)],
+        ..impl_item.clone()
+    }
+}
+
+/// Remove the `#[buffi(...)]` helper attributes (of functions and arguments) as they are not
+/// known to the compiler
+///
+/// Functions using `#[buffi(custom_error)]`, `#[buffi(direct_return)]`, `#[buffi(chunked)]`,
+/// `#[buffi(raw_bytes)]` or `#[buffi(overload = "...")]` get a marker attribute instead, so that
+/// the generator knows how the result is transferred and how the C++ function is called
+fn strip_buffi_attributes(item: &mut syn::Item) {
+    fn strip_arguments(sig: &mut syn::Signature) {
+        for arg in &mut sig.inputs {
+            if let syn::FnArg::Typed(t) = arg {
+                t.attrs.retain(|a| !a.path().is_ident("buffi"));
+            }
+        }
+    }
+
+    fn strip(attrs: &mut Vec<syn::Attribute>) {
+        // errors are already reported while generating the exported function
+        let args = FunctionArgs::from_attributes(attrs).unwrap_or_default();
+        attrs.retain(|a| !a.path().is_ident("buffi"));
+        if args.custom_error {
+            attrs.push(syn::parse_quote!(#[cfg(not(generated_custom_error_marker))]));
+        }
+        if args.direct_return {
+            attrs.push(syn::parse_quote!(#[cfg(not(generated_direct_return_marker))]));
+        }
+        if args.chunked.is_some() {
+            attrs.push(syn::parse_quote!(#[cfg(not(generated_chunked_marker))]));
+        }
+        if args.raw_bytes {
+            attrs.push(syn::parse_quote!(#[cfg(not(generated_raw_bytes_marker))]));
+        }
+        if let Some(name) = args.overload {
+            attrs.push(syn::parse_quote!(#[cfg(not(generated_overload_marker = #name))]));
+        }
+    }
+
+    match item {
+        syn::Item::Impl(impl_item) => {
+            for item in &mut impl_item.items {
+                if let syn::ImplItem::Fn(m) = item {
+                    strip(&mut m.attrs);
+                    strip_arguments(&mut m.sig);
+                }
+            }
+        }
+        syn::Item::Fn(fn_item) => {
+            strip(&mut fn_item.attrs);
+            strip_arguments(&mut fn_item.sig);
+        }
+        _ => {}
+    }
+}
+
+/// Replaces the generic parameters in `tokens` by their concrete types
+fn substitute_generics(
+    tokens: proc_macro2::TokenStream,
+    substitutions: &[(&syn::Ident, &syn::Type)],
+) -> proc_macro2::TokenStream {
+    use proc_macro2::TokenTree;
+    use quote::ToTokens;
+
+    let mut out = proc_macro2::TokenStream::new();
+    let mut tokens = tokens.into_iter().peekable();
+    let mut is_lifetime = false;
+    while let Some(token) = tokens.next() {
+        let next_is_lifetime = matches!(&token, TokenTree::Punct(p) if p.as_char() == '\'');
+        match token {
+            TokenTree::Group(group) => {
+                let mut substituted = proc_macro2::Group::new(
+                    group.delimiter(),
+                    substitute_generics(group.stream(), substitutions),
+                );
+                substituted.set_span(group.span());
+                out.extend([TokenTree::Group(substituted)]);
+            }
+            TokenTree::Ident(ref ident) if !is_lifetime => {
+                // the first `:` of `::` is joint, unlike the `:` of a bound like `T: Clone`
+                let is_qualified = matches!(
+                    tokens.peek(),
+                    Some(TokenTree::Punct(p))
+                        if p.as_char() == ':' && p.spacing() == proc_macro2::Spacing::Joint
+                );
+                match substitutions.iter().find(|(param, _)| *param == ident) {
+                    // `T::Item` needs to become `<Vec<u8>>::Item`
+                    Some((_, tpe)) if is_qualified => out.extend(quote::quote!(<#tpe>)),
+                    Some((_, tpe)) => tpe.to_tokens(&mut out),
+                    None => out.extend([token]),
+                }
+            }
+            token => out.extend([token]),
+        }
+        is_lifetime = next_is_lifetime;
+    }
+    out
+}
+
+/// Replaces `self` in `tokens` by `this`, the value `this_ptr` points to
+fn replace_self(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    use proc_macro2::TokenTree;
+
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    proc_macro2::Group::new(group.delimiter(), replace_self(group.stream()));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            TokenTree::Ident(ident) if ident == "self" => {
+                TokenTree::Ident(syn::Ident::new("this", ident.span()))
+            }
+            token => token,
+        })
+        .collect()
+}
+
+/// The name and the generic arguments of `Type<A, B>`
+fn generic_arguments(tpe: &syn::Type) -> Option<(&syn::Ident, Vec<&syn::Type>)> {
+    let syn::Type::Path(p) = tpe else {
+        return None;
+    };
+    let segment = p.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(ref args) = segment.arguments else {
+        return None;
+    };
+    let args = args
+        .args
+        .iter()
+        .map(|arg| match arg {
+            syn::GenericArgument::Type(t) => Some(t),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((&segment.ident, args))
+}
+
+/// The suffix of the function exported for an instantiation, the names of the concrete types in
+/// snake case (e.g. `vec_u8` for `Vec<u8>`)
+fn instantiation_suffix(types: &[&syn::Type]) -> String {
+    quote::quote!(#(#types)*)
+        .to_string()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|name| !name.is_empty())
+        .map(crate::imported::to_snake_case)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Exports a generic function once for each instantiation listed in `instantiate(...)`
+///
+/// Each instantiation gets a public function `{name}_{suffix}` (see [`instantiation_suffix`])
+/// with the type parameters replaced by the concrete types, which calls the generic function
+/// and is exported like any other function. The instantiations are marked as overloads of
+/// `name`, so the C++ functions share the name of the generic function.
+fn generate_instantiations(
+    fn_item: &syn::ItemFn,
+    exports: &mut Vec<proc_macro2::TokenStream>,
+    args: &ExportArgs,
+    prefix: &str,
+) -> Result<Vec<syn::ItemFn>, syn::Error> {
+    let sig = &fn_item.sig;
+    let mut params = Vec::new();
+    for param in &sig.generics.params {
+        match param {
+            syn::GenericParam::Type(t) => params.push(&t.ident),
+            syn::GenericParam::Lifetime(_) => {}
+            syn::GenericParam::Const(_) => {
+                return Err(syn::Error::new(
+                    param.span(),
+                    "`instantiate` only supports type parameters",
+                ))
+            }
+        }
+    }
+    if params.is_empty() {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            "`instantiate` is only supported for functions with type parameters",
+        ));
+    }
+    let names = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            syn::FnArg::Typed(t) => Ok(argument_name(sig, t)),
+            syn::FnArg::Receiver(r) => Err(syn::Error::new(
+                r.span(),
+                "exported functions outside of impl blocks cannot have a receiver",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // the arguments are forwarded by name, so patterns are replaced by the names
+    let mut generic_sig = sig.clone();
+    generic_sig.generics.params = sig
+        .generics
+        .params
+        .iter()
+        .filter(|p| matches!(p, syn::GenericParam::Lifetime(_)))
+        .cloned()
+        .collect();
+    generic_sig.generics.where_clause = None;
+    for (arg, name) in generic_sig.inputs.iter_mut().zip(&names) {
+        if let syn::FnArg::Typed(t) = arg {
+            t.pat = Box::new(syn::parse_quote!(#name));
+        }
+    }
+    let mut attrs = fn_item.attrs.clone();
+    if FunctionArgs::from_attributes(&attrs)?.overload.is_none() {
+        let name = sig.ident.to_string();
+        attrs.push(syn::parse_quote!(#[buffi(overload = #name)]));
+    }
+
+    let fn_name = &sig.ident;
+    let await_call = sig.asyncness.map(|_| quote::quote!(.await));
+    let mut instantiations = Vec::new();
+    for instantiation in &args.instantiations {
+        let mut substitutions = Vec::with_capacity(params.len());
+        for param in &params {
+            let Some((_, tpe)) = instantiation.iter().find(|(name, _)| name == *param) else {
+                return Err(syn::Error::new(
+                    instantiation[0].0.span(),
+                    format!("the instantiation needs a type for `{param}`"),
+                ));
+            };
+            substitutions.push((*param, tpe));
+        }
+        if let Some((name, _)) = instantiation.iter().find(|(n, _)| !params.contains(&n)) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("`{fn_name}` has no type parameter `{name}`"),
+            ));
+        }
+        let types = substitutions
+            .iter()
+            .map(|(_, tpe)| *tpe)
+            .collect::<Vec<_>>();
+        let mut instance_sig: syn::Signature = syn::parse2(substitute_generics(
+            quote::ToTokens::to_token_stream(&generic_sig),
+            &substitutions,
+        ))?;
+        instance_sig.ident = quote::format_ident!(
+            "{}_{}",
+            fn_name,
+            instantiation_suffix(&types),
+            span = fn_name.span()
+        );
+        let instance = syn::ItemFn {
+            attrs: attrs.clone(),
+            vis: fn_item.vis.clone(),
+            sig: instance_sig,
+            block: Box::new(syn::parse_quote!({
+                #fn_name::<#(#types),*>(#(#names),*)#await_call
+            })),
+        };
+        if cfg!(feature = "with_c_api") {
+            generate_exported_function(
+                &instance.sig,
+                Vec::new(),
+                exports,
+                &instance.attrs,
+                fn_item.span(),
+                args,
+                prefix.to_owned(),
+            )?;
+        }
+        let mut instance = syn::Item::Fn(instance);
+        strip_buffi_attributes(&mut instance);
+        let syn::Item::Fn(mut instance) = instance else {
+            unreachable!()
+        };
+        instance
+            .attrs
+            .push(syn::parse_quote!(#[cfg(not(generated_extern_impl))]));
+        instantiations.push(instance);
+    }
+    Ok(instantiations)
+}
+
+/// Exports a generic impl block once for each of the types listed in `instances(...)`
+///
+/// Each instance `Name = Type<Concrete>` gets a `pub type Name` alias and extern functions
+/// named `{prefix}_{name}_{function}` with the generic parameters replaced by the concrete
+/// types. The generic impl block is marked with the names of its instances, so that the
+/// generator can emit a holder class for each of them.
+fn generate_instances(
+    impl_item: &mut syn::ItemImpl,
+    exports: &mut Vec<proc_macro2::TokenStream>,
+    args: &ExportArgs,
+    prefix: &str,
+) -> Result<(), syn::Error> {
+    if args.instances.is_empty() {
+        return Err(syn::Error::new(
+            impl_item.generics.span(),
+            "generic impl blocks need to list their concrete types, \
+             e.g. `#[buffi_macro::exported(instances(IntRepository = Repository<i64>))]`",
+        ));
+    }
+    if impl_item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            impl_item.self_ty.span(),
+            "`instances` is only supported for generic impl blocks",
+        ));
+    }
+    let mut params = Vec::new();
+    for param in &impl_item.generics.params {
+        let syn::GenericParam::Type(t) = param else {
+            return Err(syn::Error::new(
+                param.span(),
+                "exported generic impl blocks only support type parameters",
+            ));
+        };
+        params.push(&t.ident);
+    }
+    let Some((self_name, self_args)) = generic_arguments(&impl_item.self_ty) else {
+        return Err(syn::Error::new(
+            impl_item.self_ty.span(),
+            "exported generic impl blocks need to be implemented for a type like `Type<T>`",
+        ));
+    };
+
+    let mut markers = Vec::new();
+    for (name, tpe) in &args.instances {
+        let instance_args = match generic_arguments(tpe) {
+            Some((instance_name, args))
+                if instance_name == self_name && args.len() == self_args.len() =>
+            {
+                args
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    tpe.span(),
+                    format!(
+                        "the instance `{name}` needs to be a `{self_name}` with concrete types"
+                    ),
+                ))
+            }
+        };
+        let substitutions = self_args
+            .iter()
+            .zip(instance_args)
+            .filter_map(|(param, concrete)| {
+                let syn::Type::Path(p) = param else {
+                    return None;
+                };
+                let ident = p.path.get_ident()?;
+                params.contains(&ident).then_some((ident, concrete))
+            })
+            .collect::<Vec<_>>();
+
+        // only the signatures are needed to generate the extern functions
+        let mut items = Vec::new();
+        for item in &impl_item.items {
+            if let syn::ImplItem::Fn(m) = item {
+                let sig =
+                    substitute_generics(quote::ToTokens::to_token_stream(&m.sig), &substitutions);
+                items.push(syn::ImplItem::Fn(syn::ImplItemFn {
+                    sig: syn::parse2(sig)?,
+                    block: syn::parse_quote!({}),
+                    ..m.clone()
+                }));
+            }
+        }
+        let instance = syn::ItemImpl {
+            generics: syn::Generics::default(),
+            self_ty: Box::new(syn::parse_quote!(#name)),
+            items,
+            ..impl_item.clone()
+        };
+        if cfg!(feature = "with_c_api") {
+            let instance_prefix = format!(
+                "{prefix}_{}",
+                crate::imported::to_snake_case(&name.to_string())
+            );
+            generate_exported_functions_for_impl_block(&instance, exports, args, instance_prefix)?;
+        }
+
+        let docs = format!(" The `{name}` instance of `{self_name}`, as exported to C++");
+        // the alias only exists if the impl block does
+        let cfgs = impl_item
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("cfg"))
+            .collect::<Vec<_>>();
+        exports.push(quote::quote! {
+            #[doc = #docs]
+            #(#cfgs)*
+            pub type #name = #tpe;
+        });
+        let name = name.to_string();
+        markers.push(syn::parse_quote!(#[cfg(not(generated_instance_marker = #name))]));
+    }
+    impl_item.attrs.extend(markers);
+    Ok(())
+}
+
 fn generate_exported_functions_for_impl_block(
     impl_item: &syn::ItemImpl,
     exports: &mut Vec<proc_macro2::TokenStream>,
+    args: &ExportArgs,
     prefix: String,
 ) -> Result<(), syn::Error> {
     let mut syn_error: Option<syn::Error> = None;
@@ -47,17 +1406,25 @@ fn generate_exported_functions_for_impl_block(
         if let syn::ImplItem::Fn(m) = item {
             if matches!(m.vis, syn::Visibility::Public(_)) {
                 let self_ty = &impl_item.self_ty;
-                let docs = m.attrs.iter().filter(|a| a.path().is_ident("doc"));
 
                 let mut arg_list = Vec::new();
                 arg_list.push(quote::quote!(this_ptr: *mut #self_ty));
 
+                // `#[cfg]` attributes of the impl block apply to each method as well
+                let attrs = impl_item
+                    .attrs
+                    .iter()
+                    .filter(|a| a.path().is_ident("cfg"))
+                    .chain(&m.attrs)
+                    .cloned()
+                    .collect::<Vec<_>>();
                 match generate_exported_function(
                     &m.sig,
                     arg_list,
                     exports,
-                    docs,
+                    &attrs,
                     item.span(),
+                    args,
                     prefix.clone(),
                 ) {
                     Ok(_) => (),
@@ -80,12 +1447,35 @@ fn generate_exported_functions_for_impl_block(
     }
 }
 
-fn generate_exported_function<'a>(
+/// Converts an internal failure of the given kind into a `SerializableError`, via the
+/// `error_hook` if there is one
+fn to_internal_error(
+    export_args: &ExportArgs,
+    kind: &str,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match export_args.error_hook {
+        Some(ref hook) => {
+            let kind = syn::Ident::new(kind, Span::call_site());
+            // serialization errors are passed as message, all other values are already owned
+            let value = if kind == "Serialization" {
+                quote::quote!(std::string::ToString::to_string(&#value))
+            } else {
+                value
+            };
+            quote::quote!(#hook(buffi::error_hook::InternalFailure::#kind(#value)))
+        }
+        None => quote::quote!(crate::errors::SerializableError::from(#value)),
+    }
+}
+
+fn generate_exported_function(
     sig: &syn::Signature,
     mut arg_list: Vec<proc_macro2::TokenStream>,
     exports: &mut Vec<proc_macro2::TokenStream>,
-    docs: impl Iterator<Item = &'a syn::Attribute>,
+    attrs: &[syn::Attribute],
     item_span: Span,
+    export_args: &ExportArgs,
     prefix: String,
 ) -> Result<(), syn::Error> {
     let is_result_type = match &sig.output {
@@ -112,71 +1502,283 @@ fn generate_exported_function<'a>(
             format!("API function '{func_name}' is not returning a 'Result'"),
         ));
     }
+    // the extern function cannot be generic, so only lifetimes can be inferred for the call,
+    // where clauses do not need to be repeated as the types are concrete
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|p| !matches!(p, syn::GenericParam::Lifetime(_)))
+    {
+        let func_name = &sig.ident;
+        return Err(syn::Error::new(
+            param.span(),
+            format!(
+                "API function '{func_name}' cannot have type or const parameters, use concrete \
+                 types, `impl Trait` arguments or `instantiate(...)` instead"
+            ),
+        ));
+    }
 
+    let docs = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .collect::<Vec<_>>();
+    // the extern functions only exist if the exported function does
+    let cfgs = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .collect::<Vec<_>>();
+    let function_args = FunctionArgs::from_attributes(attrs)?;
+    let blocking_strategy = function_args
+        .blocking_strategy
+        .or(export_args.blocking_strategy)
+        .unwrap_or_default();
     let is_free_standing = arg_list.is_empty();
+    let blocking = function_args.blocking || export_args.blocking;
+    let internal_error =
+        |kind: &str, value: proc_macro2::TokenStream| to_internal_error(export_args, kind, value);
+    if function_args.blocking && sig.asyncness.is_some() {
+        return Err(syn::Error::new(
+            sig.asyncness.span(),
+            format!(
+                "API function '{}' is async, `blocking` is only supported for synchronous functions",
+                sig.ident
+            ),
+        ));
+    }
+    if blocking && sig.asyncness.is_none() && is_free_standing && !export_args.global_runtime {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            format!(
+                "API function '{}' is not part of an impl block, \
+                 use `#[buffi_macro::exported(global_runtime)]` to run it on the blocking thread pool",
+                sig.ident
+            ),
+        ));
+    }
+    if sig.asyncness.is_some() && is_free_standing && !export_args.global_runtime {
+        return Err(syn::Error::new(
+            sig.asyncness.span(),
+            format!(
+                "async API function '{}' is not part of an impl block, \
+                 use `#[buffi_macro::exported(global_runtime)]` to execute it",
+                sig.ident
+            ),
+        ));
+    }
+    let result_transfers = [
+        ("direct_return", function_args.direct_return),
+        ("chunked", function_args.chunked.is_some()),
+        ("raw_bytes", function_args.raw_bytes),
+    ]
+    .into_iter()
+    .filter_map(|(name, used)| used.then_some(name))
+    .collect::<Vec<_>>();
+    if let [first, second, ..] = result_transfers[..] {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            format!(
+                "API function '{}' cannot use `{first}` and `{second}` at the same time",
+                sig.ident
+            ),
+        ));
+    }
+    // chunked results and raw bytes return a pointer to the chunks or bytes the same way
+    let direct_return_type = if function_args.direct_return {
+        let tpe = direct_return_type(&sig.output).ok_or_else(|| {
+            syn::Error::new(
+                sig.output.span(),
+                format!(
+                    "API function '{}' uses `direct_return`, which is only supported \
+                     for results containing a primitive type",
+                    sig.ident
+                ),
+            )
+        })?;
+        Some(quote::quote!(#tpe))
+    } else if function_args.chunked.is_some() {
+        if !returns_vec(&sig.output) {
+            return Err(syn::Error::new(
+                sig.output.span(),
+                format!(
+                    "API function '{}' uses `chunked`, which is only supported \
+                     for results containing a `Vec`",
+                    sig.ident
+                ),
+            ));
+        }
+        Some(quote::quote!(*mut buffi::chunked::ChunkedResult))
+    } else if function_args.raw_bytes {
+        if !returns_bytes(&sig.output) {
+            return Err(syn::Error::new(
+                sig.output.span(),
+                format!(
+                    "API function '{}' uses `raw_bytes`, which is only supported \
+                     for results containing a `Vec<u8>`",
+                    sig.ident
+                ),
+            ));
+        }
+        Some(quote::quote!(*mut u8))
+    } else {
+        None
+    };
     let name = &sig.ident;
     let fn_name = syn::Ident::new(&format!("{}_{}", prefix, sig.ident), sig.ident.span());
+    let mut callbacks = Vec::new();
     for arg in &sig.inputs {
         if let syn::FnArg::Typed(t) = arg {
-            let n = if let syn::Pat::Ident(ref i) = *t.pat {
-                i.ident.clone()
-            } else {
-                panic!("unknown")
-            };
-            let n_size = syn::Ident::new(&format!("{n}_size"), n.span());
-            arg_list.push(quote::quote!(#n: *const u8));
-            arg_list.push(quote::quote!(#n_size: usize));
-        }
-    }
-    arg_list.push(quote::quote!(out_ptr: *mut *mut u8));
-    let deserialized_args = sig.inputs.iter().filter_map(|arg| {
-        let span = arg.span();
-        if let syn::FnArg::Typed(t) = arg {
-            let n = if let syn::Pat::Ident(ref i) = *t.pat {
-                i.ident.clone()
-            } else {
-                panic!("unknown")
-            };
-            let n_size = syn::Ident::new(&format!("{n}_size"), n.span());
-            Some(quote::quote_spanned! {span=>
-                let slice = if #n.is_null() {
-                    &[]
-                } else {
-                    unsafe {
-                        std::slice::from_raw_parts(#n, #n_size)
-                    }
+            let n = argument_name(sig, t);
+            let kind = argument_kind(t)?;
+            if kind == ArgumentKind::Callback {
+                let Some(callback) = callback_signature(&t.ty) else {
+                    return Err(syn::Error::new(
+                        t.ty.span(),
+                        "`callback` arguments need to be an `impl Fn(..)` or `impl FnMut(..)`",
+                    ));
                 };
-                let #n = bincode::deserialize(slice)?;
-            })
-        } else {
-            None
+                if sig.asyncness.is_some() || blocking || function_args.chunked.is_some() {
+                    return Err(syn::Error::new(
+                        t.span(),
+                        format!(
+                            "API function '{}' takes the callback `{n}`, which is only supported \
+                             for synchronous functions that are neither `blocking` nor `chunked`",
+                            sig.ident
+                        ),
+                    ));
+                }
+                let args = (0..callback.inputs.len())
+                    .map(|idx| quote::format_ident!("arg{}", idx))
+                    .collect::<Vec<_>>();
+                let sizes = args
+                    .iter()
+                    .map(|arg| quote::format_ident!("{}_size", arg))
+                    .collect::<Vec<_>>();
+                let context = quote::format_ident!("{}_context", n);
+                arg_list.push(quote::quote! {
+                    #n: Option<
+                        extern "C" fn(
+                            context: *mut std::ffi::c_void,
+                            #(#args: *const u8, #sizes: usize,)*
+                            out_ptr: *mut *mut u8,
+                        ) -> usize,
+                    >
+                });
+                arg_list.push(quote::quote!(#context: *mut std::ffi::c_void));
+                callbacks.push(n);
+                continue;
+            }
+            if kind == ArgumentKind::ByValue {
+                if let syn::Type::Reference(r) = &*t.ty {
+                    return Err(syn::Error::new(
+                        r.span(),
+                        "`by_value` arguments need to be passed as owned values",
+                    ));
+                }
+                let tpe = &t.ty;
+                arg_list.push(quote::quote!(#n: #tpe));
+                continue;
+            }
+            check_trait_type(sig, &n, &t.ty)?;
+            let n_size = syn::Ident::new(&format!("{n}_size"), n.span());
+            arg_list.push(quote::quote!(#n: *const u8));
+            arg_list.push(quote::quote!(#n_size: usize));
         }
+    }
+    let mut callback_arg_list = arg_list.clone();
+    callback_arg_list.push(quote::quote!(
+        callback: Option<extern "C" fn(*mut std::ffi::c_void, *mut u8, usize)>
+    ));
+    callback_arg_list.push(quote::quote!(context: *mut std::ffi::c_void));
+    if let Some(ref tpe) = direct_return_type {
+        arg_list.push(quote::quote!(value_out: *mut #tpe));
+        arg_list.push(quote::quote!(error_out: *mut *mut u8));
+    } else {
+        arg_list.push(quote::quote!(out_ptr: *mut *mut u8));
+    }
+    let deserialize_error = export_args.error_hook.as_ref().map(|_| {
+        let error = internal_error("Serialization", quote::quote!(e));
+        quote::quote!(.map_err(|e| #error))
     });
-    let args = sig.inputs.iter().filter_map(|arg| {
-        if let syn::FnArg::Typed(t) = arg {
-            let n = if let syn::Pat::Ident(ref i) = *t.pat {
-                i.ident.clone()
+    // `codec` is the module deserializing the arguments, `bincode` or `buffi::debug_json`
+    let deserialize_args = |codec: &proc_macro2::TokenStream| {
+        sig.inputs
+            .iter()
+            .filter_map(|arg| {
+                let span = arg.span();
+                if let syn::FnArg::Typed(t) = arg {
+                    let n = argument_name(sig, t);
+                    let kind = argument_kind(t).unwrap_or_default();
+                    if kind == ArgumentKind::Callback {
+                        let message = format!("The callback `{n}` is null");
+                        let null_callback =
+                            internal_error("NullPointer", quote::quote!(#message.to_string()));
+                        return callback_signature(&t.ty)
+                            .map(|callback| callback_closure(sig, &n, callback, null_callback));
+                    }
+                    if kind == ArgumentKind::ByValue {
+                        // the value is passed as it is, so it needs to be a `#[repr(C)]` type
+                        // that does not own any memory
+                        let tpe = &t.ty;
+                        return Some(quote::quote_spanned! {span=>
+                            {
+                                fn assert_copy<T: Copy>(_: &T) {}
+                                assert_copy::<#tpe>(&#n);
+                            }
+                        });
+                    }
+                    let n_size = syn::Ident::new(&format!("{n}_size"), n.span());
+                    let owned_type = BorrowedArgument::from_type(&t.ty).map(|b| {
+                        let owned = b.owned_type();
+                        quote::quote!(: #owned)
+                    });
+                    Some(quote::quote_spanned! {span=>
+                        let slice = if #n.is_null() {
+                            &[]
+                        } else {
+                            unsafe {
+                                std::slice::from_raw_parts(#n, #n_size)
+                            }
+                        };
+                        let #n #owned_type = #codec::deserialize(slice)#deserialize_error?;
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let deserialized_args = deserialize_args(&quote::quote!(bincode));
+    let args = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| {
+            if let syn::FnArg::Typed(t) = arg {
+                let n = argument_name(sig, t);
+                Some(
+                    BorrowedArgument::from_type(&t.ty)
+                        .map(|b| b.pass(&n))
+                        .unwrap_or_else(|| quote::quote!(#n)),
+                )
             } else {
-                panic!("unknown")
-            };
-            Some(n)
-        } else {
-            None
-        }
-    });
-    let mut_this = sig.inputs.first().and_then(|s| {
-        if let syn::FnArg::Receiver(r) = s {
-            r.mutability.map(|_| quote::quote!(mut))
-        } else {
-            None
-        }
-    });
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    let this = match sig.inputs.first() {
+        Some(syn::FnArg::Receiver(r)) => receiver_from_pointer(r)?,
+        _ => quote::quote!(&*this_ptr),
+    };
     let await_call = if sig.asyncness.is_some() {
         Some(quote::quote!(.await))
     } else {
         None
     };
-    let map_err_call = if let syn::ReturnType::Type(_, ref tpe) = sig.output {
+    let map_err_call = if function_args.custom_error {
+        // internal errors are converted via `From<SerializableError>`, see `flatten_result`
+        None
+    } else if let syn::ReturnType::Type(_, ref tpe) = sig.output {
         if let syn::Type::Path(p) = &**tpe {
             if p.path
                 .segments
@@ -216,102 +1818,964 @@ fn generate_exported_function<'a>(
         allow_unwrap_default = Some(quote::quote! {#[allow(clippy::manual_unwrap_or_default)]});
     }
 
+    let (mut stats_call, mut stats_allocation) = Default::default();
+    if cfg!(feature = "with_stats") {
+        stats_call = Some(quote::quote! {buffi::stats::record_call();});
+        stats_allocation = Some(quote::quote! {buffi::stats::record_allocation(len);});
+    }
+
+    let null_this = internal_error(
+        "NullPointer",
+        quote::quote!("This pointer is null".to_string()),
+    );
+    let null_out = internal_error(
+        "NullPointer",
+        quote::quote!("Out pointer is null".to_string()),
+    );
     let this_ptr = if is_free_standing {
         None
     } else {
         Some(quote::quote_spanned! {item_span=>
             if this_ptr.is_null() {
                 #tracing_pointer
-                return Err(crate::errors::SerializableError::from("This pointer is null".to_string()));
+                return Err(#null_this);
             }
-            let this = unsafe { &#mut_this *this_ptr };
+            let this = unsafe { #this };
         })
     };
-    let out_ptr = quote::quote_spanned! {item_span=>
-        if out_ptr.is_null() {
-            #tracing_out_pointer
-            return Err(crate::errors::SerializableError::from("Out pointer is null".to_string()));
+    let out_ptr = if direct_return_type.is_some() {
+        quote::quote_spanned! {item_span=>
+            if value_out.is_null() || error_out.is_null() {
+                #tracing_out_pointer
+                return Err(#null_out);
+            }
+        }
+    } else {
+        quote::quote_spanned! {item_span=>
+            if out_ptr.is_null() {
+                #tracing_out_pointer
+                return Err(#null_out);
+            }
         }
     };
-    let deserialize = quote::quote! {
-        #this_ptr
-        #out_ptr
-        #(#deserialized_args)*
+    let deserialize_with = |deserialized_args: &[proc_macro2::TokenStream]| {
+        quote::quote! {
+            #this_ptr
+            #out_ptr
+            #(#deserialized_args)*
+        }
     };
-    let inner_block = if is_free_standing {
+    let deserialize = deserialize_with(&deserialized_args);
+    let into_chunks = function_args.chunked.as_ref().map(|chunk_len| {
         quote::quote! {
-            #name(#(#args,)*)#await_call #map_err_call
+            .map(|items| {
+                Box::into_raw(Box::new(buffi::chunked::ChunkedResult::new(
+                    items,
+                    #chunk_len,
+                    |chunk, buffer| bincode::serialize_into(buffer, chunk),
+                )))
+            })
+        }
+    });
+    let call = if is_free_standing {
+        quote::quote! {
+            #name(#(#args,)*)#await_call #map_err_call #into_chunks
         }
     } else {
         quote::quote_spanned! {item_span=>
-            this.#name(#(#args,)*)#await_call #map_err_call
+            this.#name(#(#args,)*)#await_call #map_err_call #into_chunks
         }
     };
-    let inner_block = if sig.asyncness.is_some() {
+    // with a custom error type the result of the function is wrapped in another `Result`
+    // carrying the internal `SerializableError`s, which is flattened before serialization
+    let (call, flatten_result) = if function_args.custom_error {
+        (
+            quote::quote!(Ok(#call)),
+            quote::quote! {
+                match res {
+                    Ok(res) => res,
+                    Err(e) => Err(From::from(e)),
+                }
+            },
+        )
+    } else {
+        (call, quote::quote!(res))
+    };
+    let runtime = if export_args.global_runtime {
+        let no_runtime = internal_error(
+            "Runtime",
+            quote::quote!(
+                "No global runtime registered, call `buffi::runtime::set_global` first".to_string()
+            ),
+        );
         quote::quote! {
-            #deserialize
-            let runtime = std::sync::Arc::clone(&this.runtime);
-            let fut = async move {
-                #inner_block
-            };
-            runtime.block_on(fut)
+            let runtime = buffi::runtime::global().ok_or_else(|| #no_runtime)?;
+        }
+    } else {
+        let runtime = match export_args.runtime {
+            Some(ref expr) => replace_self(quote::ToTokens::to_token_stream(expr)),
+            None => quote::quote!(this.runtime),
+        };
+        quote::quote! {
+            let runtime = std::clone::Clone::clone(&#runtime);
+        }
+    };
+    let panic_payload = if export_args.panic_details {
+        quote::quote!(buffi::panic_hook::with_panic_details(e))
+    } else {
+        quote::quote!(e)
+    };
+    let panic_error = internal_error("Panic", panic_payload);
+    let runtime_error = internal_error("Runtime", quote::quote!(e.to_string()));
+    let serialization_error = internal_error("Serialization", quote::quote!(e));
+    // waits for the spawned task `handle` and converts a panic into an error
+    let join_task = quote::quote! {
+        match handle.await {
+            Ok(o) => o,
+            Err(e) => match e.try_into_panic() {
+                Ok(e) => {
+                    #tracing_error
+                    Err(#panic_error)
+                }
+                Err(e) => Err(#runtime_error),
+            },
         }
+    };
+    let block = match blocking_strategy {
+        BlockingStrategy::BlockOn => quote::quote! {
+            runtime.block_on(fut)
+        },
+        BlockingStrategy::BlockInPlace => quote::quote! {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                tokio::task::block_in_place(|| runtime.block_on(fut))
+            } else {
+                runtime.block_on(fut)
+            }
+        },
+        BlockingStrategy::Spawn => quote::quote! {
+            let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+            let handle = runtime.spawn(fut);
+            runtime.spawn(async move {
+                let res = #join_task;
+                let _ = sender.send(res);
+            });
+            receiver
+                .recv()
+                .unwrap_or_else(|e| Err(#runtime_error))
+        },
+    };
+    let reentrancy_guard = if export_args.detect_reentrancy {
+        let allow_runtime_thread = matches!(blocking_strategy, BlockingStrategy::BlockInPlace);
+        let name = name.to_string();
+        let reentrancy_error = export_args.error_hook.as_ref().map(|_| {
+            let error = internal_error("Runtime", quote::quote!(e));
+            quote::quote!(.map_err(|e| #error))
+        });
+        Some(quote::quote! {
+            let _guard = buffi::runtime::enter_blocking_call(#name, #allow_runtime_thread)#reentrancy_error?;
+        })
     } else {
+        None
+    };
+    let inner_block_with = |deserialize: &proc_macro2::TokenStream| {
+        if sig.asyncness.is_some() {
+            quote::quote! {
+                #deserialize
+                #reentrancy_guard
+                #runtime
+                let fut = async move {
+                    #call
+                };
+                #block
+            }
+        } else if blocking {
+            quote::quote! {
+                #deserialize
+                #reentrancy_guard
+                #runtime
+                let handle = runtime.spawn_blocking(move || {
+                    #call
+                });
+                let fut = async move {
+                    #join_task
+                };
+                #block
+            }
+        } else {
+            quote::quote! {
+                #deserialize
+                #call
+            }
+        }
+    };
+    let inner_block = inner_block_with(&deserialize);
+    // turns `res` into a byte buffer, if that fails we try to at least serialize the error
+    let serialize_result_with = |codec: &proc_macro2::TokenStream| {
         quote::quote! {
-            #deserialize
-            #inner_block
+            let bytes = match #codec::serialize(&res) {
+                Ok(bytes) => {
+                    bytes
+                }
+                Err(e) => {
+                    #tracing_serializable_w
+                    res = Err(From::from(#serialization_error));
+                    match #codec::serialize(&res) {
+                        Ok(bytes) => {
+                            bytes
+                        }
+                        Err(_e) => {
+                            #tracing_serializable_e
+                            Vec::new()
+                        }
+                    }
+                }
+            };
+
+            let bytes = bytes.into_boxed_slice();
+            let len = bytes.len();
+            #stats_allocation
         }
     };
+    let serialize_result = serialize_result_with(&quote::quote!(bincode));
+    // a successful call writes the value to `value_out` and returns 0, only errors are
+    // serialized into a byte buffer
+    let (write_result, direct_return_docs) = if direct_return_type.is_some() {
+        // raw bytes are moved into the output buffer as they are and return its size
+        let write_value = if function_args.raw_bytes {
+            quote::quote! {
+                let bytes = value.into_boxed_slice();
+                let len = bytes.len();
+                #stats_allocation
+                unsafe {
+                    *value_out = Box::into_raw(bytes) as *mut u8;
+                    *error_out = std::ptr::null_mut();
+                }
+                len
+            }
+        } else {
+            quote::quote! {
+                unsafe {
+                    *value_out = value;
+                    *error_out = std::ptr::null_mut();
+                }
+                0
+            }
+        };
+        (
+            quote::quote! {
+                match #flatten_result {
+                    Ok(value) => {
+                        #write_value
+                    }
+                    Err(e) => {
+                        if error_out.is_null() {
+                            return 0;
+                        }
+                        let mut error = e;
+                        let bytes = match bincode::serialize(&error) {
+                            Ok(bytes) => {
+                                bytes
+                            }
+                            Err(e) => {
+                                #tracing_serializable_w
+                                error = From::from(#serialization_error);
+                                match bincode::serialize(&error) {
+                                    Ok(bytes) => {
+                                        bytes
+                                    }
+                                    Err(_e) => {
+                                        #tracing_serializable_e
+                                        Vec::new()
+                                    }
+                                }
+                            }
+                        };
+
+                        let bytes = bytes.into_boxed_slice();
+                        let len = bytes.len();
+                        #stats_allocation
+                        unsafe {
+                            *error_out = Box::into_raw(bytes) as *mut u8;
+                        }
+                        len
+                    }
+                }
+            },
+            if function_args.chunked.is_some() {
+                vec![
+                    String::new(),
+                    String::from(
+                        " On success `value_out` points to the chunks of the result, which are",
+                    ),
+                    format!(
+                        " fetched via `{prefix}_next_chunk` and freed via \
+                         `{prefix}_free_chunked_result`."
+                    ),
+                    String::from(
+                        " Otherwise `error_out` points to the serialized error, which needs to be",
+                    ),
+                    format!(" freed via `{prefix}_free_byte_buffer`."),
+                ]
+            } else if function_args.raw_bytes {
+                vec![
+                    String::new(),
+                    String::from(
+                        " On success `value_out` points to the returned bytes (without any bincode",
+                    ),
+                    String::from(
+                        " framing) and `error_out` is null. Otherwise `error_out` points to the",
+                    ),
+                    format!(
+                        " serialized error. Either buffer needs to be freed via \
+                         `{prefix}_free_byte_buffer`"
+                    ),
+                    String::from(" with the returned size."),
+                ]
+            } else {
+                vec![
+                    String::new(),
+                    String::from(
+                        " On success the value is written to `value_out`. Otherwise `error_out` \
+                         points",
+                    ),
+                    format!(
+                        " to the serialized error, which needs to be freed via \
+                         `{prefix}_free_byte_buffer`."
+                    ),
+                ]
+            },
+        )
+    } else {
+        (
+            quote::quote! {
+                let mut res = #flatten_result;
+                #serialize_result
+                let out: &mut *mut u8 = unsafe { &mut *out_ptr };
+                *out = Box::into_raw(bytes) as *mut u8;
+                len
+            },
+            Vec::new(),
+        )
+    };
+    let callback_docs = if callbacks.is_empty() {
+        Vec::new()
+    } else {
+        let names = callbacks
+            .iter()
+            .map(|n| format!("`{n}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        vec![
+            String::new(),
+            format!(" The callbacks {names} are called with their `_context` and the serialized"),
+            String::from(" arguments before this function returns. Each call needs to write the"),
+            format!(" serialized result to a buffer allocated via `{prefix}_alloc_byte_buffer`,"),
+            String::from(" otherwise an error is returned."),
+        ]
+    };
     exports.push(quote::quote_spanned! {item_span=>
         #(#docs)*
+        #(#[doc = #direct_return_docs])*
+        #(#[doc = #callback_docs])*
         ///
         /// # Safety
         /// Unsafe code is used to dereference pointers to byte buffers.
         /// We check every pointer before accessing it to make this process safe.
+        #(#cfgs)*
         #[cfg(not(generated_extern_function_marker))]
         #tracing_skip
         #allow_unwrap_default
         #[no_mangle]
         pub unsafe extern "C" fn #fn_name(#(#arg_list,)*) -> usize {
+            #stats_call
             let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 #inner_block
             }));
 
-            let mut res = match r {
+            let res = match r {
                 Ok(o) => {
                     o
                 },
                 Err(e) => {
                     #tracing_error
-                    Err(crate::errors::SerializableError::from(e))
+                    Err(#panic_error)
                 }
             };
-            let bytes = match bincode::serialize(&res) {
-                Ok(bytes) => {
-                    bytes
+            #write_result
+        }
+    });
+
+    // chunked results and raw bytes are only returned by the blocking function
+    if export_args.completion_callback
+        && sig.asyncness.is_some()
+        && function_args.chunked.is_none()
+        && !function_args.raw_bytes
+    {
+        let callback_fn_name = syn::Ident::new(
+            &format!("{}_{}_with_callback", prefix, sig.ident),
+            sig.ident.span(),
+        );
+        let free_result_docs =
+            format!(" The result buffer needs to be freed via `{prefix}_free_byte_buffer`.");
+        exports.push(quote::quote_spanned! {item_span=>
+            #(#docs)*
+            ///
+            /// This function does not block. It returns immediately and calls `callback`
+            /// with `context` and the serialized result as soon as the future resolved.
+            #[doc = #free_result_docs]
+            ///
+            /// # Safety
+            /// Unsafe code is used to dereference pointers to byte buffers.
+            /// We check every pointer before accessing it to make this process safe.
+            /// The `this_ptr` needs to stay valid until the callback is called.
+            #(#cfgs)*
+            #[cfg(not(generated_extern_function_marker))]
+            #tracing_skip
+            #allow_unwrap_default
+            #[no_mangle]
+            pub unsafe extern "C" fn #callback_fn_name(#(#callback_arg_list,)*) {
+                #stats_call
+                struct CallbackContext(*mut std::ffi::c_void);
+                // SAFETY: The context pointer is never dereferenced on the Rust side,
+                // it is only passed back to the callback provided by the caller
+                unsafe impl Send for CallbackContext {}
+                impl CallbackContext {
+                    fn into_inner(self) -> *mut std::ffi::c_void {
+                        self.0
+                    }
                 }
-                Err(e) => {
-                    #tracing_serializable_w
-                    res = Err(e.into());
-                    match bincode::serialize(&res) {
-                        Ok(bytes) => {
-                            bytes
-                        }
-                        Err(_e) => {
-                            #tracing_serializable_e
-                            Vec::new()
-                        }
+
+                let Some(callback) = callback else {
+                    #tracing_pointer
+                    return;
+                };
+                let context = CallbackContext(context);
+                let complete = move |res: Result<_, crate::errors::SerializableError>| {
+                    let mut res = #flatten_result;
+                    #serialize_result
+                    callback(context.into_inner(), Box::into_raw(bytes) as *mut u8, len);
+                };
+
+                let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                    || -> Result<_, crate::errors::SerializableError> {
+                        #this_ptr
+                        #(#deserialized_args)*
+                        #runtime
+                        let fut = async move {
+                            #call
+                        };
+                        Ok((runtime, fut))
+                    },
+                ));
+
+                match r {
+                    Ok(Ok((runtime, fut))) => {
+                        let handle = runtime.spawn(fut);
+                        runtime.spawn(async move {
+                            let res = #join_task;
+                            complete(res);
+                        });
+                    }
+                    Ok(Err(e)) => complete(Err(e)),
+                    Err(e) => {
+                        #tracing_error
+                        complete(Err(#panic_error))
                     }
                 }
-            };
+            }
+        });
+    }
 
-            let bytes = bytes.into_boxed_slice();
-            let len = bytes.len();
-            let out: &mut *mut u8 = unsafe { &mut *out_ptr };
-            *out = Box::into_raw(bytes) as *mut u8;
-            len
-        }
-    });
+    // only results returned via `out_ptr` can be encoded as JSON, callbacks are always called
+    // with bincode
+    if cfg!(feature = "debug_json") && direct_return_type.is_none() && callbacks.is_empty() {
+        let json_fn_name =
+            syn::Ident::new(&format!("{}_json_{}", prefix, sig.ident), sig.ident.span());
+        let json_docs = format!(
+            " The same as `{fn_name}`, but the arguments and the result are encoded as JSON"
+        );
+        let free_result_docs =
+            format!(" The result buffer needs to be freed via `{prefix}_free_byte_buffer`.");
+        let json = quote::quote!(buffi::debug_json);
+        let inner_block = inner_block_with(&deserialize_with(&deserialize_args(&json)));
+        let serialize_result = serialize_result_with(&json);
+        exports.push(quote::quote_spanned! {item_span=>
+            #(#docs)*
+            ///
+            #[doc = #json_docs]
+            /// instead of bincode, see `buffi::debug_json`.
+            #[doc = #free_result_docs]
+            ///
+            /// # Safety
+            /// Unsafe code is used to dereference pointers to byte buffers.
+            /// We check every pointer before accessing it to make this process safe.
+            #(#cfgs)*
+            #[cfg(not(generated_extern_function_marker))]
+            #tracing_skip
+            #allow_unwrap_default
+            #[no_mangle]
+            pub unsafe extern "C" fn #json_fn_name(#(#arg_list,)*) -> usize {
+                #stats_call
+                let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    #inner_block
+                }));
+
+                let res = match r {
+                    Ok(o) => {
+                        o
+                    },
+                    Err(e) => {
+                        #tracing_error
+                        Err(#panic_error)
+                    }
+                };
+                let mut res = #flatten_result;
+                #serialize_result
+                let out: &mut *mut u8 = unsafe { &mut *out_ptr };
+                *out = Box::into_raw(bytes) as *mut u8;
+                len
+            }
+        });
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        argument_name, expand, generate_constructors, impl_trait_type, receiver_from_pointer,
+        replace_self, substitute_generics, ExportArgs,
+    };
+
+    #[test]
+    fn patterns_get_synthesized_names() {
+        let sig: syn::Signature =
+            syn::parse_quote!(fn f(&self, (x, y): (f64, f64), _: u8, mut z: i32));
+        let names = sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(t) => Some(argument_name(&sig, t).to_string()),
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["arg0", "arg1", "z"]);
+    }
+
+    #[test]
+    fn impl_trait_arguments_are_deserialized_as_owned_types() {
+        let owned = |tpe: syn::TypeImplTrait| impl_trait_type(&tpe).map(|t| t.to_string());
+        assert_eq!(
+            owned(syn::parse_quote!(impl Into<String>)).as_deref(),
+            Some("String")
+        );
+        assert_eq!(
+            owned(syn::parse_quote!(impl AsRef<str> + 'static)).as_deref(),
+            Some("String")
+        );
+        assert_eq!(
+            owned(syn::parse_quote!(impl AsRef<[i64]>)).as_deref(),
+            Some("Vec < i64 >")
+        );
+        assert_eq!(owned(syn::parse_quote!(impl AsRef<Path>)), None);
+        assert_eq!(owned(syn::parse_quote!(impl Display)), None);
+    }
+
+    #[test]
+    fn generic_parameters_are_substituted() {
+        let param: syn::Ident = syn::parse_quote!(T);
+        let concrete: syn::Type = syn::parse_quote!(Vec<u8>);
+        let sig: syn::Signature = syn::parse_quote!(fn f<'T>(&'T self, item: &T, key: T::Key) -> Result<Option<T>, String>);
+        let substituted = substitute_generics(
+            quote::ToTokens::to_token_stream(&sig),
+            &[(&param, &concrete)],
+        );
+        let expected: syn::Signature = syn::parse_quote!(
+            fn f<'T>(&'T self, item: &Vec<u8>, key: <Vec<u8>>::Key) -> Result<Option<Vec<u8>>, String>
+        );
+        assert_eq!(
+            substituted.to_string(),
+            quote::ToTokens::to_token_stream(&expected).to_string()
+        );
+    }
+
+    #[test]
+    fn constructors_are_moved_out_of_the_exported_impl() {
+        let mut impl_item: syn::ItemImpl = syn::parse_quote! {
+            impl Client {
+                #[buffi(constructor)]
+                pub fn new() -> Self {
+                    Self
+                }
+
+                pub fn call(&self) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+        };
+        let args = ExportArgs::default();
+        let constructor = generate_constructors(&mut impl_item, &mut Vec::new(), &args, "buffi")
+            .unwrap()
+            .unwrap();
+        assert_eq!(impl_item.items.len(), 1);
+        let [syn::ImplItem::Fn(new)] = &constructor.items[..] else {
+            panic!("expected the constructor");
+        };
+        assert_eq!(new.sig.ident, "new");
+        let marker: syn::Attribute = syn::parse_quote!(#[cfg(not(generated_constructor_marker))]);
+        assert_eq!(new.attrs, [marker]);
+
+        let mut invalid: syn::ItemImpl = syn::parse_quote! {
+            impl Client {
+                #[buffi(constructor)]
+                pub async fn connect(url: String) -> Self {
+                    Self
+                }
+            }
+        };
+        assert!(generate_constructors(&mut invalid, &mut Vec::new(), &args, "buffi").is_err());
+    }
+
+    #[test]
+    fn functions_returning_self_are_fallible_constructors() {
+        let mut impl_item: syn::ItemImpl = syn::parse_quote! {
+            impl Client {
+                pub fn connect(url: &str, retries: u32) -> Result<Self, ConnectionError> {
+                    Ok(Self)
+                }
+
+                fn default_retries() -> Self {
+                    Self
+                }
+            }
+        };
+        let mut exports = Vec::new();
+        let constructor = generate_constructors(
+            &mut impl_item,
+            &mut exports,
+            &ExportArgs::default(),
+            "buffi",
+        )
+        .unwrap()
+        .unwrap();
+        // private functions returning `Self` are not exported as constructor
+        assert_eq!(impl_item.items.len(), 1);
+        assert_eq!(constructor.items.len(), 1);
+        let tokens = exports
+            .iter()
+            .map(|tokens| tokens.to_string())
+            .collect::<String>();
+        assert!(tokens.contains(
+            "fn buffi_create_client (url : * const u8 , url_size : usize , retries : * const u8 , \
+             retries_size : usize , value_out : * mut * mut Client , error_out : * mut * mut u8 ,)"
+        ));
+        assert!(tokens.contains("let url : String = bincode :: deserialize (slice) ?"));
+        assert!(tokens.contains(
+            "< Client > :: connect (& url , retries ,) . map_err (crate :: errors :: SerializableError :: from)"
+        ));
+        assert!(tokens.contains("fn buffi_free_client"));
+    }
+
+    #[test]
+    fn the_marker_selects_one_of_several_constructors() {
+        let mut impl_item: syn::ItemImpl = syn::parse_quote! {
+            impl Client {
+                pub fn new() -> Self {
+                    Self
+                }
+
+                #[buffi(constructor)]
+                pub fn load(path: String) -> Result<Self, ConnectionError> {
+                    Ok(Self)
+                }
+            }
+        };
+        let mut exports = Vec::new();
+        let args = ExportArgs::default();
+        let constructor = generate_constructors(&mut impl_item, &mut exports, &args, "buffi")
+            .unwrap()
+            .unwrap();
+        // `new` stays in the impl block, so it is exported as a regular function
+        let [syn::ImplItem::Fn(new)] = &impl_item.items[..] else {
+            panic!("expected the unmarked function");
+        };
+        assert_eq!(new.sig.ident, "new");
+        let [syn::ImplItem::Fn(load)] = &constructor.items[..] else {
+            panic!("expected the constructor");
+        };
+        assert_eq!(load.sig.ident, "load");
+
+        let mut ambiguous: syn::ItemImpl = syn::parse_quote! {
+            impl Client {
+                pub fn new() -> Self {
+                    Self
+                }
+
+                pub fn load(path: String) -> Result<Self, ConnectionError> {
+                    Ok(Self)
+                }
+            }
+        };
+        let error =
+            generate_constructors(&mut ambiguous, &mut Vec::new(), &args, "buffi").unwrap_err();
+        assert!(error.to_string().contains("#[buffi(constructor)]"));
+
+        let mut generic: syn::ItemImpl = syn::parse_quote! {
+            impl<T> Wrapper<T> {
+                pub fn new() -> Self {
+                    Self
+                }
+            }
+        };
+        let mut exports = Vec::new();
+        assert!(
+            generate_constructors(&mut generic, &mut exports, &args, "buffi")
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(generic.items.len(), 1);
+        assert!(exports.is_empty());
+
+        let mut marked_generic: syn::ItemImpl = syn::parse_quote! {
+            impl<T> Wrapper<T> {
+                #[buffi(constructor)]
+                pub fn new() -> Self {
+                    Self
+                }
+            }
+        };
+        assert!(
+            generate_constructors(&mut marked_generic, &mut Vec::new(), &args, "buffi").is_err()
+        );
+    }
+
+    #[test]
+    fn receivers_are_created_from_the_pointer() {
+        let receiver = |sig: syn::Signature| {
+            let Some(syn::FnArg::Receiver(r)) = sig.inputs.first() else {
+                panic!("expected a receiver");
+            };
+            receiver_from_pointer(r).map(|tokens| tokens.to_string())
+        };
+        assert_eq!(
+            receiver(syn::parse_quote!(fn f(&self))).unwrap(),
+            "& * this_ptr"
+        );
+        assert_eq!(
+            receiver(syn::parse_quote!(fn f(&mut self))).unwrap(),
+            "& mut * this_ptr"
+        );
+        assert!(receiver(syn::parse_quote!(fn f(self: Arc<Self>)))
+            .unwrap()
+            .contains("increment_strong_count"));
+        assert_eq!(
+            receiver(syn::parse_quote!(fn f(self: Box<Self>))).unwrap(),
+            "Box :: from_raw (this_ptr)"
+        );
+        assert_eq!(
+            receiver(syn::parse_quote!(fn f(self: std::pin::Pin<&mut Self>))).unwrap(),
+            "std :: pin :: Pin :: new_unchecked (& mut * this_ptr)"
+        );
+        assert!(receiver(syn::parse_quote!(fn f(self))).is_err());
+        assert!(receiver(syn::parse_quote!(fn f(self: Rc<Self>))).is_err());
+    }
+
+    #[test]
+    fn where_clauses_are_supported_but_type_parameters_are_not() {
+        let item: syn::Item = syn::parse_quote! {
+            impl<T: Clone> Repository<T> {
+                pub fn first(&self) -> Result<Option<T>, String>
+                where
+                    T: serde::Serialize,
+                    Self: Sized,
+                {
+                    Ok(self.items.first().cloned())
+                }
+            }
+        };
+        let mut args = ExportArgs::default();
+        args.instances.push((
+            syn::parse_quote!(IntRepository),
+            syn::parse_quote!(Repository<i64>),
+        ));
+        assert!(expand(item, &args).is_ok());
+
+        let item: syn::Item = syn::parse_quote! {
+            pub fn convert<'a, T: serde::Serialize>(value: &'a T) -> Result<String, String> {
+                Ok(String::new())
+            }
+        };
+        let error = expand(item, &ExportArgs::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("cannot have type or const parameters"));
+    }
+
+    #[test]
+    fn generic_functions_are_exported_per_instantiation() {
+        let item: syn::Item = syn::parse_quote! {
+            /// Returns the given value
+            pub fn identity<'a, T: Clone>(value: &'a T) -> Result<T, String> {
+                Ok(value.clone())
+            }
+        };
+        let mut args = ExportArgs::default();
+        let parser = syn::meta::parser(|meta| args.parse(meta));
+        syn::parse::Parser::parse2(parser, quote::quote!(instantiate(T = i64, T = Vec<u8>)))
+            .unwrap();
+        assert_eq!(args.instantiations.len(), 2);
+        let tokens = expand(item, &args).unwrap().to_string();
+        assert!(tokens.contains(
+            "pub fn identity_i64 < 'a > (value : & 'a i64) -> Result < i64 , String > \
+             { identity :: < i64 > (value) }"
+        ));
+        assert!(tokens.contains("pub fn identity_vec_u8 < 'a > (value : & 'a Vec < u8 >)"));
+        assert!(tokens.contains("fn buffi_identity_vec_u8"));
+        assert!(tokens.contains("generated_overload_marker = \"identity\""));
+        // only the instantiations are exported
+        assert_eq!(tokens.matches("generated_extern_impl").count(), 2);
+
+        let item: syn::Item = syn::parse_quote! {
+            pub fn pair<K, V>(key: K, value: V) -> Result<(K, V), String> {
+                Ok((key, value))
+            }
+        };
+        let mut args = ExportArgs::default();
+        args.instantiations
+            .push(vec![(syn::parse_quote!(K), syn::parse_quote!(String))]);
+        let error = expand(item, &args).unwrap_err();
+        assert!(error.to_string().contains("needs a type for `V`"));
+    }
+
+    #[test]
+    fn only_functions_impl_blocks_and_constants_are_exported() {
+        let item: syn::Item = syn::parse_quote! {
+            pub struct Client;
+        };
+        let error = expand(item, &ExportArgs::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("only functions, impl blocks and constants can be exported"));
+    }
+
+    #[test]
+    fn raw_bytes_require_a_byte_vector() {
+        let item: syn::Item = syn::parse_quote! {
+            #[buffi(raw_bytes)]
+            pub fn thumbnail(id: i64) -> Result<Vec<u8>, String> {
+                Ok(Vec::new())
+            }
+        };
+        let tokens = expand(item, &ExportArgs::default()).unwrap().to_string();
+        assert!(tokens.contains("value_out : * mut * mut u8"));
+        assert!(tokens.contains("generated_raw_bytes_marker"));
+
+        let item: syn::Item = syn::parse_quote! {
+            #[buffi(raw_bytes)]
+            pub fn sizes(id: i64) -> Result<Vec<i64>, String> {
+                Ok(Vec::new())
+            }
+        };
+        let error = expand(item, &ExportArgs::default()).unwrap_err();
+        assert!(error.to_string().contains("`Vec<u8>`"));
+
+        let item: syn::Item = syn::parse_quote! {
+            #[buffi(raw_bytes, chunked)]
+            pub fn chunks(id: i64) -> Result<Vec<u8>, String> {
+                Ok(Vec::new())
+            }
+        };
+        let error = expand(item, &ExportArgs::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("cannot use `chunked` and `raw_bytes` at the same time"));
+    }
+
+    #[test]
+    fn exported_functions_use_the_given_prefix() {
+        let item: syn::Item = syn::parse_quote! {
+            pub fn answer() -> Result<i64, String> {
+                Ok(42)
+            }
+        };
+        let mut args = ExportArgs::default();
+        let parser = syn::meta::parser(|meta| args.parse(meta));
+        syn::parse::Parser::parse_str(parser, "prefix = \"myapi\"").unwrap();
+        let tokens = expand(item, &args).unwrap().to_string();
+        assert!(tokens.contains("fn myapi_answer"));
+        assert!(!tokens.contains("buffi_answer"));
+
+        let mut args = ExportArgs::default();
+        let parser = syn::meta::parser(|meta| args.parse(meta));
+        let error = syn::parse::Parser::parse_str(parser, "prefix = \"my-api\"").unwrap_err();
+        assert!(error.to_string().contains("valid identifier"));
+    }
+
+    #[test]
+    fn callbacks_are_passed_as_function_pointers() {
+        let item: syn::Item = syn::parse_quote! {
+            pub fn import(path: String, #[buffi(callback)] progress: impl Fn(u64)) -> Result<i64, String> {
+                Ok(0)
+            }
+        };
+        let tokens = expand(item, &ExportArgs::default()).unwrap().to_string();
+        assert!(tokens.contains("progress_context : * mut std :: ffi :: c_void"));
+        assert!(tokens.contains("The callback `progress` is null"));
+
+        let item: syn::Item = syn::parse_quote! {
+            pub async fn import(#[buffi(callback)] progress: impl Fn(u64)) -> Result<i64, String> {
+                Ok(0)
+            }
+        };
+        let error = expand(
+            item,
+            &ExportArgs {
+                global_runtime: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("only supported for synchronous functions"));
+
+        let item: syn::Item = syn::parse_quote! {
+            pub fn import(#[buffi(callback)] progress: u64) -> Result<i64, String> {
+                Ok(0)
+            }
+        };
+        let error = expand(item, &ExportArgs::default()).unwrap_err();
+        assert!(error.to_string().contains("`impl Fn(..)`"));
+    }
+
+    #[test]
+    fn runtime_expressions_use_the_dereferenced_pointer() {
+        let expr: syn::Expr = syn::parse_quote!(self.inner.rt.handle());
+        assert_eq!(
+            replace_self(quote::quote!(#expr)).to_string(),
+            "this . inner . rt . handle ()"
+        );
+
+        let item: syn::Item = syn::parse_quote! {
+            impl Client {
+                pub async fn fetch(&self) -> Result<i64, String> {
+                    Ok(0)
+                }
+            }
+        };
+        let args = ExportArgs {
+            runtime: Some(expr),
+            ..Default::default()
+        };
+        let tokens = expand(item.clone(), &args).unwrap().to_string();
+        assert!(tokens.contains("std :: clone :: Clone :: clone (& this . inner . rt . handle ())"));
+
+        let args = ExportArgs {
+            global_runtime: true,
+            ..args
+        };
+        let error = expand(item, &args).unwrap_err();
+        assert!(error.to_string().contains("at the same time"));
+    }
+}
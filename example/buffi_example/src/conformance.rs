@@ -0,0 +1,130 @@
+//! Every type shape supported by BuFFI, one function per construct
+//!
+//! This module is only compiled with the `conformance` feature. The test suite generates the
+//! bindings with this feature enabled and compares the C++ declarations of each construct with
+//! the golden files in `tests/conformance`.
+
+use serde::{Deserialize, Serialize};
+
+/// All supported primitive types
+#[derive(Serialize, Deserialize)]
+pub struct Primitives {
+    pub boolean: bool,
+    pub int8: i8,
+    pub int16: i16,
+    pub int32: i32,
+    pub int64: i64,
+    pub uint8: u8,
+    pub uint16: u16,
+    pub uint32: u32,
+    pub uint64: u64,
+    pub float32: f32,
+    pub float64: f64,
+    /// Transferred as 64 bit value on every target
+    pub size: usize,
+    /// Transferred as 64 bit value on every target
+    pub offset: isize,
+    pub text: String,
+    /// Binary data, serialized as bytes
+    #[serde(with = "serde_bytes")]
+    pub blob: Vec<u8>,
+    pub buffer: serde_bytes::ByteBuf,
+}
+
+/// A generic struct, named after its arguments in C++
+#[derive(Serialize, Deserialize)]
+pub struct Wrapper<T> {
+    pub value: T,
+}
+
+/// Generic types nested in each other
+#[derive(Serialize, Deserialize)]
+pub struct NestedGenerics {
+    pub wrapped: Wrapper<Wrapper<i32>>,
+    pub nested: Vec<Option<Vec<i64>>>,
+    pub lookup: [Option<u8>; 2],
+    pub labels: [String; 2],
+    pub corners: [Wrapper<i32>; 4],
+}
+
+/// A type that refers to itself
+#[derive(Serialize, Deserialize)]
+pub struct Node {
+    pub value: i64,
+    pub next: Option<Box<Self>>,
+}
+
+/// Types of other crates that do not implement the serde traits
+pub mod remote {
+    /// A type without serde support
+    pub struct Temperature {
+        pub degrees: f64,
+    }
+}
+
+/// The serde definition of `remote::Temperature`
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "remote::Temperature")]
+pub struct TemperatureDef {
+    /// In degrees Celsius
+    pub degrees: f64,
+}
+
+/// An enum containing a type with a remote serde definition
+#[derive(Serialize, Deserialize)]
+pub enum Measurement {
+    Temperature(#[serde(with = "crate::conformance::TemperatureDef")] remote::Temperature),
+    Missing,
+}
+
+/// An enum with only unit variants
+#[derive(Serialize, Deserialize)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// An enum with all kinds of variants
+#[derive(Serialize, Deserialize)]
+pub enum Shape {
+    Point,
+    Circle(f64),
+    Rectangle(f64, f64),
+    Polygon {
+        corners: Vec<[f64; 2]>,
+        closed: bool,
+    },
+}
+
+/// Returns all primitive types
+#[buffi_macro::exported]
+pub fn conformance_primitives(value: Primitives) -> Result<Primitives, String> {
+    Ok(value)
+}
+
+/// Returns nested generic types
+#[buffi_macro::exported]
+pub fn conformance_nested_generics(value: NestedGenerics) -> Result<NestedGenerics, String> {
+    Ok(value)
+}
+
+/// Returns a recursive type
+#[buffi_macro::exported]
+pub fn conformance_recursive_types(value: Node) -> Result<Node, String> {
+    Ok(value)
+}
+
+/// Returns a type with a remote serde definition
+#[buffi_macro::exported]
+pub fn conformance_remote_types(value: Measurement) -> Result<Measurement, String> {
+    Ok(value)
+}
+
+/// Returns enums with each kind of variant
+#[buffi_macro::exported]
+pub fn conformance_enums(direction: Direction, shape: Shape) -> Result<Shape, String> {
+    let _ = direction;
+    Ok(shape)
+}
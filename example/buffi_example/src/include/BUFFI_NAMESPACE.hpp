@@ -5,6 +5,61 @@
 
 namespace BUFFI_NAMESPACE {
 
+    /// A snapshot of the recorded statistics
+    struct BufferStatistics {
+        /// The number of calls to exported functions
+        uint64_t calls;
+        /// The number of result buffers allocated by exported functions
+        uint64_t allocated_buffers;
+        /// The total size of all allocated result buffers
+        uint64_t allocated_bytes;
+        /// The number of result buffers freed via `buffi_free_byte_buffer`
+        uint64_t freed_buffers;
+        /// The total size of all freed result buffers
+        uint64_t freed_bytes;
+        /// The number of result buffers that were not freed yet
+        uint64_t outstanding_buffers;
+        /// The total size of all result buffers that were not freed yet
+        uint64_t outstanding_bytes;
+
+        friend bool operator==(const BufferStatistics&, const BufferStatistics&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static BufferStatistics bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct SerializableError {
+        std::string message;
+
+        friend bool operator==(const SerializableError&, const SerializableError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static SerializableError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Result_BufferStatistics_SerializableError {
+
+        struct Ok {
+            std::tuple<BUFFI_NAMESPACE::BufferStatistics> value;
+
+            friend bool operator==(const Ok&, const Ok&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Ok bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Err {
+            std::tuple<BUFFI_NAMESPACE::SerializableError> value;
+
+            friend bool operator==(const Err&, const Err&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Err bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Ok, Err> value;
+
+        friend bool operator==(const Result_BufferStatistics_SerializableError&, const Result_BufferStatistics_SerializableError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Result_BufferStatistics_SerializableError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
     struct CustomType;
 
     /// A custom type that needs to be available in C++ as well
@@ -19,14 +74,6 @@ namespace BUFFI_NAMESPACE {
         static CustomType bincodeDeserialize(std::vector<uint8_t>);
     };
 
-    struct SerializableError {
-        std::string message;
-
-        friend bool operator==(const SerializableError&, const SerializableError&);
-        std::vector<uint8_t> bincodeSerialize() const;
-        static SerializableError bincodeDeserialize(std::vector<uint8_t>);
-    };
-
     struct Result_CustomType_SerializableError {
 
         struct Ok {
@@ -52,6 +99,68 @@ namespace BUFFI_NAMESPACE {
         static Result_CustomType_SerializableError bincodeDeserialize(std::vector<uint8_t>);
     };
 
+    /// A type that contains reference counted fields
+    struct SharedContent {
+        /// Content that might be shared between threads
+        std::string shared;
+        /// Content that might be shared on the same thread
+        std::vector<int64_t> local;
+
+        friend bool operator==(const SharedContent&, const SharedContent&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static SharedContent bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Result_SharedContent_SerializableError {
+
+        struct Ok {
+            std::tuple<BUFFI_NAMESPACE::SharedContent> value;
+
+            friend bool operator==(const Ok&, const Ok&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Ok bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Err {
+            std::tuple<BUFFI_NAMESPACE::SerializableError> value;
+
+            friend bool operator==(const Err&, const Err&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Err bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Ok, Err> value;
+
+        friend bool operator==(const Result_SharedContent_SerializableError&, const Result_SharedContent_SerializableError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Result_SharedContent_SerializableError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Result_Array_3_f64_SerializableError {
+
+        struct Ok {
+            std::tuple<std::array<double, 3>> value;
+
+            friend bool operator==(const Ok&, const Ok&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Ok bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Err {
+            std::tuple<BUFFI_NAMESPACE::SerializableError> value;
+
+            friend bool operator==(const Err&, const Err&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Err bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Ok, Err> value;
+
+        friend bool operator==(const Result_Array_3_f64_SerializableError&, const Result_Array_3_f64_SerializableError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Result_Array_3_f64_SerializableError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
     struct Result_String_SerializableError {
 
         struct Ok {
@@ -77,6 +186,56 @@ namespace BUFFI_NAMESPACE {
         static Result_String_SerializableError bincodeDeserialize(std::vector<uint8_t>);
     };
 
+    struct Result_Vec_i64_SerializableError {
+
+        struct Ok {
+            std::tuple<std::vector<int64_t>> value;
+
+            friend bool operator==(const Ok&, const Ok&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Ok bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Err {
+            std::tuple<BUFFI_NAMESPACE::SerializableError> value;
+
+            friend bool operator==(const Err&, const Err&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Err bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Ok, Err> value;
+
+        friend bool operator==(const Result_Vec_i64_SerializableError&, const Result_Vec_i64_SerializableError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Result_Vec_i64_SerializableError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Result_Vec_u8_SerializableError {
+
+        struct Ok {
+            std::tuple<std::vector<uint8_t>> value;
+
+            friend bool operator==(const Ok&, const Ok&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Ok bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Err {
+            std::tuple<BUFFI_NAMESPACE::SerializableError> value;
+
+            friend bool operator==(const Err&, const Err&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Err bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Ok, Err> value;
+
+        friend bool operator==(const Result_Vec_u8_SerializableError&, const Result_Vec_u8_SerializableError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Result_Vec_u8_SerializableError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
     struct Result_i64_SerializableError {
 
         struct Ok {
@@ -102,6 +261,43 @@ namespace BUFFI_NAMESPACE {
         static Result_i64_SerializableError bincodeDeserialize(std::vector<uint8_t>);
     };
 
+    /// An error that is transferred as it is instead of being converted to a `SerializableError`
+    struct ValidationError {
+        /// The name of the invalid argument
+        std::string field;
+        /// Why the argument is invalid
+        std::string message;
+
+        friend bool operator==(const ValidationError&, const ValidationError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static ValidationError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Result_i64_ValidationError {
+
+        struct Ok {
+            std::tuple<int64_t> value;
+
+            friend bool operator==(const Ok&, const Ok&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Ok bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Err {
+            std::tuple<BUFFI_NAMESPACE::ValidationError> value;
+
+            friend bool operator==(const Err&, const Err&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Err bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Ok, Err> value;
+
+        friend bool operator==(const Result_i64_ValidationError&, const Result_i64_ValidationError&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Result_i64_ValidationError bincodeDeserialize(std::vector<uint8_t>);
+    };
+
     struct Result_void_SerializableError {
 
         struct Ok {
@@ -138,6 +334,66 @@ namespace BUFFI_NAMESPACE {
 } // end of namespace BUFFI_NAMESPACE
 
 
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const BufferStatistics &lhs, const BufferStatistics &rhs) {
+        if (!(lhs.calls == rhs.calls)) { return false; }
+        if (!(lhs.allocated_buffers == rhs.allocated_buffers)) { return false; }
+        if (!(lhs.allocated_bytes == rhs.allocated_bytes)) { return false; }
+        if (!(lhs.freed_buffers == rhs.freed_buffers)) { return false; }
+        if (!(lhs.freed_bytes == rhs.freed_bytes)) { return false; }
+        if (!(lhs.outstanding_buffers == rhs.outstanding_buffers)) { return false; }
+        if (!(lhs.outstanding_bytes == rhs.outstanding_bytes)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> BufferStatistics::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<BufferStatistics>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline BufferStatistics BufferStatistics::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<BufferStatistics>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::BufferStatistics>::serialize(const BUFFI_NAMESPACE::BufferStatistics &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.calls)>::serialize(obj.calls, serializer);
+    serde::Serializable<decltype(obj.allocated_buffers)>::serialize(obj.allocated_buffers, serializer);
+    serde::Serializable<decltype(obj.allocated_bytes)>::serialize(obj.allocated_bytes, serializer);
+    serde::Serializable<decltype(obj.freed_buffers)>::serialize(obj.freed_buffers, serializer);
+    serde::Serializable<decltype(obj.freed_bytes)>::serialize(obj.freed_bytes, serializer);
+    serde::Serializable<decltype(obj.outstanding_buffers)>::serialize(obj.outstanding_buffers, serializer);
+    serde::Serializable<decltype(obj.outstanding_bytes)>::serialize(obj.outstanding_bytes, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::BufferStatistics serde::Deserializable<BUFFI_NAMESPACE::BufferStatistics>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::BufferStatistics obj;
+    obj.calls = serde::Deserializable<decltype(obj.calls)>::deserialize(deserializer);
+    obj.allocated_buffers = serde::Deserializable<decltype(obj.allocated_buffers)>::deserialize(deserializer);
+    obj.allocated_bytes = serde::Deserializable<decltype(obj.allocated_bytes)>::deserialize(deserializer);
+    obj.freed_buffers = serde::Deserializable<decltype(obj.freed_buffers)>::deserialize(deserializer);
+    obj.freed_bytes = serde::Deserializable<decltype(obj.freed_bytes)>::deserialize(deserializer);
+    obj.outstanding_buffers = serde::Deserializable<decltype(obj.outstanding_buffers)>::deserialize(deserializer);
+    obj.outstanding_bytes = serde::Deserializable<decltype(obj.outstanding_bytes)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
 namespace BUFFI_NAMESPACE {
 
     inline bool operator==(const CustomType &lhs, const CustomType &rhs) {
@@ -148,13 +404,613 @@ namespace BUFFI_NAMESPACE {
 
     inline std::vector<uint8_t> CustomType::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<CustomType>::serialize(*this, serializer);
+        serde::Serializable<CustomType>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline CustomType CustomType::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<CustomType>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::CustomType>::serialize(const BUFFI_NAMESPACE::CustomType &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.some_content)>::serialize(obj.some_content, serializer);
+    serde::Serializable<decltype(obj.itself)>::serialize(obj.itself, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::CustomType serde::Deserializable<BUFFI_NAMESPACE::CustomType>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::CustomType obj;
+    obj.some_content = serde::Deserializable<decltype(obj.some_content)>::deserialize(deserializer);
+    obj.itself = serde::Deserializable<decltype(obj.itself)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Point1_f64 &lhs, const Point1_f64 &rhs) {
+        if (!(lhs.x == rhs.x)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Point1_f64::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Point1_f64>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Point1_f64 Point1_f64::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Point1_f64>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Point1_f64>::serialize(const BUFFI_NAMESPACE::Point1_f64 &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.x)>::serialize(obj.x, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Point1_f64 serde::Deserializable<BUFFI_NAMESPACE::Point1_f64>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Point1_f64 obj;
+    obj.x = serde::Deserializable<decltype(obj.x)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_Array_3_f64_SerializableError &lhs, const Result_Array_3_f64_SerializableError &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_Array_3_f64_SerializableError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_Array_3_f64_SerializableError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_Array_3_f64_SerializableError Result_Array_3_f64_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_Array_3_f64_SerializableError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_Array_3_f64_SerializableError::Ok &lhs, const Result_Array_3_f64_SerializableError::Ok &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_Array_3_f64_SerializableError::Ok::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_Array_3_f64_SerializableError::Ok>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_Array_3_f64_SerializableError::Ok Result_Array_3_f64_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_Array_3_f64_SerializableError::Ok>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Ok &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Ok obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_Array_3_f64_SerializableError::Err &lhs, const Result_Array_3_f64_SerializableError::Err &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_Array_3_f64_SerializableError::Err::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_Array_3_f64_SerializableError::Err>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_Array_3_f64_SerializableError::Err Result_Array_3_f64_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_Array_3_f64_SerializableError::Err>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Err &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_Array_3_f64_SerializableError::Err obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_BufferStatistics_SerializableError &lhs, const Result_BufferStatistics_SerializableError &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_BufferStatistics_SerializableError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_BufferStatistics_SerializableError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_BufferStatistics_SerializableError Result_BufferStatistics_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_BufferStatistics_SerializableError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_BufferStatistics_SerializableError::Ok &lhs, const Result_BufferStatistics_SerializableError::Ok &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_BufferStatistics_SerializableError::Ok::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_BufferStatistics_SerializableError::Ok>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_BufferStatistics_SerializableError::Ok Result_BufferStatistics_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_BufferStatistics_SerializableError::Ok>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Ok &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Ok obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_BufferStatistics_SerializableError::Err &lhs, const Result_BufferStatistics_SerializableError::Err &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_BufferStatistics_SerializableError::Err::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_BufferStatistics_SerializableError::Err>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_BufferStatistics_SerializableError::Err Result_BufferStatistics_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_BufferStatistics_SerializableError::Err>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Err &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_BufferStatistics_SerializableError::Err obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_CustomType_SerializableError &lhs, const Result_CustomType_SerializableError &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_CustomType_SerializableError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_CustomType_SerializableError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_CustomType_SerializableError Result_CustomType_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_CustomType_SerializableError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_CustomType_SerializableError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_CustomType_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Result_CustomType_SerializableError obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_CustomType_SerializableError::Ok &lhs, const Result_CustomType_SerializableError::Ok &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_CustomType_SerializableError::Ok::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_CustomType_SerializableError::Ok>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_CustomType_SerializableError::Ok Result_CustomType_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_CustomType_SerializableError::Ok>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_CustomType_SerializableError::Err &lhs, const Result_CustomType_SerializableError::Err &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_CustomType_SerializableError::Err::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_CustomType_SerializableError::Err>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_CustomType_SerializableError::Err Result_CustomType_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_CustomType_SerializableError::Err>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_SharedContent_SerializableError &lhs, const Result_SharedContent_SerializableError &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_SharedContent_SerializableError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_SharedContent_SerializableError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_SharedContent_SerializableError Result_SharedContent_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_SharedContent_SerializableError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_SharedContent_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_SharedContent_SerializableError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_SharedContent_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_SharedContent_SerializableError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Result_SharedContent_SerializableError obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_SharedContent_SerializableError::Ok &lhs, const Result_SharedContent_SerializableError::Ok &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_SharedContent_SerializableError::Ok::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_SharedContent_SerializableError::Ok>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_SharedContent_SerializableError::Ok Result_SharedContent_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_SharedContent_SerializableError::Ok>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Ok &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Ok obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_SharedContent_SerializableError::Err &lhs, const Result_SharedContent_SerializableError::Err &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_SharedContent_SerializableError::Err::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_SharedContent_SerializableError::Err>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_SharedContent_SerializableError::Err Result_SharedContent_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_SharedContent_SerializableError::Err>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Err &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_SharedContent_SerializableError::Err obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_String_SerializableError &lhs, const Result_String_SerializableError &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_String_SerializableError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_String_SerializableError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_String_SerializableError Result_String_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_String_SerializableError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_String_SerializableError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_String_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_String_SerializableError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Result_String_SerializableError obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_String_SerializableError::Ok &lhs, const Result_String_SerializableError::Ok &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_String_SerializableError::Ok::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_String_SerializableError::Ok>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline CustomType CustomType::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_String_SerializableError::Ok Result_String_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<CustomType>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_String_SerializableError::Ok>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -165,40 +1021,34 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::CustomType>::serialize(const BUFFI_NAMESPACE::CustomType &obj, Serializer &serializer) {
-    serializer.increase_container_depth();
-    serde::Serializable<decltype(obj.some_content)>::serialize(obj.some_content, serializer);
-    serde::Serializable<decltype(obj.itself)>::serialize(obj.itself, serializer);
-    serializer.decrease_container_depth();
+void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_String_SerializableError::Ok &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
 }
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::CustomType serde::Deserializable<BUFFI_NAMESPACE::CustomType>::deserialize(Deserializer &deserializer) {
-    deserializer.increase_container_depth();
-    BUFFI_NAMESPACE::CustomType obj;
-    obj.some_content = serde::Deserializable<decltype(obj.some_content)>::deserialize(deserializer);
-    obj.itself = serde::Deserializable<decltype(obj.itself)>::deserialize(deserializer);
-    deserializer.decrease_container_depth();
+BUFFI_NAMESPACE::Result_String_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_String_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_String_SerializableError::Ok obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     return obj;
 }
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Point1_f64 &lhs, const Point1_f64 &rhs) {
-        if (!(lhs.x == rhs.x)) { return false; }
+    inline bool operator==(const Result_String_SerializableError::Err &lhs, const Result_String_SerializableError::Err &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Point1_f64::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_String_SerializableError::Err::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Point1_f64>::serialize(*this, serializer);
+        serde::Serializable<Result_String_SerializableError::Err>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Point1_f64 Point1_f64::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_String_SerializableError::Err Result_String_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Point1_f64>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_String_SerializableError::Err>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -209,38 +1059,34 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Point1_f64>::serialize(const BUFFI_NAMESPACE::Point1_f64 &obj, Serializer &serializer) {
-    serializer.increase_container_depth();
-    serde::Serializable<decltype(obj.x)>::serialize(obj.x, serializer);
-    serializer.decrease_container_depth();
+void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_String_SerializableError::Err &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
 }
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Point1_f64 serde::Deserializable<BUFFI_NAMESPACE::Point1_f64>::deserialize(Deserializer &deserializer) {
-    deserializer.increase_container_depth();
-    BUFFI_NAMESPACE::Point1_f64 obj;
-    obj.x = serde::Deserializable<decltype(obj.x)>::deserialize(deserializer);
-    deserializer.decrease_container_depth();
+BUFFI_NAMESPACE::Result_String_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_String_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_String_SerializableError::Err obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     return obj;
 }
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Result_CustomType_SerializableError &lhs, const Result_CustomType_SerializableError &rhs) {
+    inline bool operator==(const Result_Vec_i64_SerializableError &lhs, const Result_Vec_i64_SerializableError &rhs) {
         if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Result_CustomType_SerializableError::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_Vec_i64_SerializableError::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Result_CustomType_SerializableError>::serialize(*this, serializer);
+        serde::Serializable<Result_Vec_i64_SerializableError>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Result_CustomType_SerializableError Result_CustomType_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_Vec_i64_SerializableError Result_Vec_i64_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Result_CustomType_SerializableError>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_Vec_i64_SerializableError>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -251,7 +1097,7 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_CustomType_SerializableError &obj, Serializer &serializer) {
+void serde::Serializable<BUFFI_NAMESPACE::Result_Vec_i64_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_Vec_i64_SerializableError &obj, Serializer &serializer) {
     serializer.increase_container_depth();
     serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
     serializer.decrease_container_depth();
@@ -259,9 +1105,9 @@ void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError>::
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Result_CustomType_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError>::deserialize(Deserializer &deserializer) {
+BUFFI_NAMESPACE::Result_Vec_i64_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_Vec_i64_SerializableError>::deserialize(Deserializer &deserializer) {
     deserializer.increase_container_depth();
-    BUFFI_NAMESPACE::Result_CustomType_SerializableError obj;
+    BUFFI_NAMESPACE::Result_Vec_i64_SerializableError obj;
     obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     deserializer.decrease_container_depth();
     return obj;
@@ -269,20 +1115,20 @@ BUFFI_NAMESPACE::Result_CustomType_SerializableError serde::Deserializable<BUFFI
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Result_CustomType_SerializableError::Ok &lhs, const Result_CustomType_SerializableError::Ok &rhs) {
+    inline bool operator==(const Result_Vec_i64_SerializableError::Ok &lhs, const Result_Vec_i64_SerializableError::Ok &rhs) {
         if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Result_CustomType_SerializableError::Ok::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_Vec_i64_SerializableError::Ok::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Result_CustomType_SerializableError::Ok>::serialize(*this, serializer);
+        serde::Serializable<Result_Vec_i64_SerializableError::Ok>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Result_CustomType_SerializableError::Ok Result_CustomType_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_Vec_i64_SerializableError::Ok Result_Vec_i64_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Result_CustomType_SerializableError::Ok>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_Vec_i64_SerializableError::Ok>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -293,34 +1139,34 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok &obj, Serializer &serializer) {
+void serde::Serializable<BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Ok &obj, Serializer &serializer) {
     serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
 }
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
-    BUFFI_NAMESPACE::Result_CustomType_SerializableError::Ok obj;
+BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Ok obj;
     obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     return obj;
 }
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Result_CustomType_SerializableError::Err &lhs, const Result_CustomType_SerializableError::Err &rhs) {
+    inline bool operator==(const Result_Vec_i64_SerializableError::Err &lhs, const Result_Vec_i64_SerializableError::Err &rhs) {
         if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Result_CustomType_SerializableError::Err::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_Vec_i64_SerializableError::Err::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Result_CustomType_SerializableError::Err>::serialize(*this, serializer);
+        serde::Serializable<Result_Vec_i64_SerializableError::Err>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Result_CustomType_SerializableError::Err Result_CustomType_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_Vec_i64_SerializableError::Err Result_Vec_i64_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Result_CustomType_SerializableError::Err>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_Vec_i64_SerializableError::Err>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -331,34 +1177,34 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err &obj, Serializer &serializer) {
+void serde::Serializable<BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Err &obj, Serializer &serializer) {
     serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
 }
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err>::deserialize(Deserializer &deserializer) {
-    BUFFI_NAMESPACE::Result_CustomType_SerializableError::Err obj;
+BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_Vec_i64_SerializableError::Err obj;
     obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     return obj;
 }
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Result_String_SerializableError &lhs, const Result_String_SerializableError &rhs) {
+    inline bool operator==(const Result_Vec_u8_SerializableError &lhs, const Result_Vec_u8_SerializableError &rhs) {
         if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Result_String_SerializableError::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_Vec_u8_SerializableError::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Result_String_SerializableError>::serialize(*this, serializer);
+        serde::Serializable<Result_Vec_u8_SerializableError>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Result_String_SerializableError Result_String_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_Vec_u8_SerializableError Result_Vec_u8_SerializableError::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Result_String_SerializableError>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_Vec_u8_SerializableError>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -369,7 +1215,7 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_String_SerializableError &obj, Serializer &serializer) {
+void serde::Serializable<BUFFI_NAMESPACE::Result_Vec_u8_SerializableError>::serialize(const BUFFI_NAMESPACE::Result_Vec_u8_SerializableError &obj, Serializer &serializer) {
     serializer.increase_container_depth();
     serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
     serializer.decrease_container_depth();
@@ -377,9 +1223,9 @@ void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError>::seri
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Result_String_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_String_SerializableError>::deserialize(Deserializer &deserializer) {
+BUFFI_NAMESPACE::Result_Vec_u8_SerializableError serde::Deserializable<BUFFI_NAMESPACE::Result_Vec_u8_SerializableError>::deserialize(Deserializer &deserializer) {
     deserializer.increase_container_depth();
-    BUFFI_NAMESPACE::Result_String_SerializableError obj;
+    BUFFI_NAMESPACE::Result_Vec_u8_SerializableError obj;
     obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     deserializer.decrease_container_depth();
     return obj;
@@ -387,20 +1233,20 @@ BUFFI_NAMESPACE::Result_String_SerializableError serde::Deserializable<BUFFI_NAM
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Result_String_SerializableError::Ok &lhs, const Result_String_SerializableError::Ok &rhs) {
+    inline bool operator==(const Result_Vec_u8_SerializableError::Ok &lhs, const Result_Vec_u8_SerializableError::Ok &rhs) {
         if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Result_String_SerializableError::Ok::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_Vec_u8_SerializableError::Ok::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Result_String_SerializableError::Ok>::serialize(*this, serializer);
+        serde::Serializable<Result_Vec_u8_SerializableError::Ok>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Result_String_SerializableError::Ok Result_String_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_Vec_u8_SerializableError::Ok Result_Vec_u8_SerializableError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Result_String_SerializableError::Ok>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_Vec_u8_SerializableError::Ok>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -411,34 +1257,34 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_String_SerializableError::Ok &obj, Serializer &serializer) {
+void serde::Serializable<BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Ok>::serialize(const BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Ok &obj, Serializer &serializer) {
     serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
 }
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Result_String_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_String_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
-    BUFFI_NAMESPACE::Result_String_SerializableError::Ok obj;
+BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Ok obj;
     obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     return obj;
 }
 
 namespace BUFFI_NAMESPACE {
 
-    inline bool operator==(const Result_String_SerializableError::Err &lhs, const Result_String_SerializableError::Err &rhs) {
+    inline bool operator==(const Result_Vec_u8_SerializableError::Err &lhs, const Result_Vec_u8_SerializableError::Err &rhs) {
         if (!(lhs.value == rhs.value)) { return false; }
         return true;
     }
 
-    inline std::vector<uint8_t> Result_String_SerializableError::Err::bincodeSerialize() const {
+    inline std::vector<uint8_t> Result_Vec_u8_SerializableError::Err::bincodeSerialize() const {
         auto serializer = serde::BincodeSerializer();
-        serde::Serializable<Result_String_SerializableError::Err>::serialize(*this, serializer);
+        serde::Serializable<Result_Vec_u8_SerializableError::Err>::serialize(*this, serializer);
         return std::move(serializer).bytes();
     }
 
-    inline Result_String_SerializableError::Err Result_String_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+    inline Result_Vec_u8_SerializableError::Err Result_Vec_u8_SerializableError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
         auto deserializer = serde::BincodeDeserializer(input);
-        auto value = serde::Deserializable<Result_String_SerializableError::Err>::deserialize(deserializer);
+        auto value = serde::Deserializable<Result_Vec_u8_SerializableError::Err>::deserialize(deserializer);
         if (deserializer.get_buffer_offset() < input.size()) {
             throw serde::deserialization_error("Some input bytes were not read");
         }
@@ -449,14 +1295,14 @@ namespace BUFFI_NAMESPACE {
 
 template <>
 template <typename Serializer>
-void serde::Serializable<BUFFI_NAMESPACE::Result_String_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_String_SerializableError::Err &obj, Serializer &serializer) {
+void serde::Serializable<BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Err>::serialize(const BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Err &obj, Serializer &serializer) {
     serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
 }
 
 template <>
 template <typename Deserializer>
-BUFFI_NAMESPACE::Result_String_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_String_SerializableError::Err>::deserialize(Deserializer &deserializer) {
-    BUFFI_NAMESPACE::Result_String_SerializableError::Err obj;
+BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_Vec_u8_SerializableError::Err obj;
     obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
     return obj;
 }
@@ -579,6 +1425,124 @@ BUFFI_NAMESPACE::Result_i64_SerializableError::Err serde::Deserializable<BUFFI_N
     return obj;
 }
 
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_i64_ValidationError &lhs, const Result_i64_ValidationError &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_i64_ValidationError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_i64_ValidationError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_i64_ValidationError Result_i64_ValidationError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_i64_ValidationError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_i64_ValidationError>::serialize(const BUFFI_NAMESPACE::Result_i64_ValidationError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_i64_ValidationError serde::Deserializable<BUFFI_NAMESPACE::Result_i64_ValidationError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::Result_i64_ValidationError obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_i64_ValidationError::Ok &lhs, const Result_i64_ValidationError::Ok &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_i64_ValidationError::Ok::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_i64_ValidationError::Ok>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_i64_ValidationError::Ok Result_i64_ValidationError::Ok::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_i64_ValidationError::Ok>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_i64_ValidationError::Ok>::serialize(const BUFFI_NAMESPACE::Result_i64_ValidationError::Ok &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_i64_ValidationError::Ok serde::Deserializable<BUFFI_NAMESPACE::Result_i64_ValidationError::Ok>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_i64_ValidationError::Ok obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const Result_i64_ValidationError::Err &lhs, const Result_i64_ValidationError::Err &rhs) {
+        if (!(lhs.value == rhs.value)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> Result_i64_ValidationError::Err::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<Result_i64_ValidationError::Err>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline Result_i64_ValidationError::Err Result_i64_ValidationError::Err::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<Result_i64_ValidationError::Err>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::Result_i64_ValidationError::Err>::serialize(const BUFFI_NAMESPACE::Result_i64_ValidationError::Err &obj, Serializer &serializer) {
+    serde::Serializable<decltype(obj.value)>::serialize(obj.value, serializer);
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::Result_i64_ValidationError::Err serde::Deserializable<BUFFI_NAMESPACE::Result_i64_ValidationError::Err>::deserialize(Deserializer &deserializer) {
+    BUFFI_NAMESPACE::Result_i64_ValidationError::Err obj;
+    obj.value = serde::Deserializable<decltype(obj.value)>::deserialize(deserializer);
+    return obj;
+}
+
 namespace BUFFI_NAMESPACE {
 
     inline bool operator==(const Result_void_SerializableError &lhs, const Result_void_SerializableError &rhs) {
@@ -738,3 +1702,93 @@ BUFFI_NAMESPACE::SerializableError serde::Deserializable<BUFFI_NAMESPACE::Serial
     deserializer.decrease_container_depth();
     return obj;
 }
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const SharedContent &lhs, const SharedContent &rhs) {
+        if (!(lhs.shared == rhs.shared)) { return false; }
+        if (!(lhs.local == rhs.local)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> SharedContent::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<SharedContent>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline SharedContent SharedContent::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<SharedContent>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::SharedContent>::serialize(const BUFFI_NAMESPACE::SharedContent &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.shared)>::serialize(obj.shared, serializer);
+    serde::Serializable<decltype(obj.local)>::serialize(obj.local, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::SharedContent serde::Deserializable<BUFFI_NAMESPACE::SharedContent>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::SharedContent obj;
+    obj.shared = serde::Deserializable<decltype(obj.shared)>::deserialize(deserializer);
+    obj.local = serde::Deserializable<decltype(obj.local)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace BUFFI_NAMESPACE {
+
+    inline bool operator==(const ValidationError &lhs, const ValidationError &rhs) {
+        if (!(lhs.field == rhs.field)) { return false; }
+        if (!(lhs.message == rhs.message)) { return false; }
+        return true;
+    }
+
+    inline std::vector<uint8_t> ValidationError::bincodeSerialize() const {
+        auto serializer = serde::BincodeSerializer();
+        serde::Serializable<ValidationError>::serialize(*this, serializer);
+        return std::move(serializer).bytes();
+    }
+
+    inline ValidationError ValidationError::bincodeDeserialize(std::vector<uint8_t> input) {
+        auto deserializer = serde::BincodeDeserializer(input);
+        auto value = serde::Deserializable<ValidationError>::deserialize(deserializer);
+        if (deserializer.get_buffer_offset() < input.size()) {
+            throw serde::deserialization_error("Some input bytes were not read");
+        }
+        return value;
+    }
+
+} // end of namespace BUFFI_NAMESPACE
+
+template <>
+template <typename Serializer>
+void serde::Serializable<BUFFI_NAMESPACE::ValidationError>::serialize(const BUFFI_NAMESPACE::ValidationError &obj, Serializer &serializer) {
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.field)>::serialize(obj.field, serializer);
+    serde::Serializable<decltype(obj.message)>::serialize(obj.message, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+BUFFI_NAMESPACE::ValidationError serde::Deserializable<BUFFI_NAMESPACE::ValidationError>::deserialize(Deserializer &deserializer) {
+    deserializer.increase_container_depth();
+    BUFFI_NAMESPACE::ValidationError obj;
+    obj.field = serde::Deserializable<decltype(obj.field)>::deserialize(deserializer);
+    obj.message = serde::Deserializable<decltype(obj.message)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
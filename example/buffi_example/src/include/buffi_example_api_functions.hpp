@@ -4,11 +4,300 @@
 #include <limits>
 #include <cstdint>
 
+static_assert(sizeof(std::uint8_t) == 1, "BuFFI requires 8 bit bytes");
+static_assert(sizeof(std::uint64_t) == 8, "BuFFI requires 64 bit std::uint64_t");
+static_assert(sizeof(std::size_t) == 8, "The bindings were generated for a target with 64 bit usize, but size_t has a different width");
+
+#if defined(__FLOAT_WORD_ORDER__) && defined(__BYTE_ORDER__) && __FLOAT_WORD_ORDER__ != __BYTE_ORDER__
+#error "BuFFI requires floating point values to use the same byte order as integers"
+#endif
+
+namespace BUFFI_NAMESPACE {
+// The version of the API these bindings were generated for, compare `API_VERSION` with
+// the result of `buffi_api_version()` of the loaded library
+constexpr std::uint32_t API_VERSION_MAJOR = 0;
+constexpr std::uint16_t API_VERSION_MINOR = 1;
+constexpr std::uint16_t API_VERSION_PATCH = 0;
+constexpr std::uint64_t API_VERSION = 65536;
+}
+
+namespace BUFFI_NAMESPACE {
+// The separator used by `TestClient::count_words` if none is given
+constexpr const char* DEFAULT_SEPARATOR = " ";
+// The weight of words without an explicit weight in `TestClient::count_words`
+constexpr std::int64_t DEFAULT_WEIGHT = 1;
+}
+
 struct TestClient;
 
+struct ChunkedResult;
+
+// Creates a `TestClient` via `TestClient::new`
+//
+// It needs to be freed via `buffi_free_test_client`, null is returned if the constructor
+// panics.
+extern "C" TestClient* buffi_create_test_client();
+
+// Get a client to call functions
 extern "C" TestClient* get_test_client();
+
+// Tuples are represented by `std::tuple`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_add_pair(const std::uint8_t* pair, size_t pair_size, std::uint8_t** out_ptr);
+
+// An async function that needs a `Runtime` to be executed and returns a more complex type
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
 extern "C" size_t buffi_async_function(TestClient* this_ptr, const std::uint8_t* content, size_t content_size, std::uint8_t** out_ptr);
+
+// Binary blobs can be returned without serializing them via `#[buffi(raw_bytes)]`
+//
+// On success `value_out` points to the returned bytes (without any bincode
+// framing) and `error_out` is null. Otherwise `error_out` points to the
+// serialized error. Either buffer needs to be freed via `buffi_free_byte_buffer`
+// with the returned size.
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_byte_sequence(TestClient* this_ptr, const std::uint8_t* len, size_t len_size, std::uint8_t** value_out, std::uint8_t** error_out);
+
+// Primitive results can be returned without serializing them via `#[buffi(direct_return)]`
+//
+// On success the value is written to `value_out`. Otherwise `error_out` points
+// to the serialized error, which needs to be freed via `buffi_free_byte_buffer`.
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_checked_add(TestClient* this_ptr, const std::uint8_t* a, size_t a_size, const std::uint8_t* b, size_t b_size, std::int64_t* value_out, std::uint8_t** error_out);
+
+// The number of elements in all chunks of `chunks`
+//
+// Safety:
+// `chunks` needs to be returned by a chunked function and must not be freed yet
+extern "C" size_t buffi_chunked_result_len(const ChunkedResult* chunks);
+
+// A function that might use context provided by a TestClient to do its thing
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
 extern "C" size_t buffi_client_function(TestClient* this_ptr, const std::uint8_t* input, size_t input_size, std::uint8_t** out_ptr);
+
+// Counts down from `from`, `on_step` is called for each step and stops the countdown by
+// returning `false`
+//
+// The callbacks `on_step` are called with their `_context` and the serialized
+// arguments before this function returns. Each call needs to write the
+// serialized result to a buffer allocated via `buffi_alloc_byte_buffer`,
+// otherwise an error is returned.
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_count_down(const std::uint8_t* from, size_t from_size, size_t (*on_step)(void*, const std::uint8_t*, size_t, std::uint8_t**), void* on_step_context, std::uint8_t** out_ptr);
+
+// Reference arguments are passed like their owned counterpart
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_count_words(TestClient* this_ptr, const std::uint8_t* text, size_t text_size, const std::uint8_t* separator, size_t separator_size, const std::uint8_t* weights, size_t weights_size, const std::uint8_t* default_weight, size_t default_weight_size, std::uint8_t** out_ptr);
+
+// Overloads share their C++ name, this one is `describe(int64_t)`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_describe_number(TestClient* this_ptr, const std::uint8_t* number, size_t number_size, std::uint8_t** out_ptr);
+
+// Overloads share their C++ name, this one is `describe(std::string)`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_describe_text(TestClient* this_ptr, const std::uint8_t* text, size_t text_size, std::uint8_t** out_ptr);
+
+// An async function that is not part of an impl block
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_free_standing_async_function(const std::uint8_t* input, size_t input_size, std::uint8_t** out_ptr);
+
+// A function that is not part of an impl block
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
 extern "C" size_t buffi_free_standing_function(const std::uint8_t* input, size_t input_size, std::uint8_t** out_ptr);
+
+// Reference counted fields are represented by their content on the C++ side
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_get_shared_content(TestClient* this_ptr, const std::uint8_t* content, size_t content_size, std::uint8_t** out_ptr);
+
+// Serializes the next chunk of `chunks` and points `out_ptr` to it
+//
+// The buffer is owned by `chunks` and overwritten by the next call, so it must
+// not be freed. Returns 0 after the last chunk.
+//
+// Safety:
+// `chunks` needs to be returned by a chunked function and must not be freed yet
+extern "C" size_t buffi_next_chunk(ChunkedResult* chunks, const std::uint8_t** out_ptr);
+
+// Large vectors can be transferred in several chunks via `#[buffi(chunked)]`
+//
+// On success `value_out` points to the chunks of the result, which are
+// fetched via `buffi_next_chunk` and freed via `buffi_free_chunked_result`.
+// Otherwise `error_out` points to the serialized error, which needs to be
+// freed via `buffi_free_byte_buffer`.
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_number_sequence(TestClient* this_ptr, const std::uint8_t* len, size_t len_size, ChunkedResult** value_out, std::uint8_t** error_out);
+
+// Exported types can be passed by reference as well
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_process_custom_type(TestClient* this_ptr, const std::uint8_t* item, size_t item_size, std::uint8_t** out_ptr);
+
+// Fixed size arrays are represented by `std::array`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_scale_point(TestClient* this_ptr, const std::uint8_t* point, size_t point_size, const std::uint8_t* factor, size_t factor_size, std::uint8_t** out_ptr);
+
+// Statistics about the calls to exported functions and their result buffers
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_stats(std::uint8_t** out_ptr);
+
+// Maps like `BTreeMap` or `HashMap` are represented by `std::map`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_sum_values(const std::uint8_t* values, size_t values_size, std::uint8_t** out_ptr);
+
+// `Cow` arguments and results are represented by their owned counterpart
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_trim_text(TestClient* this_ptr, const std::uint8_t* text, size_t text_size, const std::uint8_t* values, size_t values_size, std::uint8_t** out_ptr);
+
+// Here we use a type from a third party crate and return `()`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
 extern "C" size_t buffi_use_foreign_type_and_return_nothing(TestClient* this_ptr, const std::uint8_t* point, size_t point_size, std::uint8_t** out_ptr);
+
+// Functions can transfer their own error type via `#[buffi(custom_error)]`
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+extern "C" size_t buffi_validate_content(TestClient* this_ptr, const std::uint8_t* content, size_t content_size, std::uint8_t** out_ptr);
+
+// The version of this library
+//
+// The major version is stored in the upper 32 bits, the minor version in the next
+// 16 bits and the patch version in the lower 16 bits. Compare it with the
+// `API_VERSION` constant of the generated bindings
+extern "C" std::uint64_t buffi_api_version();
+
+// Allocate a buffer of `size` bytes in the memory of this library, for example to
+// pass arguments from a WASM host
+//
+// The buffer needs to be freed via `buffi_free_byte_buffer`
+extern "C" std::uint8_t* buffi_alloc_byte_buffer(size_t size);
+
+// An async function that needs a `Runtime` to be executed and returns a more complex type
+//
+// This function does not block. It returns immediately and calls `callback`
+// with `context` and the serialized result as soon as the future resolved.
+// The result buffer needs to be freed via `buffi_free_byte_buffer`.
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+// The `this_ptr` needs to stay valid until the callback is called.
+extern "C" void buffi_async_function_with_callback(TestClient* this_ptr, const std::uint8_t* content, size_t content_size, void (*callback)(void*, std::uint8_t*, size_t), void* context);
+
+// This function frees a byte buffer allocated on the Rust side
+//
+// * `ptr`: The ptr to the buffer
+// * `size`: The size of the buffer
+//
+// Safety:
+//
+// Calling this function outside a destructor is highly unsafe
+// and result in a use-after-free
 extern "C" void buffi_free_byte_buffer(std::uint8_t* ptr, size_t size);
+
+// Free the chunks returned by a chunked function
+//
+// Safety:
+// `chunks` needs to be returned by a chunked function and must not be freed yet
+extern "C" void buffi_free_chunked_result(ChunkedResult* chunks);
+
+// An async function that is not part of an impl block
+//
+// This function does not block. It returns immediately and calls `callback`
+// with `context` and the serialized result as soon as the future resolved.
+// The result buffer needs to be freed via `buffi_free_byte_buffer`.
+//
+// Safety:
+// Unsafe code is used to dereference pointers to byte buffers.
+// We check every pointer before accessing it to make this process safe.
+// The `this_ptr` needs to stay valid until the callback is called.
+extern "C" void buffi_free_standing_async_function_with_callback(const std::uint8_t* input, size_t input_size, void (*callback)(void*, std::uint8_t*, size_t), void* context);
+
+// Frees a `TestClient` created via `buffi_create_test_client`, null is ignored
+//
+// Safety:
+// `ptr` needs to be returned by `buffi_create_test_client` and must not be used afterwards
+extern "C" void buffi_free_test_client(TestClient* ptr);
+
+// Install a panic hook that records the location of each panic, so that it is
+// included in the returned error
+//
+// * `capture_backtrace`: Record a backtrace as well
+extern "C" void buffi_install_panic_hook(bool capture_backtrace);
+
+// Register a callback that receives the log messages of the Rust side
+//
+// * `callback`: Called with the log level, the target and the message of each log
+//   event, pass a null pointer to disable logging
+// * `max_level`: The most verbose level that is forwarded: 0 = off, 1 = error,
+//   2 = warn, 3 = info, 4 = debug, 5 = trace
+extern "C" void buffi_set_log_callback(void (*callback)(std::uint8_t, const char*, const char*), std::uint8_t max_level);
+
+// Register the C++ implementation of `WordFilter`
+//
+// Passing a null pointer for any function unregisters the current
+// implementation.
+//
+// Safety:
+//
+// `context` needs to stay valid until `drop` is called with it. Each function
+// is called with `context` and the serialized arguments and writes its serialized
+// result to a buffer allocated via `buffi_alloc_byte_buffer`. If it does not
+// write a buffer, the Rust side panics.
+extern "C" void buffi_set_word_filter(void* context, void (*drop)(void*), size_t (*accept)(void*, const std::uint8_t*, size_t, std::uint8_t**));
@@ -0,0 +1,47 @@
+#pragma once
+
+#include <cstddef>
+#include <limits>
+#include <string>
+#include <system_error>
+#include "BUFFI_NAMESPACE.hpp"  // IWYU pragma: export
+
+
+namespace BUFFI_NAMESPACE {
+
+enum class SerializableErrorCode : int {
+    SerializableError = 1,
+};
+
+class SerializableErrorCategory : public std::error_category {
+public:
+    const char* name() const noexcept override { return "buffi_example"; }
+
+    std::string message(int code) const override {
+        switch (code) {
+            case 1: return "SerializableError";
+            default: return "Unknown error";
+        }
+    }
+};
+
+inline const std::error_category& serializable_error_category() {
+    static SerializableErrorCategory category;
+    return category;
+}
+
+inline std::error_code make_error_code(SerializableErrorCode code) {
+    return {static_cast<int>(code), serializable_error_category()};
+}
+
+inline std::error_code make_error_code(const SerializableError& error) {
+    (void)error;
+    return make_error_code(SerializableErrorCode::SerializableError);
+}
+
+}  // end of namespace BUFFI_NAMESPACE
+
+namespace std {
+template <>
+struct is_error_code_enum<BUFFI_NAMESPACE::SerializableErrorCode> : true_type {};
+}  // end of namespace std
@@ -2,13 +2,147 @@
 
 #include <cstddef>
 #include <limits>
-#include "buffi_example_api_functions.hpp"
+#include <array>
+#include <cstdint>
+#include <exception>
+#include <future>
+#include <functional>
+#include <map>
+#include <optional>
+#include <stdexcept>
+#include <string>
+#include <tuple>
+#include <utility>
+#include <variant>
+#include <vector>
+#include "buffi_example_api_functions.hpp"  // IWYU pragma: export
 
-#include "BUFFI_NAMESPACE.hpp"
+#include "BUFFI_NAMESPACE.hpp"  // IWYU pragma: export
 
 
 namespace BUFFI_NAMESPACE {
 
+    // Tuples are represented by `std::tuple`
+    inline int64_t add_pair(const std::tuple<int64_t, int64_t>& pair) {
+        auto serializer_pair = serde::BincodeSerializer();
+        serde::Serializable<std::tuple<int64_t, int64_t>>::serialize(pair, serializer_pair);
+        std::vector<uint8_t> pair_serialized = std::move(serializer_pair).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_add_pair(pair_serialized.data(), pair_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+
+    // Counts down from `from`, `on_step` is called for each step and stops the countdown by
+    // returning `false`
+    inline int64_t count_down(const uint8_t& from, const std::function<bool(uint8_t)>& on_step) {
+        auto serializer_from = serde::BincodeSerializer();
+        serde::Serializable<uint8_t>::serialize(from, serializer_from);
+        std::vector<uint8_t> from_serialized = std::move(serializer_from).bytes();
+        auto on_step_trampoline = [](void* context, const uint8_t* arg0, size_t arg0_size, uint8_t** out_ptr) -> size_t {
+            try {
+                auto deserializer_arg0 = serde::BincodeDeserializer(std::vector<uint8_t>(arg0, arg0 + arg0_size));
+                auto arg0_value = serde::Deserializable<uint8_t>::deserialize(deserializer_arg0);
+                auto result = (*static_cast<std::function<bool(uint8_t)>*>(context))(arg0_value);
+                auto serializer = serde::BincodeSerializer();
+                serde::Serializable<bool>::serialize(result, serializer);
+                std::vector<uint8_t> serialized_result = std::move(serializer).bytes();
+                *out_ptr = buffi_alloc_byte_buffer(serialized_result.size());
+                std::copy(serialized_result.begin(), serialized_result.end(), *out_ptr);
+                return serialized_result.size();
+            } catch (...) {
+                *out_ptr = nullptr;
+                return 0;
+            }
+        };
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_count_down(from_serialized.data(), from_serialized.size(), on_step_trampoline, const_cast<std::function<bool(uint8_t)>*>(&on_step), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+
+    // An async function that is not part of an impl block
+    inline int64_t free_standing_async_function(const int64_t& input) {
+        auto serializer_input = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(input, serializer_input);
+        std::vector<uint8_t> input_serialized = std::move(serializer_input).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_free_standing_async_function(input_serialized.data(), input_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // An async function that is not part of an impl block
+    //
+    // This function does not block, the result is provided via the returned future
+    inline std::future<int64_t> free_standing_async_function_async(const int64_t& input) {
+        auto serializer_input = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(input, serializer_input);
+        std::vector<uint8_t> input_serialized = std::move(serializer_input).bytes();
+        auto promise = new std::promise<int64_t>();
+        auto future = promise->get_future();
+
+        buffi_free_standing_async_function_with_callback(input_serialized.data(), input_serialized.size(), [](void* context, uint8_t* out_ptr, size_t res_size) {
+            auto promise = static_cast<std::promise<int64_t>*>(context);
+            std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+            buffi_free_byte_buffer(out_ptr, res_size);
+            try {
+                Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+                if (out.value.index() == 0) { // Ok
+                    auto ok = std::get<0>(out.value);
+                    promise->set_value(std::get<0>(ok.value));
+                } else { // Err
+                    auto err = std::get<1>(out.value);
+                    promise->set_exception(std::make_exception_ptr(std::get<0>(err.value)));
+                }
+            } catch (...) {
+                promise->set_exception(std::current_exception());
+            }
+            delete promise;
+        }, promise);
+
+        return future;
+    }
+
+
     // A function that is not part of an impl block
     inline int64_t free_standing_function(const int64_t& input) {
         auto serializer_input = serde::BincodeSerializer();
@@ -33,4 +167,49 @@ namespace BUFFI_NAMESPACE {
     }
 
 
+    // Statistics about the calls to exported functions and their result buffers
+    inline BufferStatistics stats() {
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_stats(&out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_BufferStatistics_SerializableError out = Result_BufferStatistics_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+
+    // Maps like `BTreeMap` or `HashMap` are represented by `std::map`
+    inline int64_t sum_values(const std::map<std::string, int64_t>& values) {
+        auto serializer_values = serde::BincodeSerializer();
+        serde::Serializable<std::map<std::string, int64_t>>::serialize(values, serializer_values);
+        std::vector<uint8_t> values_serialized = std::move(serializer_values).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_sum_values(values_serialized.data(), values_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+
 }  // end of namespace BUFFI_NAMESPACE
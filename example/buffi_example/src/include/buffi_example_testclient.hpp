@@ -2,22 +2,76 @@
 
 #include <cstddef>
 #include <limits>
-#include "buffi_example_api_functions.hpp"
+#include <array>
+#include <cstdint>
+#include <exception>
+#include <future>
+#include <functional>
+#include <map>
+#include <optional>
+#include <stdexcept>
+#include <string>
+#include <tuple>
+#include <utility>
+#include <variant>
+#include <vector>
+#include "buffi_example_api_functions.hpp"  // IWYU pragma: export
 
-#include "BUFFI_NAMESPACE.hpp"
+#include "BUFFI_NAMESPACE.hpp"  // IWYU pragma: export
 
 
 namespace BUFFI_NAMESPACE {
 
 class TestClientHolder {
     TestClient* inner;
+    // Whether `inner` was created by `create()` and is freed by this holder
+    bool owned = false;
 public:
     TestClientHolder(TestClient* ptr) {
         this->inner = ptr;
     }
 
+    // Creates a `TestClient` that is freed when the returned holder is destroyed
+    static TestClientHolder create() {
+        TestClient* inner = buffi_create_test_client();
+        if (inner == nullptr) {
+            throw std::runtime_error("Failed to create a `TestClient`");
+        }
+        TestClientHolder holder(inner);
+        holder.owned = true;
+        return holder;
+    }
+
+    TestClientHolder(const TestClientHolder&) = delete;
+    TestClientHolder& operator=(const TestClientHolder&) = delete;
+
+    TestClientHolder(TestClientHolder&& other) noexcept {
+        this->inner = std::exchange(other.inner, nullptr);
+        this->owned = std::exchange(other.owned, false);
+    }
+
+    TestClientHolder& operator=(TestClientHolder&& other) noexcept {
+        std::swap(this->inner, other.inner);
+        std::swap(this->owned, other.owned);
+        return *this;
+    }
+
+    ~TestClientHolder() {
+        if (this->owned) {
+            buffi_free_test_client(this->inner);
+        }
+    }
+
+    // Whether this holder points to a `TestClient`, all other functions throw otherwise
+    bool is_valid() const {
+        return this->inner != nullptr;
+    }
+
     // An async function that needs a `Runtime` to be executed and returns a more complex type
     inline CustomType async_function(const int64_t& content) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::async_function` with a null `TestClient`");
+        }
         auto serializer_content = serde::BincodeSerializer();
         serde::Serializable<int64_t>::serialize(content, serializer_content);
         std::vector<uint8_t> content_serialized = std::move(serializer_content).bytes();
@@ -39,8 +93,96 @@ public:
         }
     }
 
+    // An async function that needs a `Runtime` to be executed and returns a more complex type
+    //
+    // This function does not block, the result is provided via the returned future
+    inline std::future<CustomType> async_function_async(const int64_t& content) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::async_function_async` with a null `TestClient`");
+        }
+        auto serializer_content = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(content, serializer_content);
+        std::vector<uint8_t> content_serialized = std::move(serializer_content).bytes();
+        auto promise = new std::promise<CustomType>();
+        auto future = promise->get_future();
+
+        buffi_async_function_with_callback(this->inner, content_serialized.data(), content_serialized.size(), [](void* context, uint8_t* out_ptr, size_t res_size) {
+            auto promise = static_cast<std::promise<CustomType>*>(context);
+            std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+            buffi_free_byte_buffer(out_ptr, res_size);
+            try {
+                Result_CustomType_SerializableError out = Result_CustomType_SerializableError::bincodeDeserialize(serialized_result);
+                if (out.value.index() == 0) { // Ok
+                    auto ok = std::get<0>(out.value);
+                    promise->set_value(std::get<0>(ok.value));
+                } else { // Err
+                    auto err = std::get<1>(out.value);
+                    promise->set_exception(std::make_exception_ptr(std::get<0>(err.value)));
+                }
+            } catch (...) {
+                promise->set_exception(std::current_exception());
+            }
+            delete promise;
+        }, promise);
+
+        return future;
+    }
+
+    // Binary blobs can be returned without serializing them via `#[buffi(raw_bytes)]`
+    inline std::vector<uint8_t> byte_sequence(const uint8_t& len) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::byte_sequence` with a null `TestClient`");
+        }
+        auto serializer_len = serde::BincodeSerializer();
+        serde::Serializable<uint8_t>::serialize(len, serializer_len);
+        std::vector<uint8_t> len_serialized = std::move(serializer_len).bytes();
+        uint8_t* value_ptr = nullptr;
+        uint8_t* error_ptr = nullptr;
+
+        size_t res_size = buffi_byte_sequence(this->inner, len_serialized.data(), len_serialized.size(), &value_ptr, &error_ptr);
+
+        if (error_ptr == nullptr) {
+            std::vector<uint8_t> value(value_ptr, value_ptr + res_size);
+            buffi_free_byte_buffer(value_ptr, res_size);
+            return value;
+        }
+        size_t error_size = res_size;
+        std::vector<uint8_t> serialized_error(error_ptr, error_ptr + error_size);
+        buffi_free_byte_buffer(error_ptr, error_size);
+        auto error = SerializableError::bincodeDeserialize(serialized_error);
+        throw error;
+    }
+
+    // Primitive results can be returned without serializing them via `#[buffi(direct_return)]`
+    inline int64_t checked_add(const int64_t& a, const int64_t& b) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::checked_add` with a null `TestClient`");
+        }
+        auto serializer_a = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(a, serializer_a);
+        std::vector<uint8_t> a_serialized = std::move(serializer_a).bytes();
+        auto serializer_b = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(b, serializer_b);
+        std::vector<uint8_t> b_serialized = std::move(serializer_b).bytes();
+        int64_t value = {};
+        uint8_t* error_ptr = nullptr;
+
+        size_t error_size = buffi_checked_add(this->inner, a_serialized.data(), a_serialized.size(), b_serialized.data(), b_serialized.size(), &value, &error_ptr);
+
+        if (error_ptr == nullptr) {
+            return value;
+        }
+        std::vector<uint8_t> serialized_error(error_ptr, error_ptr + error_size);
+        buffi_free_byte_buffer(error_ptr, error_size);
+        auto error = SerializableError::bincodeDeserialize(serialized_error);
+        throw error;
+    }
+
     // A function that might use context provided by a TestClient to do its thing
     inline std::string client_function(const std::string& input) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::client_function` with a null `TestClient`");
+        }
         auto serializer_input = serde::BincodeSerializer();
         serde::Serializable<std::string>::serialize(input, serializer_input);
         std::vector<uint8_t> input_serialized = std::move(serializer_input).bytes();
@@ -62,8 +204,262 @@ public:
         }
     }
 
+    // Reference arguments are passed like their owned counterpart
+    inline int64_t count_words(const std::string& text, const std::optional<std::string>& separator, const std::vector<int64_t>& weights, const std::optional<int64_t>& default_weight) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::count_words` with a null `TestClient`");
+        }
+        auto serializer_text = serde::BincodeSerializer();
+        serde::Serializable<std::string>::serialize(text, serializer_text);
+        std::vector<uint8_t> text_serialized = std::move(serializer_text).bytes();
+        auto serializer_separator = serde::BincodeSerializer();
+        serde::Serializable<std::optional<std::string>>::serialize(separator, serializer_separator);
+        std::vector<uint8_t> separator_serialized = std::move(serializer_separator).bytes();
+        auto serializer_weights = serde::BincodeSerializer();
+        serde::Serializable<std::vector<int64_t>>::serialize(weights, serializer_weights);
+        std::vector<uint8_t> weights_serialized = std::move(serializer_weights).bytes();
+        auto serializer_default_weight = serde::BincodeSerializer();
+        serde::Serializable<std::optional<int64_t>>::serialize(default_weight, serializer_default_weight);
+        std::vector<uint8_t> default_weight_serialized = std::move(serializer_default_weight).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_count_words(this->inner, text_serialized.data(), text_serialized.size(), separator_serialized.data(), separator_serialized.size(), weights_serialized.data(), weights_serialized.size(), default_weight_serialized.data(), default_weight_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // Overloads share their C++ name, this one is `describe(int64_t)`
+    inline std::string describe(const int64_t& number) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::describe` with a null `TestClient`");
+        }
+        auto serializer_number = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(number, serializer_number);
+        std::vector<uint8_t> number_serialized = std::move(serializer_number).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_describe_number(this->inner, number_serialized.data(), number_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_String_SerializableError out = Result_String_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // Overloads share their C++ name, this one is `describe(std::string)`
+    inline std::string describe(const std::string& text) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::describe` with a null `TestClient`");
+        }
+        auto serializer_text = serde::BincodeSerializer();
+        serde::Serializable<std::string>::serialize(text, serializer_text);
+        std::vector<uint8_t> text_serialized = std::move(serializer_text).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_describe_text(this->inner, text_serialized.data(), text_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_String_SerializableError out = Result_String_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // Reference counted fields are represented by their content on the C++ side
+    inline SharedContent get_shared_content(const std::string& content) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::get_shared_content` with a null `TestClient`");
+        }
+        auto serializer_content = serde::BincodeSerializer();
+        serde::Serializable<std::string>::serialize(content, serializer_content);
+        std::vector<uint8_t> content_serialized = std::move(serializer_content).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_get_shared_content(this->inner, content_serialized.data(), content_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_SharedContent_SerializableError out = Result_SharedContent_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // Large vectors can be transferred in several chunks via `#[buffi(chunked)]`
+    //
+    // The result is passed to `on_chunk` in several chunks as soon as they are received
+    template <typename F>
+    inline void number_sequence_chunked(const int64_t& len, F&& on_chunk) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::number_sequence` with a null `TestClient`");
+        }
+        auto serializer_len = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(len, serializer_len);
+        std::vector<uint8_t> len_serialized = std::move(serializer_len).bytes();
+        ChunkedResult* chunks = nullptr;
+        uint8_t* error_ptr = nullptr;
+
+        size_t error_size = buffi_number_sequence(this->inner, len_serialized.data(), len_serialized.size(), &chunks, &error_ptr);
+
+        if (error_ptr != nullptr) {
+            std::vector<uint8_t> serialized_error(error_ptr, error_ptr + error_size);
+            buffi_free_byte_buffer(error_ptr, error_size);
+            auto error = SerializableError::bincodeDeserialize(serialized_error);
+            throw error;
+        }
+        size_t expected_len = buffi_chunked_result_len(chunks);
+        size_t received_len = 0;
+        try {
+            const uint8_t* chunk_ptr = nullptr;
+            size_t chunk_size = 0;
+            while ((chunk_size = buffi_next_chunk(chunks, &chunk_ptr)) != 0) {
+                auto deserializer = serde::BincodeDeserializer(std::vector<uint8_t>(chunk_ptr, chunk_ptr + chunk_size));
+                auto chunk = serde::Deserializable<std::vector<int64_t>>::deserialize(deserializer);
+                received_len += chunk.size();
+                on_chunk(std::move(chunk));
+            }
+        } catch (...) {
+            buffi_free_chunked_result(chunks);
+            throw;
+        }
+        buffi_free_chunked_result(chunks);
+        if (received_len != expected_len) {
+            throw std::runtime_error("Could not transfer all chunks of `number_sequence`");
+        }
+    }
+
+    // Large vectors can be transferred in several chunks via `#[buffi(chunked)]`
+    inline std::vector<int64_t> number_sequence(const int64_t& len) {
+        std::vector<int64_t> out;
+        number_sequence_chunked(len, [&out](std::vector<int64_t>&& chunk) {
+            for (auto& item : chunk) {
+                out.push_back(std::move(item));
+            }
+        });
+        return out;
+    }
+
+    // Exported types can be passed by reference as well
+    inline int64_t process_custom_type(const CustomType& item) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::process_custom_type` with a null `TestClient`");
+        }
+        auto serializer_item = serde::BincodeSerializer();
+        serde::Serializable<CustomType>::serialize(item, serializer_item);
+        std::vector<uint8_t> item_serialized = std::move(serializer_item).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_process_custom_type(this->inner, item_serialized.data(), item_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_SerializableError out = Result_i64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // Fixed size arrays are represented by `std::array`
+    inline std::array<double, 3> scale_point(const std::array<double, 3>& point, const double& factor) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::scale_point` with a null `TestClient`");
+        }
+        auto serializer_point = serde::BincodeSerializer();
+        serde::Serializable<std::array<double, 3>>::serialize(point, serializer_point);
+        std::vector<uint8_t> point_serialized = std::move(serializer_point).bytes();
+        auto serializer_factor = serde::BincodeSerializer();
+        serde::Serializable<double>::serialize(factor, serializer_factor);
+        std::vector<uint8_t> factor_serialized = std::move(serializer_factor).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_scale_point(this->inner, point_serialized.data(), point_serialized.size(), factor_serialized.data(), factor_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_Array_3_f64_SerializableError out = Result_Array_3_f64_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
+    // `Cow` arguments and results are represented by their owned counterpart
+    inline std::string trim_text(const std::string& text, const std::vector<int64_t>& values) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::trim_text` with a null `TestClient`");
+        }
+        auto serializer_text = serde::BincodeSerializer();
+        serde::Serializable<std::string>::serialize(text, serializer_text);
+        std::vector<uint8_t> text_serialized = std::move(serializer_text).bytes();
+        auto serializer_values = serde::BincodeSerializer();
+        serde::Serializable<std::vector<int64_t>>::serialize(values, serializer_values);
+        std::vector<uint8_t> values_serialized = std::move(serializer_values).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_trim_text(this->inner, text_serialized.data(), text_serialized.size(), values_serialized.data(), values_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_String_SerializableError out = Result_String_SerializableError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
     // Here we use a type from a third party crate and return `()`
     inline void use_foreign_type_and_return_nothing(const Point1_f64& point) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::use_foreign_type_and_return_nothing` with a null `TestClient`");
+        }
         auto serializer_point = serde::BincodeSerializer();
         serde::Serializable<Point1_f64>::serialize(point, serializer_point);
         std::vector<uint8_t> point_serialized = std::move(serializer_point).bytes();
@@ -84,6 +480,32 @@ public:
         }
     }
 
+    // Functions can transfer their own error type via `#[buffi(custom_error)]`
+    inline int64_t validate_content(const int64_t& content) {
+        if (this->inner == nullptr) {
+            throw std::logic_error("Called `TestClientHolder::validate_content` with a null `TestClient`");
+        }
+        auto serializer_content = serde::BincodeSerializer();
+        serde::Serializable<int64_t>::serialize(content, serializer_content);
+        std::vector<uint8_t> content_serialized = std::move(serializer_content).bytes();
+        uint8_t* out_ptr = nullptr;
+
+        size_t res_size = buffi_validate_content(this->inner, content_serialized.data(), content_serialized.size(), &out_ptr);
+
+        std::vector<uint8_t> serialized_result(out_ptr, out_ptr + res_size);
+        Result_i64_ValidationError out = Result_i64_ValidationError::bincodeDeserialize(serialized_result);
+        buffi_free_byte_buffer(out_ptr, res_size);
+
+        if (out.value.index() == 0) { // Ok
+            auto ok = std::get<0>(out.value);
+            return std::get<0>(ok.value);
+        } else { // Err
+            auto err = std::get<1>(out.value);
+            auto error = std::get<0>(err.value);
+            throw error;
+        }
+    }
+
 };
 
 }  // end of namespace BUFFI_NAMESPACE
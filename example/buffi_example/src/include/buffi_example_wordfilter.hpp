@@ -0,0 +1,64 @@
+#pragma once
+
+#include <cstddef>
+#include <limits>
+#include <algorithm>
+#include <memory>
+#include <array>
+#include <cstdint>
+#include <functional>
+#include <map>
+#include <optional>
+#include <stdexcept>
+#include <string>
+#include <tuple>
+#include <utility>
+#include <variant>
+#include <vector>
+#include "buffi_example_api_functions.hpp"  // IWYU pragma: export
+
+#include "BUFFI_NAMESPACE.hpp"  // IWYU pragma: export
+
+
+namespace BUFFI_NAMESPACE {
+
+// Decides which words are counted by `count_words`, implemented on the C++ side
+class WordFilter {
+public:
+    virtual ~WordFilter() = default;
+
+    // Whether `word` is counted
+    virtual bool accept(const std::string& word) = 0;
+};
+
+// Registers `implementation` as the implementation of `WordFilter` used by the Rust side,
+// a null pointer unregisters the current implementation
+inline void set_word_filter(std::shared_ptr<WordFilter> implementation) {
+    if (implementation == nullptr) {
+        buffi_set_word_filter(nullptr, nullptr, nullptr);
+        return;
+    }
+    buffi_set_word_filter(
+        new std::shared_ptr<WordFilter>(std::move(implementation)),
+        [](void* context) {
+            delete static_cast<std::shared_ptr<WordFilter>*>(context);
+        },
+        [](void* context, const uint8_t* word, size_t word_size, uint8_t** out_ptr) -> size_t {
+            try {
+                auto deserializer_word = serde::BincodeDeserializer(std::vector<uint8_t>(word, word + word_size));
+                auto word_value = serde::Deserializable<std::string>::deserialize(deserializer_word);
+                auto result = (*static_cast<std::shared_ptr<WordFilter>*>(context))->accept(word_value);
+                auto serializer = serde::BincodeSerializer();
+                serde::Serializable<bool>::serialize(result, serializer);
+                std::vector<uint8_t> serialized_result = std::move(serializer).bytes();
+                *out_ptr = buffi_alloc_byte_buffer(serialized_result.size());
+                std::copy(serialized_result.begin(), serialized_result.end(), *out_ptr);
+                return serialized_result.size();
+            } catch (...) {
+                *out_ptr = nullptr;
+                return 0;
+            }
+        });
+}
+
+}  // end of namespace BUFFI_NAMESPACE
@@ -1,7 +1,11 @@
 #![allow(unexpected_cfgs)]
 
 use cgmath::Point1;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
@@ -16,17 +20,64 @@ pub fn free_standing_function(input: i64) -> Result<i64, String> {
     Ok(input)
 }
 
+/// An async function that is not part of an impl block
+#[buffi_macro::exported(global_runtime, completion_callback)]
+pub async fn free_standing_async_function(input: i64) -> Result<i64, String> {
+    Ok(input)
+}
+
+/// Tuples are represented by `std::tuple`
+#[buffi_macro::exported]
+pub fn add_pair(pair: (i64, i64)) -> Result<i64, String> {
+    Ok(pair.0 + pair.1)
+}
+
+/// Maps like `BTreeMap` or `HashMap` are represented by `std::map`
+#[buffi_macro::exported]
+pub fn sum_values(values: BTreeMap<String, i64>) -> Result<i64, String> {
+    Ok(values.values().sum())
+}
+
+/// Counts down from `from`, `on_step` is called for each step and stops the countdown by
+/// returning `false`
+#[buffi_macro::exported]
+pub fn count_down(
+    from: u8,
+    #[buffi(callback)] on_step: impl Fn(u8) -> bool,
+) -> Result<i64, String> {
+    let mut steps = 0;
+    for step in (1..=from).rev() {
+        steps += 1;
+        if !on_step(step) {
+            break;
+        }
+    }
+    Ok(steps)
+}
+
+/// An internal helper, only part of the bindings with `include_hidden_items`
+#[doc(hidden)]
+#[buffi_macro::exported]
+pub fn hidden_function(input: i64) -> Result<i64, String> {
+    Ok(input)
+}
+
+/// The separator used by `TestClient::count_words` if none is given
+#[buffi_macro::exported]
+pub const DEFAULT_SEPARATOR: &str = " ";
+
+/// The weight of words without an explicit weight in `TestClient::count_words`
+#[buffi_macro::exported]
+pub const DEFAULT_WEIGHT: i64 = 1;
+
 /// Get a client to call functions
 #[no_mangle]
 pub extern "C" fn get_test_client() -> *mut TestClient {
-    let client = TestClient {
-        runtime: Arc::new(Runtime::new().unwrap()),
-    };
-    Box::leak(Box::new(client))
+    Box::leak(Box::new(TestClient::new()))
 }
 
 /// A custom type that needs to be available in C++ as well
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct CustomType {
     /// Some content
     pub some_content: i64,
@@ -34,14 +85,57 @@ pub struct CustomType {
     pub itself: Option<Box<CustomType>>,
 }
 
-#[buffi_macro::exported]
+/// A type that contains reference counted fields
+#[derive(Serialize)]
+pub struct SharedContent {
+    /// Content that might be shared between threads
+    pub shared: Arc<String>,
+    /// Content that might be shared on the same thread
+    pub local: Rc<Vec<i64>>,
+    /// A marker that is not part of the C++ representation
+    pub marker: PhantomData<String>,
+}
+
+/// An error that is transferred as it is instead of being converted to a `SerializableError`
+#[derive(Serialize)]
+pub struct ValidationError {
+    /// The name of the invalid argument
+    pub field: String,
+    /// Why the argument is invalid
+    pub message: String,
+}
+
+// required to report internal errors like panics
+impl From<errors::SerializableError> for ValidationError {
+    fn from(value: errors::SerializableError) -> Self {
+        Self {
+            field: String::new(),
+            message: value.message,
+        }
+    }
+}
+
+#[buffi_macro::exported(completion_callback, detect_reentrancy, panic_details)]
 impl TestClient {
+    /// Clients created via `TestClientHolder::create()` are freed by the holder
+    #[buffi(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let runtime = Arc::new(Runtime::new().unwrap());
+        // the first client provides the runtime for async functions outside of impl blocks
+        let _ = buffi::runtime::set_global(runtime.handle().clone());
+        // forward log messages to the callback registered via `buffi_set_log_callback`
+        let _ = buffi::log::install();
+        Self { runtime }
+    }
+
     /// A function that might use context provided by a TestClient to do its thing
     pub fn client_function(&self, input: String) -> Result<String, String> {
         Ok(input)
     }
 
     /// An async function that needs a `Runtime` to be executed and returns a more complex type
+    #[buffi(blocking_strategy = "block_in_place")]
     pub async fn async_function(&self, content: i64) -> Result<CustomType, String> {
         Ok(CustomType {
             some_content: content,
@@ -49,13 +143,132 @@ impl TestClient {
         })
     }
 
+    /// Reference counted fields are represented by their content on the C++ side
+    pub fn get_shared_content(&self, content: String) -> Result<SharedContent, String> {
+        Ok(SharedContent {
+            local: Rc::new(vec![content.len() as i64]),
+            shared: Arc::new(content),
+            marker: PhantomData,
+        })
+    }
+
+    /// Fixed size arrays are represented by `std::array`
+    pub fn scale_point(&self, mut point: [f64; 3], factor: f64) -> Result<[f64; 3], String> {
+        for p in &mut point {
+            *p *= factor;
+        }
+        Ok(point)
+    }
+
+    /// Overloads share their C++ name, this one is `describe(int64_t)`
+    #[buffi(overload = "describe")]
+    pub fn describe_number(&self, number: i64) -> Result<String, String> {
+        Ok(format!("The number {number}"))
+    }
+
+    /// Overloads share their C++ name, this one is `describe(std::string)`
+    #[buffi(overload = "describe")]
+    pub fn describe_text(&self, text: String) -> Result<String, String> {
+        Ok(format!("The text '{text}'"))
+    }
+
+    /// `Cow` arguments and results are represented by their owned counterpart
+    pub fn trim_text(
+        &self,
+        text: Cow<'static, str>,
+        values: Cow<'static, [i64]>,
+    ) -> Result<Cow<'static, str>, String> {
+        let trimmed = text.trim();
+        if trimmed.len() == text.len() && values.is_empty() {
+            Ok(text)
+        } else {
+            Ok(Cow::Owned(format!("{trimmed}{}", values.len())))
+        }
+    }
+
+    /// Reference arguments are passed like their owned counterpart
+    pub fn count_words(
+        &self,
+        text: &str,
+        separator: Option<&str>,
+        weights: &[i64],
+        default_weight: Option<&i64>,
+    ) -> Result<i64, String> {
+        let default_weight = default_weight.copied().unwrap_or(DEFAULT_WEIGHT);
+        let filter = ImportedWordFilter::get();
+        Ok(text
+            .split(separator.unwrap_or(DEFAULT_SEPARATOR))
+            .filter(|word| filter.as_ref().is_none_or(|f| f.accept((*word).to_owned())))
+            .enumerate()
+            .map(|(i, _)| weights.get(i).copied().unwrap_or(default_weight))
+            .sum())
+    }
+
+    /// Exported types can be passed by reference as well
+    pub fn process_custom_type(&self, item: &CustomType) -> Result<i64, String> {
+        let mut sum = item.some_content;
+        let mut next = item.itself.as_deref();
+        while let Some(inner) = next {
+            sum += inner.some_content;
+            next = inner.itself.as_deref();
+        }
+        Ok(sum)
+    }
+
     /// Here we use a type from a third party crate and return `()`
+    #[buffi(blocking)]
     pub fn use_foreign_type_and_return_nothing(&self, point: Point1<f64>) -> Result<(), String> {
         println!("{:?}", point);
         Ok(())
     }
+
+    /// Functions can transfer their own error type via `#[buffi(custom_error)]`
+    #[buffi(custom_error)]
+    pub fn validate_content(&self, content: i64) -> Result<i64, ValidationError> {
+        if content < 0 {
+            Err(ValidationError {
+                field: String::from("content"),
+                message: String::from("must not be negative"),
+            })
+        } else {
+            Ok(content)
+        }
+    }
+
+    /// Primitive results can be returned without serializing them via `#[buffi(direct_return)]`
+    #[buffi(direct_return)]
+    pub fn checked_add(&self, a: i64, b: i64) -> Result<i64, String> {
+        a.checked_add(b)
+            .ok_or_else(|| String::from("The result does not fit into an i64"))
+    }
+
+    /// Large vectors can be transferred in several chunks via `#[buffi(chunked)]`
+    #[buffi(chunked = 2)]
+    pub fn number_sequence(&self, len: i64) -> Result<Vec<i64>, String> {
+        Ok((0..len).collect())
+    }
+
+    /// Binary blobs can be returned without serializing them via `#[buffi(raw_bytes)]`
+    #[buffi(raw_bytes)]
+    pub fn byte_sequence(&self, len: u8) -> Result<Vec<u8>, String> {
+        Ok((0..len).collect())
+    }
 }
 
+/// Decides which words are counted by `count_words`, implemented on the C++ side
+#[buffi_macro::imported]
+pub trait WordFilter {
+    /// Whether `word` is counted
+    fn accept(&self, word: String) -> bool;
+}
+
+buffi::export_log_callback!();
+buffi::export_panic_hook!();
+buffi::export_stats!();
+buffi::export_api_version!();
+buffi::export_alloc_byte_buffer!();
+buffi::export_chunked_results!();
+
 /// This function frees a byte buffer allocated on the Rust side
 ///
 /// * `ptr`: The ptr to the buffer
@@ -68,10 +281,13 @@ impl TestClient {
 #[no_mangle]
 pub unsafe extern "C" fn buffi_free_byte_buffer(ptr: *mut u8, size: usize) {
     if !ptr.is_null() {
+        buffi::stats::record_free(size);
         // SAFETY: We checked for null above
         let v = unsafe { Vec::from_raw_parts(ptr, size, size) };
         drop(v);
     }
 }
 
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod errors;
@@ -19,5 +19,8 @@ fn main() {
     }
     let include_dir = include_dir.canonicalize().unwrap();
 
-    buffi::generate_bindings(include_dir.as_path(), config);
+    if let Err(e) = buffi::generate_bindings(include_dir.as_path(), config) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
 }
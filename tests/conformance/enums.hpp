@@ -0,0 +1,74 @@
+    /// An enum with only unit variants
+    struct Direction {
+
+        struct North {
+            friend bool operator==(const North&, const North&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static North bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct East {
+            friend bool operator==(const East&, const East&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static East bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct South {
+            friend bool operator==(const South&, const South&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static South bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct West {
+            friend bool operator==(const West&, const West&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static West bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<North, East, South, West> value;
+
+        friend bool operator==(const Direction&, const Direction&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Direction bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    /// An enum with all kinds of variants
+    struct Shape {
+
+        struct Point {
+            friend bool operator==(const Point&, const Point&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Point bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Circle {
+            double value;
+
+            friend bool operator==(const Circle&, const Circle&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Circle bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Rectangle {
+            std::tuple<double, double> value;
+
+            friend bool operator==(const Rectangle&, const Rectangle&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Rectangle bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Polygon {
+            std::vector<std::array<double, 2>> corners;
+            bool closed;
+
+            friend bool operator==(const Polygon&, const Polygon&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Polygon bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Point, Circle, Rectangle, Polygon> value;
+
+        friend bool operator==(const Shape&, const Shape&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Shape bincodeDeserialize(std::vector<uint8_t>);
+    };
@@ -0,0 +1,28 @@
+    /// Generic types nested in each other
+    struct NestedGenerics {
+        BUFFI_NAMESPACE::Wrapper_Wrapper_i32 wrapped;
+        std::vector<std::optional<std::vector<int64_t>>> nested;
+        std::array<std::optional<uint8_t>, 2> lookup;
+        std::array<std::string, 2> labels;
+        std::array<BUFFI_NAMESPACE::Wrapper_i32, 4> corners;
+
+        friend bool operator==(const NestedGenerics&, const NestedGenerics&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static NestedGenerics bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Wrapper_Wrapper_i32 {
+        BUFFI_NAMESPACE::Wrapper_i32 value;
+
+        friend bool operator==(const Wrapper_Wrapper_i32&, const Wrapper_Wrapper_i32&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Wrapper_Wrapper_i32 bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct Wrapper_i32 {
+        int32_t value;
+
+        friend bool operator==(const Wrapper_i32&, const Wrapper_i32&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Wrapper_i32 bincodeDeserialize(std::vector<uint8_t>);
+    };
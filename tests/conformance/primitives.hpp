@@ -0,0 +1,26 @@
+    /// All supported primitive types
+    struct Primitives {
+        bool boolean;
+        int8_t int8;
+        int16_t int16;
+        int32_t int32;
+        int64_t int64;
+        uint8_t uint8;
+        uint16_t uint16;
+        uint32_t uint32;
+        uint64_t uint64;
+        float float32;
+        double float64;
+        /// Transferred as 64 bit value on every target
+        uint64_t size;
+        /// Transferred as 64 bit value on every target
+        int64_t offset;
+        std::string text;
+        /// Binary data, serialized as bytes
+        std::vector<uint8_t> blob;
+        std::vector<uint8_t> buffer;
+
+        friend bool operator==(const Primitives&, const Primitives&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Primitives bincodeDeserialize(std::vector<uint8_t>);
+    };
@@ -0,0 +1,9 @@
+    /// A type that refers to itself
+    struct Node {
+        int64_t value;
+        std::optional<serde::value_ptr<BUFFI_NAMESPACE::Node>> next;
+
+        friend bool operator==(const Node&, const Node&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Node bincodeDeserialize(std::vector<uint8_t>);
+    };
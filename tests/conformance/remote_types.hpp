@@ -0,0 +1,33 @@
+    /// An enum containing a type with a remote serde definition
+    struct Measurement {
+
+        struct Temperature {
+            BUFFI_NAMESPACE::TemperatureDef value;
+
+            friend bool operator==(const Temperature&, const Temperature&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Temperature bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        struct Missing {
+            friend bool operator==(const Missing&, const Missing&);
+            std::vector<uint8_t> bincodeSerialize() const;
+            static Missing bincodeDeserialize(std::vector<uint8_t>);
+        };
+
+        std::variant<Temperature, Missing> value;
+
+        friend bool operator==(const Measurement&, const Measurement&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Measurement bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    /// The serde definition of `remote::Temperature`
+    struct TemperatureDef {
+        /// In degrees Celsius
+        double degrees;
+
+        friend bool operator==(const TemperatureDef&, const TemperatureDef&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static TemperatureDef bincodeDeserialize(std::vector<uint8_t>);
+    };
@@ -3,32 +3,44 @@ mod tests {
     use std::collections::HashSet;
     use std::env::temp_dir;
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+
+    /// The config of the example in `example/generate_bindings`
+    fn example_config() -> buffi::Config {
+        let config_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../example/generate_bindings/api_config.toml")
+            .canonicalize()
+            .unwrap();
+        let toml_string = fs::read_to_string(config_path).expect("Config path does not exist");
+        toml::from_str(&toml_string).expect("Could not read config toml")
+    }
+
+    /// An empty output directory `name` in the temp directory
+    ///
+    /// Each test uses a separate directory, as the tests run in parallel.
+    fn output_dir(name: &str) -> PathBuf {
+        let temp_dir = temp_dir().join(name);
+        if temp_dir.exists() {
+            fs::remove_dir_all(&temp_dir).unwrap();
+        }
+        fs::create_dir_all(&temp_dir).unwrap();
+        temp_dir
+    }
+
+    /// Generates the bindings of the example with the changes of `configure` into the output
+    /// directory `name`
+    fn generate(name: &str, configure: impl FnOnce(&mut buffi::Config)) -> PathBuf {
+        let mut config = example_config();
+        configure(&mut config);
+        let temp_dir = output_dir(name);
+        buffi::generate_bindings(temp_dir.as_path(), config).unwrap();
+        temp_dir
+    }
 
     #[test]
     fn test_basic_example() {
-        // load config from example
-        let mut config_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        config_dir.push("..");
-        config_dir.push("example");
-        config_dir.push("generate_bindings");
-        config_dir.push("api_config.toml");
-        let config_dir = config_dir.canonicalize().unwrap();
-
-        let toml_string = fs::read_to_string(&config_dir).expect("Config path does not exist");
-        let config: buffi::Config =
-            toml::from_str(&toml_string).expect("Could not read config toml");
-
         // generate bindings and write them to temp directory
-        // make sure to clean temp directory first
-        let mut temp_dir = temp_dir();
-        temp_dir.push("buffi");
-        fs::create_dir_all(&temp_dir).unwrap();
-        for file in fs::read_dir(&temp_dir).unwrap() {
-            let file = file.unwrap();
-            fs::remove_file(file.path().as_path()).unwrap();
-        }
-        buffi::generate_bindings(temp_dir.as_path(), config);
+        let temp_dir = generate("buffi", |_| {});
 
         // prepare path to already generated example files in "include"
         let mut include_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -84,4 +96,511 @@ mod tests {
             }
         }
     }
+
+    /// Generates the Emscripten bindings for the example and checks which functions are bound
+    #[test]
+    fn test_embind_bindings() {
+        let temp_dir = generate("buffi_embind", |config| config.embind = Some(true));
+
+        let bindings = fs::read_to_string(temp_dir.join("buffi_example_embind.cpp")).unwrap();
+        assert!(bindings
+            .contains("emscripten::class_<BUFFI_NAMESPACE::TestClientHolder>(\"TestClient\")"));
+        assert!(bindings.contains(
+            ".function(\"client_function\", &BUFFI_NAMESPACE::TestClientHolder::client_function)"
+        ));
+        assert!(bindings.contains("emscripten::function(\"get_test_client\""));
+        // `#[buffi(constructor)]` is bound as `create()` instead of a factory function
+        assert!(bindings
+            .contains(".class_function(\"create\", &BUFFI_NAMESPACE::TestClientHolder::create)"));
+        assert!(!bindings.contains("emscripten::function(\"buffi_create_test_client\""));
+        // overloads are bound under the name of the Rust function
+        assert!(bindings.contains(
+            ".function(\"describe_number\", emscripten::select_overload<std::string(const int64_t&), BUFFI_NAMESPACE::TestClientHolder>(&BUFFI_NAMESPACE::TestClientHolder::describe))"
+        ));
+        assert!(bindings.contains("emscripten::register_vector<int64_t>(\"Vec_i64\");"));
+        assert!(bindings
+            .contains("emscripten::value_object<BUFFI_NAMESPACE::Point1_f64>(\"Point1_f64\")"));
+        // `CustomType` contains itself, which cannot be represented by embind
+        assert!(bindings.contains("// * `TestClient::async_function`"));
+        assert!(!bindings.contains("&BUFFI_NAMESPACE::TestClientHolder::async_function"));
+
+        let declarations = fs::read_to_string(temp_dir.join("buffi_example_embind.d.ts")).unwrap();
+        assert!(declarations.contains("    client_function(input: string): string;"));
+        assert!(declarations.contains("    get_test_client(): TestClient;"));
+    }
+
+    /// Generates the LuaJIT module for the example and checks the declarations and codecs
+    #[test]
+    fn test_lua_ffi() {
+        let temp_dir = generate("buffi_lua_ffi", |config| config.lua_ffi = Some(true));
+
+        let module = fs::read_to_string(temp_dir.join("buffi_example_ffi.lua")).unwrap();
+        assert!(module.contains("typedef struct TestClient TestClient;"));
+        assert!(module.contains("typedef struct ChunkedResult ChunkedResult;"));
+        assert!(module.contains(
+            "size_t buffi_client_function(TestClient* this_ptr, const uint8_t* input, size_t input_size, uint8_t** out_ptr);"
+        ));
+        assert!(!module.contains("extern \"C\""));
+        assert!(module.contains("M.lib.buffi_free_byte_buffer(ptr, size)"));
+        assert!(module.contains(
+            "M.types.SerializableError = codec.struct({\n    { \"message\", codec.str },\n})"
+        ));
+        assert!(module.contains("M.lib = ffi.load(path or \"buffi_example\")"));
+    }
+
+    /// Generates the Qt conversions for the example
+    #[test]
+    fn test_qt_conversions() {
+        let temp_dir = generate("buffi_qt_conversions", |config| {
+            config.qt_conversions = Some(true)
+        });
+
+        let conversions =
+            fs::read_to_string(temp_dir.join("buffi_example_qt_conversions.hpp")).unwrap();
+        assert!(conversions.contains("namespace BUFFI_NAMESPACE::qt {"));
+        assert!(conversions.contains("inline QString to_qt(const std::string& value) {"));
+        assert!(
+            conversions.contains("inline std::vector<uint8_t> from_qt(const QByteArray& value) {")
+        );
+        assert!(conversions
+            .contains("inline QDateTime datetime_from_unix_millis(std::int64_t millis) {"));
+    }
+
+    /// Generates the adapters with pluggable string and vector types for the example
+    #[test]
+    fn test_stl_free_adapters() {
+        let temp_dir = generate("buffi_stl_free_adapters", |config| {
+            config.stl_free_adapters = Some(true)
+        });
+
+        let traits = fs::read_to_string(temp_dir.join("buffi_example_adapter_traits.hpp")).unwrap();
+        assert!(traits.contains("struct StdTypes {"));
+        let adapters = fs::read_to_string(temp_dir.join("buffi_example_adapters.hpp")).unwrap();
+        assert!(adapters.contains("class TestClientAdapter {"));
+        assert!(adapters.contains(
+            "    adapter::Adapted<Traits, std::string> client_function(const adapter::Adapted<Traits, std::string>& input) {"
+        ));
+        assert!(adapters.contains(
+            "        return adapter::Convert<Traits, std::string>::from_std(this->inner.client_function(adapter::Convert<Traits, std::string>::to_std(input)));"
+        ));
+    }
+
+    /// Generates the runtime loader for the example
+    #[test]
+    fn test_runtime_loader() {
+        let temp_dir = generate("buffi_runtime_loader", |config| {
+            config.runtime_loader = Some(true)
+        });
+
+        let header = fs::read_to_string(temp_dir.join("buffi_example_loader.hpp")).unwrap();
+        assert!(header.contains(
+            "    size_t (*buffi_client_function)(TestClient* this_ptr, const std::uint8_t* input, size_t input_size, std::uint8_t** out_ptr) = nullptr;"
+        ));
+        assert!(header.contains("void load_library(const char* path);"));
+        let source = fs::read_to_string(temp_dir.join("buffi_example_loader.cpp")).unwrap();
+        assert!(source.contains("        resolve(table.get_test_client, \"get_test_client\");"));
+        assert!(source.contains(
+            "extern \"C\" size_t buffi_client_function(TestClient* this_ptr, const std::uint8_t* input, size_t input_size, std::uint8_t** out_ptr) {"
+        ));
+        assert!(source.contains(
+            "    return BUFFI_NAMESPACE::table.buffi_client_function(this_ptr, input, input_size, out_ptr);"
+        ));
+    }
+
+    /// Generates the linker export lists for the example
+    #[test]
+    fn test_linker_exports() {
+        let temp_dir = generate("buffi_linker_exports", |config| {
+            config.linker_exports = Some(true)
+        });
+
+        let version_script =
+            fs::read_to_string(temp_dir.join("buffi_example_exports.map")).unwrap();
+        assert!(version_script.starts_with("{\n  global:\n"));
+        assert!(version_script.contains("\n    buffi_client_function;\n"));
+        assert!(version_script.contains("\n    get_test_client;\n"));
+        assert!(version_script.ends_with("  local:\n    *;\n};\n"));
+        let def = fs::read_to_string(temp_dir.join("buffi_example_exports.def")).unwrap();
+        assert!(def.starts_with("LIBRARY buffi_example\nEXPORTS\n"));
+        assert!(def.contains("\n    buffi_client_function\n"));
+    }
+
+    /// Generates the symbol list for the example
+    #[test]
+    fn test_symbol_list() {
+        let temp_dir = generate("buffi_symbol_list", |config| {
+            config.symbol_list = Some(true)
+        });
+
+        let symbols = fs::read_to_string(temp_dir.join("buffi_example_symbols.json")).unwrap();
+        assert!(symbols.contains("  \"library\": \"buffi_example\",\n"));
+        assert!(symbols.contains(
+            "      \"name\": \"buffi_client_function\",\n      \"return_type\": \"size_t\",\n"
+        ));
+        assert!(symbols.contains(
+            "          \"name\": \"input\",\n          \"type\": \"const std::uint8_t*\"\n"
+        ));
+        // the name is removed from function pointers as well
+        assert!(symbols.contains(
+            "          \"type\": \"void (*)(std::uint8_t, const char*, const char*)\"\n"
+        ));
+    }
+
+    /// Generates the wrappers in an explicit order
+    #[test]
+    fn test_function_order() {
+        let temp_dir = generate("buffi_function_order", |config| {
+            config.function_order = Some(buffi::FunctionOrder::Explicit(vec![
+                String::from("TestClient::validate_content"),
+                String::from("client_function"),
+                // does not match the type of the function
+                String::from("Other::async_function"),
+            ]));
+        });
+
+        let header = fs::read_to_string(temp_dir.join("buffi_example_testclient.hpp")).unwrap();
+        let position = |name: &str| header.find(&format!(" {name}(")).unwrap();
+        assert!(position("validate_content") < position("client_function"));
+        assert!(position("client_function") < position("async_function"));
+        assert!(position("async_function") < position("checked_add"));
+    }
+
+    /// Generates the wrappers of functions marked with `#[doc(hidden)]`
+    #[test]
+    fn test_include_hidden_items() {
+        let temp_dir = generate("buffi_hidden_items", |config| {
+            config.include_hidden_items = Some(true)
+        });
+
+        let functions =
+            fs::read_to_string(temp_dir.join("buffi_example_free_standing_functions.hpp")).unwrap();
+        assert!(functions.contains(" hidden_function("));
+        let api_functions =
+            fs::read_to_string(temp_dir.join("buffi_example_api_functions.hpp")).unwrap();
+        assert!(api_functions.contains("buffi_hidden_function("));
+    }
+
+    /// Reports a missing output directory as an error instead of panicking
+    #[test]
+    fn test_missing_out_dir() {
+        let temp_dir = output_dir("buffi_missing_out_dir");
+        fs::remove_dir_all(&temp_dir).unwrap();
+        let error = buffi::generate_bindings(temp_dir.as_path(), example_config()).unwrap_err();
+        assert!(matches!(error, buffi::Error::MissingOutputDirectory(_)));
+    }
+
+    /// Generates the bindings inside an inline namespace
+    #[test]
+    fn test_inline_namespace() {
+        let temp_dir = generate("buffi_inline_namespace", |config| {
+            config.inline_namespace = Some(String::from("v2"))
+        });
+
+        for file in ["BUFFI_NAMESPACE.hpp", "buffi_example_testclient.hpp"] {
+            let header = fs::read_to_string(temp_dir.join(file)).unwrap();
+            assert!(header.contains("namespace BUFFI_NAMESPACE { inline namespace v2 {\n"));
+            assert!(
+                header.contains("}} // end of namespace BUFFI_NAMESPACE::v2")
+                    || header.contains("}}  // end of namespace BUFFI_NAMESPACE::v2")
+            );
+            assert!(!header.contains("namespace BUFFI_NAMESPACE {\n"));
+        }
+    }
+
+    /// Generates the wrappers with `#line` directives pointing at the Rust functions
+    #[test]
+    fn test_line_directives() {
+        let temp_dir = generate("buffi_line_directives", |config| {
+            config.line_directives = Some(true)
+        });
+
+        let example = fs::read_to_string(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../example/buffi_example/src/lib.rs"),
+        )
+        .unwrap();
+        let client_function = example
+            .lines()
+            .position(|l| l.contains("pub fn client_function("))
+            .unwrap()
+            + 1;
+        let header = fs::read_to_string(temp_dir.join("buffi_example_testclient.hpp")).unwrap();
+        assert!(header.contains(&format!(
+            "#line {client_function} \"example/buffi_example/src/lib.rs\"\n"
+        )));
+        assert!(!header.contains("BUFFI_RESTORE_LINE"));
+        // each restoring directive continues with the number of the following line
+        for (idx, line) in header.lines().enumerate() {
+            if let Some(file) = line.strip_prefix(&format!("#line {} ", idx + 2)) {
+                assert_eq!(file, "\"buffi_example_testclient.hpp\"");
+            }
+        }
+        assert!(header.contains("\"buffi_example_testclient.hpp\""));
+    }
+
+    /// Generates the symbol map for the example
+    #[test]
+    fn test_symbol_map() {
+        let temp_dir = generate("buffi_symbol_map", |config| config.symbol_map = Some(true));
+
+        let map = fs::read_to_string(temp_dir.join("buffi_example_symbol_map.json")).unwrap();
+        assert!(map.contains(
+            "    \"cpp_function\": \"BUFFI_NAMESPACE::TestClientHolder::client_function\",\n    \"symbol\": \"buffi_client_function\",\n    \"rust_function\": \"TestClient::client_function\",\n    \"file\": \"example/buffi_example/src/lib.rs\",\n"
+        ));
+        // overloads and the non-blocking variants of async functions
+        assert!(map.contains(
+            "    \"cpp_function\": \"BUFFI_NAMESPACE::TestClientHolder::describe\",\n    \"symbol\": \"buffi_describe_text\",\n"
+        ));
+        assert!(map.contains(
+            "    \"cpp_function\": \"BUFFI_NAMESPACE::TestClientHolder::async_function_async\",\n    \"symbol\": \"buffi_async_function_with_callback\",\n"
+        ));
+        assert!(map.contains(
+            "    \"cpp_function\": \"BUFFI_NAMESPACE::free_standing_function\",\n    \"symbol\": \"buffi_free_standing_function\",\n    \"rust_function\": \"free_standing_function\",\n"
+        ));
+    }
+
+    /// Generates the ABI smoke test for the example
+    #[test]
+    fn test_abi_smoke_test() {
+        let temp_dir = generate("buffi_abi_smoke_test", |config| {
+            config.abi_smoke_test = Some(true)
+        });
+
+        let source = fs::read_to_string(temp_dir.join("buffi_example_smoke_test.cpp")).unwrap();
+        assert!(source.contains("        reinterpret_cast<void (*)()>(&get_test_client),\n"));
+        assert!(source.contains(
+            "        size_t size = buffi_client_function(nullptr, nullptr, 0, &out_ptr);\n        expect_error(\"buffi_client_function\", \"`this_ptr` is null\", size, out_ptr);\n"
+        ));
+        assert!(source.contains(
+            "        std::int64_t value_out{};\n        std::uint8_t* error_out = nullptr;\n        size_t size = buffi_checked_add(nullptr, nullptr, 0, nullptr, 0, &value_out, &error_out);\n"
+        ));
+        assert!(source.contains(
+            "        size_t size = buffi_free_standing_function(nullptr, 0, &out_ptr);\n        expect_error(\"buffi_free_standing_function\", \"the serialized arguments are empty\", size, out_ptr);\n"
+        ));
+        // functions without invalid inputs are only linked
+        assert!(!source.contains("buffi_stats(&out_ptr)"));
+        assert!(!source.contains("size_t size = buffi_next_chunk("));
+    }
+
+    /// Checks the summary returned for the example
+    #[test]
+    fn test_generation_summary() {
+        let mut config = example_config();
+        config.embind = Some(true);
+        let temp_dir = output_dir("buffi_summary");
+        let summary = buffi::generate_bindings(temp_dir.as_path(), config).unwrap();
+
+        assert!(summary.functions > 0);
+        assert!(summary.types > 0);
+        let phases = summary
+            .phases
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            phases,
+            [
+                "rustdoc",
+                "loading docs",
+                "types",
+                "functions",
+                "optional bindings"
+            ]
+        );
+        let api_functions = summary
+            .files
+            .iter()
+            .find(|f| f.path.ends_with("buffi_example_api_functions.hpp"))
+            .expect("api_functions.hpp is listed");
+        assert!(api_functions.size > 0);
+        assert!(summary
+            .skipped
+            .iter()
+            .any(|item| item.name == "TestClient::async_function"));
+    }
+
+    /// Wraps the generated code in clang-tidy and GCC/Clang warning suppressions
+    #[test]
+    fn test_warning_suppression() {
+        let temp_dir = generate("buffi_warning_suppression", |config| {
+            config.clang_tidy_nolint = Some(true);
+            config.suppressed_warnings = Some(vec![String::from("-Wshadow")]);
+        });
+
+        for file in ["buffi_example_testclient.hpp", "serde.hpp"] {
+            let header = fs::read_to_string(temp_dir.join(file)).unwrap();
+            assert!(
+                header.contains(
+                    "#pragma once\n\n#if defined(__GNUC__)\n#pragma GCC diagnostic push\n"
+                ),
+                "{file}"
+            );
+            assert!(
+                header.contains(
+                    "#pragma GCC diagnostic ignored \"-Wshadow\"\n#endif\n// NOLINTBEGIN\n"
+                ),
+                "{file}"
+            );
+            assert!(
+                header.ends_with(
+                    "// NOLINTEND\n#if defined(__GNUC__)\n#pragma GCC diagnostic pop\n#endif\n"
+                ),
+                "{file}"
+            );
+        }
+    }
+
+    /// Compiles the example output with `cl.exe` to ensure that MSVC accepts the generated code
+    #[cfg(target_env = "msvc")]
+    #[test]
+    fn test_msvc_compatibility() {
+        let temp_dir = generate("buffi_msvc", |config| {
+            config.msvc_compatibility = Some(true)
+        });
+
+        let source = temp_dir.join("main.cpp");
+        fs::write(
+            &source,
+            "#include \"buffi_example_testclient.hpp\"\n\
+             #include \"buffi_example_free_standing_functions.hpp\"\n\
+             #include \"buffi_example_error_category.hpp\"\n",
+        )
+        .unwrap();
+        let output = std::process::Command::new("cl.exe")
+            .args(["/nologo", "/std:c++17", "/EHsc", "/W4", "/WX", "/c"])
+            .arg(&source)
+            .current_dir(&temp_dir)
+            .output()
+            .expect("Failed to run cl.exe, is a developer command prompt active?");
+        assert!(
+            output.status.success(),
+            "Compiling the generated code failed:\n{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+
+    /// Reports a compiler that cannot be run and leaves no translation unit behind
+    #[test]
+    fn test_compile_generated() {
+        let temp_dir = output_dir("buffi_compile_generated");
+        assert!(buffi::testing::compile_generated(&temp_dir, "c++").is_err());
+
+        buffi::generate_bindings(temp_dir.as_path(), example_config()).unwrap();
+        let error = buffi::testing::compile_generated(&temp_dir, "buffi-missing-compiler")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("buffi-missing-compiler"), "{error}");
+        assert!(!temp_dir.join("buffi_compile_generated.cpp").exists());
+    }
+
+    /// Compiles the example output with `c++`, skipped if there is no such compiler
+    #[test]
+    fn test_compile_example() {
+        if std::process::Command::new("c++")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("Skipping the compilation of the example, `c++` is not available");
+            return;
+        }
+        let temp_dir = generate("buffi_compile_example", |_| {});
+        if let Err(e) = buffi::testing::compile_generated(&temp_dir, "c++") {
+            panic!("{e}");
+        }
+    }
+
+    /// Compiles the example output placed in subdirectories via `layout`, skipped if there is no
+    /// `c++` compiler
+    #[test]
+    fn test_compile_example_with_layout() {
+        if std::process::Command::new("c++")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("Skipping the compilation of the example, `c++` is not available");
+            return;
+        }
+        let temp_dir = generate("buffi_compile_example_with_layout", |config| {
+            config.layout = Some(buffi::OutputLayout {
+                extern_declarations: Some(PathBuf::from("include/api/detail")),
+                headers: Some(PathBuf::from("include/api")),
+                runtime: Some(PathBuf::from("include/runtime")),
+            });
+        });
+        assert!(fs::read_dir(&temp_dir).unwrap().all(|entry| !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".hpp")));
+        if let Err(e) = buffi::testing::compile_generated(&temp_dir, "c++") {
+            panic!("{e}");
+        }
+    }
+
+    /// The declaration of the type `name` in the generated types header, with its doc comment
+    fn type_declaration(types: &str, name: &str) -> String {
+        let lines = types.lines().collect::<Vec<_>>();
+        let start = lines
+            .iter()
+            .position(|l| *l == format!("    struct {name} {{"))
+            .unwrap_or_else(|| panic!("`{name}` was not generated"));
+        let docs = lines[..start]
+            .iter()
+            .rev()
+            .take_while(|l| l.starts_with("    ///"))
+            .count();
+        let end = start
+            + lines[start..]
+                .iter()
+                .position(|l| *l == "    };")
+                .expect("The declaration ends");
+        let mut declaration = lines[start - docs..=end].join("\n");
+        declaration.push('\n');
+        declaration
+    }
+
+    /// Compares the C++ declarations of each construct of the `conformance` module of the
+    /// example with the golden files in `tests/conformance`
+    ///
+    /// Run with `BUFFI_BLESS=1` to update the golden files after an intended change.
+    #[test]
+    fn test_conformance() {
+        let temp_dir = generate("buffi_conformance", |config| {
+            config.crate_feature_flags = Some(vec![String::from("buffi_example/conformance")])
+        });
+
+        let types = fs::read_to_string(temp_dir.join("BUFFI_NAMESPACE.hpp")).unwrap();
+        let constructs: [(&str, &[&str]); 5] = [
+            ("primitives", &["Primitives"]),
+            (
+                "nested_generics",
+                &["NestedGenerics", "Wrapper_Wrapper_i32", "Wrapper_i32"],
+            ),
+            ("recursive_types", &["Node"]),
+            ("remote_types", &["Measurement", "TemperatureDef"]),
+            ("enums", &["Direction", "Shape"]),
+        ];
+        let golden_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("conformance");
+        let bless = std::env::var_os("BUFFI_BLESS").is_some();
+        for (construct, names) in constructs {
+            let generated = names
+                .iter()
+                .map(|name| type_declaration(&types, name))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let golden = golden_dir.join(format!("{construct}.hpp"));
+            if bless {
+                fs::write(&golden, generated).unwrap();
+                continue;
+            }
+            let expected = fs::read_to_string(&golden)
+                .unwrap_or_else(|e| panic!("Failed to read `{}`: {e}", golden.display()));
+            similar_asserts::assert_eq!(
+                golden: expected,
+                generated: generated,
+                "The declarations of `{construct}` changed"
+            );
+        }
+    }
 }